@@ -1,487 +1,543 @@
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+extern crate alloc;
+
+// The line parser, code generator, and data model are shared with the library,
+// so pci.ids is read exactly as `PciIdsParser` reads it and the embedded
+// database is generated exactly as downstream build scripts would.
+#[allow(dead_code)]
+#[path = "src/types.rs"]
+mod types;
+#[allow(dead_code)]
+#[path = "src/error.rs"]
+mod error;
+#[allow(dead_code, clippy::enum_variant_names)]
+#[path = "src/builders.rs"]
+mod builders;
+#[allow(dead_code)]
+#[path = "src/line_parser.rs"]
+mod line_parser;
+#[allow(dead_code)]
+#[path = "src/codegen.rs"]
+mod codegen;
+
+use codegen::{write_rust, GenOptions};
+use line_parser::{split_lines, Indentation, LineParser, ParserConfig, Separator, TreeBuilder};
+
+// This script runs on the host. The generated code must not depend on the host
+// or the target (no `cfg!`, `CARGO_CFG_*`, `usize`, or native-endian data), so
+// cross-compiled builds embed exactly the same database.
+
+/// The pci.ids snapshot packaged with the crate, pinned by the `vendored-db` feature.
+const SNAPSHOT_PATH: &str = "snapshot/pci.ids";
+
+/// Other buses whose ids files use the pci.ids format, and whether their feature is enabled.
+///
+/// Each `<bus>.ids` is generated into `<bus>_database.rs` for the `buses` module.
+const BUSES: [(&str, bool); 2] = [("usb", cfg!(feature = "usb")), ("sdio", cfg!(feature = "sdio"))];
 
 fn main() {
     println!("cargo:rerun-if-changed=pci.ids");
+    println!("cargo:rerun-if-changed=pci.ids.gz");
+    println!("cargo:rerun-if-changed={}", SNAPSHOT_PATH);
+    println!("cargo:rerun-if-changed=fallback.ids");
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=pci.ids.local");
+    println!("cargo:rerun-if-changed=pci.ids.d");
+    println!("cargo:rerun-if-env-changed=IDS_RS_LOCAL_IDS");
+    println!("cargo:rerun-if-env-changed=IDS_RS_DATA_DIR");
+    println!("cargo:rerun-if-env-changed=IDS_RS_STALE_MONTHS");
+    println!("cargo:rerun-if-env-changed=IDS_RS_MAX_NAME_LENGTH");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=src/types.rs");
+    println!("cargo:rerun-if-changed=src/error.rs");
+    println!("cargo:rerun-if-changed=src/builders.rs");
+    println!("cargo:rerun-if-changed=src/line_parser.rs");
+    println!("cargo:rerun-if-changed=src/codegen.rs");
 
     let out_dir = env::var("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("pci_database.rs");
 
-    // Check if pci.ids file exists
-    let pci_ids_path = "pci.ids";
-    if !Path::new(pci_ids_path).exists() {
+    let data_dir = data_dir();
+    for (bus, enabled) in BUSES {
+        if enabled {
+            generate_bus(bus, data_dir.as_deref(), &Path::new(&out_dir).join(format!("{}_database.rs", bus)));
+        }
+    }
+
+    // Prefer a pci.ids provided by the user, or the compressed file distros ship,
+    // over the snapshot packaged with the crate, unless the snapshot is pinned
+    let mut candidates = if cfg!(feature = "vendored-db") { Vec::new() } else { ids_files("pci", data_dir.as_deref()) };
+    candidates.push(PathBuf::from(SNAPSHOT_PATH));
+    let Some(pci_ids_path) = candidates.into_iter().find(|path| path.exists()) else {
         eprintln!("Warning: pci.ids file not found. Please run the update script first:");
         eprintln!("  PowerShell: .\\update_pci_ids.ps1");
         eprintln!("  Bash: ./update_pci_ids.sh");
-        eprintln!("Creating empty database...");
-
-        let empty_database = generate_empty_database();
-        fs::write(&dest_path, empty_database).unwrap();
+        write_fallback_database(&dest_path);
         return;
-    }
+    };
 
-    // Read the PCI IDs file
-    let content = match fs::read_to_string(pci_ids_path) {
-        Ok(content) => content,
+    // Read the PCI IDs file, tolerating invalid UTF-8 in names
+    let content = match fs::read(&pci_ids_path).and_then(decompress) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
         Err(e) => {
             eprintln!("Error reading pci.ids: {}", e);
-            eprintln!("Creating empty database...");
-            let empty_database = generate_empty_database();
-            fs::write(&dest_path, empty_database).unwrap();
+            write_fallback_database(&dest_path);
             return;
         }
     };
 
-    // Parse the content and generate database
-    match parse_and_generate(&content) {
-        Ok(database_code) => {
-            fs::write(&dest_path, database_code).unwrap();
+    // The packaged snapshot is only as fresh as the crate release, so its age
+    // says nothing the user can act on; only warn about files they supplied
+    let check_age = pci_ids_path != Path::new(SNAPSHOT_PATH);
+
+    // Parse the content and stream the generated database to disk
+    match parse_and_generate(&content, &dest_path, check_age) {
+        Ok(()) => {
             println!("Generated PCI database successfully");
         }
         Err(e) => {
             eprintln!("Error parsing pci.ids: {}", e);
-            eprintln!("Creating empty database...");
-            let empty_database = generate_empty_database();
-            fs::write(&dest_path, empty_database).unwrap();
+            write_fallback_database(&dest_path);
         }
     }
 }
 
-fn generate_empty_database() -> String {
-    r#"
-// Empty PCI database (pci.ids file not found or failed to parse)
-
-static VENDORS: &[crate::vendors::Vendor] = &[];
-static CLASSES: &[crate::classes::DeviceClass] = &[];
+/// Decompress gzip-compressed input, recognized by its magic bytes.
+fn decompress(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    if !bytes.starts_with(&[0x1f, 0x8b]) {
+        return Ok(bytes);
+    }
 
-/// The global PCI database instance.
-pub static GLOBAL_DATABASE: crate::database::PciDatabase = crate::database::PciDatabase::new(VENDORS, CLASSES);
-"#.to_string()
-}
+    #[cfg(feature = "flate2")]
+    {
+        use std::io::Read;
 
-// Simple parser structures for build script
-#[derive(Debug)]
-struct Vendor {
-    id: u16,
-    name: String,
-    devices: Vec<Device>,
-}
+        let mut decompressed = Vec::new();
+        flate2::read::MultiGzDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
 
-#[derive(Debug)]
-struct Device {
-    id: u16,
-    name: String,
-    subsystems: Vec<Subsystem>,
+    #[cfg(not(feature = "flate2"))]
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "gzip-compressed input requires the `flate2` feature",
+    ))
 }
 
-#[derive(Debug)]
-struct Subsystem {
-    subvendor_id: u16,
-    subdevice_id: u16,
-    name: String,
+/// The hwdata-style directory named by `IDS_RS_DATA_DIR`, holding `pci.ids`, `usb.ids`, and so on.
+fn data_dir() -> Option<PathBuf> {
+    let path = PathBuf::from(env::var_os("IDS_RS_DATA_DIR")?);
+    if !path.is_dir() {
+        panic!("IDS_RS_DATA_DIR names {}, which is not a directory", path.display());
+    }
+    Some(path)
 }
 
-#[derive(Debug)]
-struct Class {
-    id: u8,
-    name: String,
-    subclasses: Vec<SubClass>,
+/// The places to look for `<bus>.ids`, in order: the data directory, then the
+/// crate root, each as plain text and then gzip-compressed.
+fn ids_files(bus: &str, data_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in data_dir.into_iter().chain([Path::new("")]) {
+        for extension in ["ids", "ids.gz"] {
+            let path = dir.join(format!("{}.{}", bus, extension));
+            println!("cargo:rerun-if-changed={}", path.display());
+            files.push(path);
+        }
+    }
+    files
 }
 
-#[derive(Debug)]
-struct SubClass {
-    id: u8,
-    name: String,
-    prog_interfaces: Vec<ProgInterface>,
-}
+/// Generate the database of another bus from its ids file, or an empty one if there is none.
+///
+/// Only the sections in the pci.ids format are read; see [`pci_format_sections`].
+fn generate_bus(bus: &str, data_dir: Option<&Path>, dest_path: &Path) {
+    let database = match ids_files(bus, data_dir).into_iter().find(|path| path.exists()) {
+        Some(path) => {
+            let content = fs::read(&path)
+                .and_then(decompress)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+            let mut database = parse_document(&pci_format_sections(&content))
+                .unwrap_or_else(|e| panic!("cannot parse {}: {}", path.display(), e));
+            if !database.sort().is_empty() {
+                println!("cargo:warning={} is not sorted by ID; entries were reordered", path.display());
+            }
+            if let Some(key) = database.find_duplicate() {
+                panic!("{} defines {} more than once", path.display(), key);
+            }
+            database
+        }
+        None if cfg!(feature = "require-db") => {
+            panic!("the `require-db` and `{}` features are enabled, but no {}.ids was found", bus, bus)
+        }
+        None => {
+            println!("cargo:warning={}.ids not found; the `{}` feature embeds an empty database. Set IDS_RS_DATA_DIR to a directory containing it", bus, bus);
+            builders::ParsedDatabase::default()
+        }
+    };
 
-#[derive(Debug)]
-struct ProgInterface {
-    id: u8,
-    name: String,
+    let options = gen_options();
+    if cfg!(feature = "json-sidecar") {
+        write_json_file(&database, &options, dest_path).unwrap_or_else(|e| panic!("cannot write the {} JSON snapshot: {}", bus, e));
+    }
+    let report = write_database(&database, &options, dest_path).unwrap_or_else(|e| panic!("cannot generate the {} database: {}", bus, e));
+    if cfg!(feature = "build-report") {
+        print_size_report(&bus.to_uppercase(), &report);
+    }
 }
 
-fn parse_and_generate(content: &str) -> Result<String, String> {
-    let mut vendors = Vec::new();
-    let mut classes = Vec::new();
-
-    let mut current_vendor: Option<Vendor> = None;
-    let mut current_device: Option<Device> = None;
-    let mut current_class: Option<Class> = None;
-    let mut current_subclass: Option<SubClass> = None;
-    let mut parsing_mode = ParsingMode::Vendors;
+/// Keep the parts of an ids file written in the pci.ids format: vendors and
+/// their devices, and the `C` class sections.
+///
+/// `usb.ids` adds sections for HID usages, languages, and more, with their own
+/// top-level keywords, and interface lines below devices where pci.ids has
+/// subsystems. Those are dropped, so the file reads as a PCI IDs document.
+fn pci_format_sections(content: &str) -> String {
+    #[derive(PartialEq)]
+    enum Section {
+        Vendors,
+        Classes,
+        Other,
+    }
 
-    for line in content.lines() {
-        // Skip empty lines and comments
-        if line.trim().is_empty() || line.trim().starts_with('#') {
+    let mut section = Section::Vendors;
+    let mut kept = String::with_capacity(content.len());
+    for line in split_lines(content) {
+        let trimmed = line.trim();
+        // Comments carry the version and date of the file
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            kept.push_str(line);
+            kept.push('\n');
             continue;
         }
-
-        // Check for section transitions
-        if line.trim().starts_with("C ") && count_leading_tabs(line) == 0 {
-            // Switch to classes mode
-            parsing_mode = ParsingMode::Classes;
-
-            // Finalize any remaining vendor/device
-            finalize_vendor_device(&mut vendors, &mut current_vendor, &mut current_device);
+        if !line.starts_with(['\t', ' ']) {
+            let is_vendor = trimmed.len() > 4 && trimmed.as_bytes()[..4].iter().all(u8::is_ascii_hexdigit) && trimmed.as_bytes()[4].is_ascii_whitespace();
+            section = if is_vendor {
+                Section::Vendors
+            } else if trimmed.starts_with("C ") {
+                Section::Classes
+            } else {
+                Section::Other
+            };
         }
-
-        let indentation = count_leading_tabs(line);
-        let trimmed = line.trim();
-
-        match parsing_mode {
-            ParsingMode::Vendors => {
-                match indentation {
-                    0 => {
-                        // Vendor definition (XXXX  Name)
-                        finalize_vendor_device(&mut vendors, &mut current_vendor, &mut current_device);
-
-                        let (id, name) = parse_vendor_line(trimmed)?;
-                        current_vendor = Some(Vendor {
-                            id,
-                            name,
-                            devices: Vec::new(),
-                        });
-                    }
-                    1 => {
-                        // Device definition (\tXXXX  Name)
-                        if let Some(device) = current_device.take() {
-                            if let Some(ref mut vendor) = current_vendor {
-                                vendor.devices.push(device);
-                            }
-                        }
-
-                        let (id, name) = parse_device_line(trimmed)?;
-                        current_device = Some(Device {
-                            id,
-                            name,
-                            subsystems: Vec::new(),
-                        });
-                    }
-                    2 => {
-                        // Subsystem definition (\t\tXXXX XXXX  Name)
-                        if let Some(ref mut device) = current_device {
-                            let (subvendor_id, subdevice_id, name) = parse_subsystem_line(trimmed)?;
-                            device.subsystems.push(Subsystem {
-                                subvendor_id,
-                                subdevice_id,
-                                name,
-                            });
-                        }
-                    }
-                    _ => {
-                        return Err("Invalid indentation in vendor section".to_string());
-                    }
-                }
-            }
-            ParsingMode::Classes => {
-                match indentation {
-                    0 => {
-                        // Class definition (C XX  Name)
-                        finalize_class_subclass(&mut classes, &mut current_class, &mut current_subclass);
-
-                        if trimmed.starts_with("C ") {
-                            let (id, name) = parse_class_line(trimmed)?;
-                            current_class = Some(Class {
-                                id,
-                                name,
-                                subclasses: Vec::new(),
-                            });
-                        }
-                    }
-                    1 => {
-                        // Subclass definition (\tXX  Name)
-                        if let Some(subclass) = current_subclass.take() {
-                            if let Some(ref mut class) = current_class {
-                                class.subclasses.push(subclass);
-                            }
-                        }
-
-                        let (id, name) = parse_subclass_line(trimmed)?;
-                        current_subclass = Some(SubClass {
-                            id,
-                            name,
-                            prog_interfaces: Vec::new(),
-                        });
-                    }
-                    2 => {
-                        // Programming interface definition (\t\tXX  Name)
-                        if let Some(ref mut subclass) = current_subclass {
-                            let (id, name) = parse_prog_interface_line(trimmed)?;
-                            subclass.prog_interfaces.push(ProgInterface { id, name });
-                        }
-                    }
-                    _ => {
-                        return Err("Invalid indentation in class section".to_string());
-                    }
-                }
-            }
+        let depth = line.len() - line.trim_start_matches('\t').len();
+        let keep = match section {
+            Section::Vendors => depth < 2,
+            Section::Classes => true,
+            Section::Other => false,
+        };
+        if keep {
+            kept.push_str(line);
+            kept.push('\n');
         }
     }
-
-    // Finalize any remaining items
-    finalize_vendor_device(&mut vendors, &mut current_vendor, &mut current_device);
-    finalize_class_subclass(&mut classes, &mut current_class, &mut current_subclass);
-
-    Ok(generate_database_code(&vendors, &classes))
-}
-
-#[derive(Debug, Clone, Copy)]
-enum ParsingMode {
-    Vendors,
-    Classes,
+    kept
 }
 
-fn count_leading_tabs(line: &str) -> usize {
-    line.chars().take_while(|&c| c == '\t').count()
-}
-
-fn finalize_vendor_device(
-    vendors: &mut Vec<Vendor>,
-    current_vendor: &mut Option<Vendor>,
-    current_device: &mut Option<Device>,
-) {
-    if let Some(device) = current_device.take() {
-        if let Some(ref mut vendor) = current_vendor {
-            vendor.devices.push(device);
-        }
+/// Fall back to the curated or an empty database, unless the `require-db` feature forbids it.
+fn write_fallback_database(dest_path: &Path) {
+    if cfg!(feature = "require-db") {
+        panic!("the `require-db` feature is enabled, but no PCI database could be generated from pci.ids");
     }
 
-    if let Some(vendor) = current_vendor.take() {
-        vendors.push(vendor);
+    #[cfg(feature = "fallback-db")]
+    {
+        eprintln!("Creating fallback database...");
+        parse_and_generate(include_str!("fallback.ids"), dest_path, false).expect("fallback.ids should parse");
     }
-}
 
-fn finalize_class_subclass(
-    classes: &mut Vec<Class>,
-    current_class: &mut Option<Class>,
-    current_subclass: &mut Option<SubClass>,
-) {
-    if let Some(subclass) = current_subclass.take() {
-        if let Some(ref mut class) = current_class {
-            class.subclasses.push(subclass);
-        }
+    #[cfg(not(feature = "fallback-db"))]
+    {
+        eprintln!("Creating empty database...");
+        parse_and_generate("", dest_path, false).expect("an empty database should generate");
     }
+}
 
-    if let Some(class) = current_class.take() {
-        classes.push(class);
-    }
+/// Adapts an `io::Write` to the `fmt::Write` sink the code generator streams to.
+struct IoSink<W: Write> {
+    inner: W,
+    error: Option<io::Error>,
 }
 
-fn parse_vendor_line(line: &str) -> Result<(u16, String), String> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-    if parts.len() != 2 {
-        return Err("Invalid vendor line format".to_string());
+impl<W: Write> core::fmt::Write for IoSink<W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            core::fmt::Error
+        })
     }
-
-    let id = u16::from_str_radix(parts[0].trim(), 16)
-        .map_err(|_| "Invalid vendor ID".to_string())?;
-    let name = parts[1].trim().to_string();
-
-    Ok((id, name))
 }
 
-fn parse_device_line(line: &str) -> Result<(u16, String), String> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-    if parts.len() != 2 {
-        return Err("Invalid device line format".to_string());
+/// Read a PCI IDs document with the library's own line parser, keeping the snapshot fields of the header.
+fn parse_document(content: &str) -> Result<builders::ParsedDatabase, String> {
+    let mut lines = LineParser::new(Indentation::Auto, Separator::TwoSpaces, ParserConfig::new());
+    let mut tree = TreeBuilder::<String>::new(false);
+    for line in split_lines(content) {
+        match lines.parse_line(line).map_err(|e| e.to_string())? {
+            Some(event) => tree.push(event),
+            None => tree.comment(line),
+        }
     }
-
-    let id = u16::from_str_radix(parts[0].trim(), 16)
-        .map_err(|_| "Invalid device ID".to_string())?;
-    let name = parts[1].trim().to_string();
-
-    Ok((id, name))
+    Ok(tree.finish())
 }
 
-fn parse_subsystem_line(line: &str) -> Result<(u16, u16, String), String> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-    if parts.len() != 2 {
-        return Err("Invalid subsystem line format".to_string());
+/// Warn when the pci.ids snapshot is older than `IDS_RS_STALE_MONTHS` months (default 12, `0` disables).
+///
+/// Devices released after the snapshot show up as unknown, so an old file is
+/// worth refreshing. The build date is `SOURCE_DATE_EPOCH` when set, so
+/// reproducible builds stay reproducible.
+fn warn_if_stale(date: &str) {
+    let months = match env::var("IDS_RS_STALE_MONTHS") {
+        Ok(value) => value.trim().parse::<i64>().unwrap_or_else(|_| panic!("IDS_RS_STALE_MONTHS must be a number of months, not `{}`", value)),
+        Err(_) => 12,
+    };
+    if months <= 0 {
+        return;
     }
+    let now = match env::var("SOURCE_DATE_EPOCH") {
+        Ok(value) => value.trim().parse::<i64>().unwrap_or_else(|_| panic!("SOURCE_DATE_EPOCH must be a Unix timestamp, not `{}`", value)),
+        Err(_) => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64),
+    };
 
-    let ids: Vec<&str> = parts[0].split_whitespace().collect();
-    if ids.len() != 2 {
-        return Err("Invalid subsystem ID format".to_string());
+    // The header date looks like `2025-07-11 03:15:02`; ignore anything else
+    let mut fields = date.split([' ', '-']).map(|field| field.parse::<i64>().ok());
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day))) = (fields.next(), fields.next(), fields.next()) else {
+        return;
+    };
+    let age_days = now.div_euclid(86_400) - days_from_civil(year, month, day);
+    let age_months = age_days * 12 / 365;
+    if age_months >= months {
+        println!(
+            "cargo:warning=pci.ids is from {} ({} months old); newer devices will be reported as unknown. \
+             Run update_pci_ids.sh to refresh it, or set IDS_RS_STALE_MONTHS=0 to silence this warning",
+            date.split(' ').next().unwrap_or(date), age_months
+        );
     }
+}
 
-    let subvendor_id = u16::from_str_radix(ids[0].trim(), 16)
-        .map_err(|_| "Invalid subvendor ID".to_string())?;
-    let subdevice_id = u16::from_str_radix(ids[1].trim(), 16)
-        .map_err(|_| "Invalid subdevice ID".to_string())?;
-    let name = parts[1].trim().to_string();
-
-    Ok((subvendor_id, subdevice_id, name))
+/// Count the days from 1970-01-01 to a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
 }
 
-fn parse_class_line(line: &str) -> Result<(u8, String), String> {
-    if !line.starts_with("C ") {
-        return Err("Invalid class line format".to_string());
+/// Local override files, in increasing order of precedence: the `*.ids`
+/// fragments in `pci.ids.d/` by file name, `pci.ids.local`, and the file or
+/// directory named by the `IDS_RS_LOCAL_IDS` environment variable.
+fn override_files() -> Vec<PathBuf> {
+    let from_env = env::var_os("IDS_RS_LOCAL_IDS").map(PathBuf::from);
+    if let Some(path) = &from_env {
+        if !path.exists() {
+            panic!("IDS_RS_LOCAL_IDS names {}, which does not exist", path.display());
+        }
+        println!("cargo:rerun-if-changed={}", path.display());
     }
 
-    let rest = &line[2..]; // Skip "C "
-    let parts: Vec<&str> = rest.splitn(2, "  ").collect();
-    if parts.len() != 2 {
-        return Err("Invalid class line format".to_string());
+    let mut files = Vec::new();
+    for source in [Some(PathBuf::from("pci.ids.d")), Some(PathBuf::from("pci.ids.local")), from_env].into_iter().flatten() {
+        if source.is_dir() {
+            let entries = fs::read_dir(&source).unwrap_or_else(|e| panic!("cannot read {}: {}", source.display(), e));
+            let mut fragments: Vec<PathBuf> = entries
+                .map(|entry| entry.unwrap_or_else(|e| panic!("cannot read {}: {}", source.display(), e)).path())
+                .filter(|path| path.is_file() && path.extension().is_some_and(|extension| extension == "ids"))
+                .collect();
+            fragments.sort();
+            files.extend(fragments);
+        } else if source.is_file() {
+            files.push(source);
+        }
     }
-
-    let id = u8::from_str_radix(parts[0].trim(), 16)
-        .map_err(|_| "Invalid class ID".to_string())?;
-    let name = parts[1].trim().to_string();
-
-    Ok((id, name))
+    files
 }
 
-fn parse_subclass_line(line: &str) -> Result<(u8, String), String> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-    if parts.len() != 2 {
-        return Err("Invalid subclass line format".to_string());
+fn parse_and_generate(content: &str, dest_path: &Path, check_age: bool) -> Result<(), String> {
+    let mut database = parse_document(content)?;
+
+    // Lookups binary-search by ID, so restore ID order if the file is not sorted
+    if !database.sort().is_empty() {
+        println!("cargo:warning=pci.ids is not sorted by ID; entries were reordered");
+    }
+    // A duplicate would silently shadow an entry, so fail the build instead of falling back
+    if let Some(key) = database.find_duplicate() {
+        panic!("pci.ids defines {} more than once", key);
+    }
+    if let Some(date) = database.date.as_deref().filter(|_| check_age) {
+        warn_if_stale(date);
     }
 
-    let id = u8::from_str_radix(parts[0].trim(), 16)
-        .map_err(|_| "Invalid subclass ID".to_string())?;
-    let name = parts[1].trim().to_string();
+    // Local overrides rename and add entries; they are written on purpose, so
+    // any problem with them fails the build instead of falling back
+    for path in override_files() {
+        let mut local = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_document(&content))
+            .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+        local.sort();
+        if let Some(key) = local.find_duplicate() {
+            panic!("{} defines {} more than once", path.display(), key);
+        }
+        // The database still describes the pci.ids snapshot it was built from
+        local.version = None;
+        local.date = None;
+        database.merge(local);
+        eprintln!("Merged local overrides from {}", path.display());
+    }
 
-    Ok((id, name))
-}
+    write_well_known_vendors(&database, dest_path)?;
 
-fn parse_prog_interface_line(line: &str) -> Result<(u8, String), String> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-    if parts.len() != 2 {
-        return Err("Invalid programming interface line format".to_string());
+    let options = gen_options();
+    if cfg!(feature = "json-sidecar") {
+        write_json_file(&database, &options, dest_path)?;
+    }
+    // The blob comes in addition to the Rust source, so `PciDatabase::get`
+    // stays complete whichever crate enables the feature
+    if cfg!(feature = "blob") {
+        write_blob_file(&database, &options, dest_path)?;
     }
 
-    let id = u8::from_str_radix(parts[0].trim(), 16)
-        .map_err(|_| "Invalid programming interface ID".to_string())?;
-    let name = parts[1].trim().to_string();
-
-    Ok((id, name))
+    let report = write_database(&database, &options, dest_path)?;
+    if cfg!(feature = "build-report") {
+        print_size_report("PCI", &report);
+    }
+    Ok(())
 }
 
-fn generate_database_code(vendors: &[Vendor], classes: &[Class]) -> String {
-    let mut code = String::new();
+/// The generator options selected by the crate features.
+fn gen_options() -> GenOptions {
+    // Features are additive, so asking for both halves embeds both
+    let classes_only = cfg!(feature = "classes-only") && !cfg!(feature = "vendors-only");
+    let vendors_only = cfg!(feature = "vendors-only") && !cfg!(feature = "classes-only");
+    GenOptions::new()
+        .emit_subsystems(!cfg!(feature = "no-subsystems"))
+        .emit_prog_interfaces(!cfg!(feature = "no-prog-if"))
+        .emit_vendors(!classes_only)
+        .emit_classes(!vendors_only)
+        .compress_names(cfg!(feature = "compressed-names"))
+        .string_table(cfg!(feature = "string-table"))
+        .emit_phf(cfg!(feature = "phf"))
+        .non_ascii_names(non_ascii_names())
+        .max_name_length(max_name_length())
+}
 
-    code.push_str("// Generated PCI database from pci.ids\n");
-    code.push_str("// This file is automatically generated by the build script\n\n");
-
-    // Generate subsystem data
-    for vendor in vendors {
-        for device in &vendor.devices {
-            if !device.subsystems.is_empty() {
-                code.push_str(&format!(
-                    "static SUBSYSTEMS_{}_{}: &[Subsystem] = &[\n",
-                    vendor.id, device.id
-                ));
-                for subsystem in &device.subsystems {
-                    code.push_str(&format!(
-                        "    crate::devices::Subsystem::new(crate::types::SubvendorId::new(0x{:04x}), crate::types::SubdeviceId::new(0x{:04x}), {:?}),\n",
-                        subsystem.subvendor_id, subsystem.subdevice_id, subsystem.name
-                    ));
-                }
-                code.push_str("];\n\n");
-            }
+/// Stream the generated source of a database to `dest_path`.
+fn write_database(database: &builders::ParsedDatabase, options: &GenOptions, dest_path: &Path) -> Result<codegen::SizeReport, String> {
+    let file = fs::File::create(dest_path).map_err(|e| e.to_string())?;
+    let mut out = IoSink { inner: BufWriter::new(file), error: None };
+    match write_rust(&mut out, database, options) {
+        Ok(report) => {
+            out.inner.flush().map_err(|e| e.to_string())?;
+            Ok(report)
         }
+        Err(_) => Err(match out.error {
+            Some(e) => e.to_string(),
+            None => "database too large for the name index".to_string(),
+        }),
     }
+}
 
-    // Generate device data
-    for vendor in vendors {
-        if !vendor.devices.is_empty() {
-            code.push_str(&format!("static DEVICES_{}: &[crate::devices::Device] = &[\n", vendor.id));
-            for device in &vendor.devices {
-                let subsystems_ref = if device.subsystems.is_empty() {
-                    "&[]".to_string()
-                } else {
-                    format!("SUBSYSTEMS_{}_{}", vendor.id, device.id)
-                };
-
-                code.push_str(&format!(
-                    "    crate::devices::Device::new(crate::types::DeviceId::new(0x{:04x}), {:?}, {}),\n",
-                    device.id, device.name, subsystems_ref
-                ));
-            }
-            code.push_str("];\n\n");
-        }
+/// The form to store non-ASCII names in, chosen by the `escape-names` and `transliterate-names` features.
+///
+/// Transliteration wins if both features are enabled, since its names read
+/// as plain text on any console.
+fn non_ascii_names() -> types::NameForm {
+    if cfg!(feature = "transliterate-names") {
+        types::NameForm::Transliterated
+    } else if cfg!(feature = "escape-names") {
+        types::NameForm::Escaped
+    } else {
+        types::NameForm::Original
     }
+}
 
-    // Generate vendor data
-    code.push_str("static VENDORS: &[crate::vendors::Vendor] = &[\n");
-    for vendor in vendors {
-        let devices_ref = if vendor.devices.is_empty() {
-            "&[]".to_string()
-        } else {
-            format!("DEVICES_{}", vendor.id)
-        };
-
-        code.push_str(&format!(
-            "    crate::vendors::Vendor::new(crate::types::VendorId::new(0x{:04x}), {:?}, {}),\n",
-            vendor.id, vendor.name, devices_ref
-        ));
-    }
-    code.push_str("];\n\n");
-
-    // Generate programming interface data
-    for class in classes {
-        for subclass in &class.subclasses {
-            if !subclass.prog_interfaces.is_empty() {
-                code.push_str(&format!(
-                    "static PROG_INTERFACES_{}_{}: &[crate::classes::ProgInterface] = &[\n",
-                    class.id, subclass.id
-                ));
-                for prog_if in &subclass.prog_interfaces {
-                    code.push_str(&format!(
-                        "    crate::classes::ProgInterface::new(crate::types::ProgInterfaceId::new(0x{:02x}), {:?}),\n",
-                        prog_if.id, prog_if.name
-                    ));
-                }
-                code.push_str("];\n\n");
-            }
-        }
+/// The longest name to store in bytes, from `IDS_RS_MAX_NAME_LENGTH` (unset or `0` for no limit).
+fn max_name_length() -> Option<usize> {
+    let value = env::var("IDS_RS_MAX_NAME_LENGTH").ok()?;
+    match value.trim().parse::<usize>() {
+        Ok(0) => None,
+        Ok(max) => Some(max),
+        Err(_) => panic!("IDS_RS_MAX_NAME_LENGTH must be a number of bytes, not `{}`", value),
     }
+}
 
-    // Generate subclass data
-    for class in classes {
-        if !class.subclasses.is_empty() {
-            code.push_str(&format!(
-                "static SUBCLASSES_{}: &[crate::classes::SubClass] = &[\n",
-                class.id
-            ));
-            for subclass in &class.subclasses {
-                let prog_interfaces_ref = if subclass.prog_interfaces.is_empty() {
-                    "&[]".to_string()
-                } else {
-                    format!("PROG_INTERFACES_{}_{}", class.id, subclass.id)
-                };
-
-                code.push_str(&format!(
-                    "    crate::classes::SubClass::new(crate::types::SubClassId::new(0x{:02x}), {:?}, {}),\n",
-                    subclass.id, subclass.name, prog_interfaces_ref
-                ));
-            }
-            code.push_str("];\n\n");
-        }
+/// Write the database as a binary blob next to the generated source, for `BlobDatabase::get`.
+fn write_blob_file(database: &builders::ParsedDatabase, options: &GenOptions, dest_path: &Path) -> Result<(), String> {
+    let blob = codegen::write_blob(database, options).map_err(|e| e.to_string())?;
+    fs::write(dest_path.with_file_name("pci_database.bin"), blob).map_err(|e| e.to_string())
+}
+
+/// Write the embedded data as JSON next to the generated source, such as `pci_database.json`, for release tooling.
+fn write_json_file(database: &builders::ParsedDatabase, options: &GenOptions, dest_path: &Path) -> Result<(), String> {
+    let file = fs::File::create(dest_path.with_extension("json")).map_err(|e| e.to_string())?;
+    let mut out = IoSink { inner: BufWriter::new(file), error: None };
+    match codegen::write_json(&mut out, database, options) {
+        Ok(()) => out.inner.flush().map_err(|e| e.to_string()),
+        Err(_) => Err(match out.error {
+            Some(e) => e.to_string(),
+            None => "the database has duplicate entries".to_string(),
+        }),
     }
+}
 
-    // Generate class data
-    code.push_str("static CLASSES: &[crate::classes::DeviceClass] = &[\n");
-    for class in classes {
-        let subclasses_ref = if class.subclasses.is_empty() {
-            "&[]".to_string()
-        } else {
-            format!("SUBCLASSES_{}", class.id)
-        };
+/// Generate the `vendors::well_known` constants listed in `[package.metadata.well-known-vendors]`.
+fn write_well_known_vendors(database: &builders::ParsedDatabase, dest_path: &Path) -> Result<(), String> {
+    let manifest = fs::read_to_string("Cargo.toml").map_err(|e| e.to_string())?;
+    let top = manifest_section(&manifest, "package.metadata.well-known-vendors")
+        .into_iter()
+        .find(|(key, _)| key == "top")
+        .map_or(Ok(0), |(_, value)| value.parse::<usize>().map_err(|_| format!("invalid well-known vendor count `{}`", value)))?;
+    let curated = manifest_section(&manifest, "package.metadata.well-known-vendors.curated")
+        .into_iter()
+        .map(|(name, value)| match value.strip_prefix("0x").map(|hex| u16::from_str_radix(hex, 16)) {
+            Some(Ok(id)) => Ok((name, types::VendorId::new(id))),
+            _ => Err(format!("invalid ID `{}` for well-known vendor {}", value, name)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let curated: Vec<_> = curated.iter().map(|(name, id)| (name.as_str(), *id)).collect();
 
-        code.push_str(&format!(
-            "    crate::classes::DeviceClass::new(crate::types::DeviceClassId::new(0x{:02x}), {:?}, {}),\n",
-            class.id, class.name, subclasses_ref
-        ));
-    }
-    code.push_str("];\n\n");
+    let mut code = String::new();
+    codegen::write_well_known_vendors(&mut code, database, &curated, top, &GenOptions::new()).map_err(|e| e.to_string())?;
+    fs::write(dest_path.with_file_name("well_known_vendors.rs"), code).map_err(|e| e.to_string())
+}
 
-    // Generate the global database
-    code.push_str("/// The global PCI database instance.\n");
-    code.push_str("pub static GLOBAL_DATABASE: crate::database::PciDatabase = crate::database::PciDatabase::new(VENDORS, CLASSES);\n");
+/// Read the `key = value` pairs of one table of a TOML manifest.
+///
+/// Only the flat tables this script reads are supported: no quoting, arrays,
+/// or values spanning lines.
+fn manifest_section(manifest: &str, table: &str) -> Vec<(String, String)> {
+    let header = format!("[{}]", table);
+    manifest
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != header)
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.split('#').next().unwrap_or("").trim().to_string()))
+        .collect()
+}
 
-    code
-}
\ No newline at end of file
+/// Summarize the generated database as build warnings, sized for the target being built.
+fn print_size_report(bus: &str, report: &codegen::SizeReport) {
+    // Only the report looks at the target; the generated code stays target-independent
+    let pointer_size = env::var("CARGO_CFG_TARGET_POINTER_WIDTH")
+        .ok()
+        .and_then(|bits| bits.parse::<usize>().ok())
+        .map_or(8, |bits| bits / 8);
+    println!("cargo:warning={} database: {}", bus, report);
+    println!(
+        "cargo:warning={} database: about {} KiB of static data on this target",
+        bus, report.estimated_bytes(pointer_size).div_ceil(1024)
+    );
+}
@@ -0,0 +1,203 @@
+//! End-to-end device identification, as a kernel would do it.
+//!
+//! The `kernel` module below only uses `core` and `alloc`, like code in a
+//! `no_std` kernel. It scans a PCI bus through a config space access trait,
+//! walks each function's capability list, and names everything with ids_rs.
+//! The config space here is a mock, so the example runs anywhere:
+//!
+//! ```bash
+//! cargo run --example kernel_identify
+//! ```
+
+use ids_rs::VendorId;
+
+mod kernel {
+    extern crate alloc;
+
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use ids_rs::{ClassCode, DeviceId, PciDatabase, SubdeviceId, SubvendorId, VendorId};
+
+    /// Config space access, implemented by the platform (port I/O, ECAM, ...).
+    pub trait ConfigAccess {
+        /// Read a 32-bit register at a dword-aligned offset.
+        fn read_u32(&self, bus: u8, device: u8, function: u8, offset: u8) -> u32;
+    }
+
+    /// What the kernel learned about one PCI function.
+    #[derive(Debug)]
+    pub struct IdentifiedFunction {
+        pub bus: u8,
+        pub device: u8,
+        pub function: u8,
+        pub vendor_id: VendorId,
+        pub device_id: DeviceId,
+        pub class_code: ClassCode,
+        pub subsystem: Option<(SubvendorId, SubdeviceId)>,
+        pub capabilities: Vec<u8>,
+        pub name: String,
+        pub class_description: String,
+    }
+
+    const HEADER_TYPE_MULTIFUNCTION: u32 = 0x80;
+    const STATUS_CAPABILITIES_LIST: u32 = 0x10;
+
+    /// Scan every device and function on a bus and identify what is present.
+    pub fn scan_bus(access: &impl ConfigAccess, db: &PciDatabase, bus: u8) -> Vec<IdentifiedFunction> {
+        let mut found = Vec::new();
+        for device in 0..32 {
+            let header = access.read_u32(bus, device, 0, 0x0c);
+            let functions = if (header >> 16) & HEADER_TYPE_MULTIFUNCTION != 0 { 8 } else { 1 };
+            for function in 0..functions {
+                if let Some(identified) = identify(access, db, bus, device, function) {
+                    found.push(identified);
+                }
+            }
+        }
+        found
+    }
+
+    fn identify(access: &impl ConfigAccess, db: &PciDatabase, bus: u8, device: u8, function: u8) -> Option<IdentifiedFunction> {
+        let id = access.read_u32(bus, device, function, 0x00);
+        if id & 0xffff == 0xffff {
+            return None;
+        }
+        let vendor_id = VendorId::new(id as u16);
+        let device_id = DeviceId::new((id >> 16) as u16);
+        let class_code = ClassCode::from_value(access.read_u32(bus, device, function, 0x08) >> 8);
+
+        // Subsystem IDs only exist in type 0 (endpoint) headers
+        let header_type = (access.read_u32(bus, device, function, 0x0c) >> 16) & 0x7f;
+        let subsystem = match access.read_u32(bus, device, function, 0x2c) {
+            ids if header_type == 0 && ids != 0 => Some((SubvendorId::new(ids as u16), SubdeviceId::new((ids >> 16) as u16))),
+            _ => None,
+        };
+
+        let mut name = db.device_name_with_fallback(vendor_id, device_id, Some(class_code));
+        if let Some((subvendor_id, subdevice_id)) = subsystem {
+            if db.find_subsystem(vendor_id, device_id, subvendor_id, subdevice_id).is_some() {
+                name = alloc::format!("{} [{}]", name, db.subsystem_name(vendor_id, device_id, subvendor_id, subdevice_id));
+            }
+        }
+
+        Some(IdentifiedFunction {
+            bus,
+            device,
+            function,
+            vendor_id,
+            device_id,
+            class_code,
+            subsystem,
+            capabilities: capabilities(access, bus, device, function),
+            name,
+            class_description: db.describe_class(class_code),
+        })
+    }
+
+    /// Walk the capability list and return the capability IDs in order.
+    fn capabilities(access: &impl ConfigAccess, bus: u8, device: u8, function: u8) -> Vec<u8> {
+        let mut ids = Vec::new();
+        let status = access.read_u32(bus, device, function, 0x04) >> 16;
+        if status & STATUS_CAPABILITIES_LIST == 0 {
+            return ids;
+        }
+
+        let mut pointer = (access.read_u32(bus, device, function, 0x34) & 0xfc) as u8;
+        // A malformed list could loop forever; there is room for at most 48 entries
+        while pointer != 0 && ids.len() < 48 {
+            let header = access.read_u32(bus, device, function, pointer);
+            ids.push(header as u8);
+            pointer = ((header >> 8) & 0xfc) as u8;
+        }
+        ids
+    }
+
+    /// Name a capability ID.
+    pub fn capability_name(id: u8) -> &'static str {
+        match id {
+            0x01 => "Power Management",
+            0x05 => "MSI",
+            0x09 => "Vendor Specific",
+            0x10 => "PCI Express",
+            0x11 => "MSI-X",
+            _ => "Other",
+        }
+    }
+}
+
+/// A mock config space holding a few functions on bus 0.
+struct MockConfigSpace {
+    functions: Vec<((u8, u8, u8), [u32; 64])>,
+}
+
+impl MockConfigSpace {
+    fn new() -> Self {
+        let mut functions = Vec::new();
+
+        // 00:02.0 Intel I210 Ethernet with subsystem and PM -> MSI -> PCIe capabilities
+        let mut nic = [0u32; 64];
+        write(&mut nic, 0x00, 0x1533_8086);
+        write(&mut nic, 0x04, 0x0010_0000);
+        write(&mut nic, 0x08, 0x0200_0003);
+        write(&mut nic, 0x2c, 0x0001_8086);
+        write(&mut nic, 0x34, 0x40);
+        write(&mut nic, 0x40, 0x0000_5001);
+        write(&mut nic, 0x50, 0x0000_7005);
+        write(&mut nic, 0x70, 0x0000_0010);
+        functions.push(((0, 2, 0), nic));
+
+        // 00:03.0 Samsung NVMe SSD with MSI-X
+        let mut nvme = [0u32; 64];
+        write(&mut nvme, 0x00, 0xa808_144d);
+        write(&mut nvme, 0x04, 0x0010_0000);
+        write(&mut nvme, 0x08, 0x0108_0200);
+        write(&mut nvme, 0x34, 0x60);
+        write(&mut nvme, 0x60, 0x0000_0011);
+        functions.push(((0, 3, 0), nvme));
+
+        // 00:04.0 and 00:04.1: a multi-function device with an ID not in pci.ids
+        let mut usb = [0u32; 64];
+        write(&mut usb, 0x00, 0xfffe_8086);
+        write(&mut usb, 0x08, 0x0c03_3000);
+        write(&mut usb, 0x0c, 0x0080_0000);
+        functions.push(((0, 4, 0), usb));
+        let mut smbus = [0u32; 64];
+        write(&mut smbus, 0x00, 0xfffd_8086);
+        write(&mut smbus, 0x08, 0x0c05_0000);
+        functions.push(((0, 4, 1), smbus));
+
+        Self { functions }
+    }
+}
+
+fn write(registers: &mut [u32; 64], offset: u8, value: u32) {
+    registers[usize::from(offset / 4)] = value;
+}
+
+impl kernel::ConfigAccess for MockConfigSpace {
+    fn read_u32(&self, bus: u8, device: u8, function: u8, offset: u8) -> u32 {
+        self.functions
+            .iter()
+            .find(|(address, _)| *address == (bus, device, function))
+            .map_or(0xffff_ffff, |(_, registers)| registers[usize::from(offset / 4)])
+    }
+}
+
+fn main() {
+    let db = ids_rs::PciDatabase::get();
+    let config_space = MockConfigSpace::new();
+
+    for function in kernel::scan_bus(&config_space, db, 0) {
+        println!(
+            "{:02x}:{:02x}.{} [{}:{}] {}",
+            function.bus, function.device, function.function, function.vendor_id, function.device_id, function.name
+        );
+        println!("    Class {}: {}", function.class_code, function.class_description);
+        if let Some((subvendor_id, subdevice_id)) = function.subsystem {
+            println!("    Subsystem: {} ({}:{})", db.vendor_name(VendorId::new(subvendor_id.value())), subvendor_id, subdevice_id);
+        }
+        for id in &function.capabilities {
+            println!("    Capability {:02x}: {}", id, kernel::capability_name(*id));
+        }
+    }
+}
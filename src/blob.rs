@@ -0,0 +1,509 @@
+//! Zero-copy access to databases stored in the compact binary format.
+//!
+//! Compiling the Rust source generated for the full `pci.ids` dominates clean
+//! builds. A blob written by [`write_blob`](crate::codegen::write_blob) and
+//! embedded with `include_bytes!` costs next to nothing to compile, and
+//! [`BlobDatabase`] reads entries straight out of it: lookups binary-search
+//! fixed-size little-endian records and names are slices of the blob, so
+//! nothing is decoded or allocated.
+//!
+//! Downstream build scripts can write a blob in place of Rust source and open
+//! it with [`BlobDatabase::new`]. The `blob` feature embeds a blob of the
+//! crate's own database as well, read with `BlobDatabase::get`; it comes in
+//! addition to the global [`PciDatabase`](crate::PciDatabase), which stays
+//! complete.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ids_rs::blob::BlobDatabase;
+//! use ids_rs::{DeviceId, VendorId};
+//!
+//! # #[cfg(feature = "blob")]
+//! if let Some(device) = BlobDatabase::get().find_device(VendorId::new(0x8086), DeviceId::new(0x1533)) {
+//!     println!("{}", device.name());
+//! }
+//! ```
+
+use core::ops::Range;
+
+use crate::codegen::{BLOB_HEADER_LEN, BLOB_MAGIC, BLOB_RECORD_LEN, BLOB_VERSION};
+use crate::database::DatabaseStats;
+use crate::error::{PciError, PciResult};
+use crate::types::*;
+
+/// A table of fixed-size records: key, name offset, name length, first child.
+#[derive(Debug, Clone, Copy)]
+struct Records {
+    bytes: &'static [u8],
+}
+
+impl Records {
+    /// Get the number of records.
+    const fn len(&self) -> usize {
+        self.bytes.len() / BLOB_RECORD_LEN
+    }
+
+    /// Read a field of a record, or zero if the record does not exist.
+    const fn field(&self, index: usize, field: usize) -> u32 {
+        read_u32(self.bytes, index * BLOB_RECORD_LEN + field * 4)
+    }
+
+    const fn key(&self, index: usize) -> u32 {
+        self.field(index, 0)
+    }
+
+    /// Get the positions of a record's children in `children`.
+    fn children(&self, index: usize, children: &Records) -> Range<usize> {
+        let start = self.field(index, 3) as usize;
+        let end = if index + 1 < self.len() { self.field(index + 1, 3) as usize } else { children.len() };
+        start.min(end)..end.min(children.len())
+    }
+
+    /// Find the record with a key among the records in `range`.
+    fn find(&self, range: Range<usize>, key: u32) -> Option<usize> {
+        let (mut low, mut high) = (range.start, range.end);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.key(mid).cmp(&key) {
+                core::cmp::Ordering::Equal => return Some(mid),
+                core::cmp::Ordering::Less => low = mid + 1,
+                core::cmp::Ordering::Greater => high = mid,
+            }
+        }
+        None
+    }
+}
+
+/// Read a little-endian `u32`, or zero if it is out of bounds.
+const fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    if offset + 4 > bytes.len() {
+        return 0;
+    }
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+/// Get `len` bytes at `offset`, if they are in bounds.
+const fn sub_slice(bytes: &'static [u8], offset: usize, len: usize) -> Option<&'static [u8]> {
+    if offset > bytes.len() || len > bytes.len() - offset {
+        return None;
+    }
+    let (_, rest) = bytes.split_at(offset);
+    let (slice, _) = rest.split_at(len);
+    Some(slice)
+}
+
+/// A database read in place from a binary blob.
+///
+/// Vendors, devices, and classes are found by binary search, as in
+/// [`PciDatabase`](crate::PciDatabase); the entries returned are lightweight
+/// views into the blob.
+#[derive(Debug, Clone, Copy)]
+pub struct BlobDatabase {
+    tables: [Records; 6],
+    strings: &'static str,
+}
+
+impl BlobDatabase {
+    /// Open a blob produced by [`write_blob`](crate::codegen::write_blob).
+    ///
+    /// This checks the header, that every table lies within the blob, and
+    /// that the names are valid UTF-8. It is a `const fn`, so an embedded blob
+    /// can be checked at compile time.
+    ///
+    /// Fails with [`PciError::InvalidFormat`] if the bytes are not a blob of
+    /// the supported format version.
+    pub const fn new(bytes: &'static [u8]) -> PciResult<Self> {
+        if bytes.len() < BLOB_HEADER_LEN
+            || bytes[0] != BLOB_MAGIC[0]
+            || bytes[1] != BLOB_MAGIC[1]
+            || bytes[2] != BLOB_MAGIC[2]
+            || bytes[3] != BLOB_MAGIC[3]
+            || read_u32(bytes, 4) != BLOB_VERSION
+        {
+            return Err(PciError::InvalidFormat);
+        }
+
+        let mut tables = [Records { bytes: &[] }; 6];
+        let mut i = 0;
+        while i < tables.len() {
+            let offset = read_u32(bytes, 8 + i * 8) as usize;
+            let count = read_u32(bytes, 12 + i * 8) as usize;
+            let len = match count.checked_mul(BLOB_RECORD_LEN) {
+                Some(len) => len,
+                None => return Err(PciError::InvalidFormat),
+            };
+            match sub_slice(bytes, offset, len) {
+                Some(records) => tables[i] = Records { bytes: records },
+                None => return Err(PciError::InvalidFormat),
+            }
+            i += 1;
+        }
+
+        let strings = match sub_slice(bytes, read_u32(bytes, 56) as usize, read_u32(bytes, 60) as usize) {
+            Some(strings) => strings,
+            None => return Err(PciError::InvalidFormat),
+        };
+        match core::str::from_utf8(strings) {
+            Ok(strings) => Ok(Self { tables, strings }),
+            Err(_) => Err(PciError::InvalidFormat),
+        }
+    }
+
+    /// Get the database embedded by the `blob` feature.
+    ///
+    /// The blob is checked when the crate is compiled, so this is free.
+    #[cfg(feature = "blob")]
+    pub const fn get() -> &'static Self {
+        &GLOBAL_BLOB
+    }
+
+    const fn vendors_table(&self) -> &Records {
+        &self.tables[0]
+    }
+
+    /// Get the name stored in a record.
+    fn name(&self, table: usize, index: usize) -> &'static str {
+        let records = &self.tables[table];
+        let (offset, len) = (records.field(index, 1) as usize, records.field(index, 2) as usize);
+        self.strings.get(offset..offset.saturating_add(len)).unwrap_or("")
+    }
+
+    /// Get the number of vendors.
+    pub const fn vendor_count(&self) -> usize {
+        self.vendors_table().len()
+    }
+
+    /// Iterate over all vendors in ID order.
+    pub fn vendors(&self) -> impl Iterator<Item = BlobVendor<'_>> {
+        (0..self.vendor_count()).map(move |index| BlobVendor { db: self, index })
+    }
+
+    /// Iterate over all device classes in ID order.
+    pub fn classes(&self) -> impl Iterator<Item = BlobClass<'_>> {
+        (0..self.tables[3].len()).map(move |index| BlobClass { db: self, index })
+    }
+
+    /// Find a vendor by ID.
+    pub fn find_vendor(&self, vendor_id: VendorId) -> Option<BlobVendor<'_>> {
+        let index = self.tables[0].find(0..self.vendor_count(), u32::from(vendor_id.value()))?;
+        Some(BlobVendor { db: self, index })
+    }
+
+    /// Find a device by vendor and device ID.
+    pub fn find_device(&self, vendor_id: VendorId, device_id: DeviceId) -> Option<BlobDevice<'_>> {
+        self.find_vendor(vendor_id)?.find_device(device_id)
+    }
+
+    /// Find a subsystem by vendor, device, subvendor, and subdevice IDs.
+    pub fn find_subsystem(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+    ) -> Option<BlobSubsystem<'_>> {
+        self.find_device(vendor_id, device_id)?.find_subsystem(subvendor_id, subdevice_id)
+    }
+
+    /// Find a device class by ID.
+    pub fn find_class(&self, class_id: DeviceClassId) -> Option<BlobClass<'_>> {
+        let index = self.tables[3].find(0..self.tables[3].len(), u32::from(class_id.value()))?;
+        Some(BlobClass { db: self, index })
+    }
+
+    /// Find a subclass by class and subclass ID.
+    pub fn find_subclass(&self, class_id: DeviceClassId, subclass_id: SubClassId) -> Option<BlobSubClass<'_>> {
+        self.find_class(class_id)?.find_subclass(subclass_id)
+    }
+
+    /// Find a programming interface by class, subclass, and interface ID.
+    pub fn find_prog_interface(
+        &self,
+        class_id: DeviceClassId,
+        subclass_id: SubClassId,
+        prog_if_id: ProgInterfaceId,
+    ) -> Option<BlobProgInterface<'_>> {
+        self.find_subclass(class_id, subclass_id)?.find_prog_interface(prog_if_id)
+    }
+
+    /// Get statistics about the database.
+    pub const fn stats(&self) -> DatabaseStats {
+        DatabaseStats {
+            vendor_count: self.tables[0].len(),
+            device_count: self.tables[1].len(),
+            subsystem_count: self.tables[2].len(),
+            class_count: self.tables[3].len(),
+            subclass_count: self.tables[4].len(),
+            prog_interface_count: self.tables[5].len(),
+        }
+    }
+}
+
+/// A vendor in a [`BlobDatabase`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlobVendor<'a> {
+    db: &'a BlobDatabase,
+    index: usize,
+}
+
+impl<'a> BlobVendor<'a> {
+    /// Get the vendor ID.
+    pub fn id(&self) -> VendorId {
+        VendorId::new(self.db.tables[0].key(self.index) as u16)
+    }
+
+    /// Get the vendor name.
+    pub fn name(&self) -> &'static str {
+        self.db.name(0, self.index)
+    }
+
+    /// Iterate over the vendor's devices in ID order.
+    pub fn devices(&self) -> impl Iterator<Item = BlobDevice<'a>> {
+        let db = self.db;
+        db.tables[0].children(self.index, &db.tables[1]).map(move |index| BlobDevice { db, index })
+    }
+
+    /// Find one of the vendor's devices by ID.
+    pub fn find_device(&self, device_id: DeviceId) -> Option<BlobDevice<'a>> {
+        let tables = &self.db.tables;
+        let index = tables[1].find(tables[0].children(self.index, &tables[1]), u32::from(device_id.value()))?;
+        Some(BlobDevice { db: self.db, index })
+    }
+}
+
+/// A device in a [`BlobDatabase`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlobDevice<'a> {
+    db: &'a BlobDatabase,
+    index: usize,
+}
+
+impl<'a> BlobDevice<'a> {
+    /// Get the device ID.
+    pub fn id(&self) -> DeviceId {
+        DeviceId::new(self.db.tables[1].key(self.index) as u16)
+    }
+
+    /// Get the device name.
+    pub fn name(&self) -> &'static str {
+        self.db.name(1, self.index)
+    }
+
+    /// Iterate over the device's subsystems in ID order.
+    pub fn subsystems(&self) -> impl Iterator<Item = BlobSubsystem<'a>> {
+        let db = self.db;
+        db.tables[1].children(self.index, &db.tables[2]).map(move |index| BlobSubsystem { db, index })
+    }
+
+    /// Find one of the device's subsystems by subvendor and subdevice ID.
+    pub fn find_subsystem(&self, subvendor_id: SubvendorId, subdevice_id: SubdeviceId) -> Option<BlobSubsystem<'a>> {
+        let tables = &self.db.tables;
+        let key = u32::from(subvendor_id.value()) << 16 | u32::from(subdevice_id.value());
+        let index = tables[2].find(tables[1].children(self.index, &tables[2]), key)?;
+        Some(BlobSubsystem { db: self.db, index })
+    }
+}
+
+/// A subsystem in a [`BlobDatabase`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlobSubsystem<'a> {
+    db: &'a BlobDatabase,
+    index: usize,
+}
+
+impl BlobSubsystem<'_> {
+    /// Get the subvendor ID.
+    pub fn subvendor_id(&self) -> SubvendorId {
+        SubvendorId::new((self.db.tables[2].key(self.index) >> 16) as u16)
+    }
+
+    /// Get the subdevice ID.
+    pub fn subdevice_id(&self) -> SubdeviceId {
+        SubdeviceId::new(self.db.tables[2].key(self.index) as u16)
+    }
+
+    /// Get the subsystem name.
+    pub fn name(&self) -> &'static str {
+        self.db.name(2, self.index)
+    }
+}
+
+/// A device class in a [`BlobDatabase`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlobClass<'a> {
+    db: &'a BlobDatabase,
+    index: usize,
+}
+
+impl<'a> BlobClass<'a> {
+    /// Get the class ID.
+    pub fn id(&self) -> DeviceClassId {
+        DeviceClassId::new(self.db.tables[3].key(self.index) as u8)
+    }
+
+    /// Get the class name.
+    pub fn name(&self) -> &'static str {
+        self.db.name(3, self.index)
+    }
+
+    /// Iterate over the class's subclasses in ID order.
+    pub fn subclasses(&self) -> impl Iterator<Item = BlobSubClass<'a>> {
+        let db = self.db;
+        db.tables[3].children(self.index, &db.tables[4]).map(move |index| BlobSubClass { db, index })
+    }
+
+    /// Find one of the class's subclasses by ID.
+    pub fn find_subclass(&self, subclass_id: SubClassId) -> Option<BlobSubClass<'a>> {
+        let tables = &self.db.tables;
+        let index = tables[4].find(tables[3].children(self.index, &tables[4]), u32::from(subclass_id.value()))?;
+        Some(BlobSubClass { db: self.db, index })
+    }
+}
+
+/// A subclass in a [`BlobDatabase`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlobSubClass<'a> {
+    db: &'a BlobDatabase,
+    index: usize,
+}
+
+impl<'a> BlobSubClass<'a> {
+    /// Get the subclass ID.
+    pub fn id(&self) -> SubClassId {
+        SubClassId::new(self.db.tables[4].key(self.index) as u8)
+    }
+
+    /// Get the subclass name.
+    pub fn name(&self) -> &'static str {
+        self.db.name(4, self.index)
+    }
+
+    /// Iterate over the subclass's programming interfaces in ID order.
+    pub fn prog_interfaces(&self) -> impl Iterator<Item = BlobProgInterface<'a>> {
+        let db = self.db;
+        db.tables[4].children(self.index, &db.tables[5]).map(move |index| BlobProgInterface { db, index })
+    }
+
+    /// Find one of the subclass's programming interfaces by ID.
+    pub fn find_prog_interface(&self, prog_if_id: ProgInterfaceId) -> Option<BlobProgInterface<'a>> {
+        let tables = &self.db.tables;
+        let index = tables[5].find(tables[4].children(self.index, &tables[5]), u32::from(prog_if_id.value()))?;
+        Some(BlobProgInterface { db: self.db, index })
+    }
+}
+
+/// A programming interface in a [`BlobDatabase`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlobProgInterface<'a> {
+    db: &'a BlobDatabase,
+    index: usize,
+}
+
+impl BlobProgInterface<'_> {
+    /// Get the programming interface ID.
+    pub fn id(&self) -> ProgInterfaceId {
+        ProgInterfaceId::new(self.db.tables[5].key(self.index) as u8)
+    }
+
+    /// Get the programming interface name.
+    pub fn name(&self) -> &'static str {
+        self.db.name(5, self.index)
+    }
+}
+
+/// The database embedded by the `blob` feature, checked at compile time.
+#[cfg(feature = "blob")]
+static GLOBAL_BLOB: BlobDatabase = match BlobDatabase::new(include_bytes!(concat!(env!("OUT_DIR"), "/pci_database.bin"))) {
+    Ok(db) => db,
+    Err(_) => panic!("the embedded PCI database blob is invalid"),
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::{write_blob, GenOptions};
+    use crate::parser::PciIdsParser;
+    use alloc::vec::Vec;
+
+    const SAMPLE: &str = "\
+8086  Intel Corporation
+\t1533  I210 Gigabit Network Connection
+\t\t8086 0001  Ethernet Server Adapter I210-T1
+\t\t8086 0002  Ethernet Server Adapter I210-T1
+\t1539  I211 Gigabit Network Connection
+1234  Test Vendor
+C 02  Network controller
+\t00  Ethernet controller
+C 0c  Serial bus controller
+\t03  USB controller
+\t\t30  XHCI
+";
+
+    fn blob(options: &GenOptions) -> &'static [u8] {
+        let mut parser = PciIdsParser::new();
+        parser.parse(SAMPLE).unwrap();
+        write_blob(&parser.into_parsed(), options).unwrap().leak()
+    }
+
+    #[test]
+    fn test_blob_lookups() {
+        let db = BlobDatabase::new(blob(&GenOptions::new())).unwrap();
+
+        let ids: Vec<_> = db.vendors().map(|v| v.id().value()).collect();
+        assert_eq!(ids, [0x1234, 0x8086]);
+        assert_eq!(db.find_vendor(VendorId::new(0x1234)).unwrap().devices().count(), 0);
+
+        let device = db.find_device(VendorId::new(0x8086), DeviceId::new(0x1539)).unwrap();
+        assert_eq!((device.id(), device.name()), (DeviceId::new(0x1539), "I211 Gigabit Network Connection"));
+        assert_eq!(device.subsystems().count(), 0);
+        let subsystem = db
+            .find_subsystem(VendorId::new(0x8086), DeviceId::new(0x1533), SubvendorId::new(0x8086), SubdeviceId::new(0x0002))
+            .unwrap();
+        assert_eq!((subsystem.subvendor_id(), subsystem.subdevice_id()), (SubvendorId::new(0x8086), SubdeviceId::new(0x0002)));
+        assert_eq!(subsystem.name(), "Ethernet Server Adapter I210-T1");
+        assert!(db.find_device(VendorId::new(0x1234), DeviceId::new(0x1533)).is_none());
+        assert!(db.find_vendor(VendorId::new(0xffff)).is_none());
+
+        let prog_if = db
+            .find_prog_interface(DeviceClassId::new(0x0c), SubClassId::new(0x03), ProgInterfaceId::new(0x30))
+            .unwrap();
+        assert_eq!(prog_if.name(), "XHCI");
+        let subclasses: Vec<_> = db.find_class(DeviceClassId::new(0x02)).unwrap().subclasses().map(|s| s.name()).collect();
+        assert_eq!(subclasses, ["Ethernet controller"]);
+        assert_eq!(db.classes().count(), 2);
+
+        let stats = db.stats();
+        assert_eq!((stats.vendor_count, stats.device_count, stats.subsystem_count), (2, 2, 2));
+        assert_eq!(stats.total_entries(), 11);
+    }
+
+    #[test]
+    fn test_blob_options() {
+        let db = BlobDatabase::new(blob(&GenOptions::new().emit_subsystems(false).emit_classes(false))).unwrap();
+        assert_eq!(db.stats().total_entries(), 4);
+        assert!(db.find_device(VendorId::new(0x8086), DeviceId::new(0x1533)).is_some());
+        assert!(db.find_class(DeviceClassId::new(0x02)).is_none());
+    }
+
+    #[test]
+    fn test_invalid_blobs() {
+        let valid = blob(&GenOptions::new());
+        assert_eq!(BlobDatabase::new(&valid[..BLOB_HEADER_LEN - 1]).unwrap_err(), PciError::InvalidFormat);
+        assert_eq!(BlobDatabase::new(&valid[..valid.len() - 1]).unwrap_err(), PciError::InvalidFormat);
+
+        let mut wrong_version = valid.to_vec();
+        wrong_version[4] = 2;
+        assert_eq!(BlobDatabase::new(wrong_version.leak()).unwrap_err(), PciError::InvalidFormat);
+
+        // A record count whose table size overflows on 32-bit targets
+        let mut huge_count = valid.to_vec();
+        huge_count[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(BlobDatabase::new(huge_count.leak()).unwrap_err(), PciError::InvalidFormat);
+
+        let mut invalid_name = valid.to_vec();
+        let last = invalid_name.len() - 1;
+        invalid_name[last] = 0xff;
+        assert_eq!(BlobDatabase::new(invalid_name.leak()).unwrap_err(), PciError::InvalidFormat);
+    }
+}
@@ -0,0 +1,477 @@
+//! Owned data model for parsed PCI IDs data.
+//!
+//! These types are re-exported from [`parser`](crate::parser). They only depend
+//! on the ID types, so the build script includes this module together with the
+//! line parser and [`codegen`](crate::codegen) to read `pci.ids` and generate
+//! the embedded database with the same code the library and downstream build
+//! scripts use.
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+
+use crate::error::{PciError, PciResult};
+use crate::types::*;
+
+/// Internal parser state for vendors and devices.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct VendorBuilder<S = String> {
+    /// The vendor ID
+    pub id: VendorId,
+    /// The vendor name
+    pub name: S,
+    /// The devices for this vendor
+    pub devices: Vec<DeviceBuilder<S>>,
+}
+
+/// Internal parser state for devices.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct DeviceBuilder<S = String> {
+    /// The device ID
+    pub id: DeviceId,
+    /// The device name
+    pub name: S,
+    /// The subsystems for this device
+    pub subsystems: Vec<SubsystemBuilder<S>>,
+}
+
+/// Internal parser state for subsystems.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct SubsystemBuilder<S = String> {
+    /// The subvendor ID
+    pub subvendor_id: SubvendorId,
+    /// The subdevice ID
+    pub subdevice_id: SubdeviceId,
+    /// The subsystem name
+    pub name: S,
+}
+
+/// Internal parser state for device classes.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ClassBuilder<S = String> {
+    /// The device class ID
+    pub id: DeviceClassId,
+    /// The device class name
+    pub name: S,
+    /// The subclasses for this device class
+    pub subclasses: Vec<SubClassBuilder<S>>,
+}
+
+/// Internal parser state for subclasses.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct SubClassBuilder<S = String> {
+    /// The subclass ID
+    pub id: SubClassId,
+    /// The subclass name
+    pub name: S,
+    /// The programming interfaces for this subclass
+    pub prog_interfaces: Vec<ProgInterfaceBuilder<S>>,
+}
+
+/// Internal parser state for programming interfaces.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ProgInterfaceBuilder<S = String> {
+    /// The programming interface ID
+    pub id: ProgInterfaceId,
+    /// The programming interface name
+    pub name: S,
+}
+
+/// The complete result of parsing a PCI IDs file.
+///
+/// This is the input to [`generate_rust`](crate::codegen::generate_rust) and can
+/// also be assembled by hand to generate trimmed or custom databases.
+///
+/// Names are owned `String`s by default; [`PciIdsParser::parse_borrowed`](crate::parser::PciIdsParser::parse_borrowed)
+/// produces a `ParsedDatabase<&str>` whose names borrow from the input.
+#[derive(Debug, Clone)]
+pub struct ParsedDatabase<S = String> {
+    /// The parsed vendors, sorted by ID
+    pub vendors: Vec<VendorBuilder<S>>,
+    /// The parsed device classes, sorted by ID
+    pub classes: Vec<ClassBuilder<S>>,
+    /// Comment and blank lines, if the parser was asked to retain them
+    pub comments: Comments<S>,
+    /// The `Version:` field of the file header, such as `2025.07.11`
+    pub version: Option<S>,
+    /// The `Date:` field of the file header, such as `2025-07-11 03:15:02`
+    pub date: Option<S>,
+}
+
+impl<S> Default for ParsedDatabase<S> {
+    fn default() -> Self {
+        Self::new(Vec::new(), Vec::new())
+    }
+}
+
+impl<S> ParsedDatabase<S> {
+    /// Create a parsed database from vendor and class builders.
+    pub fn new(vendors: Vec<VendorBuilder<S>>, classes: Vec<ClassBuilder<S>>) -> Self {
+        Self {
+            vendors,
+            classes,
+            comments: Comments::default(),
+            version: None,
+            date: None,
+        }
+    }
+
+    /// Record the `Version:` or `Date:` field of a header comment line, unless already set.
+    pub(crate) fn read_header_line<'a>(&mut self, line: &'a str)
+    where
+        S: From<&'a str>,
+    {
+        let Some(field) = line.trim().strip_prefix('#') else {
+            return;
+        };
+        let (key, value) = match field.split_once(':') {
+            Some((key, value)) if !value.trim().is_empty() => (key.trim(), value.trim()),
+            _ => return,
+        };
+        let target = match key {
+            "Version" => &mut self.version,
+            "Date" => &mut self.date,
+            _ => return,
+        };
+        if target.is_none() {
+            *target = Some(value.into());
+        }
+    }
+
+    /// Check that every level is sorted by ID, as lookups require.
+    ///
+    /// Returns one warning per list that is out of order; an empty result means
+    /// the database is ready for code generation as-is.
+    pub fn check_order(&self) -> Vec<ParseWarning> {
+        let mut warnings = Vec::new();
+
+        if !self.vendors.is_sorted_by_key(|v| v.id) {
+            warnings.push(ParseWarning::UnsortedVendors);
+        }
+        for vendor in &self.vendors {
+            if !vendor.devices.is_sorted_by_key(|d| d.id) {
+                warnings.push(ParseWarning::UnsortedDevices(vendor.id));
+            }
+            for device in &vendor.devices {
+                if !device.subsystems.is_sorted_by_key(|s| (s.subvendor_id, s.subdevice_id)) {
+                    warnings.push(ParseWarning::UnsortedSubsystems(vendor.id, device.id));
+                }
+            }
+        }
+
+        if !self.classes.is_sorted_by_key(|c| c.id) {
+            warnings.push(ParseWarning::UnsortedClasses);
+        }
+        for class in &self.classes {
+            if !class.subclasses.is_sorted_by_key(|s| s.id) {
+                warnings.push(ParseWarning::UnsortedSubclasses(class.id));
+            }
+            for subclass in &class.subclasses {
+                if !subclass.prog_interfaces.is_sorted_by_key(|p| p.id) {
+                    warnings.push(ParseWarning::UnsortedProgInterfaces(class.id, subclass.id));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// Sort every level by ID, returning a warning for each list that had to be reordered.
+    ///
+    /// Sorting is stable, so entries with equal IDs keep their relative order.
+    pub fn sort(&mut self) -> Vec<ParseWarning> {
+        let warnings = self.check_order();
+        if warnings.is_empty() {
+            return warnings;
+        }
+
+        self.vendors.sort_by_key(|v| v.id);
+        for vendor in &mut self.vendors {
+            vendor.devices.sort_by_key(|d| d.id);
+            for device in &mut vendor.devices {
+                device.subsystems.sort_by_key(|s| (s.subvendor_id, s.subdevice_id));
+            }
+        }
+
+        self.classes.sort_by_key(|c| c.id);
+        for class in &mut self.classes {
+            class.subclasses.sort_by_key(|s| s.id);
+            for subclass in &mut class.subclasses {
+                subclass.prog_interfaces.sort_by_key(|p| p.id);
+            }
+        }
+
+        warnings
+    }
+
+    /// Find the first entry whose ID repeats the ID of an entry in the same list.
+    ///
+    /// Only adjacent entries are compared, so the database must be sorted first
+    /// (see [`sort`](Self::sort)). Lookups binary-search each list, so a
+    /// duplicate would make one of the entries unreachable.
+    pub fn find_duplicate(&self) -> Option<EntryKey> {
+        fn repeated<T, K: PartialEq>(items: &[T], key: impl Fn(&T) -> K) -> Option<&T> {
+            items.windows(2).find(|pair| key(&pair[0]) == key(&pair[1])).map(|pair| &pair[1])
+        }
+
+        if let Some(vendor) = repeated(&self.vendors, |v| v.id) {
+            return Some(EntryKey::Vendor(vendor.id));
+        }
+        for vendor in &self.vendors {
+            if let Some(device) = repeated(&vendor.devices, |d| d.id) {
+                return Some(EntryKey::Device(vendor.id, device.id));
+            }
+            for device in &vendor.devices {
+                if let Some(s) = repeated(&device.subsystems, |s| (s.subvendor_id, s.subdevice_id)) {
+                    return Some(EntryKey::Subsystem(vendor.id, device.id, s.subvendor_id, s.subdevice_id));
+                }
+            }
+        }
+
+        if let Some(class) = repeated(&self.classes, |c| c.id) {
+            return Some(EntryKey::Class(class.id));
+        }
+        for class in &self.classes {
+            if let Some(subclass) = repeated(&class.subclasses, |s| s.id) {
+                return Some(EntryKey::SubClass(class.id, subclass.id));
+            }
+            for subclass in &class.subclasses {
+                if let Some(prog_if) = repeated(&subclass.prog_interfaces, |p| p.id) {
+                    return Some(EntryKey::ProgInterface(class.id, subclass.id, prog_if.id));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// What to do when the same ID is defined more than once.
+///
+/// Lookups binary-search by ID, so duplicates must be resolved before a parsed
+/// database is used. Vendors, devices, and subsystems are keyed by their IDs
+/// within their parent, as are classes, subclasses, and programming interfaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Fail with [`PciError::DuplicateEntry`]
+    #[default]
+    Error,
+    /// Keep the first definition and ignore later ones
+    KeepFirst,
+    /// Replace earlier definitions with the last one
+    KeepLast,
+    /// Use the last name and combine the children of all definitions,
+    /// resolving duplicate children the same way
+    MergeChildren,
+}
+
+impl<S> ParsedDatabase<S> {
+    /// Merge another database into this one, giving the other one precedence.
+    ///
+    /// An entry defined in both keeps its place, takes its name from `other`,
+    /// and gets the children of both, merged the same way. This is how
+    /// [`PciIdsParser::parse_many`](crate::parser::PciIdsParser::parse_many)
+    /// combines documents and how the build script applies local override
+    /// files. Entries defined more than once within either database are merged
+    /// too, and the result is sorted. Comments are appended, and the header
+    /// fields of `other` are only used if this database has none.
+    pub fn merge(&mut self, other: Self) {
+        self.vendors.extend(other.vendors);
+        self.classes.extend(other.classes);
+        self.comments.leading.extend(other.comments.leading);
+        self.comments.trailing.extend(other.comments.trailing);
+        self.version = self.version.take().or(other.version);
+        self.date = self.date.take().or(other.date);
+        // Only `DuplicatePolicy::Error` can fail
+        let _ = self.resolve_duplicates(DuplicatePolicy::MergeChildren);
+        self.sort();
+    }
+
+    /// Apply a duplicate policy to every level of the parsed data.
+    pub(crate) fn resolve_duplicates(&mut self, policy: DuplicatePolicy) -> PciResult<()> {
+        resolve_duplicates(&mut self.vendors, policy, |v| v.id, |kept, v| {
+            kept.name = v.name;
+            kept.devices.extend(v.devices);
+        })?;
+        for vendor in &mut self.vendors {
+            resolve_duplicates(&mut vendor.devices, policy, |d| d.id, |kept, d| {
+                kept.name = d.name;
+                kept.subsystems.extend(d.subsystems);
+            })?;
+            for device in &mut vendor.devices {
+                resolve_duplicates(
+                    &mut device.subsystems,
+                    policy,
+                    |s| (s.subvendor_id, s.subdevice_id),
+                    |kept, s| kept.name = s.name,
+                )?;
+            }
+        }
+
+        resolve_duplicates(&mut self.classes, policy, |c| c.id, |kept, c| {
+            kept.name = c.name;
+            kept.subclasses.extend(c.subclasses);
+        })?;
+        for class in &mut self.classes {
+            resolve_duplicates(&mut class.subclasses, policy, |s| s.id, |kept, s| {
+                kept.name = s.name;
+                kept.prog_interfaces.extend(s.prog_interfaces);
+            })?;
+            for subclass in &mut class.subclasses {
+                resolve_duplicates(&mut subclass.prog_interfaces, policy, |p| p.id, |kept, p| kept.name = p.name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Identifies an entry by its own ID and the IDs of its parents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EntryKey {
+    /// A vendor
+    Vendor(VendorId),
+    /// A device of a vendor
+    Device(VendorId, DeviceId),
+    /// A subsystem of a device
+    Subsystem(VendorId, DeviceId, SubvendorId, SubdeviceId),
+    /// A device class
+    Class(DeviceClassId),
+    /// A subclass of a class
+    SubClass(DeviceClassId, SubClassId),
+    /// A programming interface of a subclass
+    ProgInterface(DeviceClassId, SubClassId, ProgInterfaceId),
+}
+
+impl fmt::Display for EntryKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryKey::Vendor(vendor) => write!(f, "vendor {}", vendor),
+            EntryKey::Device(vendor, device) => write!(f, "device {}:{}", vendor, device),
+            EntryKey::Subsystem(vendor, device, subvendor, subdevice) => {
+                write!(f, "subsystem {} {} of device {}:{}", subvendor, subdevice, vendor, device)
+            }
+            EntryKey::Class(class) => write!(f, "class {}", class),
+            EntryKey::SubClass(class, subclass) => write!(f, "subclass {}{}", class, subclass),
+            EntryKey::ProgInterface(class, subclass, prog_if) => {
+                write!(f, "programming interface {}{}{}", class, subclass, prog_if)
+            }
+        }
+    }
+}
+
+/// Comment and blank lines of a parsed file, kept so it can be written back unchanged.
+///
+/// Lines are stored exactly as they appeared, without their line terminator.
+/// Each run of lines belongs to the entry that follows it, so comments stay
+/// with their entries when entries are edited, added, or removed.
+#[derive(Debug, Clone)]
+pub struct Comments<S = String> {
+    /// The lines before each entry
+    pub leading: BTreeMap<EntryKey, Vec<S>>,
+    /// The lines after the last entry
+    pub trailing: Vec<S>,
+}
+
+impl<S> Default for Comments<S> {
+    fn default() -> Self {
+        Self {
+            leading: BTreeMap::new(),
+            trailing: Vec::new(),
+        }
+    }
+}
+
+impl<S> Comments<S> {
+    /// Check if no lines are kept.
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty()
+    }
+
+    /// Get the lines before an entry.
+    pub fn before(&self, key: EntryKey) -> &[S] {
+        self.leading.get(&key).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// A recoverable problem found while parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// Vendors were not sorted by ID and have been reordered
+    UnsortedVendors,
+    /// The devices of a vendor were not sorted by ID and have been reordered
+    UnsortedDevices(VendorId),
+    /// The subsystems of a device were not sorted by ID and have been reordered
+    UnsortedSubsystems(VendorId, DeviceId),
+    /// Device classes were not sorted by ID and have been reordered
+    UnsortedClasses,
+    /// The subclasses of a class were not sorted by ID and have been reordered
+    UnsortedSubclasses(DeviceClassId),
+    /// The programming interfaces of a subclass were not sorted by ID and have been reordered
+    UnsortedProgInterfaces(DeviceClassId, SubClassId),
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWarning::UnsortedVendors => write!(f, "Vendors are not sorted by ID"),
+            ParseWarning::UnsortedDevices(vendor) => {
+                write!(f, "Devices of vendor {:04x} are not sorted by ID", vendor.value())
+            }
+            ParseWarning::UnsortedSubsystems(vendor, device) => write!(
+                f,
+                "Subsystems of device {:04x}:{:04x} are not sorted by ID",
+                vendor.value(),
+                device.value()
+            ),
+            ParseWarning::UnsortedClasses => write!(f, "Device classes are not sorted by ID"),
+            ParseWarning::UnsortedSubclasses(class) => {
+                write!(f, "Subclasses of class {:02x} are not sorted by ID", class.value())
+            }
+            ParseWarning::UnsortedProgInterfaces(class, subclass) => write!(
+                f,
+                "Programming interfaces of subclass {:02x}{:02x} are not sorted by ID",
+                class.value(),
+                subclass.value()
+            ),
+        }
+    }
+}
+
+/// Resolve entries with the same key according to `policy`, keeping each
+/// surviving entry at the position of its first definition.
+///
+/// `merge` moves the name and children of a later definition into the kept one
+/// for [`DuplicatePolicy::MergeChildren`].
+fn resolve_duplicates<T, K: Ord>(
+    entries: &mut Vec<T>,
+    policy: DuplicatePolicy,
+    key: impl Fn(&T) -> K,
+    merge: impl Fn(&mut T, T),
+) -> PciResult<()> {
+    let mut positions = BTreeMap::new();
+    let mut resolved: Vec<T> = Vec::with_capacity(entries.len());
+
+    for entry in entries.drain(..) {
+        let Some(&index) = positions.get(&key(&entry)) else {
+            positions.insert(key(&entry), resolved.len());
+            resolved.push(entry);
+            continue;
+        };
+
+        match policy {
+            DuplicatePolicy::Error => return Err(PciError::DuplicateEntry),
+            DuplicatePolicy::KeepFirst => {}
+            DuplicatePolicy::KeepLast => resolved[index] = entry,
+            DuplicatePolicy::MergeChildren => merge(&mut resolved[index], entry),
+        }
+    }
+
+    *entries = resolved;
+    Ok(())
+}
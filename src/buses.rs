@@ -0,0 +1,45 @@
+//! Databases for other buses whose ids files use the pci.ids format.
+//!
+//! The `usb` and `sdio` features make the build script generate a database
+//! from `usb.ids` or `sdio.ids` as well, looking in the directory named by
+//! the `IDS_RS_DATA_DIR` environment variable (such as a checkout of hwdata
+//! or `/usr/share/hwdata`) and then in the crate root. Without the file, the
+//! database is empty, or the build fails with the `require-db` feature.
+//!
+//! Each database is a [`PciDatabase`](crate::PciDatabase) holding what the
+//! file shares with pci.ids: vendors and their devices, and classes with
+//! their subclasses and protocols. The other sections of `usb.ids`, such as
+//! HID usages and languages, and the interface lines below devices are
+//! skipped. All the features that shape the PCI database, like
+//! `compressed-names`, `string-table`, or `vendors-only`, apply to these as well.
+//!
+//! ```rust
+//! use ids_rs::{buses, DeviceId, VendorId};
+//!
+//! let usb = buses::usb::get();
+//! if let Some(device) = usb.find_device(VendorId::new(0x046d), DeviceId::new(0xc52b)) {
+//!     println!("{}", device.name());
+//! }
+//! ```
+
+/// The USB ID database generated from `usb.ids`.
+#[cfg(feature = "usb")]
+pub mod usb {
+    include!(concat!(env!("OUT_DIR"), "/usb_database.rs"));
+
+    /// Get the USB database.
+    pub const fn get() -> &'static crate::PciDatabase {
+        &GLOBAL_DATABASE
+    }
+}
+
+/// The SDIO ID database generated from `sdio.ids`.
+#[cfg(feature = "sdio")]
+pub mod sdio {
+    include!(concat!(env!("OUT_DIR"), "/sdio_database.rs"));
+
+    /// Get the SDIO database.
+    pub const fn get() -> &'static crate::PciDatabase {
+        &GLOBAL_DATABASE
+    }
+}
@@ -0,0 +1,270 @@
+//! A small cache for repeated name lookups.
+//!
+//! Logging paths often describe the same handful of devices over and over.
+//! [`LookupCache`] keeps the most recently used results of
+//! [`PciDatabase::vendor_name`] and [`PciDatabase::describe_device`] in a
+//! fixed-capacity table, so repeated lookups skip the search and allocation.
+//! The least recently used entry is evicted when the table is full.
+
+use core::fmt;
+
+use crate::database::PciDatabase;
+use crate::types::*;
+use alloc::string::String;
+
+/// The arguments of a cached lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CacheKey {
+    VendorName(VendorId),
+    DescribeDevice {
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        class_id: Option<DeviceClassId>,
+        subclass_id: Option<SubClassId>,
+        prog_interface_id: Option<ProgInterfaceId>,
+        subvendor_id: Option<SubvendorId>,
+        subdevice_id: Option<SubdeviceId>,
+    },
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    key: CacheKey,
+    value: String,
+    last_used: u64,
+}
+
+/// Hit and miss counters for a [`LookupCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that went to the database
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Get the fraction of lookups answered from the cache, or 0.0 if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate)",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+/// A least-recently-used cache of up to `N` name lookups in front of a database.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::{LookupCache, PciDatabase, VendorId};
+///
+/// let mut cache: LookupCache<'_, 16> = LookupCache::new(PciDatabase::get());
+/// for _ in 0..3 {
+///     let name = cache.vendor_name(VendorId::new(0x8086));
+///     println!("{}", name);
+/// }
+/// assert_eq!(cache.stats().hits, 2);
+/// ```
+#[derive(Debug)]
+pub struct LookupCache<'db, const N: usize> {
+    database: &'db PciDatabase,
+    entries: heapless::Vec<CacheEntry, N>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl<'db, const N: usize> LookupCache<'db, N> {
+    /// Create an empty cache in front of the given database.
+    ///
+    /// The capacity `N` must be at least 1.
+    pub fn new(database: &'db PciDatabase) -> Self {
+        const { assert!(N > 0, "LookupCache capacity must be at least 1") };
+        Self {
+            database,
+            entries: heapless::Vec::new(),
+            clock: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Get the database behind this cache.
+    pub fn database(&self) -> &'db PciDatabase {
+        self.database
+    }
+
+    /// Get the hit and miss counters.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Get the number of cached results.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache holds no results.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached results and reset the counters.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.stats = CacheStats::default();
+    }
+
+    /// Get a human-readable name for a vendor (see [`PciDatabase::vendor_name`]).
+    pub fn vendor_name(&mut self, vendor_id: VendorId) -> &str {
+        let database = self.database;
+        self.get_or_insert(CacheKey::VendorName(vendor_id), || database.vendor_name(vendor_id))
+    }
+
+    /// Get a complete description of a device (see [`PciDatabase::describe_device`]).
+    #[allow(clippy::too_many_arguments)]
+    pub fn describe_device(
+        &mut self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        class_id: Option<DeviceClassId>,
+        subclass_id: Option<SubClassId>,
+        prog_interface_id: Option<ProgInterfaceId>,
+        subvendor_id: Option<SubvendorId>,
+        subdevice_id: Option<SubdeviceId>,
+    ) -> &str {
+        let key = CacheKey::DescribeDevice {
+            vendor_id,
+            device_id,
+            class_id,
+            subclass_id,
+            prog_interface_id,
+            subvendor_id,
+            subdevice_id,
+        };
+        let database = self.database;
+        self.get_or_insert(key, || {
+            database.describe_device(
+                vendor_id,
+                device_id,
+                class_id,
+                subclass_id,
+                prog_interface_id,
+                subvendor_id,
+                subdevice_id,
+            )
+        })
+    }
+
+    fn get_or_insert(&mut self, key: CacheKey, compute: impl FnOnce() -> String) -> &str {
+        self.clock += 1;
+
+        let index = match self.entries.iter().position(|entry| entry.key == key) {
+            Some(index) => {
+                self.stats.hits += 1;
+                index
+            }
+            None => {
+                self.stats.misses += 1;
+                let entry = CacheEntry {
+                    key,
+                    value: compute(),
+                    last_used: self.clock,
+                };
+                match self.entries.push(entry) {
+                    Ok(()) => self.entries.len() - 1,
+                    Err(entry) => {
+                        // Full: replace the least recently used entry
+                        let lru = self
+                            .entries
+                            .iter()
+                            .enumerate()
+                            .min_by_key(|(_, entry)| entry.last_used)
+                            .map_or(0, |(index, _)| index);
+                        self.entries[lru] = entry;
+                        lru
+                    }
+                }
+            }
+        };
+
+        let entry = &mut self.entries[index];
+        entry.last_used = self.clock;
+        &entry.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATABASE: PciDatabase = crate::pci_database! {
+        vendors {
+            0x1000 "Vendor A" {
+                0x0001 "Widget",
+            },
+            0x2000 "Vendor B",
+            0x3000 "Vendor C",
+        }
+        classes {}
+    };
+
+    #[test]
+    fn test_hits_and_misses() {
+        let mut cache: LookupCache<'_, 4> = LookupCache::new(&DATABASE);
+
+        assert_eq!(cache.vendor_name(VendorId::new(0x1000)), "Vendor A");
+        assert_eq!(cache.vendor_name(VendorId::new(0x1000)), "Vendor A");
+        assert_eq!(cache.vendor_name(VendorId::new(0xffff)), "Unknown Vendor (ffff)");
+        let description = cache.describe_device(VendorId::new(0x1000), DeviceId::new(0x0001), None, None, None, None, None);
+        assert_eq!(description, "Vendor A Widget");
+        cache.describe_device(VendorId::new(0x1000), DeviceId::new(0x0001), None, None, None, None, None);
+
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 3 });
+        assert_eq!(cache.stats().hit_rate(), 0.4);
+        assert_eq!(cache.len(), 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.stats().hit_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache: LookupCache<'_, 2> = LookupCache::new(&DATABASE);
+
+        cache.vendor_name(VendorId::new(0x1000));
+        cache.vendor_name(VendorId::new(0x2000));
+        // Touch A so that B becomes the least recently used
+        cache.vendor_name(VendorId::new(0x1000));
+        cache.vendor_name(VendorId::new(0x3000));
+        assert_eq!(cache.len(), 2);
+
+        let before = cache.stats();
+        cache.vendor_name(VendorId::new(0x1000));
+        cache.vendor_name(VendorId::new(0x3000));
+        assert_eq!(cache.stats().hits, before.hits + 2);
+        cache.vendor_name(VendorId::new(0x2000));
+        assert_eq!(cache.stats().misses, before.misses + 1);
+    }
+
+    #[test]
+    fn test_stats_display() {
+        let stats = CacheStats { hits: 3, misses: 1 };
+        assert_eq!(alloc::format!("{}", stats), "3 hits, 1 misses (75.0% hit rate)");
+    }
+}
@@ -0,0 +1,289 @@
+//! Layered lookups across several databases.
+//!
+//! A [`ChainedDatabase`] combines databases such as a table of in-house device
+//! IDs and the built-in database behind one handle. Each lookup consults the
+//! databases in order and returns the first hit, so earlier databases take
+//! precedence and later ones fill the gaps. Queries run against every
+//! database with [`ChainedDatabase::execute`], which merges the results the
+//! same way.
+
+use crate::classes::{DeviceClass, ProgInterface, SubClass};
+use crate::database::PciDatabase;
+use crate::devices::{Device, Subsystem};
+use crate::query::{DeviceMatch, QueryBuilder};
+use crate::types::*;
+use crate::vendors::Vendor;
+use alloc::{string::String, vec::Vec};
+
+/// A query result tagged with the database it came from.
+#[derive(Debug)]
+pub struct SourcedMatch<'db> {
+    /// The position of the source database in the chain
+    pub source: usize,
+    /// The matching device
+    pub device_match: DeviceMatch<'db>,
+}
+
+/// A view over several databases, consulted in order.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::{pci_database, ChainedDatabase, PciDatabase, VendorId, DeviceId};
+///
+/// static IN_HOUSE: PciDatabase = pci_database! {
+///     vendors {
+///         0x8086 "Intel Corporation" {
+///             0xfff0 "Lab Prototype NIC",
+///         },
+///     }
+///     classes {}
+/// };
+///
+/// let db = ChainedDatabase::new(&IN_HOUSE).then(PciDatabase::get());
+/// assert_eq!(db.device_name(VendorId::new(0x8086), DeviceId::new(0xfff0)), "Lab Prototype NIC");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChainedDatabase<'db> {
+    databases: Vec<&'db PciDatabase>,
+}
+
+impl<'db> ChainedDatabase<'db> {
+    /// Create a chain starting with the given database.
+    pub fn new(database: &'db PciDatabase) -> Self {
+        Self {
+            databases: alloc::vec![database],
+        }
+    }
+
+    /// Add a database to be consulted after those already in the chain.
+    pub fn then(mut self, database: &'db PciDatabase) -> Self {
+        self.databases.push(database);
+        self
+    }
+
+    /// Get the databases in the order they are consulted.
+    pub fn databases(&self) -> &[&'db PciDatabase] {
+        &self.databases
+    }
+
+    /// Get a query builder to run with [`execute`](Self::execute).
+    ///
+    /// The builder targets the first database; `execute` runs it against all of them.
+    pub fn query(&self) -> QueryBuilder<'db> {
+        self.databases[0].query()
+    }
+
+    /// Run a query against every database in the chain and merge the results.
+    ///
+    /// Each result is tagged with the position of its database in the chain.
+    /// A device listed by an earlier database is only reported from there,
+    /// even if only the later entry matches the query, so results agree with
+    /// the chain's lookups. Results are grouped by database unless the query
+    /// sets an order, and offset and limit apply to the merged list. The
+    /// database the query was built for is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{pci_database, ChainedDatabase, PciDatabase};
+    ///
+    /// static IN_HOUSE: PciDatabase = pci_database! {
+    ///     vendors {
+    ///         0x8086 "Intel Corporation" {
+    ///             0xfff0 "Lab Prototype Ethernet NIC",
+    ///         },
+    ///     }
+    ///     classes {}
+    /// };
+    ///
+    /// let chain = ChainedDatabase::new(&IN_HOUSE).then(PciDatabase::get());
+    /// let query = chain.query().vendor_name_contains("Intel").device_name_contains("Ethernet");
+    /// for result in chain.execute(&query) {
+    ///     let origin = if result.source == 0 { "in-house" } else { "pci.ids" };
+    ///     println!("{} ({})", result.device_match.device_name(), origin);
+    /// }
+    /// ```
+    pub fn execute(&self, query: &QueryBuilder<'db>) -> Vec<SourcedMatch<'db>> {
+        query.execute_sourced(&self.databases)
+    }
+
+    fn first<T>(&self, lookup: impl Fn(&'db PciDatabase) -> Option<T>) -> Option<T> {
+        self.databases.iter().find_map(|database| lookup(database))
+    }
+
+    /// Find a vendor by ID.
+    pub fn find_vendor(&self, vendor_id: VendorId) -> Option<&'db Vendor> {
+        self.first(|database| database.find_vendor(vendor_id))
+    }
+
+    /// Find a device by vendor and device ID.
+    ///
+    /// A device is found even if its vendor also appears in an earlier
+    /// database that does not list the device.
+    pub fn find_device(&self, vendor_id: VendorId, device_id: DeviceId) -> Option<&'db Device> {
+        self.first(|database| database.find_device(vendor_id, device_id))
+    }
+
+    /// Find a subsystem by vendor, device, subvendor, and subdevice IDs.
+    pub fn find_subsystem(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+    ) -> Option<&'db Subsystem> {
+        self.first(|database| database.find_subsystem(vendor_id, device_id, subvendor_id, subdevice_id))
+    }
+
+    /// Find a device class by ID.
+    pub fn find_class(&self, class_id: DeviceClassId) -> Option<&'db DeviceClass> {
+        self.first(|database| database.find_class(class_id))
+    }
+
+    /// Find a subclass by class and subclass IDs.
+    pub fn find_subclass(&self, class_id: DeviceClassId, subclass_id: SubClassId) -> Option<&'db SubClass> {
+        self.first(|database| database.find_subclass(class_id, subclass_id))
+    }
+
+    /// Find a programming interface by class, subclass, and programming interface IDs.
+    pub fn find_prog_interface(
+        &self,
+        class_id: DeviceClassId,
+        subclass_id: SubClassId,
+        prog_interface_id: ProgInterfaceId,
+    ) -> Option<&'db ProgInterface> {
+        self.first(|database| database.find_prog_interface(class_id, subclass_id, prog_interface_id))
+    }
+
+    /// Get a human-readable name for a vendor.
+    ///
+    /// Returns "Unknown Vendor (XXXX)" if no database has the vendor.
+    pub fn vendor_name(&self, vendor_id: VendorId) -> String {
+        match self.first(|database| database.find_vendor(vendor_id).map(|vendor| database.resolve_vendor_name(vendor))) {
+            Some(name) => name.into_owned(),
+            None => alloc::format!("Unknown Vendor ({:04x})", vendor_id.value()),
+        }
+    }
+
+    /// Get a human-readable name for a device.
+    ///
+    /// Returns "Unknown Device (XXXX)" if no database has the device.
+    pub fn device_name(&self, vendor_id: VendorId, device_id: DeviceId) -> String {
+        let name = |database: &PciDatabase| database.find_device(vendor_id, device_id).map(|device| database.resolve_device_name(vendor_id, device));
+        match self.first(name) {
+            Some(name) => name.into_owned(),
+            None => alloc::format!("Unknown Device ({:04x})", device_id.value()),
+        }
+    }
+
+    /// Get a human-readable name for a subsystem.
+    ///
+    /// Returns "Unknown Subsystem (XXXX:XXXX)" if no database has the subsystem.
+    pub fn subsystem_name(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+    ) -> String {
+        let name = |database: &PciDatabase| {
+            let subsystem = database.find_subsystem(vendor_id, device_id, subvendor_id, subdevice_id)?;
+            Some(database.resolve_subsystem_name(vendor_id, device_id, subsystem))
+        };
+        match self.first(name) {
+            Some(name) => name.into_owned(),
+            None => alloc::format!(
+                "Unknown Subsystem ({:04x}:{:04x})",
+                subvendor_id.value(),
+                subdevice_id.value()
+            ),
+        }
+    }
+
+    /// Get a human-readable name for a device class.
+    ///
+    /// Returns "Unknown Class (XX)" if no database has the class.
+    pub fn class_name(&self, class_id: DeviceClassId) -> String {
+        match self.first(|database| database.find_class(class_id).map(|class| database.resolve_class_name(class))) {
+            Some(name) => name.into_owned(),
+            None => alloc::format!("Unknown Class ({:02x})", class_id.value()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static BASE: PciDatabase = crate::pci_database! {
+        vendors {
+            0x1234 "Example Corp" {
+                0x0001 "Base Widget",
+            },
+        }
+        classes {
+            0x02 "Network controller",
+        }
+    };
+
+    static EXTENSION: PciDatabase = crate::pci_database! {
+        vendors {
+            0x1234 "Example" {
+                0x0001 "Widget (internal name)",
+                0x00ff "Prototype",
+            },
+        }
+        classes {}
+    };
+
+    #[test]
+    fn test_earlier_databases_take_precedence() {
+        let vendor_id = VendorId::new(0x1234);
+
+        let chain = ChainedDatabase::new(&EXTENSION).then(&BASE);
+        assert_eq!(chain.databases().len(), 2);
+        assert_eq!(chain.vendor_name(vendor_id), "Example");
+        assert_eq!(chain.device_name(vendor_id, DeviceId::new(0x0001)), "Widget (internal name)");
+        assert_eq!(chain.device_name(vendor_id, DeviceId::new(0x00ff)), "Prototype");
+        assert_eq!(chain.class_name(DeviceClassId::new(0x02)), "Network controller");
+
+        let chain = ChainedDatabase::new(&BASE).then(&EXTENSION);
+        assert_eq!(chain.vendor_name(vendor_id), "Example Corp");
+        assert_eq!(chain.device_name(vendor_id, DeviceId::new(0x0001)), "Base Widget");
+        // The base vendor lacks this device, so the extension supplies it
+        assert_eq!(chain.device_name(vendor_id, DeviceId::new(0x00ff)), "Prototype");
+    }
+
+    #[test]
+    fn test_missing_entries() {
+        let chain = ChainedDatabase::new(&BASE);
+
+        assert!(chain.find_vendor(VendorId::new(0xffff)).is_none());
+        assert_eq!(chain.device_name(VendorId::new(0x1234), DeviceId::new(0x00ff)), "Unknown Device (00ff)");
+        assert!(chain.find_subclass(DeviceClassId::new(0x02), SubClassId::new(0x00)).is_none());
+    }
+
+    #[test]
+    fn test_execute() {
+        use crate::query::{OrderBy, SortDirection};
+
+        let chain = ChainedDatabase::new(&EXTENSION).then(&BASE);
+
+        // The base widget is shadowed by the extension's entry for the same ID
+        let results = chain.execute(&chain.query().device_name_contains("widget"));
+        let found: Vec<_> = results.iter().map(|r| (r.source, r.device_match.device_name().into_owned())).collect();
+        assert_eq!(found, [(0, String::from("Widget (internal name)"))]);
+
+        let results = chain.execute(&chain.query().device_name_contains("base"));
+        assert!(results.is_empty());
+
+        let chain = ChainedDatabase::new(&BASE).then(&EXTENSION);
+        let query = chain.query().vendor_id(VendorId::new(0x1234)).order_by(OrderBy::DeviceName, SortDirection::Descending);
+        let found: Vec<_> = chain.execute(&query).iter().map(|r| (r.source, r.device_match.device_name().into_owned())).collect();
+        assert_eq!(found, [(1, String::from("Prototype")), (0, String::from("Base Widget"))]);
+
+        assert_eq!(chain.execute(&query.clone().offset(1)).len(), 1);
+        assert_eq!(chain.execute(&query.limit(1))[0].device_match.device_name(), "Prototype");
+    }
+}
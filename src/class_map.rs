@@ -0,0 +1,265 @@
+//! Best-effort mapping from devices to device classes.
+//!
+//! The PCI IDs database lists devices and device classes separately and never
+//! records which class a device belongs to; the real class is read from the
+//! device's configuration space at runtime. This module provides a fallback
+//! for code that only has vendor and device IDs:
+//!
+//! - a small curated table of device ID ranges whose class is known, and
+//! - a keyword heuristic over device names ("Ethernet", "NVMe", "xHCI", ...).
+//!
+//! Results are best-effort. Devices with unhelpful names are not classified,
+//! and a keyword can occasionally pick the wrong class. Prefer the class code
+//! read from hardware whenever it is available.
+
+use crate::classes::well_known::*;
+use crate::database::PciDatabase;
+use crate::devices::Device;
+use crate::types::*;
+use alloc::borrow::Cow;
+
+/// A contiguous range of device IDs from one vendor sharing a known class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassRange {
+    /// The vendor ID
+    pub vendor_id: VendorId,
+    /// The first device ID in the range (inclusive)
+    pub first: DeviceId,
+    /// The last device ID in the range (inclusive)
+    pub last: DeviceId,
+    /// The device class of every device in the range
+    pub class_id: DeviceClassId,
+    /// The subclass of every device in the range, if known
+    pub subclass_id: Option<SubClassId>,
+}
+
+impl ClassRange {
+    /// Create a new class range.
+    #[inline]
+    pub const fn new(
+        vendor_id: VendorId,
+        first: DeviceId,
+        last: DeviceId,
+        class_id: DeviceClassId,
+        subclass_id: Option<SubClassId>,
+    ) -> Self {
+        Self {
+            vendor_id,
+            first,
+            last,
+            class_id,
+            subclass_id,
+        }
+    }
+
+    /// Check if a device falls within this range.
+    pub fn contains(&self, vendor_id: VendorId, device_id: DeviceId) -> bool {
+        self.vendor_id == vendor_id && self.first <= device_id && device_id <= self.last
+    }
+}
+
+/// A keyword that, when found in a device name, suggests a device class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameHint {
+    /// The lowercase keyword to look for
+    pub keyword: &'static str,
+    /// The suggested device class
+    pub class_id: DeviceClassId,
+    /// The suggested subclass, if the keyword is specific enough
+    pub subclass_id: Option<SubClassId>,
+}
+
+impl NameHint {
+    /// Create a new name hint.
+    #[inline]
+    pub const fn new(keyword: &'static str, class_id: DeviceClassId, subclass_id: Option<SubClassId>) -> Self {
+        Self {
+            keyword,
+            class_id,
+            subclass_id,
+        }
+    }
+}
+
+/// Where an inferred class came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassSource {
+    /// The device ID is in a curated range
+    CuratedRange,
+    /// A keyword was found in the device name
+    NameHeuristic,
+}
+
+/// A device class inferred for a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InferredClass {
+    /// The inferred device class
+    pub class_id: DeviceClassId,
+    /// The inferred subclass, if known
+    pub subclass_id: Option<SubClassId>,
+    /// How the class was inferred
+    pub source: ClassSource,
+}
+
+const fn sub(id: u8) -> Option<SubClassId> {
+    Some(SubClassId::new(id))
+}
+
+/// Curated device ID ranges with a known class.
+pub static CURATED_RANGES: &[ClassRange] = &[
+    // Intel I350/82576/82599/X540/I210/I211/I217 Ethernet controllers
+    ClassRange::new(VendorId::new(0x8086), DeviceId::new(0x1520), DeviceId::new(0x153b), NETWORK, sub(0x00)),
+    // Realtek RTL8125/8126/8129/810x/8139 Ethernet controllers
+    ClassRange::new(VendorId::new(0x10ec), DeviceId::new(0x8125), DeviceId::new(0x8139), NETWORK, sub(0x00)),
+    // Realtek RTL8111/8168/8169 Gigabit Ethernet controllers
+    ClassRange::new(VendorId::new(0x10ec), DeviceId::new(0x8161), DeviceId::new(0x8169), NETWORK, sub(0x00)),
+    // Realtek RTL8188/8191/8192 wireless controllers
+    ClassRange::new(VendorId::new(0x10ec), DeviceId::new(0x8171), DeviceId::new(0x8179), NETWORK, sub(0x80)),
+];
+
+/// Name keywords in priority order; the first keyword found in a name wins.
+pub static NAME_HINTS: &[NameHint] = &[
+    // Wireless before Ethernet, so "Wireless Network Connection" is not Ethernet
+    NameHint::new("wireless", NETWORK, sub(0x80)),
+    NameHint::new("wi-fi", NETWORK, sub(0x80)),
+    NameHint::new("wifi", NETWORK, sub(0x80)),
+    NameHint::new("802.11", NETWORK, sub(0x80)),
+    NameHint::new("wlan", NETWORK, sub(0x80)),
+    NameHint::new("ethernet", NETWORK, sub(0x00)),
+    NameHint::new("gigabit", NETWORK, sub(0x00)),
+    NameHint::new("network connection", NETWORK, sub(0x00)),
+    NameHint::new("infiniband", NETWORK, sub(0x07)),
+    NameHint::new("nvm express", MASS_STORAGE, sub(0x08)),
+    NameHint::new("nvme", MASS_STORAGE, sub(0x08)),
+    NameHint::new("sata", MASS_STORAGE, sub(0x06)),
+    NameHint::new("ahci", MASS_STORAGE, sub(0x06)),
+    NameHint::new("raid", MASS_STORAGE, sub(0x04)),
+    NameHint::new("serial attached scsi", MASS_STORAGE, sub(0x07)),
+    NameHint::new("scsi", MASS_STORAGE, sub(0x00)),
+    NameHint::new("xhci", SERIAL_BUS, sub(0x03)),
+    NameHint::new("ehci", SERIAL_BUS, sub(0x03)),
+    NameHint::new("ohci", SERIAL_BUS, sub(0x03)),
+    NameHint::new("uhci", SERIAL_BUS, sub(0x03)),
+    NameHint::new("usb", SERIAL_BUS, sub(0x03)),
+    NameHint::new("smbus", SERIAL_BUS, sub(0x05)),
+    NameHint::new("firewire", SERIAL_BUS, sub(0x00)),
+    NameHint::new("1394", SERIAL_BUS, sub(0x00)),
+    NameHint::new("fibre channel", SERIAL_BUS, sub(0x04)),
+    // Audio before display, so GPU audio functions are not classed as displays
+    NameHint::new("audio", MULTIMEDIA, sub(0x03)),
+    NameHint::new("vga", DISPLAY, sub(0x00)),
+    NameHint::new("geforce", DISPLAY, None),
+    NameHint::new("radeon", DISPLAY, None),
+    NameHint::new("quadro", DISPLAY, None),
+    NameHint::new("graphics", DISPLAY, None),
+    NameHint::new("root port", BRIDGE, sub(0x04)),
+    NameHint::new("pci bridge", BRIDGE, sub(0x04)),
+    NameHint::new("host bridge", BRIDGE, sub(0x00)),
+    NameHint::new("isa bridge", BRIDGE, sub(0x01)),
+    NameHint::new("lpc", BRIDGE, sub(0x01)),
+];
+
+/// Infer the class of a device from the curated ranges, then from its name.
+///
+/// This matches against [`Device::name`], which is empty under the
+/// `compressed-names` and `string-table` features; [`PciDatabase::infer_device_class`]
+/// resolves the name first.
+pub fn infer_class(vendor_id: VendorId, device: &Device) -> Option<InferredClass> {
+    infer_class_named(vendor_id, device.id(), || Cow::Borrowed(device.name()))
+}
+
+/// [`infer_class`], with the device name only fetched if no curated range matches.
+pub(crate) fn infer_class_named<N: core::ops::Deref<Target = str>>(
+    vendor_id: VendorId,
+    device_id: DeviceId,
+    name: impl FnOnce() -> N,
+) -> Option<InferredClass> {
+    if let Some(range) = CURATED_RANGES.iter().find(|range| range.contains(vendor_id, device_id)) {
+        return Some(InferredClass {
+            class_id: range.class_id,
+            subclass_id: range.subclass_id,
+            source: ClassSource::CuratedRange,
+        });
+    }
+
+    let name = name();
+    NAME_HINTS
+        .iter()
+        .find(|hint| contains_ignore_ascii_case(&name, hint.keyword))
+        .map(|hint| InferredClass {
+            class_id: hint.class_id,
+            subclass_id: hint.subclass_id,
+            source: ClassSource::NameHeuristic,
+        })
+}
+
+/// Check if `haystack` contains `needle`, ignoring ASCII case, without allocating.
+fn contains_ignore_ascii_case(haystack: &str, needle: &str) -> bool {
+    let needle = needle.as_bytes();
+    needle.is_empty()
+        || haystack
+            .as_bytes()
+            .windows(needle.len())
+            .any(|window| window.eq_ignore_ascii_case(needle))
+}
+
+impl PciDatabase {
+    /// Infer the class of a known device (best-effort).
+    ///
+    /// Returns `None` if the device is not in the database or no class could be
+    /// inferred. See the [`class_map`](crate::class_map) module for caveats.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, VendorId, DeviceId};
+    ///
+    /// let db = PciDatabase::get();
+    /// if let Some(inferred) = db.infer_device_class(VendorId::new(0x8086), DeviceId::new(0x1533)) {
+    ///     println!("Class: {}", db.class_name(inferred.class_id));
+    /// }
+    /// ```
+    pub fn infer_device_class(&self, vendor_id: VendorId, device_id: DeviceId) -> Option<InferredClass> {
+        let device = self.find_device(vendor_id, device_id)?;
+        infer_class_named(vendor_id, device_id, || self.resolve_device_name(vendor_id, device))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curated_range_takes_priority() {
+        // The name would suggest nothing, but the ID is in a curated range
+        let device = Device::new(DeviceId::new(0x1533), "I210", &[]);
+        let inferred = infer_class(VendorId::new(0x8086), &device).unwrap();
+
+        assert_eq!(inferred.class_id, NETWORK);
+        assert_eq!(inferred.subclass_id, Some(SubClassId::new(0x00)));
+        assert_eq!(inferred.source, ClassSource::CuratedRange);
+    }
+
+    #[test]
+    fn test_name_heuristic() {
+        let device = Device::new(DeviceId::new(0x0001), "Centrino Advanced-N 6205 [Wireless Network Connection]", &[]);
+        let inferred = infer_class(VendorId::new(0x1234), &device).unwrap();
+        assert_eq!(inferred.class_id, NETWORK);
+        assert_eq!(inferred.subclass_id, Some(SubClassId::new(0x80)));
+        assert_eq!(inferred.source, ClassSource::NameHeuristic);
+
+        let device = Device::new(DeviceId::new(0x0002), "GA102 High Definition Audio Controller", &[]);
+        let inferred = infer_class(VendorId::new(0x1234), &device).unwrap();
+        assert_eq!(inferred.class_id, MULTIMEDIA);
+
+        let device = Device::new(DeviceId::new(0x0003), "Mystery Widget", &[]);
+        assert!(infer_class(VendorId::new(0x1234), &device).is_none());
+    }
+
+    #[test]
+    fn test_contains_ignore_ascii_case() {
+        assert!(contains_ignore_ascii_case("Intel NVMe SSD", "nvme"));
+        assert!(contains_ignore_ascii_case("anything", ""));
+        assert!(!contains_ignore_ascii_case("USB", "usb3"));
+    }
+}
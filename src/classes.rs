@@ -1,7 +1,7 @@
 //! PCI device class definitions and utilities.
 
 use crate::types::{DeviceClassId, SubClassId, ProgInterfaceId};
-use alloc::string::ToString;
+use alloc::{borrow::Cow, string::ToString};
 
 /// Represents a PCI programming interface within a subclass.
 #[derive(Debug, Clone)]
@@ -205,28 +205,40 @@ impl DeviceClass {
 
     /// Get a human-readable description of a device with the given class, subclass, and prog-if.
     pub fn describe_device(&self, subclass_id: Option<SubClassId>, prog_interface_id: Option<ProgInterfaceId>) -> alloc::string::String {
+        self.describe_device_with(subclass_id, prog_interface_id, self.name, |subclass| subclass.name.into(), |_, prog_if| prog_if.name.into())
+    }
+
+    /// Describe a device as [`describe_device`](Self::describe_device) does, with the names given by the callers.
+    pub(crate) fn describe_device_with(
+        &self,
+        subclass_id: Option<SubClassId>,
+        prog_interface_id: Option<ProgInterfaceId>,
+        class_name: &str,
+        subclass_name: impl Fn(&SubClass) -> Cow<'static, str>,
+        prog_if_name: impl Fn(&SubClass, &ProgInterface) -> Cow<'static, str>,
+    ) -> alloc::string::String {
         use alloc::format;
 
         match (subclass_id, prog_interface_id) {
             (Some(sc_id), Some(pi_id)) => {
                 if let Some(subclass) = self.find_subclass(sc_id) {
                     if let Some(prog_if) = subclass.find_prog_interface(pi_id) {
-                        format!("{} - {} - {}", self.name, subclass.name, prog_if.name)
+                        format!("{} - {} - {}", class_name, subclass_name(subclass), prog_if_name(subclass, prog_if))
                     } else {
-                        format!("{} - {} - Unknown Programming Interface ({:02x})", self.name, subclass.name, pi_id.value())
+                        format!("{} - {} - Unknown Programming Interface ({:02x})", class_name, subclass_name(subclass), pi_id.value())
                     }
                 } else {
-                    format!("{} - Unknown Subclass ({:02x})", self.name, sc_id.value())
+                    format!("{} - Unknown Subclass ({:02x})", class_name, sc_id.value())
                 }
             }
             (Some(sc_id), None) => {
                 if let Some(subclass) = self.find_subclass(sc_id) {
-                    format!("{} - {}", self.name, subclass.name)
+                    format!("{} - {}", class_name, subclass_name(subclass))
                 } else {
-                    format!("{} - Unknown Subclass ({:02x})", self.name, sc_id.value())
+                    format!("{} - Unknown Subclass ({:02x})", class_name, sc_id.value())
                 }
             }
-            (None, _) => self.name.to_string(),
+            (None, _) => class_name.to_string(),
         }
     }
 }
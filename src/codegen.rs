@@ -0,0 +1,2124 @@
+//! Rust source generation for static PCI databases.
+//!
+//! This is the code generator used to embed the PCI IDs database, exposed so
+//! other crates can generate their own static tables (for example a database
+//! trimmed to a few vendors) from their build scripts.
+//!
+//! Build scripts run on the host, but the output is compiled for the target.
+//! The generator therefore never looks at target configuration and only emits
+//! layout-independent data: typed constructor calls with hex literals and
+//! fixed-width integers (never `usize`, native-endian bytes, or `cfg`
+//! attributes), so the same output is valid for 32-bit and big-endian targets.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ids_rs::codegen::{generate_rust, GenOptions};
+//! use ids_rs::parser::PciIdsParser;
+//!
+//! let mut parser = PciIdsParser::new();
+//! parser.parse("8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n").unwrap();
+//!
+//! let code = generate_rust(&parser.into_parsed(), &GenOptions::new().crate_path("::ids_rs"));
+//! assert!(code.contains("GLOBAL_DATABASE"));
+//! ```
+
+use alloc::{borrow::Cow, collections::BTreeMap, format, string::String, string::ToString, vec, vec::Vec};
+use core::fmt::{self, Write};
+
+use crate::builders::{ClassBuilder, DeviceBuilder, ParsedDatabase, VendorBuilder};
+use crate::error::{PciError, PciResult};
+use crate::types::{NameForm, VendorId};
+
+/// Visibility of a generated static.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// No visibility modifier (private to the including module)
+    Private,
+    /// `pub(crate)`
+    Crate,
+    /// `pub`
+    Public,
+}
+
+impl Visibility {
+    /// The visibility keyword to put in front of an item, including a trailing space.
+    fn keyword(self) -> &'static str {
+        match self {
+            Visibility::Private => "",
+            Visibility::Crate => "pub(crate) ",
+            Visibility::Public => "pub ",
+        }
+    }
+}
+
+/// Options controlling the generated Rust source.
+///
+/// The generated tables only use `const` constructors and static slices, so the
+/// same output is valid in both `no_std` and `std` crates.
+#[derive(Debug, Clone)]
+pub struct GenOptions {
+    crate_path: String,
+    static_prefix: String,
+    database_name: String,
+    table_visibility: Visibility,
+    database_visibility: Visibility,
+    emit_name_index: bool,
+    emit_subsystems: bool,
+    emit_prog_interfaces: bool,
+    emit_vendors: bool,
+    emit_classes: bool,
+    compress_names: bool,
+    string_table: bool,
+    emit_phf: bool,
+    non_ascii_names: NameForm,
+    max_name_length: Option<usize>,
+}
+
+impl GenOptions {
+    /// Create options suitable for code included inside this crate.
+    pub fn new() -> Self {
+        Self {
+            crate_path: "crate".to_string(),
+            static_prefix: String::new(),
+            database_name: "GLOBAL_DATABASE".to_string(),
+            table_visibility: Visibility::Private,
+            database_visibility: Visibility::Public,
+            emit_name_index: true,
+            emit_subsystems: true,
+            emit_prog_interfaces: true,
+            emit_vendors: true,
+            emit_classes: true,
+            compress_names: false,
+            string_table: false,
+            emit_phf: false,
+            non_ascii_names: NameForm::Original,
+            max_name_length: None,
+        }
+    }
+
+    /// Set the path used to reference this crate's types from the generated code.
+    ///
+    /// Use `"crate"` (the default) when the output is included in this crate and
+    /// `"::ids_rs"` when it is included in a downstream crate.
+    pub fn crate_path(mut self, path: &str) -> Self {
+        self.crate_path = path.to_string();
+        self
+    }
+
+    /// Set a prefix for the names of all generated table statics.
+    ///
+    /// Useful when including several generated databases in the same module,
+    /// e.g. `"NET_"` produces `NET_VENDORS`, `NET_CLASSES`, and so on.
+    pub fn static_prefix(mut self, prefix: &str) -> Self {
+        self.static_prefix = prefix.to_string();
+        self
+    }
+
+    /// Set the name of the generated database static (default `GLOBAL_DATABASE`).
+    pub fn database_name(mut self, name: &str) -> Self {
+        self.database_name = name.to_string();
+        self
+    }
+
+    /// Set the visibility of the generated table statics (default private).
+    pub fn table_visibility(mut self, visibility: Visibility) -> Self {
+        self.table_visibility = visibility;
+        self
+    }
+
+    /// Set the visibility of the generated database static (default `pub`).
+    pub fn database_visibility(mut self, visibility: Visibility) -> Self {
+        self.database_visibility = visibility;
+        self
+    }
+
+    /// Set whether to emit the sorted name index used by prefix search (default `true`).
+    ///
+    /// Without it, [`PciDatabase::names_with_prefix`](crate::PciDatabase::names_with_prefix)
+    /// falls back to a linear scan, but the generated code is smaller.
+    pub fn emit_name_index(mut self, emit: bool) -> Self {
+        self.emit_name_index = emit;
+        self
+    }
+
+    /// Set whether to emit subsystem entries (default `true`).
+    ///
+    /// Subsystems are the bulk of pci.ids; without them the generated tables
+    /// only hold vendor, device, and class names, and subsystem lookups such
+    /// as [`PciDatabase::find_subsystem`](crate::PciDatabase::find_subsystem)
+    /// return `None`.
+    pub fn emit_subsystems(mut self, emit: bool) -> Self {
+        self.emit_subsystems = emit;
+        self
+    }
+
+    /// Set whether to emit programming interfaces (default `true`).
+    ///
+    /// Without them the class tree stops at subclasses, for users that only
+    /// need class and subclass names, and lookups such as
+    /// [`PciDatabase::find_prog_interface`](crate::PciDatabase::find_prog_interface)
+    /// return `None`.
+    pub fn emit_prog_interfaces(mut self, emit: bool) -> Self {
+        self.emit_prog_interfaces = emit;
+        self
+    }
+
+    /// Set whether to emit the vendor and device tables (default `true`).
+    ///
+    /// Without them the generated database only decodes class codes, and
+    /// every vendor and device lookup returns `None`.
+    pub fn emit_vendors(mut self, emit: bool) -> Self {
+        self.emit_vendors = emit;
+        self
+    }
+
+    /// Set whether to emit the class tree (default `true`).
+    ///
+    /// Without it the generated database only names vendors and devices, and
+    /// every class lookup returns `None`.
+    pub fn emit_classes(mut self, emit: bool) -> Self {
+        self.emit_classes = emit;
+        self
+    }
+
+    /// Set whether to store names compressed (default `false`).
+    ///
+    /// The tables then hold empty names, and the names are emitted once as
+    /// front-coded, dictionary-compressed data that the `*_name_into` methods
+    /// decompress on demand; see [`compressed`](crate::compressed). This also
+    /// disables the name index, since prefix search needs the plain names.
+    pub fn compress_names(mut self, compress: bool) -> Self {
+        self.compress_names = compress;
+        self
+    }
+
+    /// Set whether to store names in a single string table (default `false`).
+    ///
+    /// The tables then hold empty names, and the names are emitted once as a
+    /// concatenated `&'static str` with an `(offset, len)` pair of `u32`s per
+    /// name, instead of a string reference in every entry. Names are looked
+    /// up through the database as with [`compress_names`](Self::compress_names),
+    /// but without decompressing, and the name index is disabled for the same
+    /// reason. Compressed names take precedence if both are set.
+    pub fn string_table(mut self, string_table: bool) -> Self {
+        self.string_table = string_table;
+        self
+    }
+
+    /// Set whether to emit perfect-hash indexes for vendor and device lookups (default `false`).
+    ///
+    /// [`PciDatabase::find_vendor`](crate::PciDatabase::find_vendor) and
+    /// [`PciDatabase::find_device`](crate::PciDatabase::find_device) then take
+    /// constant time instead of a binary search and a scan of the vendor's
+    /// devices, at the cost of about 8 bytes of tables per device; see
+    /// [`phf`](crate::phf).
+    pub fn emit_phf(mut self, emit: bool) -> Self {
+        self.emit_phf = emit;
+        self
+    }
+
+    /// Set how to store names with non-ASCII characters (default [`NameForm::Original`]).
+    ///
+    /// By default names are kept as UTF-8, and vendor, device, and subsystem
+    /// entries also carry a transliterated copy for `name_ascii`. With
+    /// [`NameForm::Escaped`] or [`NameForm::Transliterated`] the names
+    /// themselves are stored in that ASCII form instead, for firmware consoles
+    /// that print garbage for UTF-8, and the entries report it from `name_form`.
+    /// This applies to every name, including those behind the name index,
+    /// compressed names, and [`write_blob`].
+    pub fn non_ascii_names(mut self, form: NameForm) -> Self {
+        self.non_ascii_names = form;
+        self
+    }
+
+    /// Set the maximum length of a stored name in bytes (default unlimited).
+    ///
+    /// Longer names are cut at a character boundary and end with `...`, so
+    /// the string table has a fixed upper bound for firmware with tight flash
+    /// budgets, and every name fits a fixed-width display. The limit applies
+    /// after [`non_ascii_names`](Self::non_ascii_names) and to the ASCII
+    /// copies of names as well. Shortened names may no longer be unique, and
+    /// prefix search only sees what is kept.
+    pub fn max_name_length(mut self, max: Option<usize>) -> Self {
+        self.max_name_length = max;
+        self
+    }
+
+    /// Check if the table entries hold empty names, with the names stored elsewhere.
+    fn names_out_of_line(&self) -> bool {
+        self.compress_names || self.string_table
+    }
+
+    /// Check if names are stored exactly as given.
+    fn keeps_names(&self) -> bool {
+        self.non_ascii_names == NameForm::Original && self.max_name_length.is_none()
+    }
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Entry counts and name sizes of a generated database, for tracking its footprint.
+///
+/// [`write_rust`] returns the report for what it emitted, and the generated
+/// source defines it as a `DATABASE_SIZE` constant, so the effect of updating
+/// pci.ids or toggling features can be checked from a build script or at run time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SizeReport {
+    /// Number of vendors
+    pub vendors: usize,
+    /// Number of devices
+    pub devices: usize,
+    /// Number of subsystems
+    pub subsystems: usize,
+    /// Number of device classes
+    pub classes: usize,
+    /// Number of subclasses
+    pub subclasses: usize,
+    /// Number of programming interfaces
+    pub prog_interfaces: usize,
+    /// Number of name index entries
+    pub name_index_entries: usize,
+    /// Bytes of name text: the string literals, the string table, or the compressed name data and dictionary words
+    pub name_bytes: usize,
+    /// Number of words in the compressed name dictionary
+    pub dictionary_words: usize,
+    /// Number of `u32` offsets locating compressed or string table names
+    pub name_offsets: usize,
+    /// Number of `u16` displacements in the perfect-hash indexes
+    pub phf_displacements: usize,
+    /// Number of `u32` slots in the perfect-hash indexes
+    pub phf_slots: usize,
+}
+
+impl SizeReport {
+    /// Get the total number of entries.
+    pub const fn total_entries(&self) -> usize {
+        self.vendors + self.devices + self.subsystems + self.classes + self.subclasses + self.prog_interfaces
+    }
+
+    /// Estimate the bytes of static data on a target with pointers of `pointer_size` bytes.
+    ///
+    /// This counts the tables and the name text. Identical string literals are
+    /// merged by the compiler, so the binary may use somewhat less.
+    pub const fn estimated_bytes(&self, pointer_size: usize) -> usize {
+        // Each entry is its ID, padded to pointer alignment, followed by `&str`
+        // and slice references of two pointers each
+        const fn entry(id_bytes: usize, references: usize, pointer_size: usize) -> usize {
+            id_bytes.div_ceil(pointer_size) * pointer_size + references * 2 * pointer_size
+        }
+
+        self.vendors * entry(2, 6, pointer_size)
+            + self.devices * entry(2, 3, pointer_size)
+            + self.subsystems * entry(4, 2, pointer_size)
+            + self.classes * entry(1, 2, pointer_size)
+            + self.subclasses * entry(1, 2, pointer_size)
+            + self.prog_interfaces * entry(1, 1, pointer_size)
+            + self.name_index_entries * 6
+            + self.dictionary_words * 2 * pointer_size
+            + self.name_offsets * 4
+            + self.phf_displacements * 2
+            + self.phf_slots * 4
+            + self.name_bytes
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} vendors, {} devices, {} subsystems, {} classes, {} subclasses, {} programming interfaces, {} bytes of names",
+            self.vendors, self.devices, self.subsystems, self.classes, self.subclasses, self.prog_interfaces, self.name_bytes
+        )
+    }
+}
+
+/// Generate Rust source defining static tables and a database static.
+///
+/// The output is meant to be written to `OUT_DIR` and pulled in with `include!`.
+/// Lookups need every level sorted by ID; unsorted input is emitted in sorted
+/// order (see [`ParsedDatabase::check_order`] to detect this beforehand).
+///
+/// This buffers the whole output; use [`write_rust`] to stream it instead.
+///
+/// # Panics
+///
+/// Panics if the database cannot be generated, for the reasons listed on [`write_rust`].
+pub fn generate_rust(database: &ParsedDatabase, options: &GenOptions) -> String {
+    let mut code = String::new();
+    // Writing to a String cannot fail, so any error comes from the database itself
+    if write_rust(&mut code, database, options).is_err() {
+        match database.find_duplicate() {
+            Some(key) => panic!("cannot generate a database with duplicate {}", key),
+            None => panic!("database too large for the name index"),
+        }
+    }
+    code
+}
+
+/// Stream generated Rust source to a [`fmt::Write`] sink.
+///
+/// Produces the same output as [`generate_rust`] without holding it in memory,
+/// so a build script can write the full database straight to a file, and
+/// returns a [`SizeReport`] of the emitted tables.
+///
+/// Besides errors from the sink, this fails without emitting anything if two
+/// entries of the same list share an ID (see [`ParsedDatabase::find_duplicate`]),
+/// since lookups could then never reach one of them, and part-way through if
+/// the name index does not fit its `u16` fields.
+///
+/// ```rust
+/// use ids_rs::codegen::{write_rust, GenOptions};
+/// use ids_rs::parser::ParsedDatabase;
+///
+/// struct IoSink<W: std::io::Write>(W);
+///
+/// impl<W: std::io::Write> core::fmt::Write for IoSink<W> {
+///     fn write_str(&mut self, s: &str) -> core::fmt::Result {
+///         self.0.write_all(s.as_bytes()).map_err(|_| core::fmt::Error)
+///     }
+/// }
+///
+/// let mut sink = IoSink(std::io::BufWriter::new(Vec::new()));
+/// write_rust(&mut sink, &ParsedDatabase::default(), &GenOptions::new().crate_path("::ids_rs")).unwrap();
+/// ```
+pub fn write_rust<W: Write>(out: &mut W, database: &ParsedDatabase, options: &GenOptions) -> Result<SizeReport, fmt::Error> {
+    let krate = options.crate_path.as_str();
+    let p = options.static_prefix.as_str();
+    let vis = options.table_visibility.keyword();
+
+    let database = sorted_database(database)?;
+    let database = &*database;
+    let vendors = if options.emit_vendors { database.vendors.as_slice() } else { &[] };
+    let classes = &*emitted_classes(database, options);
+    // Tables are emitted from the names as given, so entries can record which
+    // ones were converted, but the indexes are built from the converted and
+    // shortened names the tables will hold
+    let named = &*stored_database(database, options)?;
+    let named_vendors = if options.emit_vendors { named.vendors.as_slice() } else { &[] };
+    let named_classes = &*emitted_classes(named, options);
+    let mut report = count_entries(vendors, classes, options);
+
+    out.write_str("// Generated PCI database from pci.ids\n")?;
+    out.write_str("// This file is automatically generated by the build script\n\n")?;
+
+    // Names are emitted as plain literals even when they repeat: rustc already
+    // merges identical string constants, so interning them into shared statics
+    // leaves the binary unchanged and only grows the rlib
+    let out_of_line = options.names_out_of_line();
+    let form = options.non_ascii_names;
+    let literal = |name: &str| if out_of_line { "\"\"".to_string() } else { format!("{:?}", stored_name(name, options)) };
+    let with_ascii_name = |name: &str| {
+        let mut calls = String::new();
+        // Transliterated names are their own ASCII copy
+        if !out_of_line && form != NameForm::Transliterated {
+            calls.push_str(&with_ascii_name(name, options.max_name_length));
+        }
+        if form != NameForm::Original && !name.is_ascii() {
+            calls.push_str(&format!(".with_name_form({}::types::NameForm::{:?})", krate, form));
+        }
+        calls
+    };
+
+    // Generate subsystem data
+    let has_subsystems = |device: &DeviceBuilder| options.emit_subsystems && !device.subsystems.is_empty();
+    for vendor in vendors {
+        for device in &vendor.devices {
+            if has_subsystems(device) {
+                writeln!(
+                    out,
+                    "{vis}static {p}SUBSYSTEMS_{}_{}: &[{}::devices::Subsystem] = &[",
+                    vendor.id.value(), device.id.value(), krate
+                )?;
+                for subsystem in &device.subsystems {
+                    writeln!(
+                        out,
+                        "    {k}::devices::Subsystem::new({k}::types::SubvendorId::new(0x{:04x}), {k}::types::SubdeviceId::new(0x{:04x}), {}){},",
+                        subsystem.subvendor_id.value(), subsystem.subdevice_id.value(), literal(&subsystem.name), with_ascii_name(&subsystem.name), k = krate
+                    )?;
+                }
+                out.write_str("];\n\n")?;
+            }
+        }
+    }
+
+    // Generate device data
+    for vendor in vendors {
+        if !vendor.devices.is_empty() {
+            writeln!(
+                out,
+                "{vis}static {p}DEVICES_{}: &[{}::devices::Device] = &[",
+                vendor.id.value(), krate
+            )?;
+            for device in &vendor.devices {
+                let subsystems_ref = if has_subsystems(device) {
+                    format!("{p}SUBSYSTEMS_{}_{}", vendor.id.value(), device.id.value())
+                } else {
+                    "&[]".to_string()
+                };
+
+                writeln!(
+                    out,
+                    "    {k}::devices::Device::new({k}::types::DeviceId::new(0x{:04x}), {}, {}){},",
+                    device.id.value(), literal(&device.name), subsystems_ref, with_ascii_name(&device.name), k = krate
+                )?;
+            }
+            out.write_str("];\n\n")?;
+        }
+    }
+
+    // Generate vendor data
+    writeln!(out, "{vis}static {p}VENDORS: &[{}::vendors::Vendor] = &[", krate)?;
+    for vendor in vendors {
+        let devices_ref = if vendor.devices.is_empty() {
+            "&[]".to_string()
+        } else {
+            format!("{p}DEVICES_{}", vendor.id.value())
+        };
+
+        writeln!(
+            out,
+            "    {k}::vendors::Vendor::new({k}::types::VendorId::new(0x{:04x}), {}, {}){},",
+            vendor.id.value(), literal(&vendor.name), devices_ref, with_ascii_name(&vendor.name), k = krate
+        )?;
+    }
+    out.write_str("];\n\n")?;
+
+    // Generate the sorted name index for prefix search
+    let emit_name_index = options.emit_name_index && !out_of_line;
+    if emit_name_index {
+        // Entries are fixed-width u16s; fail rather than emit truncated indexes
+        let name_index = build_name_index(named_vendors).ok_or(fmt::Error)?;
+        report.name_index_entries = name_index.len();
+        writeln!(out, "{vis}static {p}NAME_INDEX: &[{}::index::NameIndexEntry] = &[", krate)?;
+        for (vendor_index, device_index, offset) in name_index {
+            writeln!(
+                out,
+                "    {}::index::NameIndexEntry::new({}, {}, {}),",
+                krate, vendor_index, device_index, offset
+            )?;
+        }
+        out.write_str("];\n\n")?;
+    }
+
+    // Generate programming interface data
+    for class in classes {
+        for subclass in &class.subclasses {
+            if !subclass.prog_interfaces.is_empty() {
+                writeln!(
+                    out,
+                    "{vis}static {p}PROG_INTERFACES_{}_{}: &[{}::classes::ProgInterface] = &[",
+                    class.id.value(), subclass.id.value(), krate
+                )?;
+                for prog_if in &subclass.prog_interfaces {
+                    writeln!(
+                        out,
+                        "    {k}::classes::ProgInterface::new({k}::types::ProgInterfaceId::new(0x{:02x}), {}),",
+                        prog_if.id.value(), literal(&prog_if.name), k = krate
+                    )?;
+                }
+                out.write_str("];\n\n")?;
+            }
+        }
+    }
+
+    // Generate subclass data
+    for class in classes {
+        if !class.subclasses.is_empty() {
+            writeln!(
+                out,
+                "{vis}static {p}SUBCLASSES_{}: &[{}::classes::SubClass] = &[",
+                class.id.value(), krate
+            )?;
+            for subclass in &class.subclasses {
+                let prog_interfaces_ref = if subclass.prog_interfaces.is_empty() {
+                    "&[]".to_string()
+                } else {
+                    format!("{p}PROG_INTERFACES_{}_{}", class.id.value(), subclass.id.value())
+                };
+
+                writeln!(
+                    out,
+                    "    {k}::classes::SubClass::new({k}::types::SubClassId::new(0x{:02x}), {}, {}),",
+                    subclass.id.value(), literal(&subclass.name), prog_interfaces_ref, k = krate
+                )?;
+            }
+            out.write_str("];\n\n")?;
+        }
+    }
+
+    // Generate class data
+    writeln!(out, "{vis}static {p}CLASSES: &[{}::classes::DeviceClass] = &[", krate)?;
+    for class in classes {
+        let subclasses_ref = if class.subclasses.is_empty() {
+            "&[]".to_string()
+        } else {
+            format!("{p}SUBCLASSES_{}", class.id.value())
+        };
+
+        writeln!(
+            out,
+            "    {k}::classes::DeviceClass::new({k}::types::DeviceClassId::new(0x{:02x}), {}, {}),",
+            class.id.value(), literal(&class.name), subclasses_ref, k = krate
+        )?;
+    }
+    out.write_str("];\n\n")?;
+
+    // Generate the compressed names or the string table
+    let mut attachments = String::new();
+    if options.compress_names {
+        let names = compress_names(named_vendors, named_classes, options.emit_subsystems);
+        report.name_bytes = names.data.len() + names.dictionary.iter().map(String::len).sum::<usize>();
+        report.dictionary_words = names.dictionary.len();
+        report.name_offsets = names.restarts.len() + names.vendor_starts.len() + names.class_starts.len();
+        write!(out, "{vis}static {p}NAME_DATA: &[u8] = ")?;
+        write_byte_string(out, &names.data)?;
+        out.write_str(";\n\n")?;
+        writeln!(out, "{vis}static {p}NAME_DICTIONARY: &[&str] = &[")?;
+        for word in &names.dictionary {
+            writeln!(out, "    {:?},", word)?;
+        }
+        out.write_str("];\n\n")?;
+        for (table, values) in [("NAME_RESTARTS", &names.restarts), ("VENDOR_NAME_STARTS", &names.vendor_starts), ("CLASS_NAME_STARTS", &names.class_starts)] {
+            writeln!(out, "{vis}static {p}{table}: &[u32] = &[")?;
+            for line in values.chunks(16) {
+                out.write_str("   ")?;
+                for value in line {
+                    write!(out, " {},", value)?;
+                }
+                out.write_str("\n")?;
+            }
+            out.write_str("];\n\n")?;
+        }
+        write!(
+            attachments,
+            ".with_compressed_names({k}::compressed::CompressedNames::new({p}NAME_DATA, {p}NAME_RESTARTS, {p}NAME_DICTIONARY, {p}VENDOR_NAME_STARTS, {p}CLASS_NAME_STARTS, {}, {}))",
+            names.max_name_len, names.max_vendor_names_len, k = krate
+        )?;
+    } else if options.string_table {
+        let table = string_table(named_vendors, named_classes, options.emit_subsystems);
+        report.name_bytes = table.text.len();
+        report.name_offsets = table.spans.len() + table.vendor_starts.len() + table.class_starts.len();
+        writeln!(out, "{vis}static {p}NAME_TEXT: &str = {:?};\n", table.text)?;
+        for (name, values) in [("NAME_SPANS", &table.spans), ("VENDOR_NAME_STARTS", &table.vendor_starts), ("CLASS_NAME_STARTS", &table.class_starts)] {
+            writeln!(out, "{vis}static {p}{name}: &[u32] = &[")?;
+            for line in values.chunks(16) {
+                out.write_str("   ")?;
+                for value in line {
+                    write!(out, " {},", value)?;
+                }
+                out.write_str("\n")?;
+            }
+            out.write_str("];\n\n")?;
+        }
+        write!(
+            attachments,
+            ".with_compressed_names({k}::compressed::CompressedNames::string_table({p}NAME_TEXT, {p}NAME_SPANS, {p}VENDOR_NAME_STARTS, {p}CLASS_NAME_STARTS, {}, {}))",
+            table.max_name_len, table.max_vendor_names_len, k = krate
+        )?;
+    }
+
+    // Generate the perfect-hash indexes
+    if options.emit_phf && !vendors.is_empty() {
+        // Values are fixed-width u32s; fail rather than emit truncated indexes
+        let (vendor_phf, device_phf) = build_phf_indexes(vendors).ok_or(fmt::Error)?;
+        for (name, tables) in [("VENDOR", &vendor_phf), ("DEVICE", &device_phf)] {
+            writeln!(out, "{vis}static {p}{name}_PHF_DISPLACEMENTS: &[u16] = &[")?;
+            for line in tables.displacements.chunks(16) {
+                out.write_str("   ")?;
+                for value in line {
+                    write!(out, " {},", value)?;
+                }
+                out.write_str("\n")?;
+            }
+            out.write_str("];\n\n")?;
+            writeln!(out, "{vis}static {p}{name}_PHF_SLOTS: &[u32] = &[")?;
+            for line in tables.slots.chunks(8) {
+                out.write_str("   ")?;
+                for value in line {
+                    write!(out, " 0x{:08x},", value)?;
+                }
+                out.write_str("\n")?;
+            }
+            out.write_str("];\n\n")?;
+            report.phf_displacements += tables.displacements.len();
+            report.phf_slots += tables.slots.len();
+        }
+        write!(
+            attachments,
+            ".with_phf({k}::phf::PhfIndex::new({p}VENDOR_PHF_DISPLACEMENTS, {p}VENDOR_PHF_SLOTS), {k}::phf::PhfIndex::new({p}DEVICE_PHF_DISPLACEMENTS, {p}DEVICE_PHF_SLOTS))",
+            k = krate
+        )?;
+    }
+
+    // Generate the snapshot constants
+    let version = database.version.as_deref().unwrap_or("");
+    let date = database.date.as_deref().unwrap_or("");
+    let dvis = options.database_visibility.keyword();
+    out.write_str("/// The `Version:` field of the pci.ids header the database was generated from, or empty if unknown.\n")?;
+    writeln!(out, "{dvis}const {p}DATABASE_VERSION: &str = {:?};", version)?;
+    out.write_str("/// The `Date:` field of the pci.ids header the database was generated from, or empty if unknown.\n")?;
+    writeln!(out, "{dvis}const {p}DATABASE_DATE: &str = {:?};\n", date)?;
+    write!(attachments, ".with_version({p}DATABASE_VERSION, {p}DATABASE_DATE)")?;
+    out.write_str("/// Entry counts and name sizes of the generated database; see `SizeReport::estimated_bytes`.\n")?;
+    writeln!(
+        out,
+        "{dvis}const {p}DATABASE_SIZE: {k}::codegen::SizeReport = {k}::codegen::SizeReport {{ vendors: {}, devices: {}, subsystems: {}, classes: {}, subclasses: {}, prog_interfaces: {}, name_index_entries: {}, name_bytes: {}, dictionary_words: {}, name_offsets: {}, phf_displacements: {}, phf_slots: {} }};\n",
+        report.vendors, report.devices, report.subsystems, report.classes, report.subclasses, report.prog_interfaces,
+        report.name_index_entries, report.name_bytes, report.dictionary_words, report.name_offsets,
+        report.phf_displacements, report.phf_slots, k = krate
+    )?;
+
+    // Generate the database
+    if emit_name_index {
+        write!(attachments, ".with_name_index({p}NAME_INDEX)")?;
+    }
+    out.write_str("/// The global PCI database instance.\n")?;
+    writeln!(
+        out,
+        "{}static {}: {k}::database::PciDatabase = {k}::database::PciDatabase::new({p}VENDORS, {p}CLASSES){};",
+        options.database_visibility.keyword(), options.database_name, attachments, k = krate
+    )?;
+
+    Ok(report)
+}
+
+/// Magic bytes at the start of a binary database.
+pub(crate) const BLOB_MAGIC: [u8; 4] = *b"PCIB";
+
+/// Version of the binary database format.
+pub(crate) const BLOB_VERSION: u32 = 1;
+
+/// Length of the binary database header: magic, version, six tables, and the strings.
+pub(crate) const BLOB_HEADER_LEN: usize = 64;
+
+/// Length of a record in a binary database table.
+pub(crate) const BLOB_RECORD_LEN: usize = 16;
+
+/// Serialize a database into the compact binary format read by [`BlobDatabase`](crate::blob::BlobDatabase).
+///
+/// This is an alternative to [`write_rust`] for large databases: the blob is
+/// written to `OUT_DIR` and embedded with `include_bytes!`, which compiles in
+/// a fraction of the time the equivalent Rust source takes. All integers are
+/// little-endian, so the blob means the same on every target.
+///
+/// The `emit_vendors`, `emit_classes`, `emit_subsystems`, and
+/// `emit_prog_interfaces` options apply; names are always stored uncompressed,
+/// once per distinct name, and no name index or perfect-hash index is emitted. Unsorted input is stored sorted.
+///
+/// Fails with [`PciError::DuplicateEntry`] if two entries of the same list
+/// share an ID, and with [`PciError::LimitExceeded`] if the blob would exceed
+/// 4 GiB.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::blob::BlobDatabase;
+/// use ids_rs::codegen::{write_blob, GenOptions};
+/// use ids_rs::parser::PciIdsParser;
+/// use ids_rs::VendorId;
+///
+/// let mut parser = PciIdsParser::new();
+/// parser.parse("8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n").unwrap();
+///
+/// let blob = write_blob(&parser.into_parsed(), &GenOptions::new()).unwrap();
+/// let db = BlobDatabase::new(blob.leak()).unwrap();
+/// assert_eq!(db.find_vendor(VendorId::new(0x8086)).unwrap().name(), "Intel Corporation");
+/// ```
+pub fn write_blob(database: &ParsedDatabase, options: &GenOptions) -> PciResult<Vec<u8>> {
+    let database = &*stored_database(database, options).map_err(|_| PciError::DuplicateEntry)?;
+    let vendors = if options.emit_vendors { database.vendors.as_slice() } else { &[] };
+    let classes = &*emitted_classes(database, options);
+
+    // Tables in header order; each record is a key, a name, and its first child
+    let mut tables: [Vec<[u32; 3]>; 6] = Default::default();
+    let mut first_children: [Vec<u32>; 6] = Default::default();
+    let mut strings = BlobStrings::default();
+    let mut name = |name| strings.add(name);
+    let count = |table: &Vec<[u32; 3]>| u32::try_from(table.len()).map_err(|_| PciError::LimitExceeded);
+
+    for vendor in vendors {
+        first_children[0].push(count(&tables[1])?);
+        let [offset, len] = name(&vendor.name)?;
+        tables[0].push([u32::from(vendor.id.value()), offset, len]);
+        for device in &vendor.devices {
+            first_children[1].push(count(&tables[2])?);
+            let [offset, len] = name(&device.name)?;
+            tables[1].push([u32::from(device.id.value()), offset, len]);
+            for subsystem in device.subsystems.iter().filter(|_| options.emit_subsystems) {
+                first_children[2].push(0);
+                let key = u32::from(subsystem.subvendor_id.value()) << 16 | u32::from(subsystem.subdevice_id.value());
+                let [offset, len] = name(&subsystem.name)?;
+                tables[2].push([key, offset, len]);
+            }
+        }
+    }
+    for class in classes {
+        first_children[3].push(count(&tables[4])?);
+        let [offset, len] = name(&class.name)?;
+        tables[3].push([u32::from(class.id.value()), offset, len]);
+        for subclass in &class.subclasses {
+            first_children[4].push(count(&tables[5])?);
+            let [offset, len] = name(&subclass.name)?;
+            tables[4].push([u32::from(subclass.id.value()), offset, len]);
+            for prog_if in &subclass.prog_interfaces {
+                first_children[5].push(0);
+                let [offset, len] = name(&prog_if.name)?;
+                tables[5].push([u32::from(prog_if.id.value()), offset, len]);
+            }
+        }
+    }
+
+    let records: usize = tables.iter().map(Vec::len).sum();
+    let mut blob = Vec::with_capacity(BLOB_HEADER_LEN + records * BLOB_RECORD_LEN + strings.text.len());
+    blob.extend_from_slice(&BLOB_MAGIC);
+    blob.extend_from_slice(&BLOB_VERSION.to_le_bytes());
+    let mut position = BLOB_HEADER_LEN;
+    for table in &tables {
+        blob.extend_from_slice(&u32::try_from(position).map_err(|_| PciError::LimitExceeded)?.to_le_bytes());
+        blob.extend_from_slice(&count(table)?.to_le_bytes());
+        position += table.len() * BLOB_RECORD_LEN;
+    }
+    blob.extend_from_slice(&u32::try_from(position).map_err(|_| PciError::LimitExceeded)?.to_le_bytes());
+    blob.extend_from_slice(&u32::try_from(strings.text.len()).map_err(|_| PciError::LimitExceeded)?.to_le_bytes());
+
+    for (table, first_children) in tables.iter().zip(&first_children) {
+        for (&[key, offset, len], first_child) in table.iter().zip(first_children) {
+            for value in [key, offset, len, *first_child] {
+                blob.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+    blob.extend_from_slice(strings.text.as_bytes());
+    u32::try_from(blob.len()).map_err(|_| PciError::LimitExceeded)?;
+
+    Ok(blob)
+}
+
+/// The string area of a binary database, storing each distinct name once.
+#[derive(Default)]
+struct BlobStrings<'a> {
+    text: String,
+    offsets: BTreeMap<&'a str, u32>,
+}
+
+impl<'a> BlobStrings<'a> {
+    /// Store a name if it is new, returning its offset and length.
+    fn add(&mut self, name: &'a str) -> PciResult<[u32; 2]> {
+        let len = u32::try_from(name.len()).map_err(|_| PciError::LimitExceeded)?;
+        let offset = match self.offsets.get(name) {
+            Some(&offset) => offset,
+            None => {
+                let offset = u32::try_from(self.text.len()).map_err(|_| PciError::LimitExceeded)?;
+                self.text.push_str(name);
+                self.offsets.insert(name, offset);
+                offset
+            }
+        };
+        Ok([offset, len])
+    }
+}
+
+/// Write a database as JSON, for tooling that audits the embedded data.
+///
+/// The output holds exactly what [`write_rust`] or [`write_blob`] embeds
+/// with the same options: the entries they keep, sorted by ID, with names as
+/// stored. IDs are lowercase hex strings as in pci.ids, and every entry is on
+/// a line of its own, so two snapshots can be compared with a plain diff:
+///
+/// ```text
+/// {
+///   "version": "2025.07.11",
+///   "date": "2025-07-11 03:15:02",
+///   "vendors": [
+///     {"id": "8086", "name": "Intel Corporation", "devices": [
+///       {"id": "1533", "name": "I210 Gigabit Network Connection", "subsystems": []}
+///     ]}
+///   ],
+///   "classes": []
+/// }
+/// ```
+///
+/// An unknown version or date is `null`. Like [`write_rust`], this fails
+/// without writing anything if two entries of the same list share an ID.
+///
+/// ```rust
+/// use ids_rs::codegen::{write_json, GenOptions};
+/// use ids_rs::parser::PciIdsParser;
+///
+/// let mut parser = PciIdsParser::new();
+/// parser.parse("8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n").unwrap();
+///
+/// let mut json = String::new();
+/// write_json(&mut json, &parser.into_parsed(), &GenOptions::new()).unwrap();
+/// assert!(json.contains(r#"{"id": "1533", "name": "I210 Gigabit Network Connection", "subsystems": []}"#));
+/// ```
+pub fn write_json<W: Write>(out: &mut W, database: &ParsedDatabase, options: &GenOptions) -> fmt::Result {
+    let database = &*stored_database(database, options)?;
+    let vendors = if options.emit_vendors { database.vendors.as_slice() } else { &[] };
+    let classes = &*emitted_classes(database, options);
+
+    out.write_str("{\n  \"version\": ")?;
+    write_json_string(out, database.version.as_deref())?;
+    out.write_str(",\n  \"date\": ")?;
+    write_json_string(out, database.date.as_deref())?;
+
+    out.write_str(",\n  \"vendors\": [")?;
+    for (i, vendor) in vendors.iter().enumerate() {
+        write!(out, "{}    {{\"id\": \"{:04x}\", \"name\": ", json_separator(i), vendor.id.value())?;
+        write_json_string(out, Some(&vendor.name))?;
+        out.write_str(", \"devices\": [")?;
+        for (j, device) in vendor.devices.iter().enumerate() {
+            write!(out, "{}      {{\"id\": \"{:04x}\", \"name\": ", json_separator(j), device.id.value())?;
+            write_json_string(out, Some(&device.name))?;
+            out.write_str(", \"subsystems\": [")?;
+            let subsystems = if options.emit_subsystems { device.subsystems.as_slice() } else { &[] };
+            for (k, subsystem) in subsystems.iter().enumerate() {
+                write!(
+                    out,
+                    "{}        {{\"subvendor\": \"{:04x}\", \"subdevice\": \"{:04x}\", \"name\": ",
+                    json_separator(k), subsystem.subvendor_id.value(), subsystem.subdevice_id.value()
+                )?;
+                write_json_string(out, Some(&subsystem.name))?;
+                out.write_str("}")?;
+            }
+            write!(out, "{}]}}", json_closing(subsystems.is_empty(), "      "))?;
+        }
+        write!(out, "{}]}}", json_closing(vendor.devices.is_empty(), "    "))?;
+    }
+    write!(out, "{}]", json_closing(vendors.is_empty(), "  "))?;
+
+    out.write_str(",\n  \"classes\": [")?;
+    for (i, class) in classes.iter().enumerate() {
+        write!(out, "{}    {{\"id\": \"{:02x}\", \"name\": ", json_separator(i), class.id.value())?;
+        write_json_string(out, Some(&class.name))?;
+        out.write_str(", \"subclasses\": [")?;
+        for (j, subclass) in class.subclasses.iter().enumerate() {
+            write!(out, "{}      {{\"id\": \"{:02x}\", \"name\": ", json_separator(j), subclass.id.value())?;
+            write_json_string(out, Some(&subclass.name))?;
+            out.write_str(", \"prog_interfaces\": [")?;
+            for (k, prog_if) in subclass.prog_interfaces.iter().enumerate() {
+                write!(out, "{}        {{\"id\": \"{:02x}\", \"name\": ", json_separator(k), prog_if.id.value())?;
+                write_json_string(out, Some(&prog_if.name))?;
+                out.write_str("}")?;
+            }
+            write!(out, "{}]}}", json_closing(subclass.prog_interfaces.is_empty(), "      "))?;
+        }
+        write!(out, "{}]}}", json_closing(class.subclasses.is_empty(), "    "))?;
+    }
+    write!(out, "{}]", json_closing(classes.is_empty(), "  "))?;
+    out.write_str("\n}\n")
+}
+
+/// The text in front of the `index`-th element of a JSON array, which starts a line.
+fn json_separator(index: usize) -> &'static str {
+    if index == 0 {
+        "\n"
+    } else {
+        ",\n"
+    }
+}
+
+/// The text in front of the `]` closing a JSON array: nothing if it is empty, or a line break and indentation.
+fn json_closing(empty: bool, indent: &str) -> String {
+    if empty {
+        String::new()
+    } else {
+        format!("\n{}", indent)
+    }
+}
+
+/// Write a JSON string, or `null` for `None`.
+fn write_json_string<W: Write>(out: &mut W, value: Option<&str>) -> fmt::Result {
+    let Some(value) = value else {
+        return out.write_str("null");
+    };
+    out.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Legal-entity words left off the end of derived constant names.
+const CONSTANT_NAME_SUFFIXES: &[&str] = &[
+    "INC", "INCORPORATED", "CORPORATION", "CORP", "CO", "COMPANY", "LTD", "LIMITED", "LLC", "GMBH", "AG", "AB", "SA", "BV", "OY",
+];
+
+/// Generate `VendorId` constants for well-known vendors.
+///
+/// Emits a `pub const` for every `(name, id)` pair in `curated`, in order,
+/// followed by one for each of the `top` vendors with the most devices that
+/// are not already listed. Each constant is documented with the vendor's name
+/// from `database`, so the constants follow pci.ids as it changes; curated
+/// vendors missing from `database` are still emitted, so code naming them keeps
+/// compiling against a trimmed or fallback database.
+///
+/// Names for the added vendors come from the first of their bracketed aliases
+/// that is not taken (`AMD`, or else `ATI`, for `"Advanced Micro Devices, Inc.
+/// [AMD/ATI]"`) or else their name without legal-entity suffixes
+/// (`MELLANOX_TECHNOLOGIES`). Vendors left without a free, valid identifier
+/// are skipped.
+///
+/// The output is meant to be pulled in with `include!` inside a module;
+/// `crate_path` applies as for [`write_rust`].
+///
+/// ```rust
+/// use ids_rs::codegen::{write_well_known_vendors, GenOptions};
+/// use ids_rs::parser::PciIdsParser;
+/// use ids_rs::VendorId;
+///
+/// let mut parser = PciIdsParser::new();
+/// parser.parse("15b3  Mellanox Technologies\n\t1017  MT27800 Family [ConnectX-5]\n8086  Intel Corporation\n").unwrap();
+///
+/// let mut code = String::new();
+/// let options = GenOptions::new().crate_path("::ids_rs");
+/// write_well_known_vendors(&mut code, &parser.into_parsed(), &[("INTEL", VendorId::new(0x8086))], 1, &options).unwrap();
+/// assert!(code.contains("pub const INTEL: ::ids_rs::types::VendorId = ::ids_rs::types::VendorId::new(0x8086);"));
+/// assert!(code.contains("pub const MELLANOX_TECHNOLOGIES:"));
+/// ```
+pub fn write_well_known_vendors<W: Write>(
+    out: &mut W,
+    database: &ParsedDatabase,
+    curated: &[(&str, VendorId)],
+    top: usize,
+    options: &GenOptions,
+) -> fmt::Result {
+    let krate = options.crate_path.as_str();
+    let name_of = |id: VendorId| database.vendors.iter().find(|vendor| vendor.id == id).map(|vendor| vendor.name.as_str());
+
+    let mut by_devices: Vec<&VendorBuilder> = database.vendors.iter().collect();
+    by_devices.sort_by_key(|vendor| (core::cmp::Reverse(vendor.devices.len()), vendor.id));
+    let added = by_devices
+        .into_iter()
+        .filter(|vendor| !vendor.devices.is_empty() && !curated.iter().any(|&(_, id)| id == vendor.id))
+        .map(|vendor| (constant_names(&vendor.name), vendor.id));
+
+    let mut taken: Vec<String> = Vec::new();
+    let mut remaining = top;
+    let constants = curated.iter().map(|&(name, id)| (vec![name.to_string()], id, true)).chain(added.map(|(names, id)| (names, id, false)));
+    for (names, id, is_curated) in constants {
+        let Some(name) = names.into_iter().find(|name| !taken.contains(name)) else {
+            continue;
+        };
+        if !is_curated {
+            if remaining == 0 {
+                break;
+            }
+            remaining -= 1;
+        }
+        let doc = match name_of(id) {
+            Some(vendor_name) => escape_markdown(vendor_name),
+            None => format!("Vendor {:04x}", id.value()),
+        };
+        writeln!(out, "#[doc = {:?}]", doc)?;
+        writeln!(out, "pub const {}: {k}::types::VendorId = {k}::types::VendorId::new(0x{:04x});", name, id.value(), k = krate)?;
+        taken.push(name);
+    }
+    Ok(())
+}
+
+/// Derive candidate constant names from a vendor name: its aliases, then its canonical name.
+fn constant_names(vendor_name: &str) -> Vec<String> {
+    let (canonical, aliases) = match vendor_name.trim_end().strip_suffix(']').and_then(|rest| rest.rsplit_once('[')) {
+        Some((canonical, aliases)) => (canonical, aliases),
+        None => (vendor_name, ""),
+    };
+    let sources = aliases.split('/').chain(canonical.split([',', '(']).next());
+    sources.filter(|source| source.is_ascii()).filter_map(constant_name).collect()
+}
+
+/// Turn ASCII text into an upper-case identifier, if it gives a valid one.
+fn constant_name(text: &str) -> Option<String> {
+    let mut words: Vec<String> = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_uppercase())
+        .collect();
+    while words.len() > 1 && CONSTANT_NAME_SUFFIXES.contains(&words[words.len() - 1].as_str()) {
+        words.pop();
+    }
+    let name = words.join("_");
+    name.starts_with(|c: char| c.is_ascii_alphabetic()).then_some(name)
+}
+
+/// Escape the characters rustdoc would read as Markdown, such as the brackets of aliases.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Select the class tree to emit, trimmed to subclasses unless programming interfaces are wanted.
+fn emitted_classes<'a>(database: &'a ParsedDatabase, options: &GenOptions) -> Cow<'a, [ClassBuilder]> {
+    if !options.emit_classes {
+        return Cow::Borrowed(&[]);
+    }
+    if options.emit_prog_interfaces {
+        return Cow::Borrowed(&database.classes);
+    }
+    let mut classes = database.classes.clone();
+    for subclass in classes.iter_mut().flat_map(|class| &mut class.subclasses) {
+        subclass.prog_interfaces.clear();
+    }
+    Cow::Owned(classes)
+}
+
+/// Sort a copy of the database if it is out of order, failing on duplicate entries.
+///
+/// Binary search finds only one of several entries with the same ID, so no
+/// writer accepts them.
+fn sorted_database(database: &ParsedDatabase) -> Result<Cow<'_, ParsedDatabase>, fmt::Error> {
+    let database = if database.check_order().is_empty() {
+        Cow::Borrowed(database)
+    } else {
+        let mut copy = database.clone();
+        copy.sort();
+        Cow::Owned(copy)
+    };
+    if database.find_duplicate().is_some() {
+        return Err(fmt::Error);
+    }
+    Ok(database)
+}
+
+/// Sort the database as [`sorted_database`] does, then store its names as the options ask.
+fn stored_database<'a>(database: &'a ParsedDatabase, options: &GenOptions) -> Result<Cow<'a, ParsedDatabase>, fmt::Error> {
+    let database = sorted_database(database)?;
+    if options.keeps_names() {
+        return Ok(database);
+    }
+    Ok(Cow::Owned(store_names(&database, options)))
+}
+
+/// Copy a database with its names stored as the options ask; see [`stored_name`].
+fn store_names(database: &ParsedDatabase, options: &GenOptions) -> ParsedDatabase {
+    let mut converted = database.clone();
+    let convert = |name: &mut String| {
+        if let Cow::Owned(stored) = stored_name(name, options) {
+            *name = stored;
+        }
+    };
+    for vendor in &mut converted.vendors {
+        convert(&mut vendor.name);
+        for device in &mut vendor.devices {
+            convert(&mut device.name);
+            for subsystem in &mut device.subsystems {
+                convert(&mut subsystem.name);
+            }
+        }
+    }
+    for class in &mut converted.classes {
+        convert(&mut class.name);
+        for subclass in &mut class.subclasses {
+            convert(&mut subclass.name);
+            for prog_if in &mut subclass.prog_interfaces {
+                convert(&mut prog_if.name);
+            }
+        }
+    }
+    converted
+}
+
+/// Store a name as the options ask: escaped or transliterated, then shortened to the maximum length.
+fn stored_name<'a>(name: &'a str, options: &GenOptions) -> Cow<'a, str> {
+    let name = convert_name(name, options.non_ascii_names);
+    match options.max_name_length {
+        Some(max) if name.len() > max => Cow::Owned(truncate_name(&name, max)),
+        _ => name,
+    }
+}
+
+/// Ellipsis marking a name shortened to the maximum length; ASCII, so it prints everywhere.
+const NAME_ELLIPSIS: &str = "...";
+
+/// Shorten a name to at most `max` bytes, ending it with an ellipsis if there is room.
+///
+/// Names are cut at a character boundary, and never inside a `\u{..}` escape.
+fn truncate_name(name: &str, max: usize) -> String {
+    let budget = if max > NAME_ELLIPSIS.len() { max - NAME_ELLIPSIS.len() } else { max };
+    let mut cut = budget;
+    while !name.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    if let Some(escape) = name[..cut].rfind("\\u{") {
+        if !name[escape..cut].contains('}') {
+            cut = escape;
+        }
+    }
+    let mut truncated = name[..cut].trim_end().to_string();
+    if budget < max {
+        truncated.push_str(NAME_ELLIPSIS);
+    }
+    truncated
+}
+
+/// Store a name in the given form; ASCII names are always kept as they are.
+fn convert_name(name: &str, form: NameForm) -> Cow<'_, str> {
+    if name.is_ascii() {
+        return Cow::Borrowed(name);
+    }
+    match form {
+        NameForm::Original => Cow::Borrowed(name),
+        NameForm::Escaped => {
+            let mut escaped = String::with_capacity(name.len() * 2);
+            for c in name.chars() {
+                if c.is_ascii() {
+                    escaped.push(c);
+                } else {
+                    escaped.extend(c.escape_unicode());
+                }
+            }
+            Cow::Owned(escaped)
+        }
+        NameForm::Transliterated => Cow::Owned(to_ascii_lossy(name)),
+    }
+}
+
+/// Count the entries and name bytes the tables will hold.
+fn count_entries(vendors: &[VendorBuilder], classes: &[ClassBuilder], options: &GenOptions) -> SizeReport {
+    let mut report = SizeReport::default();
+    let mut add_name = |name: &str, transliterated: bool| {
+        if !options.names_out_of_line() {
+            report.name_bytes += stored_name(name, options).len();
+            if transliterated && !name.is_ascii() && options.non_ascii_names != NameForm::Transliterated {
+                report.name_bytes += stored_name(&to_ascii_lossy(name), options).len();
+            }
+        }
+    };
+
+    for vendor in vendors {
+        add_name(&vendor.name, true);
+        for device in &vendor.devices {
+            add_name(&device.name, true);
+            for subsystem in device.subsystems.iter().filter(|_| options.emit_subsystems) {
+                add_name(&subsystem.name, true);
+            }
+        }
+    }
+    for class in classes {
+        add_name(&class.name, false);
+        for subclass in &class.subclasses {
+            add_name(&subclass.name, false);
+            for prog_if in &subclass.prog_interfaces {
+                add_name(&prog_if.name, false);
+            }
+        }
+    }
+
+    report.vendors = vendors.len();
+    report.devices = vendors.iter().map(|v| v.devices.len()).sum();
+    if options.emit_subsystems {
+        report.subsystems = vendors.iter().flat_map(|v| &v.devices).map(|d| d.subsystems.len()).sum();
+    }
+    report.classes = classes.len();
+    report.subclasses = classes.iter().map(|c| c.subclasses.len()).sum();
+    report.prog_interfaces = classes.iter().flat_map(|c| &c.subclasses).map(|s| s.prog_interfaces.len()).sum();
+    report
+}
+
+/// Device index marking a name index entry that refers to the vendor name.
+pub(crate) const NAME_INDEX_VENDOR: u16 = u16::MAX;
+
+/// Collect (vendor index, device index, key offset) triples sorted by case-folded key.
+///
+/// Returns `None` if any index or offset does not fit in a `u16`.
+pub(crate) fn build_name_index(vendors: &[VendorBuilder]) -> Option<Vec<(u16, u16, u16)>> {
+    let mut entries = Vec::new();
+
+    for (vendor_index, vendor) in vendors.iter().enumerate() {
+        let vendor_index = u16::try_from(vendor_index).ok()?;
+        for offset in key_offsets(&vendor.name) {
+            entries.push((vendor_index, NAME_INDEX_VENDOR, u16::try_from(offset).ok()?));
+        }
+        for (device_index, device) in vendor.devices.iter().enumerate() {
+            let device_index = u16::try_from(device_index).ok().filter(|&i| i != NAME_INDEX_VENDOR)?;
+            for offset in key_offsets(&device.name) {
+                entries.push((vendor_index, device_index, u16::try_from(offset).ok()?));
+            }
+        }
+    }
+
+    entries.sort_by_cached_key(|&(vendor_index, device_index, offset)| {
+        let vendor = &vendors[vendor_index as usize];
+        let name = if device_index == NAME_INDEX_VENDOR {
+            &vendor.name
+        } else {
+            &vendor.devices[device_index as usize].name
+        };
+        name[offset as usize..].to_ascii_lowercase()
+    });
+
+    Some(entries)
+}
+
+/// Iterate over the byte offsets in a name that are indexed for prefix search.
+///
+/// This is the start of the name and the first non-space character after
+/// every opening bracket.
+pub(crate) fn key_offsets(name: &str) -> impl Iterator<Item = usize> + '_ {
+    let bytes = name.as_bytes();
+    core::iter::once(0).chain(
+        bytes
+            .iter()
+            .enumerate()
+            .filter(|&(_, &b)| b == b'[')
+            .filter_map(move |(i, _)| {
+                let mut start = i + 1;
+                while start < bytes.len() && bytes[start] == b' ' {
+                    start += 1;
+                }
+                (start < bytes.len()).then_some(start)
+            }),
+    )
+}
+
+/// Slot value marking an empty slot of a perfect-hash index.
+pub(crate) const PHF_EMPTY_SLOT: u32 = u32::MAX;
+
+/// Keys per bucket of a perfect-hash index, on average.
+const PHF_BUCKET_SIZE: usize = 4;
+
+/// Hash a perfect-hash key with a seed.
+///
+/// Bucket selection uses seed 0 and slot selection the bucket's displacement
+/// plus one. This is the MurmurHash3 finalizer, in `u32` arithmetic only so the
+/// tables mean the same on every target.
+pub(crate) const fn phf_hash(key: u32, seed: u32) -> u32 {
+    let mut hash = key ^ seed.wrapping_mul(0x9e37_79b9);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ (hash >> 16)
+}
+
+/// The tables of a perfect-hash index.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct PhfTables {
+    pub(crate) displacements: Vec<u16>,
+    pub(crate) slots: Vec<u32>,
+}
+
+/// Build the vendor and device perfect-hash indexes.
+///
+/// Vendor keys are vendor IDs mapping to vendor indices; device keys are
+/// `vendor << 16 | device` mapping to `vendor index << 16 | device index`.
+/// Returns `None` if any index does not fit in a `u16`.
+pub(crate) fn build_phf_indexes(vendors: &[VendorBuilder]) -> Option<(PhfTables, PhfTables)> {
+    let mut vendor_entries = Vec::with_capacity(vendors.len());
+    let mut device_entries = Vec::new();
+    for (vendor_index, vendor) in vendors.iter().enumerate() {
+        let vendor_index = u16::try_from(vendor_index).ok()?;
+        vendor_entries.push((u32::from(vendor.id.value()), u32::from(vendor_index)));
+        for (device_index, device) in vendor.devices.iter().enumerate() {
+            let device_index = u16::try_from(device_index).ok()?;
+            let value = u32::from(vendor_index) << 16 | u32::from(device_index);
+            if value == PHF_EMPTY_SLOT {
+                return None;
+            }
+            device_entries.push((u32::from(vendor.id.value()) << 16 | u32::from(device.id.value()), value));
+        }
+    }
+
+    Some((build_phf(&vendor_entries)?, build_phf(&device_entries)?))
+}
+
+/// Build a perfect-hash index over distinct keys with "hash and displace".
+///
+/// Keys are split into buckets, and the buckets, largest first, are each given
+/// the smallest displacement that places all their keys in free slots.
+fn build_phf(entries: &[(u32, u32)]) -> Option<PhfTables> {
+    if entries.is_empty() {
+        return Some(PhfTables::default());
+    }
+
+    let bucket_count = entries.len().div_ceil(PHF_BUCKET_SIZE);
+    let slot_count = entries.len() + entries.len() / 8 + 1;
+    let mut buckets = alloc::vec![Vec::new(); bucket_count];
+    for &(key, value) in entries {
+        buckets[phf_hash(key, 0) as usize % bucket_count].push((key, value));
+    }
+    let mut order: Vec<usize> = (0..bucket_count).collect();
+    order.sort_by_key(|&bucket| core::cmp::Reverse(buckets[bucket].len()));
+
+    let mut displacements = alloc::vec![0u16; bucket_count];
+    let mut slots = alloc::vec![PHF_EMPTY_SLOT; slot_count];
+    let mut placed = Vec::new();
+    for bucket in order.into_iter().filter(|&bucket| !buckets[bucket].is_empty()) {
+        let displacement = (0..=u16::MAX).find(|&displacement| {
+            placed.clear();
+            buckets[bucket].iter().all(|&(key, _)| {
+                let slot = phf_hash(key, u32::from(displacement) + 1) as usize % slot_count;
+                let free = slots[slot] == PHF_EMPTY_SLOT && !placed.contains(&slot);
+                placed.push(slot);
+                free
+            })
+        })?;
+        displacements[bucket] = displacement;
+        for (&slot, &(_, value)) in placed.iter().zip(&buckets[bucket]) {
+            slots[slot] = value;
+        }
+    }
+
+    Some(PhfTables { displacements, slots })
+}
+
+/// Number of names between restart points in compressed name data.
+pub(crate) const NAME_RESTART_INTERVAL: usize = 16;
+
+/// First byte value standing for a dictionary word in compressed name data.
+pub(crate) const NAME_DICTIONARY_BASE: u8 = 0x80;
+
+/// Byte value escaping a literal non-ASCII byte in compressed name data.
+pub(crate) const NAME_ESCAPE: u8 = 0xff;
+
+/// Compressed names and the tables locating them.
+pub(crate) struct CompressedNameTables {
+    pub(crate) data: Vec<u8>,
+    pub(crate) restarts: Vec<u32>,
+    pub(crate) dictionary: Vec<String>,
+    pub(crate) vendor_starts: Vec<u32>,
+    pub(crate) class_starts: Vec<u32>,
+    pub(crate) max_name_len: usize,
+    pub(crate) max_vendor_names_len: usize,
+}
+
+/// Compress all names in table order.
+///
+/// Each name is stored as the length of the prefix shared with the previous
+/// name (one byte, zero at restart points), the LEB128 length of the rest,
+/// and the rest, where bytes from [`NAME_DICTIONARY_BASE`] stand for
+/// dictionary words and [`NAME_ESCAPE`] precedes a literal non-ASCII byte.
+///
+/// Each vendor with its devices and subsystems, and each class with its
+/// subclasses and programming interfaces, forms a block that starts at a
+/// restart point, so it decompresses without reading any other block. Name
+/// numbers skip to the next multiple of [`NAME_RESTART_INTERVAL`] between
+/// blocks, so restart `n` is still the one before name `n * NAME_RESTART_INTERVAL`.
+pub(crate) fn compress_names(vendors: &[VendorBuilder], classes: &[ClassBuilder], subsystems: bool) -> CompressedNameTables {
+    let blocks: Vec<Vec<&[u8]>> = name_blocks(vendors, classes, subsystems)
+        .into_iter()
+        .map(|block| block.into_iter().map(str::as_bytes).collect())
+        .collect();
+
+    // The words saving the most bytes when replaced by a single byte
+    let mut counts = BTreeMap::<&[u8], usize>::new();
+    for name in blocks.iter().flatten() {
+        for token in name_tokens(name).filter(|token| token.len() > 1) {
+            *counts.entry(token).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<_> = counts.into_iter().map(|(token, count)| (token, (token.len() - 1) * count)).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    ranked.truncate(usize::from(NAME_ESCAPE - NAME_DICTIONARY_BASE));
+    let codes: BTreeMap<&[u8], u8> = ranked.iter().zip(NAME_DICTIONARY_BASE..).map(|(&(token, _), code)| (token, code)).collect();
+
+    let mut data = Vec::new();
+    let mut restarts = Vec::new();
+    let mut starts = Vec::with_capacity(blocks.len());
+    let mut previous: &[u8] = &[];
+    let mut suffix = Vec::new();
+    for block in &blocks {
+        starts.push((restarts.len() * NAME_RESTART_INTERVAL) as u32);
+        for (number, name) in block.iter().enumerate() {
+            if number % NAME_RESTART_INTERVAL == 0 {
+                restarts.push(data.len() as u32);
+                previous = &[];
+            }
+            let prefix = name.iter().zip(previous).take(usize::from(u8::MAX)).take_while(|(a, b)| a == b).count();
+
+            suffix.clear();
+            for token in name_tokens(&name[prefix..]) {
+                match codes.get(token) {
+                    Some(&code) => suffix.push(code),
+                    None => {
+                        for &byte in token {
+                            if byte >= NAME_DICTIONARY_BASE {
+                                suffix.push(NAME_ESCAPE);
+                            }
+                            suffix.push(byte);
+                        }
+                    }
+                }
+            }
+
+            data.push(prefix as u8);
+            let mut len = suffix.len();
+            while len >= 0x80 {
+                data.push(len as u8 | 0x80);
+                len >>= 7;
+            }
+            data.push(len as u8);
+            data.extend_from_slice(&suffix);
+            previous = name;
+        }
+    }
+    let class_starts = starts.split_off(vendors.len());
+
+    CompressedNameTables {
+        data,
+        restarts,
+        // Tokens are ASCII, so they are valid strings
+        dictionary: ranked.iter().map(|(token, _)| String::from_utf8_lossy(token).into_owned()).collect(),
+        vendor_starts: starts,
+        class_starts,
+        max_name_len: max_name_len(&blocks),
+        max_vendor_names_len: max_vendor_names_len(&blocks[..vendors.len()]),
+    }
+}
+
+/// Collect all names in table order, one block per vendor and per class.
+fn name_blocks<'a>(vendors: &'a [VendorBuilder], classes: &'a [ClassBuilder], subsystems: bool) -> Vec<Vec<&'a str>> {
+    let mut blocks = Vec::with_capacity(vendors.len() + classes.len());
+    for vendor in vendors {
+        let mut block = vec![vendor.name.as_str()];
+        for device in &vendor.devices {
+            block.push(device.name.as_str());
+            for subsystem in device.subsystems.iter().filter(|_| subsystems) {
+                block.push(subsystem.name.as_str());
+            }
+        }
+        blocks.push(block);
+    }
+    for class in classes {
+        let mut block = vec![class.name.as_str()];
+        for subclass in &class.subclasses {
+            block.push(subclass.name.as_str());
+            for prog_if in &subclass.prog_interfaces {
+                block.push(prog_if.name.as_str());
+            }
+        }
+        blocks.push(block);
+    }
+    blocks
+}
+
+fn max_name_len<T: AsRef<[u8]>>(blocks: &[Vec<T>]) -> usize {
+    blocks.iter().flatten().map(|name| name.as_ref().len()).max().unwrap_or(0)
+}
+
+/// Get the length of the largest vendor's names, separated by newlines as
+/// when decompressed together.
+fn max_vendor_names_len<T: AsRef<[u8]>>(vendor_blocks: &[Vec<T>]) -> usize {
+    vendor_blocks
+        .iter()
+        .map(|block| block.iter().map(|name| name.as_ref().len() + 1).sum::<usize>() - 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Names in a single string and the tables locating them.
+pub(crate) struct StringTable {
+    pub(crate) text: String,
+    /// The offset and length of every name in `text`, in table order
+    pub(crate) spans: Vec<u32>,
+    pub(crate) vendor_starts: Vec<u32>,
+    pub(crate) class_starts: Vec<u32>,
+    pub(crate) max_name_len: usize,
+    pub(crate) max_vendor_names_len: usize,
+}
+
+/// Concatenate all names in table order into one string.
+///
+/// Names are numbered as for [`compress_names`], but without gaps between
+/// blocks. Repeated names are stored once and share a span.
+pub(crate) fn string_table(vendors: &[VendorBuilder], classes: &[ClassBuilder], subsystems: bool) -> StringTable {
+    let blocks = name_blocks(vendors, classes, subsystems);
+    let mut text = String::new();
+    let mut offsets = BTreeMap::<&str, u32>::new();
+    let mut spans = Vec::new();
+    let mut starts = Vec::with_capacity(blocks.len());
+    for block in &blocks {
+        starts.push((spans.len() / 2) as u32);
+        for &name in block {
+            let offset = *offsets.entry(name).or_insert_with(|| {
+                text.push_str(name);
+                (text.len() - name.len()) as u32
+            });
+            spans.extend([offset, name.len() as u32]);
+        }
+    }
+    let class_starts = starts.split_off(vendors.len());
+
+    StringTable {
+        max_name_len: max_name_len(&blocks),
+        max_vendor_names_len: max_vendor_names_len(&blocks[..vendors.len()]),
+        text,
+        spans,
+        vendor_starts: starts,
+        class_starts,
+    }
+}
+
+/// Split a name into ASCII words with their trailing space, and single other bytes.
+fn name_tokens(name: &[u8]) -> impl Iterator<Item = &[u8]> + '_ {
+    let mut rest = name;
+    core::iter::from_fn(move || {
+        let first = *rest.first()?;
+        let mut len = 1;
+        if first.is_ascii_alphanumeric() {
+            len = rest.iter().take_while(|b| b.is_ascii_alphanumeric()).count();
+            if rest.get(len) == Some(&b' ') {
+                len += 1;
+            }
+        }
+        let (token, tail) = rest.split_at(len);
+        rest = tail;
+        Some(token)
+    })
+}
+
+/// Write bytes as a byte string literal, wrapped over several lines.
+fn write_byte_string<W: Write>(out: &mut W, bytes: &[u8]) -> fmt::Result {
+    out.write_str("b\"")?;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let line_start = i % 100 == 0;
+        if line_start && i > 0 {
+            out.write_str("\\\n")?;
+        }
+        match byte {
+            // A continuation line skips leading whitespace, so escape it there
+            b' ' if line_start => out.write_str("\\x20")?,
+            b'"' | b'\\' => write!(out, "\\{}", byte as char)?,
+            b' '..=b'~' => out.write_char(byte as char)?,
+            _ => write!(out, "\\x{:02x}", byte)?,
+        }
+    }
+    out.write_str("\"")
+}
+
+/// The `.with_ascii_name(...)` call to append to a constructor, or nothing for ASCII names.
+fn with_ascii_name(name: &str, max_len: Option<usize>) -> String {
+    if name.is_ascii() {
+        return String::new();
+    }
+    let ascii = to_ascii_lossy(name);
+    match max_len {
+        Some(max) if ascii.len() > max => format!(".with_ascii_name({:?})", truncate_name(&ascii, max)),
+        _ => format!(".with_ascii_name({:?})", ascii),
+    }
+}
+
+/// Transliterate a name to ASCII, replacing characters with no mapping by `?`.
+pub(crate) fn to_ascii_lossy(name: &str) -> String {
+    let mut ascii = String::with_capacity(name.len());
+    for c in name.chars() {
+        let replacement = match c {
+            c if c.is_ascii() => {
+                ascii.push(c);
+                continue;
+            }
+            c if c.is_whitespace() => " ",
+            'à'..='å' => "a",
+            'À'..='Å' => "A",
+            'è'..='ë' => "e",
+            'È'..='Ë' => "E",
+            'ì'..='ï' => "i",
+            'Ì'..='Ï' => "I",
+            'ò'..='ö' | 'ø' => "o",
+            'Ò'..='Ö' | 'Ø' => "O",
+            'ù'..='ü' => "u",
+            'Ù'..='Ü' => "U",
+            'ç' => "c",
+            'Ç' => "C",
+            'ñ' => "n",
+            'Ñ' => "N",
+            'ý' | 'ÿ' => "y",
+            'Ý' => "Y",
+            'ß' => "ss",
+            'æ' => "ae",
+            'Æ' => "AE",
+            '¹' => "1",
+            '²' => "2",
+            '³' => "3",
+            '®' => "(R)",
+            '©' => "(C)",
+            '™' => "(TM)",
+            'µ' => "u",
+            '×' => "x",
+            '°' => "deg",
+            '‐'..='—' => "-",
+            '‘' | '’' => "'",
+            '“' | '”' => "\"",
+            '…' => "...",
+            // Cyrillic letters that pass for Latin ones in part numbers
+            'А' => "A",
+            'В' => "B",
+            'Е' => "E",
+            'К' => "K",
+            'М' => "M",
+            'Н' => "H",
+            'О' => "O",
+            'Р' => "P",
+            'С' => "C",
+            'Т' => "T",
+            'Х' => "X",
+            'а' => "a",
+            'е' => "e",
+            'о' => "o",
+            'р' => "p",
+            'с' => "c",
+            'х' => "x",
+            _ => "?",
+        };
+        ascii.push_str(replacement);
+    }
+    ascii
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::EntryKey;
+    use crate::parser::PciIdsParser;
+    use crate::types::*;
+
+    const SAMPLE: &str = "\
+8086  Intel Corporation
+\t1533  I210 Gigabit Network Connection
+\t\t8086 0001  Ethernet Server Adapter I210-T1
+C 02  Network controller
+\t00  Ethernet controller
+";
+
+    fn parse(content: &str) -> ParsedDatabase {
+        let mut parser = PciIdsParser::new();
+        parser.parse(content).expect("Failed to parse");
+        parser.into_parsed()
+    }
+
+    #[test]
+    fn test_generate_rust_default_crate_path() {
+        let code = generate_rust(&parse(SAMPLE), &GenOptions::new());
+
+        assert!(code.contains("static DEVICES_32902: &[crate::devices::Device]"));
+        assert!(code.contains("crate::types::SubvendorId::new(0x8086), crate::types::SubdeviceId::new(0x0001), \"Ethernet Server Adapter I210-T1\""));
+        assert!(code.contains("crate::classes::DeviceClass::new(crate::types::DeviceClassId::new(0x02), \"Network controller\", SUBCLASSES_2)"));
+        assert!(code.contains("pub static GLOBAL_DATABASE: crate::database::PciDatabase"));
+    }
+
+    #[test]
+    fn test_generate_rust_external_crate_path() {
+        let code = generate_rust(&parse(SAMPLE), &GenOptions::new().crate_path("::ids_rs"));
+
+        assert!(!code.contains("crate::"));
+        assert!(code.contains("::ids_rs::vendors::Vendor::new(::ids_rs::types::VendorId::new(0x8086), \"Intel Corporation\", DEVICES_32902)"));
+    }
+
+    #[test]
+    fn test_generate_rust_empty() {
+        let code = generate_rust(&ParsedDatabase::default(), &GenOptions::new());
+
+        assert!(code.contains("static VENDORS: &[crate::vendors::Vendor] = &[\n];"));
+        assert!(code.contains("static CLASSES: &[crate::classes::DeviceClass] = &[\n];"));
+        assert!(code.contains("GLOBAL_DATABASE"));
+    }
+
+    #[test]
+    fn test_generate_rust_naming_and_visibility() {
+        let options = GenOptions::new()
+            .static_prefix("NET_")
+            .database_name("NET_DATABASE")
+            .table_visibility(Visibility::Crate)
+            .database_visibility(Visibility::Private)
+            .emit_name_index(false);
+        let code = generate_rust(&parse(SAMPLE), &options);
+
+        assert!(code.contains("pub(crate) static NET_DEVICES_32902: &[crate::devices::Device]"));
+        assert!(code.contains("\"Intel Corporation\", NET_DEVICES_32902)"));
+        assert!(code.contains("pub(crate) static NET_SUBCLASSES_2:"));
+        assert!(code.contains("\nstatic NET_DATABASE: crate::database::PciDatabase = crate::database::PciDatabase::new(NET_VENDORS, NET_CLASSES).with_version(NET_DATABASE_VERSION, NET_DATABASE_DATE);\n"));
+        assert!(code.contains("\nconst NET_DATABASE_VERSION: &str = \"\";\n"));
+        assert!(!code.contains("NAME_INDEX"));
+        assert!(!code.contains("GLOBAL_DATABASE"));
+    }
+
+    #[test]
+    fn test_generate_rust_without_subsystems() {
+        let database = parse(SAMPLE);
+        assert!(generate_rust(&database, &GenOptions::new()).contains("SUBSYSTEMS_"));
+
+        let code = generate_rust(&database, &GenOptions::new().emit_subsystems(false));
+        assert!(!code.contains("SUBSYSTEMS_"));
+        assert!(!code.contains("Subsystem::new"));
+        assert!(code.contains("\"Intel Corporation\", DEVICES_32902)"));
+    }
+
+    #[test]
+    fn test_generate_rust_halves() {
+        let database = parse(SAMPLE);
+
+        let code = generate_rust(&database, &GenOptions::new().emit_vendors(false));
+        assert!(code.contains("static VENDORS: &[crate::vendors::Vendor] = &[\n];"));
+        assert!(!code.contains("Intel Corporation"));
+        assert!(code.contains("Network controller"));
+
+        let code = generate_rust(&database, &GenOptions::new().emit_classes(false));
+        assert!(code.contains("static CLASSES: &[crate::classes::DeviceClass] = &[\n];"));
+        assert!(code.contains("Intel Corporation"));
+        assert!(!code.contains("Network controller"));
+    }
+
+    #[test]
+    fn test_generate_rust_compressed_names() {
+        let code = generate_rust(&parse(SAMPLE), &GenOptions::new().compress_names(true));
+
+        assert!(code.contains("static NAME_DATA: &[u8] = b\""));
+        assert!(code.contains("crate::vendors::Vendor::new(crate::types::VendorId::new(0x8086), \"\", DEVICES_32902)"));
+        assert!(code.contains(".with_compressed_names(crate::compressed::CompressedNames::new(NAME_DATA, NAME_RESTARTS, NAME_DICTIONARY, VENDOR_NAME_STARTS, CLASS_NAME_STARTS, "));
+        assert!(!code.contains("Intel Corporation"));
+        assert!(!code.contains("NAME_INDEX"));
+    }
+
+    #[test]
+    fn test_generate_rust_string_table() {
+        let mut code = String::new();
+        let report = write_rust(&mut code, &parse(SAMPLE), &GenOptions::new().string_table(true)).unwrap();
+
+        assert!(code.contains("static NAME_TEXT: &str = \"Intel Corporation"));
+        assert!(code.contains("crate::vendors::Vendor::new(crate::types::VendorId::new(0x8086), \"\", DEVICES_32902)"));
+        assert!(code.contains(".with_compressed_names(crate::compressed::CompressedNames::string_table(NAME_TEXT, NAME_SPANS, VENDOR_NAME_STARTS, CLASS_NAME_STARTS, "));
+        assert!(!code.contains("NAME_INDEX"));
+        assert!(!code.contains("NAME_DATA"));
+        assert_eq!(report.name_index_entries, 0);
+
+        // Compressed names take precedence
+        let compressed = generate_rust(&parse(SAMPLE), &GenOptions::new().string_table(true).compress_names(true));
+        assert!(compressed.contains("NAME_DATA"));
+        assert!(!compressed.contains("NAME_TEXT"));
+    }
+
+    #[test]
+    fn test_generate_rust_phf() {
+        assert!(!generate_rust(&parse(SAMPLE), &GenOptions::new()).contains("PHF"));
+
+        let mut code = String::new();
+        let report = write_rust(&mut code, &parse(SAMPLE), &GenOptions::new().emit_phf(true)).unwrap();
+        assert!(code.contains("static VENDOR_PHF_DISPLACEMENTS: &[u16] = &[\n    0,\n];"));
+        assert!(code.contains("static DEVICE_PHF_SLOTS: &[u32] = &[\n    0x00000000, 0xffffffff,\n];"));
+        assert!(code.contains(".with_phf(crate::phf::PhfIndex::new(VENDOR_PHF_DISPLACEMENTS, VENDOR_PHF_SLOTS), crate::phf::PhfIndex::new(DEVICE_PHF_DISPLACEMENTS, DEVICE_PHF_SLOTS))"));
+        assert_eq!((report.phf_displacements, report.phf_slots), (2, 4));
+
+        // Classes alone need no index
+        let classes_only = generate_rust(&parse(SAMPLE), &GenOptions::new().emit_phf(true).emit_vendors(false));
+        assert!(!classes_only.contains("PHF"));
+    }
+
+    #[test]
+    fn test_byte_string_escapes() {
+        let mut bytes = alloc::vec![b'x'; 100];
+        bytes.extend_from_slice(b" \"\\\x80\n");
+        let mut literal = String::new();
+        write_byte_string(&mut literal, &bytes).unwrap();
+
+        assert!(literal.starts_with("b\"xxx"));
+        assert!(literal.ends_with("x\\\n\\x20\\\"\\\\\\x80\\x0a\""));
+    }
+
+    #[test]
+    fn test_generate_rust_sorts_unsorted_input() {
+        let mut database = parse(SAMPLE);
+        database.vendors.insert(0, parse("abcd  Unsorted Vendor\n").vendors.remove(0));
+        assert!(!database.check_order().is_empty());
+
+        let code = generate_rust(&database, &GenOptions::new());
+        let intel = code.find("\"Intel Corporation\"").unwrap();
+        let unsorted = code.find("\"Unsorted Vendor\"").unwrap();
+        assert!(intel < unsorted);
+    }
+
+    #[test]
+    fn test_write_rust_rejects_duplicates() {
+        let mut database = parse(SAMPLE);
+        assert_eq!(database.find_duplicate(), None);
+
+        let subsystem = database.vendors[0].devices[0].subsystems[0].clone();
+        database.vendors[0].devices[0].subsystems.push(subsystem);
+        assert_eq!(
+            database.find_duplicate(),
+            Some(EntryKey::Subsystem(VendorId::new(0x8086), DeviceId::new(0x1533), SubvendorId::new(0x8086), SubdeviceId::new(0x0001)))
+        );
+
+        // Duplicates that only become adjacent once sorted are found too
+        let mut database = parse(SAMPLE);
+        database.classes.insert(0, parse("C 02  Network controller\n").classes.remove(0));
+        database.classes.insert(0, parse("C 03  Display controller\n").classes.remove(0));
+        let mut out = String::new();
+        assert!(write_rust(&mut out, &database, &GenOptions::new()).is_err());
+        assert!(out.is_empty());
+        assert_eq!(database.sort().len(), 1);
+        assert_eq!(database.find_duplicate().unwrap().to_string(), "class 02");
+    }
+
+    #[test]
+    fn test_write_blob() {
+        let blob = write_blob(&parse(SAMPLE), &GenOptions::new()).unwrap();
+
+        assert_eq!(&blob[..4], b"PCIB");
+        assert_eq!(read_header(&blob, 4), BLOB_VERSION);
+        // One record in every table but the programming interfaces, then the names
+        let tables: Vec<_> = (0..6).map(|i| (read_header(&blob, 8 + i * 8), read_header(&blob, 12 + i * 8))).collect();
+        assert_eq!(tables, [(64, 1), (80, 1), (96, 1), (112, 1), (128, 1), (144, 0)]);
+        assert_eq!((read_header(&blob, 56), read_header(&blob, 60)), (144, 116));
+        assert_eq!(blob.len(), 260);
+        // The vendor record: ID, name, and its first device
+        assert_eq!((read_header(&blob, 64), read_header(&blob, 68), read_header(&blob, 72), read_header(&blob, 76)), (0x8086, 0, 17, 0));
+        assert_eq!(&blob[144..161], b"Intel Corporation");
+
+        let blob = write_blob(&parse(SAMPLE), &GenOptions::new().emit_vendors(false)).unwrap();
+        assert_eq!((read_header(&blob, 8), read_header(&blob, 12)), (64, 0));
+
+        let mut database = parse(SAMPLE);
+        database.vendors.push(database.vendors[0].clone());
+        assert_eq!(write_blob(&database, &GenOptions::new()).unwrap_err(), PciError::DuplicateEntry);
+    }
+
+    #[test]
+    fn test_write_json() {
+        let mut json = String::new();
+        write_json(&mut json, &parse(SAMPLE), &GenOptions::new()).unwrap();
+        assert_eq!(
+            json,
+            r#"{
+  "version": null,
+  "date": null,
+  "vendors": [
+    {"id": "8086", "name": "Intel Corporation", "devices": [
+      {"id": "1533", "name": "I210 Gigabit Network Connection", "subsystems": [
+        {"subvendor": "8086", "subdevice": "0001", "name": "Ethernet Server Adapter I210-T1"}
+      ]}
+    ]}
+  ],
+  "classes": [
+    {"id": "02", "name": "Network controller", "subclasses": [
+      {"id": "00", "name": "Ethernet controller", "prog_interfaces": []}
+    ]}
+  ]
+}
+"#
+        );
+
+        // Only what is embedded, with names as stored
+        let mut json = String::new();
+        let options = GenOptions::new().emit_subsystems(false).emit_classes(false).non_ascii_names(NameForm::Transliterated);
+        write_json(&mut json, &parse("8086  Intel \"Corp\" \\ Co\n\t1533  Wi-Fi® 5\n\t\t8086 0001  Adapter\n"), &options).unwrap();
+        assert!(json.contains(r#"{"id": "8086", "name": "Intel \"Corp\" \\ Co", "devices": ["#));
+        assert!(json.contains(r#"{"id": "1533", "name": "Wi-Fi(R) 5", "subsystems": []}"#));
+        assert!(json.ends_with("  \"classes\": []\n}\n"));
+
+        let mut database = parse(SAMPLE);
+        database.vendors.push(database.vendors[0].clone());
+        assert!(write_json(&mut String::new(), &database, &GenOptions::new()).is_err());
+    }
+
+    fn read_header(blob: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(blob[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_write_well_known_vendors() {
+        let database = parse(
+            "1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\t7340  Navi 14\n\t7341  Navi 14\n\
+             1022  Advanced Micro Devices, Inc. [AMD]\n\t1480  Starship/Matisse Root Complex\n\
+             10b7  3Com Corporation\n\t9200  3c905C-TX/TX-M [Tornado]\n\
+             15b3  Mellanox Technologies\n\t1017  MT27800 Family [ConnectX-5]\n\
+             8086  Intel Corporation\n",
+        );
+        let well_known = |curated: &[(&str, VendorId)], top| {
+            let mut code = String::new();
+            write_well_known_vendors(&mut code, &database, curated, top, &GenOptions::new()).unwrap();
+            code
+        };
+
+        let curated = [("AMD", VendorId::new(0x1022)), ("INTEL", VendorId::new(0x8086)), ("TI", VendorId::new(0x104c))];
+        let code = well_known(&curated, 10);
+        let names: Vec<_> = code.lines().filter_map(|line| line.strip_prefix("pub const ")?.split(':').next()).collect();
+        // Curated constants come first; 3Com's name is not an identifier, and AMD is taken, so 1002 becomes ATI
+        assert_eq!(names, ["AMD", "INTEL", "TI", "ATI", "MELLANOX_TECHNOLOGIES"]);
+        assert!(code.contains("#[doc = \"Advanced Micro Devices, Inc. \\\\[AMD/ATI\\\\]\"]\npub const ATI: crate::types::VendorId = crate::types::VendorId::new(0x1002);"));
+        assert!(code.contains("#[doc = \"Vendor 104c\"]\npub const TI:"));
+
+        assert_eq!(well_known(&curated, 1).matches("pub const").count(), 4);
+        assert!(well_known(&[], 0).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate vendor 8086")]
+    fn test_generate_rust_panics_on_duplicates() {
+        let mut database = parse(SAMPLE);
+        database.vendors.push(database.vendors[0].clone());
+        generate_rust(&database, &GenOptions::new());
+    }
+
+    #[test]
+    fn test_size_report() {
+        use core::mem::size_of;
+
+        let database = parse("15cf  Hilscher Gesellschaft für Systemautomation mbH\n\t0001  Card\n\t\t15cf 0002  Sub\nC 02  Network controller\n\t00  Ethernet controller\n\t\t01  IF\n");
+        let mut code = String::new();
+        let report = write_rust(&mut code, &database, &GenOptions::new()).unwrap();
+
+        assert_eq!(report.total_entries(), 6);
+        assert_eq!(report.name_bytes, 47 + 46 + 4 + 3 + 18 + 19 + 2);
+        assert!(code.contains("const DATABASE_SIZE: crate::codegen::SizeReport = crate::codegen::SizeReport { vendors: 1, devices: 1, subsystems: 1, classes: 1, subclasses: 1, prog_interfaces: 1, name_index_entries: 2, name_bytes: 139, dictionary_words: 0, name_offsets: 0, phf_displacements: 0, phf_slots: 0 };"));
+
+        let tables = size_of::<crate::vendors::Vendor>()
+            + size_of::<crate::devices::Device>()
+            + size_of::<crate::devices::Subsystem>()
+            + size_of::<crate::classes::DeviceClass>()
+            + size_of::<crate::classes::SubClass>()
+            + size_of::<crate::classes::ProgInterface>()
+            + 2 * size_of::<crate::index::NameIndexEntry>();
+        assert_eq!(report.estimated_bytes(size_of::<usize>()), tables + report.name_bytes);
+
+        let compressed = write_rust(&mut String::new(), &database, &GenOptions::new().compress_names(true).emit_subsystems(false)).unwrap();
+        assert_eq!((compressed.subsystems, compressed.name_index_entries, compressed.name_offsets), (0, 0, 4));
+        assert!(compressed.name_bytes > 0 && compressed.name_bytes < report.name_bytes);
+    }
+
+    #[test]
+    fn test_write_rust_matches_generate_rust() {
+        let database = parse(SAMPLE);
+        let options = GenOptions::new();
+
+        let mut streamed = String::new();
+        write_rust(&mut streamed, &database, &options).unwrap();
+        assert_eq!(streamed, generate_rust(&database, &options));
+    }
+
+    #[test]
+    fn test_generate_rust_is_target_independent() {
+        let code = generate_rust(&parse(SAMPLE), &GenOptions::new());
+
+        // Nothing whose meaning depends on pointer width or endianness
+        for token in ["usize", "isize", "cfg", "_ne_bytes", "_le_bytes", "_be_bytes", "transmute", "include_bytes"] {
+            assert!(!code.contains(token), "generated code contains {:?}", token);
+        }
+    }
+
+    #[test]
+    fn test_name_index_rejects_oversized_offsets() {
+        let mut database = parse(SAMPLE);
+        let mut long_name = "x".repeat(usize::from(u16::MAX) + 1);
+        long_name.push_str(" [Alias]");
+        database.vendors[0].name = long_name;
+
+        assert!(build_name_index(&database.vendors).is_none());
+        assert!(write_rust(&mut String::new(), &database, &GenOptions::new()).is_err());
+        assert!(write_rust(&mut String::new(), &database, &GenOptions::new().emit_name_index(false)).is_ok());
+    }
+
+    #[test]
+    fn test_ascii_names() {
+        let code = generate_rust(&parse("15cf  Hilscher Gesellschaft für Systemautomation mbH\n\t2b42  Wi-Fi® 5\u{a0}Radio\n"), &GenOptions::new());
+
+        assert!(code.contains("\"Hilscher Gesellschaft für Systemautomation mbH\", DEVICES_5583).with_ascii_name(\"Hilscher Gesellschaft fur Systemautomation mbH\")"));
+        assert!(code.contains(".with_ascii_name(\"Wi-Fi(R) 5 Radio\")"));
+        assert!(!generate_rust(&parse(SAMPLE), &GenOptions::new()).contains("with_ascii_name"));
+
+        assert_eq!(to_ascii_lossy("82С935 X²\u{3000}"), "82C935 X2 ");
+        assert_eq!(to_ascii_lossy("Straße 日本"), "Strasse ??");
+    }
+
+    #[test]
+    fn test_generate_rust_without_prog_interfaces() {
+        let database = parse("C 0c  Serial bus controller\n\t03  USB controller\n\t\t30  XHCI\n");
+        let options = GenOptions::new().emit_prog_interfaces(false);
+
+        let code = generate_rust(&database, &options);
+        assert!(!code.contains("PROG_INTERFACES") && !code.contains("XHCI"));
+        assert!(code.contains("SubClassId::new(0x03), \"USB controller\", &[])"));
+        let report = write_rust(&mut String::new(), &database, &options).unwrap();
+        assert_eq!((report.subclasses, report.prog_interfaces), (1, 0));
+
+        let blob = write_blob(&database, &options).unwrap();
+        assert_eq!((read_header(&blob, 48), read_header(&blob, 52)), (96, 0));
+        // The parsed database itself is left alone
+        assert_eq!(database.classes[0].subclasses[0].prog_interfaces.len(), 1);
+    }
+
+    #[test]
+    fn test_non_ascii_names() {
+        let database = parse("15cf  Hilscher Gesellschaft für Systemautomation mbH\n\t2b42  Wi-Fi® 5 Radio\n\t2b43  Radio\n");
+        let generate = |form| generate_rust(&database, &GenOptions::new().non_ascii_names(form));
+
+        let code = generate(NameForm::Escaped);
+        assert!(code.contains(
+            "\"Hilscher Gesellschaft f\\\\u{fc}r Systemautomation mbH\", DEVICES_5583).with_ascii_name(\"Hilscher Gesellschaft fur Systemautomation mbH\").with_name_form(crate::types::NameForm::Escaped)"
+        ));
+        assert!(code.contains("\"Wi-Fi\\\\u{ae} 5 Radio\", &[]).with_ascii_name(\"Wi-Fi(R) 5 Radio\").with_name_form(crate::types::NameForm::Escaped)"));
+        assert!(code.contains("\"Radio\", &[]),"));
+
+        let code = generate(NameForm::Transliterated);
+        assert!(code.contains("\"Wi-Fi(R) 5 Radio\", &[]).with_name_form(crate::types::NameForm::Transliterated),"));
+        assert!(!code.contains("with_ascii_name") && code.is_ascii());
+
+        // The size report and indexes cover the names as stored
+        let report = write_rust(&mut String::new(), &database, &GenOptions::new().non_ascii_names(NameForm::Transliterated)).unwrap();
+        assert_eq!(report.name_bytes, "Hilscher Gesellschaft fur Systemautomation mbHWi-Fi(R) 5 RadioRadio".len());
+        let converted = store_names(&database, &GenOptions::new().non_ascii_names(NameForm::Transliterated));
+        assert_eq!(converted.vendors[0].devices[0].name, "Wi-Fi(R) 5 Radio");
+        assert_eq!(convert_name("Radio", NameForm::Escaped), "Radio");
+    }
+
+    #[test]
+    fn test_max_name_length() {
+        assert_eq!(truncate_name("Advanced Micro Devices, Inc. [AMD/ATI]", 20), "Advanced Micro De...");
+        assert_eq!(truncate_name("Wi-Fi 5 Radio", 9), "Wi-Fi...");
+        assert_eq!(truncate_name("Gesellschaft für", 17), "Gesellschaft f...");
+        assert_eq!(truncate_name("f\\u{fc}r Systeme", 9), "f...");
+        assert_eq!(truncate_name("Radio", 2), "Ra");
+
+        let database = parse("15cf  Hilscher Gesellschaft für Systemautomation mbH\n\t2b42  Wi-Fi® 5 Radio\n\t2b43  Radio\n");
+        let options = GenOptions::new().max_name_length(Some(12));
+        let code = generate_rust(&database, &options);
+        assert!(code.contains("\"Hilscher...\", DEVICES_5583).with_ascii_name(\"Hilscher...\")"));
+        assert!(code.contains("\"Wi-Fi® 5...\", &[]).with_ascii_name(\"Wi-Fi(R)...\")"));
+        assert!(code.contains("\"Radio\", &[]),"));
+
+        let report = write_rust(&mut String::new(), &database, &options).unwrap();
+        assert_eq!(report.name_bytes, "Hilscher...Wi-Fi® 5...Radio".len() + "Hilscher...Wi-Fi(R)...".len());
+    }
+
+    #[test]
+    fn test_write_rust_propagates_sink_errors() {
+        struct FailingSink;
+
+        impl Write for FailingSink {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        assert!(write_rust(&mut FailingSink, &parse(SAMPLE), &GenOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_key_offsets() {
+        let name = "GA102 [GeForce RTX 3080]";
+        let offsets: alloc::vec::Vec<usize> = key_offsets(name).collect();
+        assert_eq!(offsets, [0, 7]);
+        assert!(name[7..].starts_with("GeForce"));
+
+        let offsets: alloc::vec::Vec<usize> = key_offsets("Trailing [").collect();
+        assert_eq!(offsets, [0]);
+    }
+}
@@ -0,0 +1,1031 @@
+//! Compressed name storage for flash-constrained targets.
+//!
+//! With the `compressed-names` feature (or
+//! [`GenOptions::compress_names`](crate::codegen::GenOptions::compress_names)
+//! for generated databases), the tables hold empty names and every name is
+//! stored once in a [`CompressedNames`] blob instead. Names are front-coded
+//! against the previous name in table order, restarting every
+//! [`RESTART_INTERVAL`](CompressedNames::RESTART_INTERVAL) names and at the
+//! start of every vendor and class, and frequent words are replaced by
+//! one-byte codes into a small dictionary.
+//!
+//! The `*_name_into` methods on [`PciDatabase`] decompress a name into a
+//! caller-provided buffer, so no allocation is needed. They also work on
+//! uncompressed databases, where they copy the stored name, so code written
+//! against them is independent of the feature.
+//!
+//! [`GenOptions::string_table`](crate::codegen::GenOptions::string_table)
+//! (the `string-table` feature) stores the names uncompressed in the same
+//! place instead: one concatenated string plus an `(offset, len)` pair per
+//! name, so a name is a slice of that string rather than a `&'static str` of
+//! its own in every table entry. Names are looked up the same way in both
+//! layouts, and the allocating lookups borrow string table names without
+//! copying them.
+//!
+//! Since each vendor's names decompress on their own,
+//! [`vendor_names_into`](PciDatabase::vendor_names_into) decompresses a whole
+//! vendor at once for repeated lookups, needing at most
+//! [`max_vendor_names_len`](CompressedNames::max_vendor_names_len) bytes.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ids_rs::{PciDatabase, PciError, VendorId};
+//!
+//! let db = PciDatabase::get();
+//! let mut buffer = [0u8; 256];
+//! match db.vendor_name_into(VendorId::new(0x8086), &mut buffer) {
+//!     Ok(name) => println!("{}", name),
+//!     Err(PciError::VendorNotFound) => println!("Unknown vendor"),
+//!     Err(e) => println!("{}", e),
+//! }
+//! ```
+
+use crate::class_map::{infer_class_named, InferredClass};
+use crate::classes::{DeviceClass, ProgInterface, SubClass};
+use crate::codegen::{NAME_DICTIONARY_BASE, NAME_ESCAPE, NAME_RESTART_INTERVAL};
+use crate::database::PciDatabase;
+use crate::devices::{Device, Subsystem};
+use crate::error::{PciError, PciResult};
+use crate::types::*;
+use crate::vendors::Vendor;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::{Ref, RefCell};
+use core::ops::{Deref, Range};
+
+/// Front-coded, dictionary-compressed names of a generated database.
+///
+/// Names are numbered in table order: each vendor is followed by its devices,
+/// each device by its subsystems, and after the vendors each class is
+/// followed by its subclasses, each subclass by its programming interfaces.
+/// Every vendor and class starts a new block at the next multiple of
+/// [`RESTART_INTERVAL`](Self::RESTART_INTERVAL), so numbers between blocks
+/// are skipped.
+///
+/// A string table, from [`string_table`](Self::string_table), numbers its
+/// names the same way without skipping any, and holds their text and an
+/// `(offset, len)` pair per name instead of compressed data.
+#[derive(Debug, Clone, Copy)]
+pub struct CompressedNames {
+    data: &'static [u8],
+    restarts: &'static [u32],
+    dictionary: &'static [&'static str],
+    text: &'static str,
+    spans: &'static [u32],
+    vendor_starts: &'static [u32],
+    class_starts: &'static [u32],
+    max_name_len: usize,
+    max_vendor_names_len: usize,
+}
+
+impl CompressedNames {
+    /// Number of names between restart points, where no prefix is shared.
+    pub const RESTART_INTERVAL: usize = NAME_RESTART_INTERVAL;
+
+    /// No compressed names; the database tables hold the names.
+    pub const EMPTY: Self = Self::new(&[], &[], &[], &[], &[], 0, 0);
+
+    /// Create compressed names from generated tables.
+    ///
+    /// This is used by generated code; the arguments must come from the code
+    /// generator and match the tables of the database they are attached to.
+    #[doc(hidden)]
+    pub const fn new(
+        data: &'static [u8],
+        restarts: &'static [u32],
+        dictionary: &'static [&'static str],
+        vendor_starts: &'static [u32],
+        class_starts: &'static [u32],
+        max_name_len: usize,
+        max_vendor_names_len: usize,
+    ) -> Self {
+        Self { data, restarts, dictionary, text: "", spans: &[], vendor_starts, class_starts, max_name_len, max_vendor_names_len }
+    }
+
+    /// Create a string table from generated tables, with the `(offset, len)`
+    /// of every name in `text` flattened into `spans`.
+    ///
+    /// This is used by generated code; see [`new`](Self::new).
+    #[doc(hidden)]
+    pub const fn string_table(
+        text: &'static str,
+        spans: &'static [u32],
+        vendor_starts: &'static [u32],
+        class_starts: &'static [u32],
+        max_name_len: usize,
+        max_vendor_names_len: usize,
+    ) -> Self {
+        Self { data: &[], restarts: &[], dictionary: &[], text, spans, vendor_starts, class_starts, max_name_len, max_vendor_names_len }
+    }
+
+    /// Check if there are no compressed names.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.restarts.is_empty() && self.spans.is_empty()
+    }
+
+    /// Check if the names are a string table, stored uncompressed.
+    #[inline]
+    pub const fn is_string_table(&self) -> bool {
+        !self.spans.is_empty()
+    }
+
+    /// Get the length in bytes of the longest name, the buffer size that
+    /// decompresses any name.
+    #[inline]
+    pub const fn max_name_len(&self) -> usize {
+        self.max_name_len
+    }
+
+    /// Get the length in bytes of the largest vendor's names, the buffer size
+    /// for [`vendor_names_into`](PciDatabase::vendor_names_into) on any vendor.
+    #[inline]
+    pub const fn max_vendor_names_len(&self) -> usize {
+        self.max_vendor_names_len
+    }
+
+    /// Get the size in bytes of the compressed name data and its tables.
+    pub fn compressed_size(&self) -> usize {
+        self.data.len()
+            + self.text.len()
+            + self.dictionary.iter().map(|word| word.len()).sum::<usize>()
+            + 4 * (self.restarts.len() + self.spans.len() + self.vendor_starts.len() + self.class_starts.len())
+    }
+
+    /// Get the name with the given number from a string table, or `None` if
+    /// the names are compressed.
+    fn stored_name(&self, number: usize) -> Option<PciResult<&'static str>> {
+        if !self.is_string_table() {
+            return None;
+        }
+        let span = self.spans.get(2 * number..2 * number + 2);
+        let name = span.and_then(|span| self.text.get(span[0] as usize..(span[0] as usize).checked_add(span[1] as usize)?));
+        Some(name.ok_or(PciError::InvalidFormat))
+    }
+
+    /// Decompress the name with the given number into `buffer`.
+    fn name<'b>(&self, number: usize, buffer: &'b mut [u8]) -> PciResult<&'b str> {
+        if let Some(name) = self.stored_name(number) {
+            return copy_name(name?, buffer);
+        }
+        let restart = self.restarts.get(number / Self::RESTART_INTERVAL).ok_or(PciError::InvalidFormat)?;
+        let mut pos = *restart as usize;
+        let mut len = 0;
+        for _ in 0..=number % Self::RESTART_INTERVAL {
+            len = self.decode(&mut pos, buffer, 0..len, 0)?;
+        }
+
+        core::str::from_utf8(&buffer[..len]).map_err(|_| PciError::InvalidFormat)
+    }
+
+    /// Decompress `count` names of the block starting at name `first` into
+    /// `buffer`, separated by newlines, passing where each name ends up to `on_name`.
+    fn block<'b>(&self, first: usize, count: usize, buffer: &'b mut [u8], mut on_name: impl FnMut(Range<usize>)) -> PciResult<&'b str> {
+        if self.is_string_table() {
+            let mut len = 0;
+            for number in first..first + count {
+                if number > first {
+                    *buffer.get_mut(len).ok_or(PciError::BufferTooSmall)? = b'\n';
+                    len += 1;
+                }
+                let name = copy_name(self.stored_name(number).unwrap_or(Err(PciError::InvalidFormat))?, &mut buffer[len..])?.len();
+                on_name(len..len + name);
+                len += name;
+            }
+            return core::str::from_utf8(&buffer[..len]).map_err(|_| PciError::InvalidFormat);
+        }
+        let restart = self.restarts.get(first / Self::RESTART_INTERVAL).ok_or(PciError::InvalidFormat)?;
+        let mut pos = *restart as usize;
+        let mut previous = 0..0;
+        for i in 0..count {
+            let start = if i == 0 {
+                0
+            } else {
+                *buffer.get_mut(previous.end).ok_or(PciError::BufferTooSmall)? = b'\n';
+                previous.end + 1
+            };
+            previous = start..self.decode(&mut pos, buffer, previous, start)?;
+            on_name(previous.clone());
+        }
+
+        core::str::from_utf8(&buffer[..previous.end]).map_err(|_| PciError::InvalidFormat)
+    }
+
+    /// Decode the name at `pos` into `buffer` from `start`, sharing its prefix
+    /// with the name already in `buffer[previous]`, and return where it ends.
+    fn decode(&self, pos: &mut usize, buffer: &mut [u8], previous: core::ops::Range<usize>, start: usize) -> PciResult<usize> {
+        let prefix = usize::from(*self.data.get(*pos).ok_or(PciError::InvalidFormat)?);
+        let (size, size_len) = read_varint(self.data.get(*pos + 1..).ok_or(PciError::InvalidFormat)?)?;
+        let suffix_start = *pos + 1 + size_len;
+        let suffix = self.data.get(suffix_start..suffix_start + size).ok_or(PciError::InvalidFormat)?;
+        if prefix > previous.len() {
+            return Err(PciError::InvalidFormat);
+        }
+
+        if start + prefix > buffer.len() {
+            return Err(PciError::BufferTooSmall);
+        }
+        buffer.copy_within(previous.start..previous.start + prefix, start);
+        let mut len = start + prefix;
+        let mut rest = suffix;
+        while !rest.is_empty() {
+            // Copy runs of plain ASCII at once
+            let run = rest.iter().position(|&byte| byte >= NAME_DICTIONARY_BASE).unwrap_or(rest.len());
+            let (word, skip) = match rest[0] {
+                _ if run > 0 => (&rest[..run], run),
+                NAME_ESCAPE => (rest.get(1..2).ok_or(PciError::InvalidFormat)?, 2),
+                byte => {
+                    let word = self.dictionary.get(usize::from(byte - NAME_DICTIONARY_BASE));
+                    (word.ok_or(PciError::InvalidFormat)?.as_bytes(), 1)
+                }
+            };
+            buffer.get_mut(len..len + word.len()).ok_or(PciError::BufferTooSmall)?.copy_from_slice(word);
+            len += word.len();
+            rest = &rest[skip..];
+        }
+        *pos = suffix_start + size;
+        Ok(len)
+    }
+}
+
+impl Default for CompressedNames {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+/// The names of a vendor and its devices and subsystems, decompressed together
+/// by [`PciDatabase::vendor_names_into`].
+///
+/// Lookups scan the decompressed names, which are in table order, one per line.
+#[derive(Debug, Clone, Copy)]
+pub struct VendorNames<'b> {
+    vendor: &'static Vendor,
+    text: &'b str,
+}
+
+impl<'b> VendorNames<'b> {
+    /// Get the vendor these names belong to.
+    #[inline]
+    pub fn vendor(&self) -> &'static Vendor {
+        self.vendor
+    }
+
+    /// Get the vendor name.
+    pub fn vendor_name(&self) -> &'b str {
+        self.line(0)
+    }
+
+    /// Get the name of one of the vendor's devices.
+    pub fn device_name(&self, device_id: DeviceId) -> Option<&'b str> {
+        let (number, _) = self.device_number(device_id)?;
+        Some(self.line(number))
+    }
+
+    /// Get the name of a subsystem of one of the vendor's devices.
+    pub fn subsystem_name(&self, device_id: DeviceId, subvendor_id: SubvendorId, subdevice_id: SubdeviceId) -> Option<&'b str> {
+        let (number, device) = self.device_number(device_id)?;
+        let index = device
+            .subsystems()
+            .iter()
+            .position(|subsystem| subsystem.subvendor_id == subvendor_id && subsystem.subdevice_id == subdevice_id)?;
+        Some(self.line(number + 1 + index))
+    }
+
+    /// Get all the names, one per line in table order.
+    #[inline]
+    pub fn as_str(&self) -> &'b str {
+        self.text
+    }
+
+    /// Find a device and the line of its name.
+    fn device_number(&self, device_id: DeviceId) -> Option<(usize, &'static Device)> {
+        let devices = self.vendor.devices();
+        let index = devices.binary_search_by_key(&device_id.value(), |device| device.id.value()).ok()?;
+        Some((names_before(devices, index, |device| device.subsystems().len()), &devices[index]))
+    }
+
+    fn line(&self, number: usize) -> &'b str {
+        self.text.split('\n').nth(number).unwrap_or("")
+    }
+}
+
+/// Read a LEB128-encoded length, returning it and the number of bytes read.
+fn read_varint(bytes: &[u8]) -> PciResult<(usize, usize)> {
+    let mut value = 0usize;
+    for (i, &byte) in bytes.iter().enumerate().take(4) {
+        value |= usize::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(PciError::InvalidFormat)
+}
+
+/// Copy a stored name into `buffer`.
+fn copy_name<'b>(name: &str, buffer: &'b mut [u8]) -> PciResult<&'b str> {
+    let target = buffer.get_mut(..name.len()).ok_or(PciError::BufferTooSmall)?;
+    target.copy_from_slice(name.as_bytes());
+    core::str::from_utf8(target).map_err(|_| PciError::InvalidFormat)
+}
+
+/// Count the names from the start of a vendor or device up to one of its children.
+fn names_before<T>(children: &[T], index: usize, grandchildren: impl Fn(&T) -> usize) -> usize {
+    1 + children[..index].iter().map(|child| 1 + grandchildren(child)).sum::<usize>()
+}
+
+impl PciDatabase {
+    /// Get the compressed names attached to this database, if any.
+    pub fn compressed_names(&self) -> Option<&CompressedNames> {
+        let names = self.compressed_names_table();
+        (!names.is_empty()).then_some(names)
+    }
+
+    /// Write a vendor name into `buffer` and return it.
+    ///
+    /// The name is decompressed if the database stores compressed names, and
+    /// copied otherwise. Fails with [`PciError::VendorNotFound`] for unknown
+    /// vendors and [`PciError::BufferTooSmall`] if a name does not fit; a
+    /// buffer of [`CompressedNames::max_name_len`] bytes always suffices.
+    pub fn vendor_name_into<'b>(&self, vendor_id: VendorId, buffer: &'b mut [u8]) -> PciResult<&'b str> {
+        let (number, vendor) = self.vendor_number(vendor_id)?;
+        self.name_into(number, vendor.name(), buffer)
+    }
+
+    /// Write the names of a vendor and all its devices and subsystems into
+    /// `buffer`, for repeated lookups without decompressing each name again.
+    ///
+    /// Each vendor's names are compressed independently of the rest of the
+    /// database, so only that vendor is decompressed, and a buffer of
+    /// [`CompressedNames::max_vendor_names_len`] bytes always suffices. Like
+    /// the other `*_into` methods, this also works on uncompressed databases.
+    /// Fails with [`PciError::VendorNotFound`] for unknown vendors and
+    /// [`PciError::BufferTooSmall`] if the names do not fit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{DeviceId, PciDatabase, VendorId};
+    ///
+    /// let db = PciDatabase::get();
+    /// let mut buffer = vec![0u8; 1 << 20];
+    /// if let Ok(names) = db.vendor_names_into(VendorId::new(0x8086), &mut buffer) {
+    ///     assert_eq!(names.vendor_name(), "Intel Corporation");
+    ///     println!("{:?}", names.device_name(DeviceId::new(0x1533)));
+    /// }
+    /// ```
+    pub fn vendor_names_into<'b>(&self, vendor_id: VendorId, buffer: &'b mut [u8]) -> PciResult<VendorNames<'b>> {
+        let index = self.index_of_vendor(vendor_id).ok_or(PciError::VendorNotFound)?;
+        let vendor = &self.vendors()[index];
+        let count = names_before(vendor.devices(), vendor.devices().len(), |device| device.subsystems().len());
+        let text = match self.compressed_names() {
+            Some(names) => names.block(names.vendor_starts[index] as usize, count, buffer, |_| {})?,
+            None => {
+                let names = core::iter::once(vendor.name()).chain(
+                    vendor.devices().iter().flat_map(|device| core::iter::once(device.name()).chain(device.subsystems().iter().map(|subsystem| subsystem.name()))),
+                );
+                let mut len = 0;
+                for (i, name) in names.enumerate() {
+                    let separator = if i == 0 { "" } else { "\n" };
+                    for part in [separator, name] {
+                        buffer.get_mut(len..len + part.len()).ok_or(PciError::BufferTooSmall)?.copy_from_slice(part.as_bytes());
+                        len += part.len();
+                    }
+                }
+                core::str::from_utf8(&buffer[..len]).map_err(|_| PciError::InvalidFormat)?
+            }
+        };
+        Ok(VendorNames { vendor, text })
+    }
+
+    /// Write a device name into `buffer` and return it.
+    ///
+    /// See [`vendor_name_into`](Self::vendor_name_into).
+    pub fn device_name_into<'b>(&self, vendor_id: VendorId, device_id: DeviceId, buffer: &'b mut [u8]) -> PciResult<&'b str> {
+        let (number, device) = self.device_number(vendor_id, device_id)?;
+        self.name_into(number, device.name(), buffer)
+    }
+
+    /// Write a subsystem name into `buffer` and return it.
+    ///
+    /// See [`vendor_name_into`](Self::vendor_name_into). Unknown subsystems
+    /// fail with [`PciError::DeviceNotFound`].
+    pub fn subsystem_name_into<'b>(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+        buffer: &'b mut [u8],
+    ) -> PciResult<&'b str> {
+        let (number, subsystem) = self.subsystem_number(vendor_id, device_id, subvendor_id, subdevice_id)?;
+        self.name_into(number, subsystem.name(), buffer)
+    }
+
+    /// Write a class name into `buffer` and return it.
+    ///
+    /// See [`vendor_name_into`](Self::vendor_name_into). Unknown classes fail
+    /// with [`PciError::ClassNotFound`].
+    pub fn class_name_into<'b>(&self, class_id: DeviceClassId, buffer: &'b mut [u8]) -> PciResult<&'b str> {
+        let (number, class) = self.class_number(class_id)?;
+        self.name_into(number, class.name(), buffer)
+    }
+
+    /// Write a subclass name into `buffer` and return it.
+    ///
+    /// See [`class_name_into`](Self::class_name_into). Unknown subclasses fail
+    /// with [`PciError::SubclassNotFound`].
+    pub fn subclass_name_into<'b>(&self, class_id: DeviceClassId, subclass_id: SubClassId, buffer: &'b mut [u8]) -> PciResult<&'b str> {
+        let (number, subclass) = self.subclass_number(class_id, subclass_id)?;
+        self.name_into(number, subclass.name(), buffer)
+    }
+
+    /// Write a programming interface name into `buffer` and return it.
+    ///
+    /// See [`class_name_into`](Self::class_name_into). Unknown programming
+    /// interfaces fail with [`PciError::ProgInterfaceNotFound`].
+    pub fn prog_interface_name_into<'b>(
+        &self,
+        class_id: DeviceClassId,
+        subclass_id: SubClassId,
+        prog_interface_id: ProgInterfaceId,
+        buffer: &'b mut [u8],
+    ) -> PciResult<&'b str> {
+        let (number, prog_if) = self.prog_interface_number(class_id, subclass_id, prog_interface_id)?;
+        self.name_into(number, prog_if.name(), buffer)
+    }
+
+    /// Get the name of a vendor of this database, decompressed if the tables hold empty names.
+    ///
+    /// The `resolve_*` methods back the allocating name APIs, so they give the
+    /// same results with and without compressed names. String table names are
+    /// borrowed rather than copied.
+    pub(crate) fn resolve_vendor_name(&self, vendor: &Vendor) -> Cow<'static, str> {
+        self.resolve_name(vendor.name(), || Ok(self.vendor_number(vendor.id())?.0))
+    }
+
+    /// Get the name of a device of this database; see [`resolve_vendor_name`](Self::resolve_vendor_name).
+    pub(crate) fn resolve_device_name(&self, vendor_id: VendorId, device: &Device) -> Cow<'static, str> {
+        self.resolve_name(device.name(), || Ok(self.device_number(vendor_id, device.id())?.0))
+    }
+
+    /// Get the name of a subsystem of this database; see [`resolve_vendor_name`](Self::resolve_vendor_name).
+    pub(crate) fn resolve_subsystem_name(&self, vendor_id: VendorId, device_id: DeviceId, subsystem: &Subsystem) -> Cow<'static, str> {
+        self.resolve_name(subsystem.name(), || {
+            Ok(self.subsystem_number(vendor_id, device_id, subsystem.subvendor_id, subsystem.subdevice_id)?.0)
+        })
+    }
+
+    /// Get the name of a class of this database; see [`resolve_vendor_name`](Self::resolve_vendor_name).
+    pub(crate) fn resolve_class_name(&self, class: &DeviceClass) -> Cow<'static, str> {
+        self.resolve_name(class.name(), || Ok(self.class_number(class.id())?.0))
+    }
+
+    /// Get the name of a subclass of this database; see [`resolve_vendor_name`](Self::resolve_vendor_name).
+    pub(crate) fn resolve_subclass_name(&self, class_id: DeviceClassId, subclass: &SubClass) -> Cow<'static, str> {
+        self.resolve_name(subclass.name(), || Ok(self.subclass_number(class_id, subclass.id())?.0))
+    }
+
+    /// Get the name of a programming interface of this database; see [`resolve_vendor_name`](Self::resolve_vendor_name).
+    pub(crate) fn resolve_prog_interface_name(&self, class_id: DeviceClassId, subclass_id: SubClassId, prog_if: &ProgInterface) -> Cow<'static, str> {
+        self.resolve_name(prog_if.name(), || Ok(self.prog_interface_number(class_id, subclass_id, prog_if.id())?.0))
+    }
+
+    /// Describe a device of `class` as [`DeviceClass::describe_device`] does, with resolved names.
+    pub(crate) fn resolve_class_description(
+        &self,
+        class: &DeviceClass,
+        subclass_id: Option<SubClassId>,
+        prog_interface_id: Option<ProgInterfaceId>,
+    ) -> String {
+        class.describe_device_with(
+            subclass_id,
+            prog_interface_id,
+            &self.resolve_class_name(class),
+            |subclass| self.resolve_subclass_name(class.id(), subclass),
+            |subclass, prog_if| self.resolve_prog_interface_name(class.id(), subclass.id(), prog_if),
+        )
+    }
+
+    /// Get the name with the given number, or `name` for uncompressed databases.
+    ///
+    /// Compressed names are decompressed on the stack unless some name is
+    /// longer than [`STACK_NAME_LEN`], so the only allocation is the returned string.
+    fn resolve_name(&self, name: &'static str, number: impl FnOnce() -> PciResult<usize>) -> Cow<'static, str> {
+        let Some(names) = self.compressed_names() else {
+            return Cow::Borrowed(name);
+        };
+        let Ok(number) = number() else {
+            return Cow::Borrowed("");
+        };
+        if let Some(name) = names.stored_name(number) {
+            return Cow::Borrowed(name.unwrap_or(""));
+        }
+        if names.max_name_len() <= STACK_NAME_LEN {
+            let mut buffer = [0; STACK_NAME_LEN];
+            return Cow::Owned(names.name(number, &mut buffer).map(String::from).unwrap_or_default());
+        }
+        let mut buffer = alloc::vec![0; names.max_name_len()];
+        Cow::Owned(names.name(number, &mut buffer).map(String::from).unwrap_or_default())
+    }
+
+    /// Get the name with the given number into `buffer`, or copy `name` for uncompressed databases.
+    fn name_into<'b>(&self, number: usize, name: &str, buffer: &'b mut [u8]) -> PciResult<&'b str> {
+        match self.compressed_names() {
+            Some(names) => names.name(number, buffer),
+            None => copy_name(name, buffer),
+        }
+    }
+
+    /// Find a vendor and the number of its name in the compressed names.
+    fn vendor_number(&self, vendor_id: VendorId) -> PciResult<(usize, &Vendor)> {
+        let index = self.index_of_vendor(vendor_id).ok_or(PciError::VendorNotFound)?;
+        let start = self.compressed_names_table().vendor_starts.get(index).map_or(0, |&start| start as usize);
+        Ok((start, &self.vendors()[index]))
+    }
+
+    /// Find a device and the number of its name in the compressed names.
+    fn device_number(&self, vendor_id: VendorId, device_id: DeviceId) -> PciResult<(usize, &Device)> {
+        let vendor_index = self.index_of_vendor(vendor_id).ok_or(PciError::VendorNotFound)?;
+        let devices = self.vendors()[vendor_index].devices();
+        let index = devices
+            .binary_search_by_key(&device_id.value(), |device| device.id.value())
+            .map_err(|_| PciError::DeviceNotFound)?;
+
+        let start = self.compressed_names_table().vendor_starts.get(vendor_index).map_or(0, |&start| start as usize);
+        Ok((start + names_before(devices, index, |device| device.subsystems().len()), &devices[index]))
+    }
+
+    /// Find a subsystem and the number of its name in the compressed names.
+    ///
+    /// Unknown subsystems fail with [`PciError::DeviceNotFound`].
+    fn subsystem_number(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+    ) -> PciResult<(usize, &Subsystem)> {
+        let (number, device) = self.device_number(vendor_id, device_id)?;
+        let index = device
+            .subsystems()
+            .iter()
+            .position(|subsystem| subsystem.subvendor_id == subvendor_id && subsystem.subdevice_id == subdevice_id)
+            .ok_or(PciError::DeviceNotFound)?;
+        Ok((number + 1 + index, &device.subsystems()[index]))
+    }
+
+    /// Find a class and the number of its name in the compressed names.
+    fn class_number(&self, class_id: DeviceClassId) -> PciResult<(usize, &DeviceClass)> {
+        let index = self.index_of_class(class_id).ok_or(PciError::ClassNotFound)?;
+        let start = self.compressed_names_table().class_starts.get(index).map_or(0, |&start| start as usize);
+        Ok((start, &self.classes()[index]))
+    }
+
+    /// Find a subclass and the number of its name in the compressed names.
+    fn subclass_number(&self, class_id: DeviceClassId, subclass_id: SubClassId) -> PciResult<(usize, &SubClass)> {
+        let class_index = self.index_of_class(class_id).ok_or(PciError::ClassNotFound)?;
+        let subclasses = self.classes()[class_index].subclasses();
+        let index = subclasses
+            .iter()
+            .position(|subclass| subclass.id == subclass_id)
+            .ok_or(PciError::SubclassNotFound)?;
+
+        let start = self.compressed_names_table().class_starts.get(class_index).map_or(0, |&start| start as usize);
+        Ok((start + names_before(subclasses, index, |subclass| subclass.prog_interfaces().len()), &subclasses[index]))
+    }
+
+    /// Find a programming interface and the number of its name in the compressed names.
+    fn prog_interface_number(
+        &self,
+        class_id: DeviceClassId,
+        subclass_id: SubClassId,
+        prog_interface_id: ProgInterfaceId,
+    ) -> PciResult<(usize, &ProgInterface)> {
+        let (number, subclass) = self.subclass_number(class_id, subclass_id)?;
+        let index = subclass
+            .prog_interfaces()
+            .iter()
+            .position(|prog_if| prog_if.id == prog_interface_id)
+            .ok_or(PciError::ProgInterfaceNotFound)?;
+        Ok((number + 1 + index, &subclass.prog_interfaces()[index]))
+    }
+}
+
+/// The size of the stack buffer names are decompressed into before being copied out.
+const STACK_NAME_LEN: usize = 256;
+
+/// Resolves names for a run of lookups that mostly stay within one vendor.
+///
+/// Finding one device name in the compressed names means counting the names
+/// before it, so matching every device of a large vendor one by one takes
+/// quadratic time. Once asked for a second name of the same vendor, the cache
+/// decompresses all of its names with
+/// [`vendor_names_into`](PciDatabase::vendor_names_into) and serves later
+/// lookups from them. Without compressed names it just borrows the tables.
+pub(crate) struct NameCache<'db> {
+    database: &'db PciDatabase,
+    vendor: RefCell<CachedVendor>,
+}
+
+/// The decompressed names of the vendor a [`NameCache`] was last asked about.
+#[derive(Default)]
+struct CachedVendor {
+    /// The vendor, and whether its names have been decompressed
+    vendor: Option<(VendorId, bool)>,
+    /// The number of the vendor's name
+    start: usize,
+    text: Vec<u8>,
+    /// The byte range of every name in `text`, in table order
+    lines: Vec<Range<usize>>,
+    /// The line of every device's name
+    device_lines: Vec<usize>,
+}
+
+impl<'db> NameCache<'db> {
+    pub(crate) fn new(database: &'db PciDatabase) -> Self {
+        Self { database, vendor: RefCell::default() }
+    }
+
+    pub(crate) fn vendor_name(&self, vendor: &Vendor) -> Cow<'static, str> {
+        self.database.resolve_vendor_name(vendor)
+    }
+
+    pub(crate) fn device_name(&self, vendor: &Vendor, device: &Device) -> CachedName<'_> {
+        self.cached(vendor, device, 0)
+            .unwrap_or_else(|| CachedName::Resolved(self.database.resolve_device_name(vendor.id(), device)))
+    }
+
+    pub(crate) fn subsystem_name(&self, vendor: &Vendor, device: &Device, subsystem: &Subsystem) -> CachedName<'_> {
+        let index = device.subsystems().iter().position(|other| {
+            other.subvendor_id == subsystem.subvendor_id && other.subdevice_id == subsystem.subdevice_id
+        });
+        index.and_then(|index| self.cached(vendor, device, 1 + index)).unwrap_or_else(|| {
+            CachedName::Resolved(self.database.resolve_subsystem_name(vendor.id(), device.id(), subsystem))
+        })
+    }
+
+    /// Infer the class of a device from its resolved name; see [`infer_class`](crate::class_map::infer_class).
+    pub(crate) fn infer_class(&self, vendor: &Vendor, device: &Device) -> Option<InferredClass> {
+        infer_class_named(vendor.id(), device.id(), || self.device_name(vendor, device))
+    }
+
+    /// Look a name up in the decompressed vendor, `offset` names after its device's.
+    ///
+    /// Switching vendors while an earlier name is still borrowed would free
+    /// it, so lookups then miss and the caller resolves the name directly.
+    /// String tables are not decompressed, but the cache still saves counting
+    /// the names before each device.
+    fn cached(&self, vendor: &Vendor, device: &Device, offset: usize) -> Option<CachedName<'_>> {
+        let names = self.database.compressed_names()?;
+        {
+            let mut cached = self.vendor.try_borrow_mut().ok()?;
+            match cached.vendor {
+                Some((vendor_id, true)) if vendor_id == vendor.id() => {}
+                Some((vendor_id, false)) if vendor_id == vendor.id() => cached.load(self.database, names, vendor)?,
+                _ => {
+                    cached.vendor = Some((vendor.id(), false));
+                    return None;
+                }
+            }
+        }
+
+        let index = vendor.devices().binary_search_by_key(&device.id.value(), |device| device.id.value()).ok()?;
+        let cached = self.vendor.borrow();
+        let line = cached.device_lines[index] + offset;
+        if let Some(name) = names.stored_name(cached.start + line) {
+            return name.ok().map(|name| CachedName::Resolved(Cow::Borrowed(name)));
+        }
+        Ref::filter_map(cached, |cached| {
+            let range = cached.lines.get(line)?.clone();
+            core::str::from_utf8(&cached.text[range]).ok()
+        })
+        .ok()
+        .map(CachedName::Cached)
+    }
+}
+
+/// A name from a [`NameCache`], borrowed from the decompressed vendor on a hit.
+pub(crate) enum CachedName<'c> {
+    Cached(Ref<'c, str>),
+    Resolved(Cow<'static, str>),
+}
+
+impl Deref for CachedName<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            CachedName::Cached(name) => name,
+            CachedName::Resolved(name) => name,
+        }
+    }
+}
+
+impl CachedVendor {
+    fn load(&mut self, database: &PciDatabase, names: &CompressedNames, vendor: &Vendor) -> Option<()> {
+        let index = database.index_of_vendor(vendor.id())?;
+        self.start = *names.vendor_starts.get(index)? as usize;
+        self.lines.clear();
+        if !names.is_string_table() {
+            let count = names_before(vendor.devices(), vendor.devices().len(), |device| device.subsystems().len());
+            self.text.resize(names.max_vendor_names_len(), 0);
+            names.block(self.start, count, &mut self.text, |range| self.lines.push(range)).ok()?;
+        }
+
+        self.device_lines.clear();
+        let mut line = 1;
+        for device in vendor.devices() {
+            self.device_lines.push(line);
+            line += 1 + device.subsystems().len();
+        }
+
+        self.vendor = Some((vendor.id(), true));
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::ParsedDatabase;
+    use crate::codegen::{compress_names, string_table};
+    use crate::parser::PciIdsParser;
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    const SAMPLE: &str = "\
+1234  Example Network Technologies
+\t0001  Example Gigabit Ethernet Controller
+\t\t1234 0001  Example Gigabit Ethernet Adapter
+\t\t1234 0002  Example Gigabit Ethernet Adapter (Dual Port)
+\t0002  Example Gigabit Ethernet Controller (Copper)
+5678  Caf\u{e9} Systems
+\t0003  Caf\u{e9} Accelerator
+
+C 02  Network controller
+\t00  Ethernet controller
+\t80  Network controller
+C 0c  Serial bus controller
+\t03  USB controller
+\t\t30  XHCI
+";
+
+    fn leak<T>(items: Vec<T>) -> &'static [T] {
+        Box::leak(items.into_boxed_slice())
+    }
+
+    /// Parse `content` and clear every name in the tables, as the generator does.
+    fn parse_stripped(content: &str) -> (ParsedDatabase, ParsedDatabase) {
+        let mut parser = PciIdsParser::new();
+        parser.parse(content).unwrap();
+        let parsed = parser.into_parsed();
+        let mut stripped = parsed.clone();
+        for vendor in &mut stripped.vendors {
+            vendor.name.clear();
+            for device in &mut vendor.devices {
+                device.name.clear();
+                for subsystem in &mut device.subsystems {
+                    subsystem.name.clear();
+                }
+            }
+        }
+        for class in &mut stripped.classes {
+            class.name.clear();
+            for subclass in &mut class.subclasses {
+                subclass.name.clear();
+                for prog_if in &mut subclass.prog_interfaces {
+                    prog_if.name.clear();
+                }
+            }
+        }
+        (parsed, stripped)
+    }
+
+    /// Build a database with empty table names and compressed names, as the generator does.
+    fn compressed_database(content: &str) -> PciDatabase {
+        let (parsed, stripped) = parse_stripped(content);
+        let compressed = compress_names(&parsed.vendors, &parsed.classes, true);
+        let dictionary = compressed.dictionary.iter().map(|word| &*Box::leak(word.clone().into_boxed_str())).collect();
+        let names = CompressedNames::new(
+            leak(compressed.data),
+            leak(compressed.restarts),
+            leak(dictionary),
+            leak(compressed.vendor_starts),
+            leak(compressed.class_starts),
+            compressed.max_name_len,
+            compressed.max_vendor_names_len,
+        );
+        stripped.into_database().with_compressed_names(names)
+    }
+
+    /// Build a database with empty table names and a string table, as the generator does.
+    fn string_table_database(content: &str) -> PciDatabase {
+        let (parsed, stripped) = parse_stripped(content);
+        let table = string_table(&parsed.vendors, &parsed.classes, true);
+        let names = CompressedNames::string_table(
+            Box::leak(table.text.into_boxed_str()),
+            leak(table.spans),
+            leak(table.vendor_starts),
+            leak(table.class_starts),
+            table.max_name_len,
+            table.max_vendor_names_len,
+        );
+        stripped.into_database().with_compressed_names(names)
+    }
+
+    #[test]
+    fn test_compressed_names_round_trip() {
+        let mut parser = PciIdsParser::new();
+        parser.parse(SAMPLE).unwrap();
+        let plain = parser.into_database();
+        let db = compressed_database(SAMPLE);
+        assert!(db.compressed_names().is_some());
+        assert!(plain.compressed_names().is_none());
+        assert_eq!(db.find_vendor(VendorId::new(0x1234)).unwrap().name(), "");
+
+        let mut buffer = [0u8; 64];
+        for vendor in plain.vendors() {
+            assert_eq!(db.vendor_name_into(vendor.id, &mut buffer).unwrap(), vendor.name());
+            for device in vendor.devices() {
+                assert_eq!(db.device_name_into(vendor.id, device.id, &mut buffer).unwrap(), device.name());
+                for subsystem in device.subsystems() {
+                    let name = db.subsystem_name_into(vendor.id, device.id, subsystem.subvendor_id, subsystem.subdevice_id, &mut buffer);
+                    assert_eq!(name.unwrap(), subsystem.name());
+                }
+            }
+        }
+        for class in plain.classes() {
+            assert_eq!(db.class_name_into(class.id, &mut buffer).unwrap(), class.name());
+            for subclass in class.subclasses() {
+                assert_eq!(db.subclass_name_into(class.id, subclass.id, &mut buffer).unwrap(), subclass.name());
+                for prog_if in subclass.prog_interfaces() {
+                    let name = db.prog_interface_name_into(class.id, subclass.id, prog_if.id, &mut buffer);
+                    assert_eq!(name.unwrap(), prog_if.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compressed_names_restart_points() {
+        let mut content = alloc::string::String::from("1234  Vendor\n");
+        for id in 0..40 {
+            content.push_str(&alloc::format!("\t{:04x}  Device number {}\n", id, id));
+        }
+        let db = compressed_database(&content);
+        let names = db.compressed_names().unwrap();
+        assert_eq!(names.restarts.len(), 41usize.div_ceil(CompressedNames::RESTART_INTERVAL));
+        assert!(names.compressed_size() < content.len());
+
+        let mut buffer = [0u8; 32];
+        assert_eq!(db.device_name_into(VendorId::new(0x1234), DeviceId::new(0x0027), &mut buffer), Ok("Device number 39"));
+        assert_eq!(db.device_name_into(VendorId::new(0x1234), DeviceId::new(0x000f), &mut buffer), Ok("Device number 15"));
+
+        // Every vendor and class starts a block of its own
+        let db = compressed_database(SAMPLE);
+        let names = db.compressed_names().unwrap();
+        assert_eq!(names.vendor_starts, [0, 16]);
+        assert_eq!(names.class_starts, [32, 48]);
+        assert_eq!(names.restarts.len(), 4);
+        assert_eq!(db.vendor_name_into(VendorId::new(0x5678), &mut buffer), Ok("Caf\u{e9} Systems"));
+    }
+
+    #[test]
+    fn test_vendor_names_into() {
+        let mut parser = PciIdsParser::new();
+        parser.parse(SAMPLE).unwrap();
+        let plain = parser.into_database();
+        let db = compressed_database(SAMPLE);
+        let max = db.compressed_names().unwrap().max_vendor_names_len();
+
+        for database in [&db, &plain] {
+            let mut buffer = alloc::vec![0u8; max];
+            let names = database.vendor_names_into(VendorId::new(0x1234), &mut buffer).unwrap();
+            assert_eq!(names.as_str().len(), max);
+            assert_eq!(names.vendor_name(), "Example Network Technologies");
+            assert_eq!(names.device_name(DeviceId::new(0x0002)), Some("Example Gigabit Ethernet Controller (Copper)"));
+            assert_eq!(names.device_name(DeviceId::new(0x0003)), None);
+            let subsystem = names.subsystem_name(DeviceId::new(0x0001), SubvendorId::new(0x1234), SubdeviceId::new(0x0002));
+            assert_eq!(subsystem, Some("Example Gigabit Ethernet Adapter (Dual Port)"));
+
+            let names = database.vendor_names_into(VendorId::new(0x5678), &mut buffer).unwrap();
+            assert_eq!(names.as_str(), "Caf\u{e9} Systems\nCaf\u{e9} Accelerator");
+
+            assert_eq!(database.vendor_names_into(VendorId::new(0xffff), &mut buffer).err(), Some(PciError::VendorNotFound));
+            assert_eq!(database.vendor_names_into(VendorId::new(0x1234), &mut buffer[..max - 1]).err(), Some(PciError::BufferTooSmall));
+        }
+    }
+
+    #[test]
+    fn test_name_cache() {
+        let mut parser = PciIdsParser::new();
+        parser.parse(SAMPLE).unwrap();
+        let plain = parser.into_database();
+        let db = compressed_database(SAMPLE);
+
+        // Lookups agree with the tables whether they hit the cache or not
+        let cache = NameCache::new(&db);
+        for (vendor, expected) in db.vendors().iter().zip(plain.vendors()) {
+            assert_eq!(cache.vendor_name(vendor), expected.name());
+            for (device, expected) in vendor.devices().iter().zip(expected.devices()).rev() {
+                assert_eq!(&*cache.device_name(vendor, device), expected.name());
+                for (subsystem, expected) in device.subsystems().iter().zip(expected.subsystems()) {
+                    assert_eq!(&*cache.subsystem_name(vendor, device, subsystem), expected.name());
+                }
+            }
+        }
+        let (vendor, device) = (&db.vendors()[0], &db.vendors()[0].devices()[0]);
+        assert!(matches!(cache.device_name(vendor, device), CachedName::Resolved(_)));
+        assert!(matches!(cache.device_name(vendor, device), CachedName::Cached(_)));
+        assert!(matches!(
+            NameCache::new(&plain).device_name(&plain.vendors()[0], &plain.vendors()[0].devices()[0]),
+            CachedName::Resolved(Cow::Borrowed(_))
+        ));
+
+        // Queries and prefix search see the decompressed names
+        assert_eq!(db.search_devices("copper").len(), 1);
+        assert_eq!(db.query().subsystem_name_contains("dual port").count(), 1);
+        assert_eq!(db.names_with_prefix("caf").len(), 2);
+        assert_eq!(db.vendor_name(VendorId::new(0x5678)), "Caf\u{e9} Systems");
+        assert_eq!(db.names_with_prefix("caf")[0].name(&db), "Caf\u{e9} Systems");
+
+        // So do the names and descriptions of matches and statistics
+        let device_match = db.query().device_name_contains("copper").execute_first().unwrap();
+        assert_eq!(device_match.description(), "Example Network Technologies Example Gigabit Ethernet Controller (Copper) (Network controller)");
+        assert_eq!(db.search_classes("serial")[0].description(), "Serial bus controller (USB controller)");
+        assert_eq!(db.vendor_stats()[1].to_string(), "5678 Caf\u{e9} Systems: 1 devices, 0 subsystems");
+        assert!(db.class_histogram().to_string().contains("02 Network controller: 2 subclasses"));
+        assert!(db.query().summarize().to_string().contains("Network controller: "));
+    }
+
+    #[test]
+    fn test_string_table() {
+        let mut parser = PciIdsParser::new();
+        parser.parse(SAMPLE).unwrap();
+        let plain = parser.into_database();
+        let db = string_table_database(SAMPLE);
+        let names = db.compressed_names().unwrap();
+        assert!(names.is_string_table());
+        assert!(db.capabilities().string_table);
+        assert!(!db.capabilities().compressed_names);
+        assert_eq!(names.vendor_starts, [0, 5]);
+        assert_eq!(names.class_starts, [7, 10]);
+        // The repeated "Network controller" is stored once
+        assert_eq!(names.text.matches("Network controller").count(), 1);
+
+        let mut buffer = [0u8; 64];
+        for (vendor, expected) in db.vendors().iter().zip(plain.vendors()) {
+            assert_eq!(db.vendor_name_into(vendor.id, &mut buffer).unwrap(), expected.name());
+            assert!(matches!(db.resolve_vendor_name(vendor), Cow::Borrowed(name) if name == expected.name()));
+            for (device, expected) in vendor.devices().iter().zip(expected.devices()) {
+                assert!(matches!(db.resolve_device_name(vendor.id(), device), Cow::Borrowed(name) if name == expected.name()));
+                for (subsystem, expected) in device.subsystems().iter().zip(expected.subsystems()) {
+                    assert_eq!(db.resolve_subsystem_name(vendor.id(), device.id(), subsystem), expected.name());
+                }
+            }
+        }
+        for class in plain.classes() {
+            for subclass in class.subclasses() {
+                assert_eq!(db.subclass_name_into(class.id, subclass.id, &mut buffer).unwrap(), subclass.name());
+                for prog_if in subclass.prog_interfaces() {
+                    let name = db.prog_interface_name_into(class.id, subclass.id, prog_if.id, &mut buffer);
+                    assert_eq!(name.unwrap(), prog_if.name());
+                }
+            }
+        }
+
+        // The cache borrows from the string table once it has numbered a vendor's names
+        let cache = NameCache::new(&db);
+        for (vendor, expected) in db.vendors().iter().zip(plain.vendors()) {
+            for (device, expected) in vendor.devices().iter().zip(expected.devices()).rev() {
+                assert_eq!(&*cache.device_name(vendor, device), expected.name());
+                for (subsystem, expected) in device.subsystems().iter().zip(expected.subsystems()) {
+                    assert!(matches!(cache.subsystem_name(vendor, device, subsystem), CachedName::Resolved(Cow::Borrowed(name)) if name == expected.name()));
+                }
+            }
+        }
+
+        let names = db.vendor_names_into(VendorId::new(0x5678), &mut buffer).unwrap();
+        assert_eq!(names.as_str(), "Caf\u{e9} Systems\nCaf\u{e9} Accelerator");
+        assert_eq!(db.search_devices("copper").len(), 1);
+        assert_eq!(db.names_with_prefix("caf").len(), 2);
+        assert_eq!(db.vendor_name_into(VendorId::new(0x1234), &mut [0u8; 8]), Err(PciError::BufferTooSmall));
+    }
+
+    #[test]
+    fn test_name_into_errors() {
+        let db = compressed_database(SAMPLE);
+        let mut buffer = [0u8; 64];
+
+        assert_eq!(db.vendor_name_into(VendorId::new(0xffff), &mut buffer), Err(PciError::VendorNotFound));
+        assert_eq!(db.device_name_into(VendorId::new(0x1234), DeviceId::new(0xffff), &mut buffer), Err(PciError::DeviceNotFound));
+        assert_eq!(db.class_name_into(DeviceClassId::new(0x03), &mut buffer), Err(PciError::ClassNotFound));
+        assert_eq!(db.subclass_name_into(DeviceClassId::new(0x02), SubClassId::new(0x01), &mut buffer), Err(PciError::SubclassNotFound));
+        assert_eq!(db.vendor_name_into(VendorId::new(0x1234), &mut [0u8; 8]), Err(PciError::BufferTooSmall));
+        assert_eq!(db.compressed_names().unwrap().max_name_len(), "Example Gigabit Ethernet Adapter (Dual Port)".len());
+    }
+}
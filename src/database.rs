@@ -3,39 +3,220 @@
 use crate::vendors::Vendor;
 use crate::devices::{Device, Subsystem};
 use crate::classes::{DeviceClass, SubClass, ProgInterface};
+use crate::index::{self, NameIndexEntry, NameMatch};
+use crate::oem::OemName;
+use crate::compressed::CompressedNames;
+use crate::phf::PhfIndex;
 use crate::types::*;
-use alloc::string::ToString;
+use alloc::{string::ToString, vec::Vec};
 
 /// The main PCI database containing all vendor, device, and class information.
 ///
 /// This struct provides the primary interface for querying PCI device information.
 /// The database is populated at compile time from the PCI IDs file, ensuring
 /// zero runtime overhead for database loading.
-#[derive(Debug)]
+///
+/// Besides the global instance returned by [`get`](Self::get), any number of
+/// databases can coexist in one process (for example two snapshots being
+/// compared). Every method operates only on the instance it is called on.
+///
+/// Its `Debug` output summarizes the database rather than listing every
+/// entry, since query results and statistics hold a reference to it.
+#[derive(Clone, Copy)]
 pub struct PciDatabase {
     /// All known PCI vendors
     vendors: &'static [Vendor],
     /// All known PCI device classes
     classes: &'static [DeviceClass],
+    /// Vendor and device name keys sorted for prefix search
+    name_index: &'static [NameIndexEntry],
+    /// OEM display names sorted by IDs
+    oem_names: &'static [OemName],
+    /// Names stored compressed instead of in the tables
+    compressed_names: CompressedNames,
+    /// Perfect-hash index from vendor IDs to vendor positions
+    vendor_phf: PhfIndex,
+    /// Perfect-hash index from vendor and device IDs to their positions
+    device_phf: PhfIndex,
+    /// The pci.ids version the database was generated from, or empty
+    version: &'static str,
+    /// The pci.ids date the database was generated from, or empty
+    date: &'static str,
 }
 
 impl PciDatabase {
     /// Create a new database with the given vendors and classes.
     ///
-    /// This is primarily used by the build script to create the static database.
-    #[doc(hidden)]
+    /// This is used by the build script for the global database, and can be used
+    /// to build custom static databases that work with every lookup and query
+    /// method. Vendors, the devices of each vendor, and classes must be sorted by
+    /// ID without duplicates; check this with [`is_sorted`](Self::is_sorted) or
+    /// [`validate`](Self::validate). The [`pci_database!`](crate::pci_database)
+    /// macro offers a more compact syntax and checks ordering at compile time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, Vendor, VendorId, Device, DeviceId};
+    ///
+    /// static DEVICES: &[Device] = &[Device::new(DeviceId::new(0x0001), "Custom Accelerator", &[])];
+    /// static VENDORS: &[Vendor] = &[Vendor::new(VendorId::new(0x1234), "Example Corp", DEVICES)];
+    /// static MY_DATABASE: PciDatabase = PciDatabase::new(VENDORS, &[]);
+    ///
+    /// assert_eq!(MY_DATABASE.vendor_name(VendorId::new(0x1234)), "Example Corp");
+    /// ```
     pub const fn new(vendors: &'static [Vendor], classes: &'static [DeviceClass]) -> Self {
-        Self { vendors, classes }
+        Self {
+            vendors,
+            classes,
+            name_index: &[],
+            oem_names: &[],
+            compressed_names: CompressedNames::EMPTY,
+            vendor_phf: PhfIndex::EMPTY,
+            device_phf: PhfIndex::EMPTY,
+            version: "",
+            date: "",
+        }
+    }
+
+    /// Attach a sorted name index to this database.
+    ///
+    /// This is primarily used by the build script. Without an index,
+    /// [`names_with_prefix`](Self::names_with_prefix) falls back to a linear scan.
+    #[doc(hidden)]
+    pub const fn with_name_index(mut self, name_index: &'static [NameIndexEntry]) -> Self {
+        self.name_index = name_index;
+        self
+    }
+
+    /// Attach a table of OEM display names, sorted by IDs (as returned by
+    /// [`parse_oem_names`](crate::oem::parse_oem_names)).
+    ///
+    /// [`describe_device`](Self::describe_device) then prefers the OEM name for
+    /// matching subsystems. See the [`oem`](crate::oem) module for details.
+    pub const fn with_oem_names(mut self, oem_names: &'static [OemName]) -> Self {
+        self.oem_names = oem_names;
+        self
+    }
+
+    /// Attach compressed names, for databases whose tables hold empty names.
+    ///
+    /// This is used by generated code; see the [`compressed`](crate::compressed) module.
+    #[doc(hidden)]
+    pub const fn with_compressed_names(mut self, compressed_names: CompressedNames) -> Self {
+        self.compressed_names = compressed_names;
+        self
+    }
+
+    /// Attach perfect-hash indexes for vendor and device lookups.
+    ///
+    /// This is used by generated code; see the [`phf`](crate::phf) module.
+    #[doc(hidden)]
+    pub const fn with_phf(mut self, vendor_phf: PhfIndex, device_phf: PhfIndex) -> Self {
+        self.vendor_phf = vendor_phf;
+        self.device_phf = device_phf;
+        self
+    }
+
+    /// Record the pci.ids snapshot this database was generated from.
+    ///
+    /// The generated database calls this with the `Version:` and `Date:`
+    /// fields of the pci.ids header; pass empty strings for unknown fields.
+    pub const fn with_version(mut self, version: &'static str, date: &'static str) -> Self {
+        self.version = version;
+        self.date = date;
+        self
+    }
+
+    /// Get the version of the pci.ids snapshot this database was generated
+    /// from, such as `"2025.07.11"`, if known.
+    ///
+    /// For the global database this is [`DATABASE_VERSION`], so binaries can
+    /// report exactly which snapshot they embed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// match PciDatabase::get().version() {
+    ///     Some(version) => println!("pci.ids version {}", version),
+    ///     None => println!("pci.ids version unknown"),
+    /// }
+    /// ```
+    pub const fn version(&self) -> Option<&'static str> {
+        if self.version.is_empty() {
+            None
+        } else {
+            Some(self.version)
+        }
+    }
+
+    /// Get the date of the pci.ids snapshot this database was generated from,
+    /// such as `"2025-07-11 03:15:02"`, if known.
+    ///
+    /// For the global database this is [`DATABASE_DATE`].
+    pub const fn date(&self) -> Option<&'static str> {
+        if self.date.is_empty() {
+            None
+        } else {
+            Some(self.date)
+        }
     }
 
     /// Get the global PCI database instance.
     ///
     /// This function returns a reference to the statically compiled PCI database.
     /// The database is populated at compile time, so this function has zero cost.
-    pub fn get() -> &'static Self {
+    pub const fn get() -> &'static Self {
         &GLOBAL_DATABASE
     }
 
+    /// Version of the static table layout used by this crate.
+    ///
+    /// This is bumped whenever the structure of generated databases changes
+    /// incompatibly, so tools linked against different builds can tell which
+    /// layout they are reading.
+    pub const LAYOUT_VERSION: u32 = 2;
+
+    /// Describe the layout and optional indexes compiled into this database.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let caps = db.capabilities();
+    /// if caps.name_index {
+    ///     println!("Prefix search is indexed");
+    /// }
+    /// ```
+    pub const fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            layout_version: Self::LAYOUT_VERSION,
+            name_index: !self.name_index.is_empty(),
+            compressed_names: !self.compressed_names.is_empty() && !self.compressed_names.is_string_table(),
+            string_table: self.compressed_names.is_string_table(),
+            phf: !self.vendor_phf.is_empty(),
+        }
+    }
+
+    /// Get the compressed names table, which is empty for uncompressed databases.
+    pub(crate) const fn compressed_names_table(&self) -> &CompressedNames {
+        &self.compressed_names
+    }
+
+    /// Get the vendor perfect-hash index, which is empty unless one was generated.
+    pub(crate) const fn vendor_phf(&self) -> &PhfIndex {
+        &self.vendor_phf
+    }
+
+    /// Get the device perfect-hash index, which is empty unless one was generated.
+    pub(crate) const fn device_phf(&self) -> &PhfIndex {
+        &self.device_phf
+    }
+
     /// Get all vendors in the database.
     #[inline]
     pub const fn vendors(&self) -> &'static [Vendor] {
@@ -48,6 +229,44 @@ impl PciDatabase {
         self.classes
     }
 
+    /// Get the attached OEM display names.
+    #[inline]
+    pub const fn oem_names(&self) -> &'static [OemName] {
+        self.oem_names
+    }
+
+    /// Check, in a const context if needed, that vendors, the devices of each
+    /// vendor, and classes are sorted by ID without duplicates.
+    ///
+    /// Use [`validate`](Self::validate) to find out which entries are wrong.
+    pub const fn is_sorted(&self) -> bool {
+        let mut i = 0;
+        while i < self.vendors.len() {
+            if i > 0 && self.vendors[i - 1].id.value() >= self.vendors[i].id.value() {
+                return false;
+            }
+            let devices = self.vendors[i].devices;
+            let mut j = 1;
+            while j < devices.len() {
+                if devices[j - 1].id.value() >= devices[j].id.value() {
+                    return false;
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+
+        let mut i = 1;
+        while i < self.classes.len() {
+            if self.classes[i - 1].id.value() >= self.classes[i].id.value() {
+                return false;
+            }
+            i += 1;
+        }
+
+        true
+    }
+
     /// Find a vendor by ID.
     ///
     /// # Examples
@@ -62,10 +281,74 @@ impl PciDatabase {
     ///     println!("Found vendor: {}", vendor.name());
     /// }
     /// ```
-    pub fn find_vendor(&self, vendor_id: VendorId) -> Option<&Vendor> {
+    ///
+    /// This is a `const fn`, so statically known IDs can be resolved at compile time:
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, Vendor, VendorId};
+    ///
+    /// const INTEL: Option<&Vendor> = PciDatabase::get().find_vendor(VendorId::new(0x8086));
+    /// ```
+    pub const fn find_vendor(&self, vendor_id: VendorId) -> Option<&Vendor> {
+        match self.index_of_vendor(vendor_id) {
+            Some(index) => Some(&self.vendors[index]),
+            None => None,
+        }
+    }
+
+    /// Get the position of a vendor in [`vendors`](Self::vendors).
+    ///
+    /// Vendors are stored in ID order, so indices are stable for a given
+    /// database and can be stored as compact references to entries. They shift
+    /// when the database is regenerated from a newer pci.ids, so only exchange
+    /// them between programs built against the same database.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{pci_database, PciDatabase, VendorId};
+    ///
+    /// static DB: PciDatabase = pci_database! {
+    ///     vendors {
+    ///         0x1af4 "Red Hat, Inc.",
+    ///         0x8086 "Intel Corporation",
+    ///     }
+    ///     classes {}
+    /// };
+    ///
+    /// let index = DB.index_of_vendor(VendorId::new(0x8086)).unwrap();
+    /// assert_eq!(index, 1);
+    /// assert_eq!(DB.vendor_at(index).unwrap().id(), VendorId::new(0x8086));
+    /// ```
+    pub const fn index_of_vendor(&self, vendor_id: VendorId) -> Option<usize> {
+        if let Some(index) = self.phf_vendor_index(vendor_id) {
+            return index;
+        }
+
         // Use binary search since vendors are sorted by ID
-        self.vendors.binary_search_by_key(&vendor_id, |v| v.id()).ok()
-            .map(|index| &self.vendors[index])
+        let mut low = 0;
+        let mut high = self.vendors.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let id = self.vendors[mid].id.value();
+            if id == vendor_id.value() {
+                return Some(mid);
+            } else if id < vendor_id.value() {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        None
+    }
+
+    /// Get the vendor at a position returned by [`index_of_vendor`](Self::index_of_vendor).
+    pub const fn vendor_at(&self, index: usize) -> Option<&Vendor> {
+        if index < self.vendors.len() {
+            Some(&self.vendors[index])
+        } else {
+            None
+        }
     }
 
     /// Find a device by vendor and device IDs.
@@ -84,6 +367,9 @@ impl PciDatabase {
     /// }
     /// ```
     pub fn find_device(&self, vendor_id: VendorId, device_id: DeviceId) -> Option<&Device> {
+        if let Some(index) = self.phf_device_index(vendor_id, device_id) {
+            return index.map(|(vendor, device)| &self.vendors[vendor].devices[device]);
+        }
         self.find_vendor(vendor_id)?.find_device(device_id)
     }
 
@@ -129,10 +415,44 @@ impl PciDatabase {
     ///     println!("Found class: {}", class.name());
     /// }
     /// ```
-    pub fn find_class(&self, class_id: DeviceClassId) -> Option<&DeviceClass> {
+    ///
+    /// Like [`find_vendor`](Self::find_vendor), this is a `const fn`.
+    pub const fn find_class(&self, class_id: DeviceClassId) -> Option<&DeviceClass> {
+        match self.index_of_class(class_id) {
+            Some(index) => Some(&self.classes[index]),
+            None => None,
+        }
+    }
+
+    /// Get the position of a device class in [`classes`](Self::classes).
+    ///
+    /// Like [`index_of_vendor`](Self::index_of_vendor), indices follow ID order
+    /// and are stable for a given database.
+    pub const fn index_of_class(&self, class_id: DeviceClassId) -> Option<usize> {
         // Use binary search since classes are sorted by ID
-        self.classes.binary_search_by_key(&class_id, |c| c.id()).ok()
-            .map(|index| &self.classes[index])
+        let mut low = 0;
+        let mut high = self.classes.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let id = self.classes[mid].id.value();
+            if id == class_id.value() {
+                return Some(mid);
+            } else if id < class_id.value() {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        None
+    }
+
+    /// Get the device class at a position returned by [`index_of_class`](Self::index_of_class).
+    pub const fn class_at(&self, index: usize) -> Option<&DeviceClass> {
+        if index < self.classes.len() {
+            Some(&self.classes[index])
+        } else {
+            None
+        }
     }
 
     /// Find a subclass by class and subclass IDs.
@@ -180,12 +500,115 @@ impl PciDatabase {
             .find_prog_interface(subclass_id, prog_interface_id)
     }
 
+    /// Resolve a class code to its class, subclass, and programming interface in one call.
+    ///
+    /// Each level is `None` if it is not in the database; a missing class also
+    /// leaves the subclass and programming interface unresolved.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, ClassCode};
+    ///
+    /// let db = PciDatabase::get();
+    /// // Class code register of an xHCI USB controller
+    /// let (class, subclass, prog_if) = db.find_by_class_code(ClassCode::from_value(0x0c0330));
+    ///
+    /// if let (Some(class), Some(subclass), Some(prog_if)) = (class, subclass, prog_if) {
+    ///     println!("{} / {} / {}", class.name(), subclass.name(), prog_if.name());
+    /// }
+    /// ```
+    pub fn find_by_class_code(
+        &self,
+        class_code: ClassCode,
+    ) -> (Option<&DeviceClass>, Option<&SubClass>, Option<&ProgInterface>) {
+        let class = self.find_class(class_code.class_id());
+        let subclass = class.and_then(|class| class.find_subclass(class_code.subclass_id()));
+        let prog_interface = subclass.and_then(|subclass| subclass.find_prog_interface(class_code.prog_interface_id()));
+        (class, subclass, prog_interface)
+    }
+
+    /// Find all vendors and devices whose name, or a bracketed part of it, starts with a prefix.
+    ///
+    /// Matching is ASCII case-insensitive. A device named `"GA102 [GeForce RTX 3080]"`
+    /// matches both `"ga102"` and `"GeForce"`. Results are returned in database order.
+    ///
+    /// Lookups use the sorted name index generated at build time, so they cost a
+    /// binary search plus the number of matches rather than a scan of every name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// for name_match in db.names_with_prefix("GeForce") {
+    ///     println!("{} {}", db.vendor_name(name_match.vendor().id()), name_match.name(db));
+    /// }
+    /// ```
+    pub fn names_with_prefix(&self, prefix: &str) -> Vec<NameMatch<'_>> {
+        // The index keys are the table names, which are empty when compressed
+        if self.name_index.is_empty() || self.compressed_names().is_some() {
+            return self.names_with_prefix_scan(prefix);
+        }
+
+        let start = self.name_index.partition_point(|entry| {
+            index::cmp_prefix_folded(entry.key(self.vendors), prefix).is_lt()
+        });
+
+        let mut hits: Vec<(u16, u16)> = self.name_index[start..]
+            .iter()
+            .take_while(|entry| index::cmp_prefix_folded(entry.key(self.vendors), prefix).is_eq())
+            .map(|entry| (entry.vendor, entry.device))
+            .collect();
+
+        // Vendor entries use the sentinel device index, so map it below every device
+        hits.sort_unstable_by_key(|&(vendor, device)| (vendor, device.wrapping_add(1)));
+        hits.dedup();
+
+        hits.into_iter()
+            .filter_map(|(vendor, device)| {
+                NameIndexEntry::new(vendor, device, 0).resolve(self.vendors)
+            })
+            .collect()
+    }
+
+    fn names_with_prefix_scan(&self, prefix: &str) -> Vec<NameMatch<'_>> {
+        let matches = |name: &str| {
+            index::key_offsets(name)
+                .any(|offset| index::cmp_prefix_folded(&name[offset..], prefix).is_eq())
+        };
+
+        let compressed = self.compressed_names().is_some();
+        let mut buffer = alloc::vec![0; self.compressed_names().map_or(0, CompressedNames::max_vendor_names_len)];
+        let mut results = Vec::new();
+        for vendor in self.vendors {
+            // Compressed names are decompressed a vendor at a time, one per line in table order
+            let block = if compressed { self.vendor_names_into(vendor.id(), &mut buffer).map_or("", |names| names.as_str()) } else { "" };
+            let mut lines = block.split('\n');
+            let mut next_name = |stored: &'static str| if compressed { lines.next().unwrap_or("") } else { stored };
+
+            if matches(next_name(vendor.name())) {
+                results.push(NameMatch::Vendor(vendor));
+            }
+            for device in vendor.devices() {
+                if matches(next_name(device.name())) {
+                    results.push(NameMatch::Device(vendor, device));
+                }
+                for subsystem in device.subsystems() {
+                    next_name(subsystem.name());
+                }
+            }
+        }
+        results
+    }
+
     /// Get a human-readable name for a vendor.
     ///
     /// Returns "Unknown Vendor (XXXX)" if the vendor ID is not found.
     pub fn vendor_name(&self, vendor_id: VendorId) -> alloc::string::String {
         match self.find_vendor(vendor_id) {
-            Some(vendor) => vendor.name().to_string(),
+            Some(vendor) => self.resolve_vendor_name(vendor).into_owned(),
             None => alloc::format!("Unknown Vendor ({:04x})", vendor_id.value()),
         }
     }
@@ -195,11 +618,57 @@ impl PciDatabase {
     /// Returns "Unknown Device (XXXX)" if the device ID is not found.
     pub fn device_name(&self, vendor_id: VendorId, device_id: DeviceId) -> alloc::string::String {
         match self.find_device(vendor_id, device_id) {
-            Some(device) => device.name().to_string(),
+            Some(device) => self.resolve_device_name(vendor_id, device).into_owned(),
             None => alloc::format!("Unknown Device ({:04x})", device_id.value()),
         }
     }
 
+    /// Get a human-readable name for a device, falling back to a generic
+    /// class-based name for unknown devices.
+    ///
+    /// Known devices get their database name. For an unknown device with a
+    /// class code, the name is built from the vendor and the subclass (or class)
+    /// name, e.g. `"Unknown Intel Corporation Ethernet controller (1234)"`.
+    /// Otherwise this returns the same as [`device_name`](Self::device_name).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, ClassCode, VendorId, DeviceId};
+    ///
+    /// let db = PciDatabase::get();
+    /// let name = db.device_name_with_fallback(
+    ///     VendorId::new(0x8086),
+    ///     DeviceId::new(0xfffe),
+    ///     Some(ClassCode::from_value(0x020000)),
+    /// );
+    /// println!("{}", name);
+    /// ```
+    pub fn device_name_with_fallback(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        class_code: Option<ClassCode>,
+    ) -> alloc::string::String {
+        if let Some(device) = self.find_device(vendor_id, device_id) {
+            return self.resolve_device_name(vendor_id, device).into_owned();
+        }
+
+        let class = class_code.and_then(|code| self.find_class(code.class_id()));
+        let generic = match (class, class_code) {
+            (Some(class), Some(code)) => match class.find_subclass(code.subclass_id()) {
+                Some(subclass) => self.resolve_subclass_name(class.id(), subclass),
+                None => self.resolve_class_name(class),
+            },
+            _ => return self.device_name(vendor_id, device_id),
+        };
+
+        match self.find_vendor(vendor_id) {
+            Some(vendor) => alloc::format!("Unknown {} {} ({:04x})", self.resolve_vendor_name(vendor), generic, device_id.value()),
+            None => alloc::format!("Unknown {} ({:04x})", generic, device_id.value()),
+        }
+    }
+
     /// Get a human-readable name for a subsystem.
     ///
     /// Returns "Unknown Subsystem (XXXX:XXXX)" if the subsystem is not found.
@@ -211,7 +680,7 @@ impl PciDatabase {
         subdevice_id: SubdeviceId,
     ) -> alloc::string::String {
         match self.find_subsystem(vendor_id, device_id, subvendor_id, subdevice_id) {
-            Some(subsystem) => subsystem.name().to_string(),
+            Some(subsystem) => self.resolve_subsystem_name(vendor_id, device_id, subsystem).into_owned(),
             None => alloc::format!(
                 "Unknown Subsystem ({:04x}:{:04x})",
                 subvendor_id.value(),
@@ -225,15 +694,59 @@ impl PciDatabase {
     /// Returns "Unknown Class (XX)" if the class ID is not found.
     pub fn class_name(&self, class_id: DeviceClassId) -> alloc::string::String {
         match self.find_class(class_id) {
-            Some(class) => class.name().to_string(),
+            Some(class) => self.resolve_class_name(class).into_owned(),
             None => alloc::format!("Unknown Class ({:02x})", class_id.value()),
         }
     }
 
+    /// Describe a class code as `"Class - Subclass - Programming Interface"`.
+    ///
+    /// Unknown levels are shown as `Unknown ... (xx)`. The programming interface
+    /// is omitted for subclasses that define none.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, ClassCode};
+    ///
+    /// let db = PciDatabase::get();
+    /// // e.g. "Mass storage controller - Non-Volatile memory controller - NVM Express"
+    /// println!("{}", db.describe_class(ClassCode::from_value(0x010802)));
+    /// ```
+    pub fn describe_class(&self, class_code: ClassCode) -> alloc::string::String {
+        let mut description = alloc::string::String::new();
+        let _ = self.write_class_description(&mut description, class_code);
+        description
+    }
+
+    /// Write the description produced by [`describe_class`](Self::describe_class)
+    /// to `out` without allocating, unless names have to be decompressed.
+    pub fn write_class_description<W: core::fmt::Write>(&self, out: &mut W, class_code: ClassCode) -> core::fmt::Result {
+        let class = match self.find_class(class_code.class_id()) {
+            Some(class) => class,
+            None => return write!(out, "Unknown Class ({})", class_code.class_id()),
+        };
+        let class_name = self.resolve_class_name(class);
+        let subclass = match class.find_subclass(class_code.subclass_id()) {
+            Some(subclass) => subclass,
+            None => return write!(out, "{} - Unknown Subclass ({})", class_name, class_code.subclass_id()),
+        };
+
+        write!(out, "{} - {}", class_name, self.resolve_subclass_name(class.id(), subclass))?;
+        if subclass.prog_interfaces().is_empty() {
+            return Ok(());
+        }
+        match subclass.find_prog_interface(class_code.prog_interface_id()) {
+            Some(prog_if) => write!(out, " - {}", self.resolve_prog_interface_name(class.id(), subclass.id(), prog_if)),
+            None => write!(out, " - Unknown Programming Interface ({})", class_code.prog_interface_id()),
+        }
+    }
+
     /// Get a complete description of a device including vendor, device, and class information.
     ///
     /// This is the most comprehensive lookup function, providing a full description
-    /// of a PCI device based on all available identifiers.
+    /// of a PCI device based on all available identifiers. If an OEM name is attached
+    /// for the subsystem, it replaces the vendor, device, and subsystem names.
     ///
     /// # Examples
     ///
@@ -253,6 +766,7 @@ impl PciDatabase {
     ///
     /// println!("Device: {}", description);
     /// ```
+    #[allow(clippy::too_many_arguments)]
     pub fn describe_device(
         &self,
         vendor_id: VendorId,
@@ -265,21 +779,26 @@ impl PciDatabase {
     ) -> alloc::string::String {
         use alloc::format;
 
-        let vendor_name = self.vendor_name(vendor_id);
-        let device_name = self.device_name(vendor_id, device_id);
+        let oem_name = match (subvendor_id, subdevice_id) {
+            (Some(subvendor_id), Some(subdevice_id)) => self.oem_name(vendor_id, device_id, subvendor_id, subdevice_id),
+            _ => None,
+        };
 
-        let mut description = format!("{} {}", vendor_name, device_name);
+        let mut description = match oem_name {
+            Some(oem_name) => oem_name.to_string(),
+            None => format!("{} {}", self.vendor_name(vendor_id), self.device_name(vendor_id, device_id)),
+        };
 
         // Add class information if available
         if let Some(class_id) = class_id {
             if let Some(class) = self.find_class(class_id) {
-                let class_desc = class.describe_device(subclass_id, prog_interface_id);
+                let class_desc = self.resolve_class_description(class, subclass_id, prog_interface_id);
                 description = format!("{} ({})", description, class_desc);
             }
         }
 
         // Add subsystem information if available
-        if let (Some(subvendor_id), Some(subdevice_id)) = (subvendor_id, subdevice_id) {
+        if let (Some(subvendor_id), Some(subdevice_id), None) = (subvendor_id, subdevice_id, oem_name) {
             let subsystem_name = self.subsystem_name(vendor_id, device_id, subvendor_id, subdevice_id);
             description = format!("{} [{}]", description, subsystem_name);
         }
@@ -290,6 +809,8 @@ impl PciDatabase {
     /// Get statistics about the database.
     ///
     /// Returns information about the number of vendors, devices, classes, etc.
+    /// The counts of the global database are also available as the constant
+    /// [`DATABASE_SIZE`], together with its estimated static data size.
     pub fn stats(&self) -> DatabaseStats {
         let mut total_devices = 0;
         let mut total_subsystems = 0;
@@ -320,17 +841,97 @@ impl PciDatabase {
         }
     }
 
-    /// Iterate over all vendors in the database.
+    /// Get device and subsystem counts for every vendor, in database order.
+    pub fn vendor_stats(&self) -> Vec<VendorStats<'_>> {
+        self.vendors.iter().map(|vendor| VendorStats::new(self, vendor)).collect()
+    }
+
+    /// Get the `n` vendors with the most devices.
+    ///
+    /// Vendors with equal device counts are ordered by subsystem count, then by ID.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// for stats in db.top_vendors(10) {
+    ///     println!("{}", stats);
+    /// }
+    /// ```
+    pub fn top_vendors(&self, n: usize) -> Vec<VendorStats<'_>> {
+        let mut stats = self.vendor_stats();
+        stats.sort_by(|a, b| {
+            b.device_count
+                .cmp(&a.device_count)
+                .then(b.subsystem_count.cmp(&a.subsystem_count))
+                .then(a.vendor.id().cmp(&b.vendor.id()))
+        });
+        stats.truncate(n);
+        stats
+    }
+
+    /// Get subclass and programming interface counts for every device class.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// println!("{}", db.class_histogram());
+    /// ```
+    pub fn class_histogram(&self) -> ClassHistogram<'_> {
+        ClassHistogram {
+            classes: self.classes.iter().map(|class| ClassStats::new(self, class)).collect(),
+        }
+    }
+
+    /// Iterate over all vendors in the database, in ID order.
+    ///
+    /// The iterator is exact-size and double-ended.
     pub fn iter_vendors(&self) -> core::slice::Iter<'_, Vendor> {
         self.vendors.iter()
     }
 
-    /// Iterate over all device classes in the database.
+    /// Iterate over all device classes in the database, in ID order.
+    ///
+    /// The iterator is exact-size and double-ended.
     pub fn iter_classes(&self) -> core::slice::Iter<'_, DeviceClass> {
         self.classes.iter()
     }
 }
 
+impl core::fmt::Debug for PciDatabase {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PciDatabase")
+            .field("vendors", &self.vendors.len())
+            .field("classes", &self.classes.len())
+            .field("version", &self.version())
+            .field("capabilities", &self.capabilities())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Layout metadata and optional features of a database, for feature detection.
+///
+/// New fields may be added as more optional data is supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The table layout version (see [`PciDatabase::LAYOUT_VERSION`])
+    pub layout_version: u32,
+    /// Whether a build-time name index is available for prefix search
+    pub name_index: bool,
+    /// Whether names are stored compressed, leaving the table names empty
+    pub compressed_names: bool,
+    /// Whether names are stored in a single string table, leaving the table names empty
+    pub string_table: bool,
+    /// Whether vendor and device lookups use build-time perfect-hash indexes
+    pub phf: bool,
+}
+
 /// Statistics about the PCI database.
 #[derive(Debug, Clone, Copy)]
 pub struct DatabaseStats {
@@ -383,6 +984,99 @@ impl core::fmt::Display for DatabaseStats {
     }
 }
 
+/// Device and subsystem counts for a single vendor.
+#[derive(Debug, Clone, Copy)]
+pub struct VendorStats<'db> {
+    /// The database the vendor belongs to, for resolving its name
+    pub database: &'db PciDatabase,
+    /// The vendor
+    pub vendor: &'db Vendor,
+    /// Number of devices from this vendor
+    pub device_count: usize,
+    /// Total number of subsystems across this vendor's devices
+    pub subsystem_count: usize,
+}
+
+impl<'db> VendorStats<'db> {
+    /// Compute statistics for a vendor of `database`.
+    pub fn new(database: &'db PciDatabase, vendor: &'db Vendor) -> Self {
+        Self {
+            database,
+            vendor,
+            device_count: vendor.device_count(),
+            subsystem_count: vendor.devices().iter().map(Device::subsystem_count).sum(),
+        }
+    }
+}
+
+impl core::fmt::Display for VendorStats<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} {}: {} devices, {} subsystems",
+            self.vendor.id(),
+            self.database.resolve_vendor_name(self.vendor),
+            self.device_count,
+            self.subsystem_count
+        )
+    }
+}
+
+/// Subclass and programming interface counts for a single device class.
+#[derive(Debug, Clone, Copy)]
+pub struct ClassStats<'db> {
+    /// The database the class belongs to, for resolving its name
+    pub database: &'db PciDatabase,
+    /// The device class
+    pub class: &'db DeviceClass,
+    /// Number of subclasses in this class
+    pub subclass_count: usize,
+    /// Total number of programming interfaces across this class's subclasses
+    pub prog_interface_count: usize,
+}
+
+impl<'db> ClassStats<'db> {
+    /// Compute statistics for a device class of `database`.
+    pub fn new(database: &'db PciDatabase, class: &'db DeviceClass) -> Self {
+        Self {
+            database,
+            class,
+            subclass_count: class.subclass_count(),
+            prog_interface_count: class.subclasses().iter().map(SubClass::prog_interface_count).sum(),
+        }
+    }
+}
+
+impl core::fmt::Display for ClassStats<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} {}: {} subclasses, {} programming interfaces",
+            self.class.id(),
+            self.database.resolve_class_name(self.class),
+            self.subclass_count,
+            self.prog_interface_count
+        )
+    }
+}
+
+/// Per-class breakdown of the database, in class ID order.
+#[derive(Debug, Clone)]
+pub struct ClassHistogram<'db> {
+    /// Statistics for each device class
+    pub classes: Vec<ClassStats<'db>>,
+}
+
+impl core::fmt::Display for ClassHistogram<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PCI Class Histogram:")?;
+        for stats in &self.classes {
+            write!(f, "\n  {}", stats)?;
+        }
+        Ok(())
+    }
+}
+
 // This will be generated by the build script
 include!(concat!(env!("OUT_DIR"), "/pci_database.rs"));
 
@@ -411,4 +1105,190 @@ mod tests {
         assert_eq!(stats.device_count, 0);
         assert_eq!(stats.total_entries(), 0);
     }
+
+    #[test]
+    fn test_describe_class() {
+        let db = crate::pci_database! {
+            vendors {}
+            classes {
+                0x0c "Serial bus controller" {
+                    0x03 "USB controller" {
+                        0x30 "XHCI",
+                    },
+                    0x05 "SMBus",
+                },
+            }
+        };
+
+        let describe = |value| db.describe_class(ClassCode::from_value(value));
+        assert_eq!(describe(0x0c0330), "Serial bus controller - USB controller - XHCI");
+        assert_eq!(describe(0x0c0340), "Serial bus controller - USB controller - Unknown Programming Interface (40)");
+        assert_eq!(describe(0x0c0500), "Serial bus controller - SMBus");
+        assert_eq!(describe(0x0c0700), "Serial bus controller - Unknown Subclass (07)");
+        assert_eq!(describe(0x020000), "Unknown Class (02)");
+
+        // The writer variant works with fixed-capacity buffers
+        let mut buffer: heapless::String<64> = heapless::String::new();
+        db.write_class_description(&mut buffer, ClassCode::from_value(0x0c0330)).unwrap();
+        assert_eq!(buffer.as_str(), "Serial bus controller - USB controller - XHCI");
+        let mut small: heapless::String<8> = heapless::String::new();
+        assert!(db.write_class_description(&mut small, ClassCode::from_value(0x0c0330)).is_err());
+    }
+
+    #[test]
+    fn test_device_name_with_fallback() {
+        let db = crate::pci_database! {
+            vendors {
+                0x8086 "Intel Corporation" {
+                    0x0001 "Widget",
+                },
+            }
+            classes {
+                0x02 "Network controller" {
+                    0x00 "Ethernet controller",
+                },
+            }
+        };
+        let name = |vendor, device, code: Option<u32>| {
+            db.device_name_with_fallback(VendorId::new(vendor), DeviceId::new(device), code.map(ClassCode::from_value))
+        };
+
+        assert_eq!(name(0x8086, 0x0001, Some(0x020000)), "Widget");
+        assert_eq!(name(0x8086, 0x1234, Some(0x020000)), "Unknown Intel Corporation Ethernet controller (1234)");
+        assert_eq!(name(0x8086, 0x1234, Some(0x028000)), "Unknown Intel Corporation Network controller (1234)");
+        assert_eq!(name(0x1af4, 0x1234, Some(0x020000)), "Unknown Ethernet controller (1234)");
+        assert_eq!(name(0x8086, 0x1234, Some(0x030000)), "Unknown Device (1234)");
+        assert_eq!(name(0x8086, 0x1234, None), "Unknown Device (1234)");
+    }
+
+    #[test]
+    fn test_capabilities() {
+        static INDEX: &[NameIndexEntry] = &[NameIndexEntry::new(0, NameIndexEntry::VENDOR, 0)];
+        const DB: PciDatabase = crate::pci_database! {
+            vendors {
+                0x1000 "Vendor",
+            }
+            classes {}
+        };
+
+        let caps = DB.capabilities();
+        assert_eq!(caps.layout_version, PciDatabase::LAYOUT_VERSION);
+        assert!(!caps.name_index);
+
+        let caps = DB.with_name_index(INDEX).capabilities();
+        assert!(caps.name_index);
+    }
+
+    #[test]
+    fn test_const_lookups() {
+        const DB: PciDatabase = crate::pci_database! {
+            vendors {
+                0x1000 "First",
+                0x2000 "Second",
+                0x3000 "Third",
+            }
+            classes {
+                0x01 "Mass storage controller",
+                0x02 "Network controller",
+            }
+        };
+        const SECOND: Option<&Vendor> = DB.find_vendor(VendorId::new(0x2000));
+        const MISSING: Option<&Vendor> = DB.find_vendor(VendorId::new(0x2001));
+        const NETWORK: Option<&DeviceClass> = DB.find_class(DeviceClassId::new(0x02));
+
+        assert_eq!(SECOND.map(|v| v.name()), Some("Second"));
+        assert!(MISSING.is_none());
+        assert_eq!(NETWORK.map(|c| c.name()), Some("Network controller"));
+
+        for vendor in DB.vendors() {
+            assert_eq!(DB.find_vendor(vendor.id()).map(|v| v.id()), Some(vendor.id()));
+        }
+        assert!(DB.find_vendor(VendorId::new(0x0000)).is_none());
+        assert!(DB.find_vendor(VendorId::new(0xffff)).is_none());
+        assert!(PciDatabase::new(&[], &[]).find_class(DeviceClassId::new(0x02)).is_none());
+    }
+
+    #[test]
+    fn test_index_access() {
+        const DB: PciDatabase = crate::pci_database! {
+            vendors {
+                0x1000 "First",
+                0x2000 "Second",
+            }
+            classes {
+                0x01 "Mass storage controller",
+                0x02 "Network controller",
+            }
+        };
+        const SECOND: Option<usize> = DB.index_of_vendor(VendorId::new(0x2000));
+
+        assert_eq!(SECOND, Some(1));
+        assert_eq!(DB.vendor_at(1).map(|v| v.name()), Some("Second"));
+        assert!(DB.vendor_at(2).is_none());
+        assert!(DB.index_of_vendor(VendorId::new(0x1500)).is_none());
+        assert_eq!(DB.index_of_class(DeviceClassId::new(0x02)), Some(1));
+        assert_eq!(DB.class_at(0).map(|c| c.name()), Some("Mass storage controller"));
+        assert!(DB.class_at(usize::MAX).is_none());
+    }
+
+    #[test]
+    fn test_vendor_stats_and_top_vendors() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Small Vendor" {
+                    0x0001 "Widget" {
+                        0x1028 0x0001 "OEM Card A",
+                        0x1028 0x0002 "OEM Card B",
+                    },
+                },
+                0x2000 "Large Vendor" {
+                    0x0001 "Widget",
+                    0x0002 "Gadget",
+                },
+                0x3000 "Empty Vendor",
+            }
+            classes {}
+        };
+
+        let stats = db.vendor_stats();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0].device_count, 1);
+        assert_eq!(stats[0].subsystem_count, 2);
+
+        let top = db.top_vendors(2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].vendor.id(), VendorId::new(0x2000));
+        assert_eq!(top[1].vendor.id(), VendorId::new(0x1000));
+        assert_eq!(alloc::format!("{}", top[1]), "1000 Small Vendor: 1 devices, 2 subsystems");
+
+        assert_eq!(db.top_vendors(10).len(), 3);
+    }
+
+    #[test]
+    fn test_class_histogram() {
+        let db = crate::pci_database! {
+            vendors {}
+            classes {
+                0x02 "Network controller",
+                0x0c "Serial bus controller" {
+                    0x03 "USB controller" {
+                        0x00 "UHCI",
+                        0x30 "XHCI",
+                    },
+                    0x05 "SMBus",
+                },
+            }
+        };
+
+        let histogram = db.class_histogram();
+        assert_eq!(histogram.classes.len(), 2);
+        assert_eq!(histogram.classes[1].subclass_count, 2);
+        assert_eq!(histogram.classes[1].prog_interface_count, 2);
+        assert_eq!(
+            alloc::format!("{}", histogram),
+            "PCI Class Histogram:\n  \
+             02 Network controller: 0 subclasses, 0 programming interfaces\n  \
+             0c Serial bus controller: 2 subclasses, 2 programming interfaces"
+        );
+    }
 }
\ No newline at end of file
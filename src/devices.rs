@@ -1,6 +1,6 @@
 //! PCI device definitions and utilities.
 
-use crate::types::{DeviceId, SubvendorId, SubdeviceId};
+use crate::types::{DeviceId, NameForm, SubvendorId, SubdeviceId};
 
 /// Represents a PCI subsystem device.
 #[derive(Debug, Clone)]
@@ -11,6 +11,10 @@ pub struct Subsystem {
     pub subdevice_id: SubdeviceId,
     /// The subsystem name
     pub name: &'static str,
+    /// The name transliterated to ASCII
+    ascii_name: &'static str,
+    /// Whether the name was escaped or transliterated at generation time
+    name_form: NameForm,
 }
 
 impl Subsystem {
@@ -21,9 +25,30 @@ impl Subsystem {
             subvendor_id,
             subdevice_id,
             name,
+            ascii_name: name,
+            name_form: NameForm::Original,
         }
     }
 
+    /// Set the ASCII version of the name returned by [`name_ascii`](Self::name_ascii).
+    ///
+    /// The generated database calls this for every name that is not plain ASCII.
+    #[inline]
+    pub const fn with_ascii_name(mut self, ascii_name: &'static str) -> Self {
+        self.ascii_name = ascii_name;
+        self
+    }
+
+    /// Record how the name was stored, returned by [`name_form`](Self::name_form).
+    ///
+    /// The generated database calls this for every non-ASCII name it escaped
+    /// or transliterated.
+    #[inline]
+    pub const fn with_name_form(mut self, name_form: NameForm) -> Self {
+        self.name_form = name_form;
+        self
+    }
+
     /// Get the subvendor ID.
     #[inline]
     pub const fn subvendor_id(&self) -> SubvendorId {
@@ -41,6 +66,22 @@ impl Subsystem {
     pub const fn name(&self) -> &'static str {
         self.name
     }
+
+    /// Get the subsystem name as ASCII, for consoles and serial logs that cannot render UTF-8.
+    ///
+    /// See [`Vendor::name_ascii`](crate::vendors::Vendor::name_ascii).
+    #[inline]
+    pub const fn name_ascii(&self) -> &'static str {
+        self.ascii_name
+    }
+
+    /// Check whether the name is as given in pci.ids, or was escaped or transliterated to ASCII.
+    ///
+    /// See [`Vendor::name_form`](crate::vendors::Vendor::name_form).
+    #[inline]
+    pub const fn name_form(&self) -> NameForm {
+        self.name_form
+    }
 }
 
 impl PartialEq for Subsystem {
@@ -60,13 +101,42 @@ pub struct Device {
     pub name: &'static str,
     /// The subsystems for this device
     pub subsystems: &'static [Subsystem],
+    /// The name transliterated to ASCII
+    ascii_name: &'static str,
+    /// Whether the name was escaped or transliterated at generation time
+    name_form: NameForm,
 }
 
 impl Device {
     /// Create a new device.
     #[inline]
     pub const fn new(id: DeviceId, name: &'static str, subsystems: &'static [Subsystem]) -> Self {
-        Self { id, name, subsystems }
+        Self {
+            id,
+            name,
+            subsystems,
+            ascii_name: name,
+            name_form: NameForm::Original,
+        }
+    }
+
+    /// Set the ASCII version of the name returned by [`name_ascii`](Self::name_ascii).
+    ///
+    /// The generated database calls this for every name that is not plain ASCII.
+    #[inline]
+    pub const fn with_ascii_name(mut self, ascii_name: &'static str) -> Self {
+        self.ascii_name = ascii_name;
+        self
+    }
+
+    /// Record how the name was stored, returned by [`name_form`](Self::name_form).
+    ///
+    /// The generated database calls this for every non-ASCII name it escaped
+    /// or transliterated.
+    #[inline]
+    pub const fn with_name_form(mut self, name_form: NameForm) -> Self {
+        self.name_form = name_form;
+        self
     }
 
     /// Get the device ID.
@@ -81,6 +151,22 @@ impl Device {
         self.name
     }
 
+    /// Get the device name as ASCII, for consoles and serial logs that cannot render UTF-8.
+    ///
+    /// See [`Vendor::name_ascii`](crate::vendors::Vendor::name_ascii).
+    #[inline]
+    pub const fn name_ascii(&self) -> &'static str {
+        self.ascii_name
+    }
+
+    /// Check whether the name is as given in pci.ids, or was escaped or transliterated to ASCII.
+    ///
+    /// See [`Vendor::name_form`](crate::vendors::Vendor::name_form).
+    #[inline]
+    pub const fn name_form(&self) -> NameForm {
+        self.name_form
+    }
+
     /// Get all subsystems for this device.
     #[inline]
     pub const fn subsystems(&self) -> &'static [Subsystem] {
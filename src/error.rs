@@ -23,6 +23,16 @@ pub enum PciError {
     SubclassNotFound,
     /// Programming interface not found
     ProgInterfaceNotFound,
+    /// Invalid search pattern
+    InvalidPattern,
+    /// The same ID was defined more than once
+    DuplicateEntry,
+    /// Parsing was cancelled by a progress hook
+    Cancelled,
+    /// The input exceeded a limit set with a parser configuration
+    LimitExceeded,
+    /// A caller-provided buffer is too small for the result
+    BufferTooSmall,
 }
 
 impl fmt::Display for PciError {
@@ -37,9 +47,17 @@ impl fmt::Display for PciError {
             PciError::ClassNotFound => write!(f, "Device class not found"),
             PciError::SubclassNotFound => write!(f, "Subclass not found"),
             PciError::ProgInterfaceNotFound => write!(f, "Programming interface not found"),
+            PciError::InvalidPattern => write!(f, "Invalid search pattern"),
+            PciError::DuplicateEntry => write!(f, "Duplicate entry in PCI IDs file"),
+            PciError::Cancelled => write!(f, "Parsing was cancelled"),
+            PciError::LimitExceeded => write!(f, "Parser limit exceeded"),
+            PciError::BufferTooSmall => write!(f, "Buffer too small"),
         }
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for PciError {}
+
 /// Result type for PCI database operations.
 pub type PciResult<T> = Result<T, PciError>;
\ No newline at end of file
@@ -0,0 +1,148 @@
+//! Build-time name indexes for fast prefix search.
+//!
+//! The build script emits a table of [`NameIndexEntry`] values sorted by
+//! (ASCII case-folded) name. Each vendor and device contributes one entry for
+//! the start of its name and one for the start of every bracketed section,
+//! so `"GA102 [GeForce RTX 3080]"` can be found by both `"GA102"` and
+//! `"GeForce"`. Prefix lookups are a binary search followed by a scan over
+//! the matching range.
+
+use alloc::borrow::Cow;
+use core::cmp::Ordering;
+
+pub(crate) use crate::codegen::key_offsets;
+use crate::database::PciDatabase;
+use crate::devices::Device;
+use crate::vendors::Vendor;
+
+/// A single entry in the sorted name index.
+///
+/// Entries reference vendors and devices by their position in the database
+/// arrays and point at the byte offset within the name where the indexed key
+/// starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameIndexEntry {
+    /// Index of the vendor in the database vendor array
+    pub vendor: u16,
+    /// Index of the device within the vendor, or [`NameIndexEntry::VENDOR`]
+    pub device: u16,
+    /// Byte offset into the name where the indexed key starts
+    pub offset: u16,
+}
+
+impl NameIndexEntry {
+    /// Sentinel device index marking an entry that refers to the vendor name.
+    pub const VENDOR: u16 = crate::codegen::NAME_INDEX_VENDOR;
+
+    /// Create a new index entry.
+    #[inline]
+    pub const fn new(vendor: u16, device: u16, offset: u16) -> Self {
+        Self { vendor, device, offset }
+    }
+
+    /// Check if this entry refers to a vendor name rather than a device name.
+    #[inline]
+    pub const fn is_vendor(&self) -> bool {
+        self.device == Self::VENDOR
+    }
+
+    /// Resolve this entry against a vendor array.
+    pub(crate) fn resolve<'db>(&self, vendors: &'db [Vendor]) -> Option<NameMatch<'db>> {
+        let vendor = vendors.get(self.vendor as usize)?;
+        if self.is_vendor() {
+            Some(NameMatch::Vendor(vendor))
+        } else {
+            let device = vendor.devices().get(self.device as usize)?;
+            Some(NameMatch::Device(vendor, device))
+        }
+    }
+
+    /// Get the indexed key for this entry.
+    pub(crate) fn key<'db>(&self, vendors: &'db [Vendor]) -> &'db str {
+        let name = match self.resolve(vendors) {
+            Some(name_match) => name_match.stored_name(),
+            None => return "",
+        };
+        name.get(self.offset as usize..).unwrap_or("")
+    }
+}
+
+/// A vendor or device whose name matched a prefix search.
+#[derive(Debug, Clone, Copy)]
+pub enum NameMatch<'db> {
+    /// A vendor name matched
+    Vendor(&'db Vendor),
+    /// A device name matched
+    Device(&'db Vendor, &'db Device),
+}
+
+impl<'db> NameMatch<'db> {
+    /// Get the vendor for this match (the owning vendor for device matches).
+    pub fn vendor(&self) -> &'db Vendor {
+        match self {
+            NameMatch::Vendor(vendor) => vendor,
+            NameMatch::Device(vendor, _) => vendor,
+        }
+    }
+
+    /// Get the device for this match, if it is a device match.
+    pub fn device(&self) -> Option<&'db Device> {
+        match self {
+            NameMatch::Vendor(_) => None,
+            NameMatch::Device(_, device) => Some(device),
+        }
+    }
+
+    /// Get the full name that matched, decompressed from `database` (the
+    /// database searched) under the `compressed-names` feature, or read from
+    /// its string table under `string-table`.
+    pub fn name(&self, database: &PciDatabase) -> Cow<'static, str> {
+        match self {
+            NameMatch::Vendor(vendor) => database.resolve_vendor_name(vendor),
+            NameMatch::Device(vendor, device) => database.resolve_device_name(vendor.id(), device),
+        }
+    }
+
+    /// Get the name stored in the tables, which is empty if names are compressed.
+    pub(crate) fn stored_name(&self) -> &'static str {
+        match self {
+            NameMatch::Vendor(vendor) => vendor.name(),
+            NameMatch::Device(_, device) => device.name(),
+        }
+    }
+}
+
+/// Compare a key against a prefix using ASCII case folding, considering only
+/// the first `prefix.len()` bytes of the key.
+///
+/// Returns `Ordering::Equal` when `key` starts with `prefix`.
+pub(crate) fn cmp_prefix_folded(key: &str, prefix: &str) -> Ordering {
+    let key = key.as_bytes();
+    let prefix = prefix.as_bytes();
+
+    for (i, &p) in prefix.iter().enumerate() {
+        match key.get(i) {
+            Some(&k) => match k.to_ascii_lowercase().cmp(&p.to_ascii_lowercase()) {
+                Ordering::Equal => continue,
+                other => return other,
+            },
+            None => return Ordering::Less,
+        }
+    }
+
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cmp_prefix_folded() {
+        assert_eq!(cmp_prefix_folded("GeForce RTX", "geforce"), Ordering::Equal);
+        assert_eq!(cmp_prefix_folded("GeForce RTX", "GEFORCE R"), Ordering::Equal);
+        assert_eq!(cmp_prefix_folded("Ge", "geforce"), Ordering::Less);
+        assert_eq!(cmp_prefix_folded("Intel", "geforce"), Ordering::Greater);
+        assert_eq!(cmp_prefix_folded("anything", ""), Ordering::Equal);
+    }
+}
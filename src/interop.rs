@@ -0,0 +1,181 @@
+//! Naming devices enumerated with the `pci_types` crate.
+//!
+//! The ID types of [`pci_types`](https://crates.io/crates/pci_types) are plain
+//! integer aliases, so they already convert into this crate's types with
+//! `From`: `VendorId::from(vendor_id)`, `SubvendorId::from(subsystem_vendor_id)`,
+//! and `ClassCode::from((base_class, sub_class, interface))` for the bytes
+//! returned by `PciHeader::revision_and_class`. This module adds conversions
+//! between the two [`PciAddress`] types, and
+//! [`PciDatabase::identify_header`], which reads a function's IDs and class
+//! code through a `ConfigRegionAccess` and looks up their names in one call.
+//!
+//! This module requires the `pci_types` feature.
+
+use crate::database::PciDatabase;
+use crate::types::*;
+use alloc::string::String;
+use pci_types::{ConfigRegionAccess, EndpointHeader, PciHeader};
+
+impl From<pci_types::PciAddress> for PciAddress {
+    fn from(address: pci_types::PciAddress) -> Self {
+        Self::new(address.segment(), address.bus(), address.device(), address.function())
+    }
+}
+
+impl From<PciAddress> for pci_types::PciAddress {
+    fn from(address: PciAddress) -> Self {
+        Self::new(address.segment(), address.bus(), address.device(), address.function())
+    }
+}
+
+/// A PCI function read through `pci_types`, with its names looked up.
+///
+/// Returned by [`PciDatabase::identify_header`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentifiedFunction {
+    /// The address of the function
+    pub address: PciAddress,
+    /// The vendor ID from the header
+    pub vendor_id: VendorId,
+    /// The device ID from the header
+    pub device_id: DeviceId,
+    /// The revision ID from the header
+    pub revision: u8,
+    /// The class code from the header
+    pub class_code: ClassCode,
+    /// The subsystem IDs, read only for endpoint (type 0) headers
+    pub subsystem: Option<(SubvendorId, SubdeviceId)>,
+    /// The vendor name, as returned by [`PciDatabase::vendor_name`]
+    pub vendor_name: String,
+    /// The device name, as returned by [`PciDatabase::device_name_with_fallback`]
+    pub device_name: String,
+    /// The class description, as returned by [`PciDatabase::describe_class`]
+    pub class_name: String,
+}
+
+impl PciDatabase {
+    /// Read the IDs and class code of a function and look up their names.
+    ///
+    /// Returns `None` if no function responds at the header's address, that is
+    /// if its vendor ID reads as `0xffff`. Unknown devices are named after
+    /// their class, as by [`device_name_with_fallback`](Self::device_name_with_fallback).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use ids_rs::PciDatabase;
+    /// use pci_types::{PciAddress, PciHeader};
+    ///
+    /// let db = PciDatabase::get();
+    /// for device in 0..32 {
+    ///     let header = PciHeader::new(PciAddress::new(0, 0, device, 0));
+    ///     if let Some(function) = db.identify_header(&header, &access) {
+    ///         println!("{} {}: {} {}", function.address, function.class_name, function.vendor_name, function.device_name);
+    ///     }
+    /// }
+    /// ```
+    pub fn identify_header(&self, header: &PciHeader, access: impl ConfigRegionAccess) -> Option<IdentifiedFunction> {
+        let (vendor_id, device_id) = header.id(&access);
+        if vendor_id == 0xffff {
+            return None;
+        }
+        let (vendor_id, device_id) = (VendorId::from(vendor_id), DeviceId::from(device_id));
+        let (revision, base_class, sub_class, interface) = header.revision_and_class(&access);
+        let class_code = ClassCode::from((base_class, sub_class, interface));
+        let subsystem = EndpointHeader::from_header(PciHeader::new(header.address()), &access).map(|endpoint| {
+            let (subdevice_id, subvendor_id) = endpoint.subsystem(&access);
+            (SubvendorId::from(subvendor_id), SubdeviceId::from(subdevice_id))
+        });
+
+        Some(IdentifiedFunction {
+            address: header.address().into(),
+            vendor_id,
+            device_id,
+            revision,
+            class_code,
+            subsystem,
+            vendor_name: self.vendor_name(vendor_id),
+            device_name: self.device_name_with_fallback(vendor_id, device_id, Some(class_code)),
+            class_name: self.describe_class(class_code),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    static DATABASE: PciDatabase = crate::pci_database! {
+        vendors {
+            0x8086 "Intel Corporation" {
+                0x10d3 "82574L Gigabit Network Connection",
+            },
+        }
+        classes {
+            0x02 "Network controller" {
+                0x00 "Ethernet controller",
+            },
+            0x06 "Bridge" {
+                0x04 "PCI bridge",
+            },
+        }
+    };
+
+    /// The configuration header of one function; other addresses read as all ones.
+    struct FakeConfig {
+        address: pci_types::PciAddress,
+        registers: [u32; 16],
+    }
+
+    impl ConfigRegionAccess for FakeConfig {
+        unsafe fn read(&self, address: pci_types::PciAddress, offset: u16) -> u32 {
+            match address == self.address {
+                true => self.registers[usize::from(offset / 4)],
+                false => 0xffff_ffff,
+            }
+        }
+
+        unsafe fn write(&self, _address: pci_types::PciAddress, _offset: u16, _value: u32) {}
+    }
+
+    fn config(device_id: u16, class: u32, header_type: u8) -> FakeConfig {
+        let mut registers = [0; 16];
+        registers[0] = (u32::from(device_id) << 16) | 0x8086;
+        registers[2] = (class << 8) | 0x03;
+        registers[3] = u32::from(header_type) << 16;
+        registers[11] = 0x0000_8086;
+        FakeConfig { address: pci_types::PciAddress::new(0, 2, 0x1f, 3), registers }
+    }
+
+    #[test]
+    fn test_address_conversions() {
+        let address = PciAddress::from(pci_types::PciAddress::new(1, 2, 0x1f, 3));
+        assert_eq!(address, PciAddress::new(1, 2, 0x1f, 3));
+        assert_eq!(address.to_string(), "0001:02:1f.3");
+        assert_eq!(pci_types::PciAddress::from(address), pci_types::PciAddress::new(1, 2, 0x1f, 3));
+    }
+
+    #[test]
+    fn test_identify_header() {
+        let access = config(0x10d3, 0x020000, 0x00);
+        let header = PciHeader::new(access.address);
+        let function = DATABASE.identify_header(&header, &access).unwrap();
+        assert_eq!(function.address, PciAddress::new(0, 2, 0x1f, 3));
+        assert_eq!((function.vendor_id, function.device_id, function.revision), (VendorId::new(0x8086), DeviceId::new(0x10d3), 3));
+        assert_eq!(function.class_code, ClassCode::from_value(0x020000));
+        assert_eq!(function.subsystem, Some((SubvendorId::new(0x8086), SubdeviceId::new(0x0000))));
+        assert_eq!(function.vendor_name, "Intel Corporation");
+        assert_eq!(function.device_name, "82574L Gigabit Network Connection");
+        assert_eq!(function.class_name, "Network controller - Ethernet controller");
+
+        // Bridges have no subsystem register, and unknown devices are named after their class
+        let access = config(0xffff, 0x060400, 0x01);
+        let function = DATABASE.identify_header(&header, &access).unwrap();
+        assert_eq!(function.subsystem, None);
+        assert_eq!(function.device_name, "Unknown Intel Corporation PCI bridge (ffff)");
+
+        let empty = PciHeader::new(pci_types::PciAddress::new(0, 3, 0, 0));
+        assert_eq!(DATABASE.identify_header(&empty, &access), None);
+    }
+}
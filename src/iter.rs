@@ -0,0 +1,212 @@
+//! Iterators over database entries.
+//!
+//! All iterators here know their exact length and can be walked from both
+//! ends, so they compose with `rev`, `len`, `zip`, and friends without
+//! collecting into a `Vec` first.
+
+use core::iter::FusedIterator;
+use core::slice;
+
+use crate::compressed::NameCache;
+use crate::database::PciDatabase;
+use crate::devices::Device;
+use crate::types::*;
+use crate::vendors::Vendor;
+
+/// An iterator over every device in a database, paired with its vendor.
+///
+/// Devices are yielded in database order: by vendor ID, then device ID.
+#[derive(Debug, Clone)]
+pub struct DeviceIter<'db> {
+    database: &'db PciDatabase,
+    /// Vendors whose devices have not been started from either end
+    vendors: &'db [Vendor],
+    front_vendor: Option<&'db Vendor>,
+    front: slice::Iter<'db, Device>,
+    back_vendor: Option<&'db Vendor>,
+    back: slice::Iter<'db, Device>,
+    remaining: usize,
+}
+
+impl<'db> DeviceIter<'db> {
+    pub(crate) fn new(database: &'db PciDatabase) -> Self {
+        let vendors = database.vendors();
+        Self {
+            database,
+            vendors,
+            front_vendor: None,
+            front: [].iter(),
+            back_vendor: None,
+            back: [].iter(),
+            remaining: vendors.iter().map(Vendor::device_count).sum(),
+        }
+    }
+
+    /// Keep only devices whose inferred class is `class_id`.
+    ///
+    /// Classes are inferred with the best-effort [`class_map`](crate::class_map), since pci.ids
+    /// does not record device classes.
+    pub fn filter_class(
+        self,
+        class_id: DeviceClassId,
+    ) -> impl DoubleEndedIterator<Item = (&'db Vendor, &'db Device)> + FusedIterator {
+        let names = NameCache::new(self.database);
+        self.filter(move |(vendor, device)| {
+            names.infer_class(vendor, device).is_some_and(|inferred| inferred.class_id == class_id)
+        })
+    }
+
+    /// Keep only devices from the given vendor.
+    pub fn filter_vendor(
+        self,
+        vendor_id: VendorId,
+    ) -> impl DoubleEndedIterator<Item = (&'db Vendor, &'db Device)> + FusedIterator {
+        self.filter(move |(vendor, _)| vendor.id() == vendor_id)
+    }
+}
+
+impl<'db> Iterator for DeviceIter<'db> {
+    type Item = (&'db Vendor, &'db Device);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (Some(vendor), Some(device)) = (self.front_vendor, self.front.next()) {
+                self.remaining -= 1;
+                return Some((vendor, device));
+            }
+            match self.vendors.split_first() {
+                Some((vendor, rest)) => {
+                    self.vendors = rest;
+                    self.front_vendor = Some(vendor);
+                    self.front = vendor.devices().iter();
+                }
+                None => {
+                    // Only the vendor being consumed from the back is left
+                    let device = self.back.next()?;
+                    self.remaining -= 1;
+                    return self.back_vendor.map(|vendor| (vendor, device));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl DoubleEndedIterator for DeviceIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (Some(vendor), Some(device)) = (self.back_vendor, self.back.next_back()) {
+                self.remaining -= 1;
+                return Some((vendor, device));
+            }
+            match self.vendors.split_last() {
+                Some((vendor, rest)) => {
+                    self.vendors = rest;
+                    self.back_vendor = Some(vendor);
+                    self.back = vendor.devices().iter();
+                }
+                None => {
+                    // Only the vendor being consumed from the front is left
+                    let device = self.front.next_back()?;
+                    self.remaining -= 1;
+                    return self.front_vendor.map(|vendor| (vendor, device));
+                }
+            }
+        }
+    }
+}
+
+impl ExactSizeIterator for DeviceIter<'_> {}
+
+impl FusedIterator for DeviceIter<'_> {}
+
+impl PciDatabase {
+    /// Iterate over every device in the database, paired with its vendor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    /// use ids_rs::classes::well_known::NETWORK;
+    ///
+    /// let db = PciDatabase::get();
+    /// println!("{} devices", db.iter_devices().len());
+    ///
+    /// // The last five network devices, without collecting everything first
+    /// for (vendor, device) in db.iter_devices().filter_class(NETWORK).rev().take(5) {
+    ///     println!("{} {}", vendor.name(), device.name());
+    /// }
+    /// ```
+    pub fn iter_devices(&self) -> DeviceIter<'_> {
+        DeviceIter::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    static DATABASE: PciDatabase = crate::pci_database! {
+        vendors {
+            0x1000 "A" {
+                0x0001 "A1",
+                0x0002 "A2 Ethernet",
+            },
+            0x2000 "B",
+            0x3000 "C" {
+                0x0001 "C1 Ethernet",
+            },
+        }
+        classes {}
+    };
+
+    fn names<'a>(iter: impl Iterator<Item = (&'a Vendor, &'a Device)>) -> Vec<&'static str> {
+        iter.map(|(_, device)| device.name()).collect()
+    }
+
+    #[test]
+    fn test_forward_and_backward() {
+        let db = &DATABASE;
+
+        assert_eq!(db.iter_devices().len(), 3);
+        assert_eq!(names(db.iter_devices()), ["A1", "A2 Ethernet", "C1 Ethernet"]);
+        assert_eq!(names(db.iter_devices().rev()), ["C1 Ethernet", "A2 Ethernet", "A1"]);
+        assert_eq!(PciDatabase::new(&[], &[]).iter_devices().len(), 0);
+    }
+
+    #[test]
+    fn test_meeting_in_the_middle() {
+        let db = &DATABASE;
+
+        // Both ends working through the same vendor
+        let mut iter = db.iter_devices();
+        assert_eq!(iter.next().map(|(_, d)| d.name()), Some("A1"));
+        assert_eq!(iter.next_back().map(|(_, d)| d.name()), Some("C1 Ethernet"));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back().map(|(v, d)| (v.name(), d.name())), Some(("A", "A2 Ethernet")));
+        assert_eq!(iter.len(), 0);
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+
+        let mut iter = db.iter_devices();
+        assert_eq!(iter.next_back().map(|(_, d)| d.name()), Some("C1 Ethernet"));
+        assert_eq!(iter.next_back().map(|(_, d)| d.name()), Some("A2 Ethernet"));
+        assert_eq!(iter.next().map(|(_, d)| d.name()), Some("A1"));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_adapters() {
+        use crate::classes::well_known::{DISPLAY, NETWORK};
+
+        let db = &DATABASE;
+        assert_eq!(names(db.iter_devices().filter_class(NETWORK)), ["A2 Ethernet", "C1 Ethernet"]);
+        assert_eq!(names(db.iter_devices().filter_class(NETWORK).rev()), ["C1 Ethernet", "A2 Ethernet"]);
+        assert_eq!(db.iter_devices().filter_class(DISPLAY).count(), 0);
+        assert_eq!(names(db.iter_devices().filter_vendor(VendorId::new(0x3000))), ["C1 Ethernet"]);
+    }
+}
@@ -38,22 +38,62 @@
 //! ```
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod macros;
 
 pub mod error;
 pub mod types;
 pub mod vendors;
 pub mod devices;
 pub mod classes;
+pub mod class_map;
+mod builders;
+mod line_parser;
 pub mod parser;
+pub mod codegen;
+pub mod writer;
 pub mod database;
+pub mod index;
+pub mod iter;
 pub mod query;
+pub mod query_syntax;
+pub mod oem;
+pub mod chain;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod overlay;
+pub mod validate;
+pub mod compressed;
+pub mod phf;
+pub mod blob;
+#[cfg(feature = "aho-corasick")]
+pub mod multi;
+#[cfg(feature = "pci_types")]
+pub mod interop;
+#[cfg(any(feature = "usb", feature = "sdio"))]
+pub mod buses;
 
 pub use error::*;
 pub use types::*;
-pub use database::PciDatabase;
+pub use database::{PciDatabase, Capabilities, DatabaseStats, VendorStats, ClassStats, ClassHistogram, DATABASE_VERSION, DATABASE_DATE, DATABASE_SIZE};
+pub use index::NameMatch;
+pub use iter::DeviceIter;
+pub use chain::{ChainedDatabase, SourcedMatch};
+#[cfg(feature = "cache")]
+pub use cache::{LookupCache, CacheStats};
+pub use overlay::OverlayDatabase;
 pub use query::*;
+pub use validate::{ValidationIssue, ValidationReport};
+pub use compressed::{CompressedNames, VendorNames};
+pub use phf::PhfIndex;
+pub use blob::BlobDatabase;
+#[cfg(feature = "pci_types")]
+pub use interop::IdentifiedFunction;
 
 // Re-export commonly used types
 pub use vendors::Vendor;
 pub use devices::{Device, Subsystem};
-pub use classes::{DeviceClass, SubClass, ProgInterface};
\ No newline at end of file
+pub use classes::{DeviceClass, SubClass, ProgInterface};
+pub use oem::{OemName, RebrandedDevice};
\ No newline at end of file
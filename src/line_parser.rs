@@ -0,0 +1,637 @@
+//! Line-level parsing of the PCI IDs format.
+//!
+//! The public types are re-exported from [`parser`](crate::parser). This
+//! module only depends on the ID types, the builders, and the error type, so
+//! the build script includes it to read `pci.ids` with the same parser the
+//! library uses instead of a copy that could drift from it.
+
+use alloc::vec::Vec;
+
+use crate::builders::{
+    ClassBuilder, DeviceBuilder, EntryKey, ParsedDatabase, ProgInterfaceBuilder, SubClassBuilder, SubsystemBuilder,
+    VendorBuilder,
+};
+use crate::error::{PciError, PciResult};
+use crate::types::*;
+
+/// Parser state for tracking which section we're currently parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParsingMode {
+    /// Parsing vendor and device information
+    Vendors,
+    /// Parsing device class information
+    Classes,
+}
+
+/// How indentation levels are written in a PCI IDs file.
+///
+/// The upstream file indents with one tab per level, but some tooling rewrites
+/// it with spaces. Lines indented in any other way fail with
+/// [`PciError::InvalidIndentation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Indentation {
+    /// One tab per level
+    Tabs,
+    /// A fixed number of spaces per level (zero is treated as [`Tabs`](Self::Tabs))
+    Spaces(usize),
+    /// Detect tabs or spaces from the first indented line
+    #[default]
+    Auto,
+}
+
+/// How an entry ID is separated from its name.
+///
+/// The upstream file always uses two spaces, but several third-party ids
+/// files use a single space or a tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Separator {
+    /// Exactly two spaces, as in the upstream file
+    #[default]
+    TwoSpaces,
+    /// Any single space or tab, for third-party files
+    ///
+    /// Names are still trimmed, so two spaces work as well.
+    Whitespace,
+}
+
+/// Limits applied while parsing, for input that cannot be trusted.
+///
+/// Parsing never panics on malformed input, and every line is checked before
+/// it is stored. These limits additionally bound the memory a hostile file,
+/// such as one received over the network, can make the parser use. Exceeding
+/// any of them fails with [`PciError::LimitExceeded`].
+///
+/// Every limit defaults to unlimited. The upstream `pci.ids` has lines of
+/// under 200 bytes and fewer than 100,000 entries.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::parser::{ParserConfig, PciIdsParser};
+/// use ids_rs::PciError;
+///
+/// let config = ParserConfig::new().max_line_length(1024).max_name_length(256).max_entries(200_000);
+/// let mut parser = PciIdsParser::new().config(config);
+/// parser.parse("8086  Intel Corporation\n").unwrap();
+///
+/// let mut parser = PciIdsParser::new().config(ParserConfig::new().max_entries(1));
+/// assert_eq!(parser.parse("1234  A\n5678  B\n"), Err(PciError::LimitExceeded));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserConfig {
+    pub(crate) max_line_length: usize,
+    max_name_length: usize,
+    max_entries: usize,
+}
+
+impl ParserConfig {
+    /// Create a configuration without limits.
+    pub fn new() -> Self {
+        Self {
+            max_line_length: usize::MAX,
+            max_name_length: usize::MAX,
+            max_entries: usize::MAX,
+        }
+    }
+
+    /// Set the maximum length of a line in bytes, excluding its line terminator.
+    ///
+    /// `PciIdsParser::parse_reader` never buffers more than one line of this
+    /// length, so it also bounds the memory used to read the input.
+    pub fn max_line_length(mut self, bytes: usize) -> Self {
+        self.max_line_length = bytes;
+        self
+    }
+
+    /// Set the maximum length of an entry name in bytes.
+    pub fn max_name_length(mut self, bytes: usize) -> Self {
+        self.max_name_length = bytes;
+        self
+    }
+
+    /// Set the maximum number of entries of all kinds in one document.
+    pub fn max_entries(mut self, entries: usize) -> Self {
+        self.max_entries = entries;
+        self
+    }
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An entry read by [`StreamingParser`](crate::parser::StreamingParser).
+///
+/// Child entries carry the IDs of their parents, so consumers need no state of
+/// their own to place them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseEvent<'a> {
+    /// A vendor line; the devices that follow belong to it
+    Vendor {
+        /// The vendor ID
+        id: VendorId,
+        /// The vendor name
+        name: &'a str,
+    },
+    /// A device of the current vendor
+    Device {
+        /// The ID of the vendor the device belongs to
+        vendor: VendorId,
+        /// The device ID
+        id: DeviceId,
+        /// The device name
+        name: &'a str,
+    },
+    /// A subsystem of the current device
+    Subsystem {
+        /// The ID of the vendor the device belongs to
+        vendor: VendorId,
+        /// The ID of the device the subsystem belongs to
+        device: DeviceId,
+        /// The subvendor ID
+        subvendor_id: SubvendorId,
+        /// The subdevice ID
+        subdevice_id: SubdeviceId,
+        /// The subsystem name
+        name: &'a str,
+    },
+    /// A device class line; the subclasses that follow belong to it
+    Class {
+        /// The device class ID
+        id: DeviceClassId,
+        /// The device class name
+        name: &'a str,
+    },
+    /// A subclass of the current class
+    SubClass {
+        /// The ID of the class the subclass belongs to
+        class: DeviceClassId,
+        /// The subclass ID
+        id: SubClassId,
+        /// The subclass name
+        name: &'a str,
+    },
+    /// A programming interface of the current subclass
+    ProgInterface {
+        /// The ID of the class the subclass belongs to
+        class: DeviceClassId,
+        /// The ID of the subclass the interface belongs to
+        subclass: SubClassId,
+        /// The programming interface ID
+        id: ProgInterfaceId,
+        /// The programming interface name
+        name: &'a str,
+    },
+}
+
+/// Line-level state shared by [`PciIdsParser`](crate::parser::PciIdsParser),
+/// [`StreamingParser`](crate::parser::StreamingParser), and the build script.
+#[derive(Debug, Clone)]
+pub(crate) struct LineParser {
+    mode: ParsingMode,
+    pub(crate) indentation: Indentation,
+    pub(crate) separator: Separator,
+    pub(crate) config: ParserConfig,
+    entries: usize,
+    first_line: bool,
+    vendor: Option<VendorId>,
+    device: Option<DeviceId>,
+    class: Option<DeviceClassId>,
+    subclass: Option<SubClassId>,
+}
+
+impl LineParser {
+    pub(crate) fn new(indentation: Indentation, separator: Separator, config: ParserConfig) -> Self {
+        Self {
+            mode: ParsingMode::Vendors,
+            indentation,
+            separator,
+            config,
+            entries: 0,
+            first_line: true,
+            vendor: None,
+            device: None,
+            class: None,
+            subclass: None,
+        }
+    }
+
+    /// Parse one line, returning the entry it defines, if any.
+    ///
+    /// Entries whose parent is missing are validated but not reported.
+    pub(crate) fn parse_line<'a>(&mut self, line: &'a str) -> PciResult<Option<ParseEvent<'a>>> {
+        if line.len() > self.config.max_line_length {
+            return Err(PciError::LimitExceeded);
+        }
+
+        let line = if self.first_line {
+            self.first_line = false;
+            line.strip_prefix('\u{feff}').unwrap_or(line)
+        } else {
+            line
+        };
+        let trimmed = line.trim();
+
+        // Skip empty lines and comments
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return Ok(None);
+        }
+
+        if self.indentation == Indentation::Auto {
+            self.indentation = detect_indentation(line);
+        }
+        let indentation = indentation_level(line, self.indentation)?;
+
+        // Check for section transitions
+        if indentation == 0 {
+            if trimmed.starts_with("C ") {
+                self.mode = ParsingMode::Classes;
+                self.vendor = None;
+                self.device = None;
+            } else if self.mode == ParsingMode::Classes && looks_like_vendor_line(trimmed, self.separator) {
+                self.mode = ParsingMode::Vendors;
+                self.class = None;
+                self.subclass = None;
+            }
+        }
+
+        let event = match self.mode {
+            ParsingMode::Vendors => self.parse_vendor_section(trimmed, indentation)?,
+            ParsingMode::Classes => self.parse_class_section(trimmed, indentation)?,
+        };
+
+        if let Some(event) = &event {
+            self.entries += 1;
+            if self.entries > self.config.max_entries || event_name(event).len() > self.config.max_name_length {
+                return Err(PciError::LimitExceeded);
+            }
+        }
+
+        Ok(event)
+    }
+
+    fn parse_vendor_section<'a>(&mut self, trimmed: &'a str, indentation: usize) -> PciResult<Option<ParseEvent<'a>>> {
+        match indentation {
+            0 => {
+                // Vendor definition (XXXX  Name)
+                let (id, name) = parse_vendor_line(trimmed, self.separator)?;
+                self.vendor = Some(id);
+                self.device = None;
+                Ok(Some(ParseEvent::Vendor { id, name }))
+            }
+            1 => {
+                // Device definition (\tXXXX  Name)
+                let (id, name) = parse_device_line(trimmed, self.separator)?;
+                self.device = Some(id);
+                Ok(self.vendor.map(|vendor| ParseEvent::Device { vendor, id, name }))
+            }
+            2 => {
+                // Subsystem definition (\t\tXXXX XXXX  Name)
+                let Some(device) = self.device else {
+                    return Ok(None);
+                };
+                let (subvendor_id, subdevice_id, name) = parse_subsystem_line(trimmed, self.separator)?;
+                Ok(self.vendor.map(|vendor| ParseEvent::Subsystem {
+                    vendor,
+                    device,
+                    subvendor_id,
+                    subdevice_id,
+                    name,
+                }))
+            }
+            _ => Err(PciError::InvalidIndentation),
+        }
+    }
+
+    fn parse_class_section<'a>(&mut self, trimmed: &'a str, indentation: usize) -> PciResult<Option<ParseEvent<'a>>> {
+        match indentation {
+            0 => {
+                // Class definition (C XX  Name)
+                self.subclass = None;
+                if !trimmed.starts_with("C ") {
+                    self.class = None;
+                    return Ok(None);
+                }
+
+                let (id, name) = parse_class_line(trimmed, self.separator)?;
+                self.class = Some(id);
+                Ok(Some(ParseEvent::Class { id, name }))
+            }
+            1 => {
+                // Subclass definition (\tXX  Name)
+                let (id, name) = parse_subclass_line(trimmed, self.separator)?;
+                self.subclass = Some(id);
+                Ok(self.class.map(|class| ParseEvent::SubClass { class, id, name }))
+            }
+            2 => {
+                // Programming interface definition (\t\tXX  Name)
+                let Some(subclass) = self.subclass else {
+                    return Ok(None);
+                };
+                let (id, name) = parse_prog_interface_line(trimmed, self.separator)?;
+                Ok(self.class.map(|class| ParseEvent::ProgInterface { class, subclass, id, name }))
+            }
+            _ => Err(PciError::InvalidIndentation),
+        }
+    }
+}
+
+/// Get the name of the entry an event reports.
+pub(crate) fn event_name<'a>(event: &ParseEvent<'a>) -> &'a str {
+    match *event {
+        ParseEvent::Vendor { name, .. }
+        | ParseEvent::Device { name, .. }
+        | ParseEvent::Subsystem { name, .. }
+        | ParseEvent::Class { name, .. }
+        | ParseEvent::SubClass { name, .. }
+        | ParseEvent::ProgInterface { name, .. } => name,
+    }
+}
+
+/// Get the key of the entry an event reports.
+fn entry_key(event: &ParseEvent<'_>) -> EntryKey {
+    match *event {
+        ParseEvent::Vendor { id, .. } => EntryKey::Vendor(id),
+        ParseEvent::Device { vendor, id, .. } => EntryKey::Device(vendor, id),
+        ParseEvent::Subsystem { vendor, device, subvendor_id, subdevice_id, .. } => {
+            EntryKey::Subsystem(vendor, device, subvendor_id, subdevice_id)
+        }
+        ParseEvent::Class { id, .. } => EntryKey::Class(id),
+        ParseEvent::SubClass { class, id, .. } => EntryKey::SubClass(class, id),
+        ParseEvent::ProgInterface { class, subclass, id, .. } => EntryKey::ProgInterface(class, subclass, id),
+    }
+}
+
+/// Split text into lines ending with LF, CRLF, or a bare CR.
+pub(crate) fn split_lines(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .split('\n')
+        .flat_map(|line| line.strip_suffix('\r').unwrap_or(line).split('\r'))
+}
+
+/// Check if a top-level line looks like a vendor line (4 hex digits followed by a separator).
+fn looks_like_vendor_line(trimmed: &str, separator: Separator) -> bool {
+    let bytes = trimmed.as_bytes();
+    let separated = match separator {
+        Separator::TwoSpaces => bytes.get(4..6) == Some(b"  "),
+        Separator::Whitespace => matches!(bytes.get(4), Some(b' ' | b'\t')),
+    };
+    separated && bytes[..4].iter().all(u8::is_ascii_hexdigit)
+}
+
+/// Assembles builders from parse events, in file order.
+pub(crate) struct TreeBuilder<S> {
+    parsed: ParsedDatabase<S>,
+    current_vendor: Option<VendorBuilder<S>>,
+    current_device: Option<DeviceBuilder<S>>,
+    current_class: Option<ClassBuilder<S>>,
+    current_subclass: Option<SubClassBuilder<S>>,
+    retain_comments: bool,
+    pending_comments: Vec<S>,
+    started: bool,
+}
+
+impl<S> TreeBuilder<S> {
+    pub(crate) fn new(retain_comments: bool) -> Self {
+        Self {
+            parsed: ParsedDatabase::default(),
+            current_vendor: None,
+            current_device: None,
+            current_class: None,
+            current_subclass: None,
+            retain_comments,
+            pending_comments: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// Record a line that produced no entry, if it is a comment or blank line to keep.
+    pub(crate) fn comment<'a>(&mut self, line: &'a str)
+    where
+        S: From<&'a str>,
+    {
+        if !self.started {
+            self.parsed.read_header_line(line.trim_start_matches('\u{feff}'));
+        }
+        let trimmed = line.trim_start_matches('\u{feff}').trim();
+        if self.retain_comments && (trimmed.is_empty() || trimmed.starts_with('#')) {
+            self.pending_comments.push(line.into());
+        }
+    }
+
+    /// Forget the comments recorded for an entry that is not kept.
+    pub(crate) fn drop_comments(&mut self) {
+        self.pending_comments.clear();
+    }
+
+    pub(crate) fn push<'a>(&mut self, event: ParseEvent<'a>)
+    where
+        S: From<&'a str>,
+    {
+        self.started = true;
+        if !self.pending_comments.is_empty() {
+            let lines = self.parsed.comments.leading.entry(entry_key(&event)).or_default();
+            lines.append(&mut self.pending_comments);
+        }
+
+        match event {
+            ParseEvent::Vendor { id, name } => {
+                self.finalize_vendor_device();
+                self.current_vendor = Some(VendorBuilder {
+                    id,
+                    name: name.into(),
+                    devices: Vec::new(),
+                });
+            }
+            ParseEvent::Device { id, name, .. } => {
+                if let (Some(vendor), Some(device)) = (self.current_vendor.as_mut(), self.current_device.take()) {
+                    vendor.devices.push(device);
+                }
+                self.current_device = Some(DeviceBuilder {
+                    id,
+                    name: name.into(),
+                    subsystems: Vec::new(),
+                });
+            }
+            ParseEvent::Subsystem { subvendor_id, subdevice_id, name, .. } => {
+                if let Some(device) = self.current_device.as_mut() {
+                    device.subsystems.push(SubsystemBuilder {
+                        subvendor_id,
+                        subdevice_id,
+                        name: name.into(),
+                    });
+                }
+            }
+            ParseEvent::Class { id, name } => {
+                self.finalize_class_subclass();
+                self.current_class = Some(ClassBuilder {
+                    id,
+                    name: name.into(),
+                    subclasses: Vec::new(),
+                });
+            }
+            ParseEvent::SubClass { id, name, .. } => {
+                if let (Some(class), Some(subclass)) = (self.current_class.as_mut(), self.current_subclass.take()) {
+                    class.subclasses.push(subclass);
+                }
+                self.current_subclass = Some(SubClassBuilder {
+                    id,
+                    name: name.into(),
+                    prog_interfaces: Vec::new(),
+                });
+            }
+            ParseEvent::ProgInterface { id, name, .. } => {
+                if let Some(subclass) = self.current_subclass.as_mut() {
+                    subclass.prog_interfaces.push(ProgInterfaceBuilder { id, name: name.into() });
+                }
+            }
+        }
+    }
+
+    /// Move the current device into the current vendor, and the vendor into the result.
+    fn finalize_vendor_device(&mut self) {
+        if let Some(device) = self.current_device.take() {
+            if let Some(ref mut vendor) = self.current_vendor {
+                vendor.devices.push(device);
+            }
+        }
+
+        if let Some(vendor) = self.current_vendor.take() {
+            self.parsed.vendors.push(vendor);
+        }
+    }
+
+    /// Move the current subclass into the current class, and the class into the result.
+    fn finalize_class_subclass(&mut self) {
+        if let Some(subclass) = self.current_subclass.take() {
+            if let Some(ref mut class) = self.current_class {
+                class.subclasses.push(subclass);
+            }
+        }
+
+        if let Some(class) = self.current_class.take() {
+            self.parsed.classes.push(class);
+        }
+    }
+
+    /// Finalize any remaining items and return the result.
+    pub(crate) fn finish(mut self) -> ParsedDatabase<S> {
+        self.finalize_vendor_device();
+        self.finalize_class_subclass();
+        self.parsed.comments.trailing = self.pending_comments;
+        self.parsed
+    }
+}
+
+/// Count the number of leading tabs in a line.
+fn count_leading_tabs(line: &str) -> usize {
+    line.chars().take_while(|&c| c == '\t').count()
+}
+
+/// Detect the indentation style from a line, or `Auto` if it is not indented.
+fn detect_indentation(line: &str) -> Indentation {
+    match line.as_bytes().first() {
+        Some(b'\t') => Indentation::Tabs,
+        Some(b' ') => Indentation::Spaces(line.bytes().take_while(|&b| b == b' ').count()),
+        _ => Indentation::Auto,
+    }
+}
+
+/// Get the indentation level of a line.
+fn indentation_level(line: &str, style: Indentation) -> PciResult<usize> {
+    match style {
+        Indentation::Spaces(width) if width > 0 => {
+            let spaces = line.bytes().take_while(|&b| b == b' ').count();
+            if spaces % width != 0 {
+                return Err(PciError::InvalidIndentation);
+            }
+            Ok(spaces / width)
+        }
+        _ => Ok(count_leading_tabs(line)),
+    }
+}
+
+/// Split a line into its ID field and its name at the first separator.
+fn split_id(line: &str, separator: Separator) -> PciResult<(&str, &str)> {
+    let split = match separator {
+        Separator::TwoSpaces => line.split_once("  "),
+        Separator::Whitespace => line.split_once([' ', '\t']),
+    };
+    let (id, name) = split.ok_or(PciError::InvalidFormat)?;
+
+    Ok((id, name.trim()))
+}
+
+/// Parse a vendor line: "XXXX  Name"
+fn parse_vendor_line(line: &str, separator: Separator) -> PciResult<(VendorId, &str)> {
+    let (id, name) = split_id(line, separator)?;
+
+    Ok((VendorId::new(parse_hex_u16(id)?), name))
+}
+
+/// Parse a device line: "XXXX  Name"
+fn parse_device_line(line: &str, separator: Separator) -> PciResult<(DeviceId, &str)> {
+    let (id, name) = split_id(line, separator)?;
+
+    Ok((DeviceId::new(parse_hex_u16(id)?), name))
+}
+
+/// Parse a subsystem line: "XXXX XXXX  Name"
+fn parse_subsystem_line(line: &str, separator: Separator) -> PciResult<(SubvendorId, SubdeviceId, &str)> {
+    let (subvendor_id, subdevice_id, name) = match separator {
+        Separator::TwoSpaces => {
+            let (ids, name) = split_id(line, separator)?;
+            let ids: Vec<&str> = ids.split_whitespace().collect();
+            if ids.len() != 2 {
+                return Err(PciError::InvalidFormat);
+            }
+            (ids[0], ids[1], name)
+        }
+        // The two IDs are the first two fields
+        Separator::Whitespace => {
+            let (subvendor_id, rest) = split_id(line, separator)?;
+            let (subdevice_id, name) = split_id(rest, separator)?;
+            (subvendor_id, subdevice_id, name)
+        }
+    };
+
+    Ok((
+        SubvendorId::new(parse_hex_u16(subvendor_id)?),
+        SubdeviceId::new(parse_hex_u16(subdevice_id)?),
+        name,
+    ))
+}
+
+/// Parse a class line: "C XX  Name"
+fn parse_class_line(line: &str, separator: Separator) -> PciResult<(DeviceClassId, &str)> {
+    let rest = line.strip_prefix("C ").ok_or(PciError::InvalidFormat)?;
+    let (id, name) = split_id(rest, separator)?;
+
+    Ok((DeviceClassId::new(parse_hex_u8(id)?), name))
+}
+
+/// Parse a subclass line: "XX  Name"
+fn parse_subclass_line(line: &str, separator: Separator) -> PciResult<(SubClassId, &str)> {
+    let (id, name) = split_id(line, separator)?;
+
+    Ok((SubClassId::new(parse_hex_u8(id)?), name))
+}
+
+/// Parse a programming interface line: "XX  Name"
+fn parse_prog_interface_line(line: &str, separator: Separator) -> PciResult<(ProgInterfaceId, &str)> {
+    let (id, name) = split_id(line, separator)?;
+
+    Ok((ProgInterfaceId::new(parse_hex_u8(id)?), name))
+}
+
+/// Parse a hexadecimal string to u16.
+fn parse_hex_u16(hex_str: &str) -> PciResult<u16> {
+    u16::from_str_radix(hex_str.trim(), 16).map_err(|_| PciError::InvalidHexValue)
+}
+
+/// Parse a hexadecimal string to u8.
+fn parse_hex_u8(hex_str: &str) -> PciResult<u8> {
+    u8::from_str_radix(hex_str.trim(), 16).map_err(|_| PciError::InvalidHexValue)
+}
@@ -0,0 +1,122 @@
+//! Macros for declaring custom static databases.
+
+/// Declare a custom static [`PciDatabase`](crate::PciDatabase).
+///
+/// Downstream crates can embed their own curated or trimmed tables and use
+/// every lookup and query method on them. The macro expands to a constant
+/// expression, so the result can initialize a `static` or `const`.
+///
+/// Vendors, the devices of each vendor, and classes must be listed in
+/// ascending ID order without duplicates, because lookups use binary search.
+/// This is checked at compile time.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::{pci_database, PciDatabase, VendorId, DeviceId};
+///
+/// static MY_DATABASE: PciDatabase = pci_database! {
+///     vendors {
+///         0x1af4 "Red Hat, Inc.",
+///         0x8086 "Intel Corporation" {
+///             0x10d3 "82574L Gigabit Network Connection",
+///             0x1533 "I210 Gigabit Network Connection" {
+///                 0x8086 0x0001 "Ethernet Server Adapter I210-T1",
+///             },
+///         },
+///     }
+///     classes {
+///         0x02 "Network controller" {
+///             0x00 "Ethernet controller",
+///         },
+///         0x0c "Serial bus controller" {
+///             0x03 "USB controller" {
+///                 0x30 "XHCI",
+///             },
+///         },
+///     }
+/// };
+///
+/// let device = MY_DATABASE.find_device(VendorId::new(0x8086), DeviceId::new(0x1533));
+/// assert_eq!(device.map(|d| d.name()), Some("I210 Gigabit Network Connection"));
+/// ```
+///
+/// Out-of-order entries are rejected at compile time:
+///
+/// ```compile_fail
+/// use ids_rs::{pci_database, PciDatabase};
+///
+/// static UNSORTED: PciDatabase = pci_database! {
+///     vendors {
+///         0x8086 "Intel Corporation",
+///         0x1022 "Advanced Micro Devices, Inc. [AMD]",
+///     }
+///     classes {}
+/// };
+/// ```
+#[macro_export]
+macro_rules! pci_database {
+    (
+        vendors { $($vendors:tt)* }
+        classes { $($classes:tt)* }
+    ) => {{
+        const DATABASE: $crate::PciDatabase = $crate::PciDatabase::new(
+            $crate::pci_database!(@vendors $($vendors)*),
+            $crate::pci_database!(@classes $($classes)*),
+        );
+        const _: () = assert!(DATABASE.is_sorted(), "pci_database! entries must be sorted by ID without duplicates");
+        DATABASE
+    }};
+
+    (@vendors $($id:literal $name:literal $({ $($devices:tt)* })?),* $(,)?) => {
+        &[$(
+            $crate::Vendor::new(
+                $crate::VendorId::new($id),
+                $name,
+                $crate::pci_database!(@devices $($($devices)*)?),
+            )
+        ),*]
+    };
+
+    (@devices $($id:literal $name:literal $({ $($subsystems:tt)* })?),* $(,)?) => {
+        &[$(
+            $crate::Device::new(
+                $crate::DeviceId::new($id),
+                $name,
+                $crate::pci_database!(@subsystems $($($subsystems)*)?),
+            )
+        ),*]
+    };
+
+    (@subsystems $($subvendor:literal $subdevice:literal $name:literal),* $(,)?) => {
+        &[$(
+            $crate::Subsystem::new($crate::SubvendorId::new($subvendor), $crate::SubdeviceId::new($subdevice), $name)
+        ),*]
+    };
+
+    (@classes $($id:literal $name:literal $({ $($subclasses:tt)* })?),* $(,)?) => {
+        &[$(
+            $crate::DeviceClass::new(
+                $crate::DeviceClassId::new($id),
+                $name,
+                $crate::pci_database!(@subclasses $($($subclasses)*)?),
+            )
+        ),*]
+    };
+
+    (@subclasses $($id:literal $name:literal $({ $($prog_interfaces:tt)* })?),* $(,)?) => {
+        &[$(
+            $crate::SubClass::new(
+                $crate::SubClassId::new($id),
+                $name,
+                $crate::pci_database!(@prog_interfaces $($($prog_interfaces)*)?),
+            )
+        ),*]
+    };
+
+    (@prog_interfaces $($id:literal $name:literal),* $(,)?) => {
+        &[$(
+            $crate::ProgInterface::new($crate::ProgInterfaceId::new($id), $name)
+        ),*]
+    };
+}
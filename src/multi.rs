@@ -0,0 +1,150 @@
+//! Multi-pattern name search.
+//!
+//! Classification jobs often test a name against dozens of keywords. Running
+//! [`PciDatabase::search_devices`] once per keyword rescans every name each
+//! time; a [`MultiSearch`] compiles all terms into one Aho-Corasick automaton
+//! and finds every term in a single pass over the name.
+//!
+//! Matching ignores ASCII case only, so non-ASCII letters must be given in the
+//! case used by the database.
+//!
+//! This module requires the `aho-corasick` feature.
+
+use crate::compressed::NameCache;
+use crate::database::PciDatabase;
+use crate::error::{PciError, PciResult};
+use crate::query::DeviceMatch;
+use aho_corasick::AhoCorasick;
+use alloc::vec::Vec;
+
+/// A set of search terms compiled into one automaton.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::multi::MultiSearch;
+///
+/// let search = MultiSearch::new(&["ethernet", "wifi", "wireless"]).unwrap();
+/// assert!(search.is_match("82574L Gigabit Ethernet Controller"));
+/// assert_eq!(search.matched_terms("Wireless Ethernet Bridge"), [0, 2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiSearch {
+    automaton: AhoCorasick,
+    term_count: usize,
+}
+
+impl MultiSearch {
+    /// Compile a set of search terms.
+    ///
+    /// Returns [`PciError::InvalidPattern`] if the automaton would be too large.
+    pub fn new<T: AsRef<[u8]>>(terms: &[T]) -> PciResult<Self> {
+        let automaton = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .build(terms)
+            .map_err(|_| PciError::InvalidPattern)?;
+
+        Ok(Self {
+            automaton,
+            term_count: terms.len(),
+        })
+    }
+
+    /// Get the number of terms in the set.
+    pub fn len(&self) -> usize {
+        self.term_count
+    }
+
+    /// Check if the set has no terms.
+    pub fn is_empty(&self) -> bool {
+        self.term_count == 0
+    }
+
+    /// Check if `name` contains any of the terms.
+    pub fn is_match(&self, name: &str) -> bool {
+        self.automaton.is_match(name)
+    }
+
+    /// Get the indices of all terms found in `name`, in ascending order.
+    pub fn matched_terms(&self, name: &str) -> Vec<usize> {
+        let mut terms: Vec<usize> = self
+            .automaton
+            .find_overlapping_iter(name)
+            .map(|found| found.pattern().as_usize())
+            .collect();
+        terms.sort_unstable();
+        terms.dedup();
+        terms
+    }
+}
+
+impl PciDatabase {
+    /// Search for devices whose name contains any of the terms (ASCII case-insensitive).
+    ///
+    /// To run the same terms repeatedly, compile them once with
+    /// [`MultiSearch::new`] and use [`search_devices_with`](Self::search_devices_with).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let network_devices = db.search_any(&["ethernet", "wifi", "wireless"]).unwrap();
+    /// ```
+    pub fn search_any<T: AsRef<[u8]>>(&self, terms: &[T]) -> PciResult<Vec<DeviceMatch<'_>>> {
+        Ok(self.search_devices_with(&MultiSearch::new(terms)?))
+    }
+
+    /// Search for devices whose name contains any term of a compiled set.
+    pub fn search_devices_with(&self, search: &MultiSearch) -> Vec<DeviceMatch<'_>> {
+        let mut results = Vec::new();
+
+        let names = NameCache::new(self);
+        for vendor in self.vendors() {
+            for device in vendor.devices() {
+                if search.is_match(&names.device_name(vendor, device)) {
+                    results.push(DeviceMatch::new(self, vendor, device));
+                }
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matched_terms() {
+        let search = MultiSearch::new(&["ethernet", "net", "wifi"]).unwrap();
+        assert_eq!(search.len(), 3);
+        assert_eq!(search.matched_terms("Gigabit ETHERNET"), [0, 1]);
+        assert_eq!(search.matched_terms("WiFi WiFi"), [2]);
+        assert!(search.matched_terms("Audio").is_empty());
+
+        let empty = MultiSearch::new::<&str>(&[]).unwrap();
+        assert!(empty.is_empty());
+        assert!(!empty.is_match("Ethernet"));
+    }
+
+    #[test]
+    fn test_search_any() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Test Vendor" {
+                    0x0001 "Gigabit Ethernet",
+                    0x0002 "HD Audio",
+                    0x0003 "Wireless-AC 9260",
+                },
+            }
+            classes {}
+        };
+
+        let results = db.search_any(&["ethernet", "wireless"]).unwrap();
+        let names: Vec<_> = results.iter().map(|m| m.device_name()).collect();
+        assert_eq!(names, ["Gigabit Ethernet", "Wireless-AC 9260"]);
+    }
+}
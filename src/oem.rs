@@ -0,0 +1,318 @@
+//! OEM display names for rebranded devices.
+//!
+//! Server and workstation vendors often ship third-party chips under their own
+//! product names, e.g. a Broadcom RAID controller sold as "Dell PERC H755".
+//! pci.ids records these, if at all, as subsystem names. An OEM name table maps
+//! a full (vendor, device, subvendor, subdevice) tuple to the marketing name,
+//! and [`PciDatabase::describe_device`] uses it in place of the generic vendor,
+//! device, and subsystem names once attached with [`PciDatabase::with_oem_names`].
+//!
+//! Tables can be written as static arrays of [`OemName`] or parsed from a text
+//! file with [`parse_oem_names`]. Each non-empty line of the file holds four
+//! hexadecimal IDs followed by the name; `#` starts a comment line:
+//!
+//! ```text
+//! # vendor device subvendor subdevice  name
+//! 1000 10e2 1028 1ae0  Dell PERC H755 Front
+//! ```
+//!
+//! [`PciDatabase::devices_rebranded_by`] answers the reverse question: which
+//! chips an OEM puts in its products, judging by the subsystem entries.
+
+use crate::database::PciDatabase;
+use crate::devices::{Device, Subsystem};
+use crate::error::{PciError, PciResult};
+use crate::types::*;
+use crate::vendors::Vendor;
+use alloc::vec::Vec;
+
+/// An OEM display name for one subsystem of a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OemName {
+    /// The chip vendor ID
+    pub vendor_id: VendorId,
+    /// The chip device ID
+    pub device_id: DeviceId,
+    /// The OEM subvendor ID
+    pub subvendor_id: SubvendorId,
+    /// The OEM subdevice ID
+    pub subdevice_id: SubdeviceId,
+    /// The OEM display name
+    pub name: &'static str,
+}
+
+impl OemName {
+    /// Create a new OEM name entry.
+    #[inline]
+    pub const fn new(
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+        name: &'static str,
+    ) -> Self {
+        Self {
+            vendor_id,
+            device_id,
+            subvendor_id,
+            subdevice_id,
+            name,
+        }
+    }
+
+    /// Get the OEM display name.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The sort key of this entry.
+    fn key(&self) -> (VendorId, DeviceId, SubvendorId, SubdeviceId) {
+        (self.vendor_id, self.device_id, self.subvendor_id, self.subdevice_id)
+    }
+}
+
+/// Parse an OEM name file into a table sorted for lookup.
+///
+/// Names borrow from `content`, so it must live for `'static` (for example
+/// from `include_str!`, or a leaked `String` read at runtime). When the same
+/// IDs appear more than once, the last entry wins.
+///
+/// # Errors
+///
+/// Returns [`PciError::InvalidHexValue`] for a malformed ID and
+/// [`PciError::InvalidFormat`] for a line without four IDs and a name.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::{PciDatabase, VendorId, DeviceId, SubvendorId, SubdeviceId};
+/// use ids_rs::oem::parse_oem_names;
+///
+/// let names = parse_oem_names("1000 10e2 1028 1ae0  Dell PERC H755 Front").unwrap();
+/// let db = PciDatabase::get().with_oem_names(names.leak());
+///
+/// assert_eq!(
+///     db.oem_name(VendorId::new(0x1000), DeviceId::new(0x10e2), SubvendorId::new(0x1028), SubdeviceId::new(0x1ae0)),
+///     Some("Dell PERC H755 Front")
+/// );
+/// ```
+pub fn parse_oem_names(content: &'static str) -> PciResult<Vec<OemName>> {
+    let mut names = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut rest = line;
+        let mut ids = [0u16; 4];
+        for id in &mut ids {
+            let (field, remainder) = rest.split_once(char::is_whitespace).ok_or(PciError::InvalidFormat)?;
+            if field.len() != 4 {
+                return Err(PciError::InvalidHexValue);
+            }
+            *id = u16::from_str_radix(field, 16).map_err(|_| PciError::InvalidHexValue)?;
+            rest = remainder.trim_start();
+        }
+
+        if rest.is_empty() {
+            return Err(PciError::InvalidFormat);
+        }
+
+        names.push(OemName::new(
+            VendorId::new(ids[0]),
+            DeviceId::new(ids[1]),
+            SubvendorId::new(ids[2]),
+            SubdeviceId::new(ids[3]),
+            rest,
+        ));
+    }
+
+    // Stable sort keeps file order within equal keys, so keeping the last of
+    // each run lets later lines override earlier ones
+    names.sort_by_key(OemName::key);
+    names.dedup_by(|later, earlier| {
+        let same = later.key() == earlier.key();
+        if same {
+            *earlier = *later;
+        }
+        same
+    });
+
+    Ok(names)
+}
+
+/// A device with subsystem entries from one OEM.
+#[derive(Debug, Clone)]
+pub struct RebrandedDevice<'db> {
+    /// The chip vendor
+    pub vendor: &'db Vendor,
+    /// The chip
+    pub device: &'db Device,
+    /// The device's subsystems from the OEM, in database order
+    pub subsystems: Vec<&'db Subsystem>,
+}
+
+impl<'db> RebrandedDevice<'db> {
+    /// Iterate over the OEM's names for this device.
+    pub fn subsystem_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.subsystems.iter().map(|subsystem| subsystem.name())
+    }
+}
+
+impl PciDatabase {
+    /// Find every device with at least one subsystem entry from the given subvendor.
+    ///
+    /// Results are in database order, each with the matching subsystems.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, SubvendorId};
+    ///
+    /// let db = PciDatabase::get();
+    /// // Chips that Dell ships in its products
+    /// for rebrand in db.devices_rebranded_by(SubvendorId::new(0x1028)).iter().take(5) {
+    ///     println!("{} {}:", rebrand.vendor.name(), rebrand.device.name());
+    ///     for name in rebrand.subsystem_names() {
+    ///         println!("    {}", name);
+    ///     }
+    /// }
+    /// ```
+    pub fn devices_rebranded_by(&self, subvendor_id: SubvendorId) -> Vec<RebrandedDevice<'_>> {
+        let mut results = Vec::new();
+        for vendor in self.vendors() {
+            for device in vendor.devices() {
+                let subsystems: Vec<&Subsystem> = device
+                    .iter_subsystems()
+                    .filter(|subsystem| subsystem.subvendor_id() == subvendor_id)
+                    .collect();
+                if !subsystems.is_empty() {
+                    results.push(RebrandedDevice { vendor, device, subsystems });
+                }
+            }
+        }
+        results
+    }
+
+    /// Look up the OEM display name for a subsystem, if an OEM name table is attached.
+    pub fn oem_name(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+    ) -> Option<&'static str> {
+        let key = (vendor_id, device_id, subvendor_id, subdevice_id);
+        let names = self.oem_names();
+        names
+            .binary_search_by_key(&key, OemName::key)
+            .ok()
+            .map(|index| names[index].name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_oem_names() {
+        let names = parse_oem_names(
+            "# comment\n\
+             \n\
+             1000 10e2 1028 1ae1  Dell PERC H755 Adapter\n\
+             1000 10e2 1028 1ae0\tOld Name\n\
+             1000 10e2 1028 1ae0  Dell PERC H755 Front\n",
+        )
+        .unwrap();
+
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0].subdevice_id, SubdeviceId::new(0x1ae0));
+        assert_eq!(names[0].name(), "Dell PERC H755 Front");
+        assert_eq!(names[1].name(), "Dell PERC H755 Adapter");
+    }
+
+    #[test]
+    fn test_parse_oem_names_errors() {
+        assert_eq!(parse_oem_names("1000 10e2 1028 zzzz  Name"), Err(PciError::InvalidHexValue));
+        assert_eq!(parse_oem_names("1000 10e2 1028 1ae  Name"), Err(PciError::InvalidHexValue));
+        assert_eq!(parse_oem_names("1000 10e2 1028 1ae0"), Err(PciError::InvalidFormat));
+        assert_eq!(parse_oem_names("1000 10e2 1028 1ae0   "), Err(PciError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_describe_device_uses_oem_name() {
+        static OEM_NAMES: &[OemName] = &[OemName::new(
+            VendorId::new(0x1000),
+            DeviceId::new(0x10e2),
+            SubvendorId::new(0x1028),
+            SubdeviceId::new(0x1ae0),
+            "Dell PERC H755 Front",
+        )];
+
+        let plain = crate::pci_database! {
+            vendors {
+                0x1000 "Broadcom / LSI" {
+                    0x10e2 "MegaRAID 12GSAS/PCIe Secure SAS39xx" {
+                        0x1028 0x1ae0 "PERC H755 Front",
+                    },
+                },
+            }
+            classes {}
+        };
+        let oem = plain.with_oem_names(OEM_NAMES);
+        let describe = |db: &PciDatabase, subdevice| {
+            db.describe_device(
+                VendorId::new(0x1000),
+                DeviceId::new(0x10e2),
+                None,
+                None,
+                None,
+                Some(SubvendorId::new(0x1028)),
+                Some(SubdeviceId::new(subdevice)),
+            )
+        };
+
+        assert_eq!(
+            describe(&plain, 0x1ae0),
+            "Broadcom / LSI MegaRAID 12GSAS/PCIe Secure SAS39xx [PERC H755 Front]"
+        );
+        assert_eq!(describe(&oem, 0x1ae0), "Dell PERC H755 Front");
+
+        // Subsystems without an OEM name keep the generic names
+        assert!(describe(&oem, 0x1ae1).starts_with("Broadcom / LSI MegaRAID"));
+        assert!(plain.oem_name(VendorId::new(0x1000), DeviceId::new(0x10e2), SubvendorId::new(0x1028), SubdeviceId::new(0x1ae0)).is_none());
+    }
+
+    #[test]
+    fn test_devices_rebranded_by() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Broadcom / LSI" {
+                    0x0001 "Ethernet" {
+                        0x103c 0x0002 "Ethernet 1Gb",
+                    },
+                    0x10e2 "MegaRAID" {
+                        0x1028 0x1ae0 "PERC H755 Front",
+                        0x1028 0x1ae1 "PERC H755 Adapter",
+                        0x103c 0x0001 "Smart Array",
+                    },
+                },
+            }
+            classes {}
+        };
+
+        let dell = db.devices_rebranded_by(SubvendorId::new(0x1028));
+        assert_eq!(dell.len(), 1);
+        assert_eq!(dell[0].device.name(), "MegaRAID");
+        assert!(dell[0].subsystem_names().eq(["PERC H755 Front", "PERC H755 Adapter"]));
+
+        let hp = db.devices_rebranded_by(SubvendorId::new(0x103c));
+        let names: Vec<&str> = hp.iter().map(|rebrand| rebrand.device.name()).collect();
+        assert_eq!(names, ["Ethernet", "MegaRAID"]);
+        assert!(db.devices_rebranded_by(SubvendorId::new(0x8086)).is_empty());
+    }
+}
@@ -0,0 +1,335 @@
+//! Runtime name overrides on top of a static database.
+//!
+//! Pre-release silicon often has IDs that are not in pci.ids yet, and some
+//! upstream names are wrong or unhelpful for a given product. An
+//! [`OverlayDatabase`] keeps a small set of vendor, device, and subsystem
+//! names that can be changed at runtime and are checked before the static
+//! database on every lookup.
+
+use crate::database::PciDatabase;
+use crate::types::*;
+use crate::writer::PciIdsWriter;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// A static database with runtime-mutable name overrides.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::{OverlayDatabase, PciDatabase, VendorId, DeviceId};
+///
+/// let mut db = OverlayDatabase::new(PciDatabase::get());
+/// db.set_device_name(VendorId::new(0x8086), DeviceId::new(0xfff0), "Next-gen NIC (engineering sample)");
+///
+/// assert_eq!(
+///     db.device_name(VendorId::new(0x8086), DeviceId::new(0xfff0)),
+///     "Next-gen NIC (engineering sample)"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct OverlayDatabase<'db> {
+    base: &'db PciDatabase,
+    vendors: BTreeMap<VendorId, String>,
+    devices: BTreeMap<(VendorId, DeviceId), String>,
+    subsystems: BTreeMap<(VendorId, DeviceId, SubvendorId, SubdeviceId), String>,
+}
+
+impl<'db> OverlayDatabase<'db> {
+    /// Create an overlay with no overrides on top of the given database.
+    pub fn new(base: &'db PciDatabase) -> Self {
+        Self {
+            base,
+            vendors: BTreeMap::new(),
+            devices: BTreeMap::new(),
+            subsystems: BTreeMap::new(),
+        }
+    }
+
+    /// Get the database underneath the overrides.
+    pub fn base(&self) -> &'db PciDatabase {
+        self.base
+    }
+
+    /// Get the number of overrides.
+    pub fn override_count(&self) -> usize {
+        self.vendors.len() + self.devices.len() + self.subsystems.len()
+    }
+
+    /// Add or replace the name of a vendor.
+    pub fn set_vendor_name(&mut self, vendor_id: VendorId, name: &str) {
+        self.vendors.insert(vendor_id, name.to_string());
+    }
+
+    /// Add or replace the name of a device.
+    pub fn set_device_name(&mut self, vendor_id: VendorId, device_id: DeviceId, name: &str) {
+        self.devices.insert((vendor_id, device_id), name.to_string());
+    }
+
+    /// Add or replace the name of a subsystem.
+    pub fn set_subsystem_name(
+        &mut self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+        name: &str,
+    ) {
+        self.subsystems
+            .insert((vendor_id, device_id, subvendor_id, subdevice_id), name.to_string());
+    }
+
+    /// Remove a vendor name override, returning whether one existed.
+    pub fn remove_vendor_name(&mut self, vendor_id: VendorId) -> bool {
+        self.vendors.remove(&vendor_id).is_some()
+    }
+
+    /// Remove a device name override, returning whether one existed.
+    pub fn remove_device_name(&mut self, vendor_id: VendorId, device_id: DeviceId) -> bool {
+        self.devices.remove(&(vendor_id, device_id)).is_some()
+    }
+
+    /// Remove a subsystem name override, returning whether one existed.
+    pub fn remove_subsystem_name(
+        &mut self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+    ) -> bool {
+        self.subsystems
+            .remove(&(vendor_id, device_id, subvendor_id, subdevice_id))
+            .is_some()
+    }
+
+    /// Remove all overrides.
+    pub fn clear(&mut self) {
+        self.vendors.clear();
+        self.devices.clear();
+        self.subsystems.clear();
+    }
+
+    /// Find the name of a vendor, checking overrides first.
+    pub fn find_vendor_name(&self, vendor_id: VendorId) -> Option<&str> {
+        match self.vendors.get(&vendor_id) {
+            Some(name) => Some(name),
+            None => self.base.find_vendor(vendor_id).map(|vendor| vendor.name()),
+        }
+    }
+
+    /// Find the name of a device, checking overrides first.
+    pub fn find_device_name(&self, vendor_id: VendorId, device_id: DeviceId) -> Option<&str> {
+        match self.devices.get(&(vendor_id, device_id)) {
+            Some(name) => Some(name),
+            None => self.base.find_device(vendor_id, device_id).map(|device| device.name()),
+        }
+    }
+
+    /// Find the name of a subsystem, checking overrides first.
+    pub fn find_subsystem_name(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+    ) -> Option<&str> {
+        match self.subsystems.get(&(vendor_id, device_id, subvendor_id, subdevice_id)) {
+            Some(name) => Some(name),
+            None => self
+                .base
+                .find_subsystem(vendor_id, device_id, subvendor_id, subdevice_id)
+                .map(|subsystem| subsystem.name()),
+        }
+    }
+
+    /// Get a human-readable name for a vendor.
+    ///
+    /// Returns "Unknown Vendor (XXXX)" if the vendor is neither overridden nor in the database.
+    pub fn vendor_name(&self, vendor_id: VendorId) -> String {
+        match self.vendors.get(&vendor_id) {
+            Some(name) => name.clone(),
+            None => self.base.vendor_name(vendor_id),
+        }
+    }
+
+    /// Get a human-readable name for a device.
+    ///
+    /// Returns "Unknown Device (XXXX)" if the device is neither overridden nor in the database.
+    pub fn device_name(&self, vendor_id: VendorId, device_id: DeviceId) -> String {
+        match self.devices.get(&(vendor_id, device_id)) {
+            Some(name) => name.clone(),
+            None => self.base.device_name(vendor_id, device_id),
+        }
+    }
+
+    /// Get a human-readable name for a subsystem.
+    ///
+    /// Returns "Unknown Subsystem (XXXX:XXXX)" if the subsystem is neither overridden nor in the database.
+    pub fn subsystem_name(
+        &self,
+        vendor_id: VendorId,
+        device_id: DeviceId,
+        subvendor_id: SubvendorId,
+        subdevice_id: SubdeviceId,
+    ) -> String {
+        match self.subsystems.get(&(vendor_id, device_id, subvendor_id, subdevice_id)) {
+            Some(name) => name.clone(),
+            None => self.base.subsystem_name(vendor_id, device_id, subvendor_id, subdevice_id),
+        }
+    }
+
+    /// Write the database with all overrides applied in pci.ids format.
+    ///
+    /// Overridden names replace the base names, and IDs that only exist as
+    /// overrides are added in ID order. A device or subsystem override under a
+    /// vendor with no name of its own is written under the placeholder from
+    /// [`vendor_name`](Self::vendor_name).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{OverlayDatabase, PciDatabase, VendorId, DeviceId};
+    ///
+    /// let mut db = OverlayDatabase::new(PciDatabase::get());
+    /// db.set_device_name(VendorId::new(0x8086), DeviceId::new(0xfff0), "Next-gen NIC (engineering sample)");
+    ///
+    /// let mut text = String::new();
+    /// db.write_pci_ids(&mut text).unwrap();
+    /// ```
+    pub fn write_pci_ids<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        let mut writer = PciIdsWriter::new(out);
+
+        let vendor_ids: BTreeSet<VendorId> = self
+            .base
+            .vendors()
+            .iter()
+            .map(|vendor| vendor.id())
+            .chain(self.vendors.keys().copied())
+            .chain(self.devices.keys().map(|&(vendor_id, _)| vendor_id))
+            .chain(self.subsystems.keys().map(|&(vendor_id, ..)| vendor_id))
+            .collect();
+
+        for vendor_id in vendor_ids {
+            writer.vendor(vendor_id, &self.vendor_name(vendor_id))?;
+
+            let base_devices = self.base.find_vendor(vendor_id).map_or(&[][..], |vendor| vendor.devices());
+            let device_keys = (vendor_id, DeviceId::new(0))..=(vendor_id, DeviceId::new(u16::MAX));
+            let subsystem_keys = (vendor_id, DeviceId::new(0), SubvendorId::new(0), SubdeviceId::new(0))
+                ..=(vendor_id, DeviceId::new(u16::MAX), SubvendorId::new(u16::MAX), SubdeviceId::new(u16::MAX));
+            let device_ids: BTreeSet<DeviceId> = base_devices
+                .iter()
+                .map(|device| device.id())
+                .chain(self.devices.range(device_keys).map(|(&(_, device_id), _)| device_id))
+                .chain(self.subsystems.range(subsystem_keys).map(|(&(_, device_id, ..), _)| device_id))
+                .collect();
+
+            for device_id in device_ids {
+                writer.device(device_id, &self.device_name(vendor_id, device_id))?;
+
+                let base_subsystems = self
+                    .base
+                    .find_device(vendor_id, device_id)
+                    .map_or(&[][..], |device| device.subsystems());
+                let subsystem_keys = (vendor_id, device_id, SubvendorId::new(0), SubdeviceId::new(0))
+                    ..=(vendor_id, device_id, SubvendorId::new(u16::MAX), SubdeviceId::new(u16::MAX));
+                let subsystem_ids: BTreeSet<(SubvendorId, SubdeviceId)> = base_subsystems
+                    .iter()
+                    .map(|subsystem| (subsystem.subvendor_id(), subsystem.subdevice_id()))
+                    .chain(self.subsystems.range(subsystem_keys).map(|(&(_, _, sv, sd), _)| (sv, sd)))
+                    .collect();
+
+                for (subvendor_id, subdevice_id) in subsystem_ids {
+                    let name = self.subsystem_name(vendor_id, device_id, subvendor_id, subdevice_id);
+                    writer.subsystem(subvendor_id, subdevice_id, &name)?;
+                }
+            }
+        }
+
+        writer.classes(self.base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static BASE: PciDatabase = crate::pci_database! {
+        vendors {
+            0x1234 "Example Corp" {
+                0x0001 "Widget" {
+                    0x1234 0x0001 "Retail Widget",
+                },
+            },
+        }
+        classes {}
+    };
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let mut db = OverlayDatabase::new(&BASE);
+        let (v, d) = (VendorId::new(0x1234), DeviceId::new(0x0001));
+        let (sv, sd) = (SubvendorId::new(0x1234), SubdeviceId::new(0x0001));
+
+        assert_eq!(db.vendor_name(v), "Example Corp");
+        assert_eq!(db.subsystem_name(v, d, sv, sd), "Retail Widget");
+
+        db.set_vendor_name(v, "Example");
+        db.set_device_name(v, d, "Widget Mk II");
+        db.set_device_name(v, DeviceId::new(0x00ff), "Unreleased Widget");
+        db.set_subsystem_name(v, d, sv, sd, "Retail Widget Mk II");
+        assert_eq!(db.override_count(), 4);
+
+        assert_eq!(db.vendor_name(v), "Example");
+        assert_eq!(db.device_name(v, d), "Widget Mk II");
+        assert_eq!(db.device_name(v, DeviceId::new(0x00ff)), "Unreleased Widget");
+        assert_eq!(db.subsystem_name(v, d, sv, sd), "Retail Widget Mk II");
+        // The base database is untouched
+        assert_eq!(db.base().device_name(v, d), "Widget");
+
+        // Replacing keeps one override per key
+        db.set_device_name(v, d, "Widget Mk III");
+        assert_eq!(db.device_name(v, d), "Widget Mk III");
+        assert_eq!(db.override_count(), 4);
+    }
+
+    #[test]
+    fn test_removing_overrides() {
+        let mut db = OverlayDatabase::new(&BASE);
+        let v = VendorId::new(0x1234);
+
+        db.set_vendor_name(v, "Example");
+        assert!(db.remove_vendor_name(v));
+        assert!(!db.remove_vendor_name(v));
+        assert_eq!(db.vendor_name(v), "Example Corp");
+
+        db.set_device_name(v, DeviceId::new(0x00ff), "Unreleased Widget");
+        db.clear();
+        assert_eq!(db.override_count(), 0);
+        assert!(db.find_device_name(v, DeviceId::new(0x00ff)).is_none());
+        assert_eq!(db.device_name(v, DeviceId::new(0x00ff)), "Unknown Device (00ff)");
+    }
+
+    #[test]
+    fn test_write_pci_ids() {
+        let mut db = OverlayDatabase::new(&BASE);
+        let v = VendorId::new(0x1234);
+
+        db.set_device_name(v, DeviceId::new(0x0001), "Widget Mk II");
+        db.set_device_name(v, DeviceId::new(0x00ff), "Unreleased Widget");
+        db.set_subsystem_name(v, DeviceId::new(0x00ff), SubvendorId::new(0x1234), SubdeviceId::new(0x0002), "OEM Widget");
+        db.set_vendor_name(VendorId::new(0x0abc), "Internal Lab");
+
+        let mut text = String::new();
+        db.write_pci_ids(&mut text).unwrap();
+        assert_eq!(
+            text,
+            "0abc  Internal Lab\n\
+             1234  Example Corp\n\
+             \t0001  Widget Mk II\n\
+             \t\t1234 0001  Retail Widget\n\
+             \t00ff  Unreleased Widget\n\
+             \t\t1234 0002  OEM Widget\n"
+        );
+    }
+}
@@ -1,92 +1,159 @@
 //! Parser for the PCI IDs database format.
 
-use alloc::{string::String, vec::Vec, string::ToString};
+use alloc::{string::String, vec::Vec};
+use crate::classes::{DeviceClass, ProgInterface, SubClass};
+use crate::codegen::{build_name_index, generate_rust, to_ascii_lossy, GenOptions};
+use crate::database::PciDatabase;
+use crate::devices::{Device, Subsystem};
 use crate::error::{PciError, PciResult};
+use crate::index::NameIndexEntry;
 use crate::types::*;
+use crate::validate::{check_next, ValidationIssue, ValidationReport};
+use crate::vendors::Vendor;
+use core::ops::ControlFlow;
 
-/// Parser state for tracking which section we're currently parsing.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ParsingMode {
-    /// Parsing vendor and device information
-    Vendors,
-    /// Parsing device class information
-    Classes,
+pub use crate::builders::{
+    ClassBuilder, Comments, DeviceBuilder, DuplicatePolicy, EntryKey, ParseWarning, ParsedDatabase, ProgInterfaceBuilder, SubClassBuilder,
+    SubsystemBuilder, VendorBuilder,
+};
+pub use crate::line_parser::{Indentation, ParseEvent, ParserConfig, Separator};
+
+use crate::line_parser::{event_name, split_lines, LineParser, TreeBuilder};
+
+/// The first two bytes of a gzip stream.
+#[cfg(feature = "std")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+impl ParsedDatabase {
+    /// Convert the parsed data into a database for runtime lookups.
+    ///
+    /// The result is an ordinary [`PciDatabase`], so every lookup, query, and
+    /// search method works on it exactly as on the built-in database, including
+    /// the prefix name index and ASCII fallback names.
+    ///
+    /// The database tables have a `'static` lifetime, so their memory is leaked
+    /// and never freed. This is meant for loading a `pci.ids` once at startup;
+    /// do not call it repeatedly in a long-running program.
+    ///
+    /// Every level is sorted by ID first. Duplicate IDs are kept as-is, so
+    /// databases assembled by hand should not contain any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::PciIdsParser;
+    /// use ids_rs::VendorId;
+    ///
+    /// let mut parser = PciIdsParser::new();
+    /// parser.parse("1234  Example Corp\n\t0001  Custom Accelerator\n").unwrap();
+    ///
+    /// let db = parser.into_database();
+    /// assert_eq!(db.vendor_name(VendorId::new(0x1234)), "Example Corp");
+    /// ```
+    pub fn into_database(mut self) -> PciDatabase {
+        self.sort();
+
+        let name_index = build_name_index(&self.vendors).map(|entries| {
+            leak(entries.into_iter().map(|(vendor, device, offset)| NameIndexEntry::new(vendor, device, offset)).collect())
+        });
+        let vendors = leak(self.vendors.into_iter().map(leak_vendor).collect());
+        let classes = leak(self.classes.into_iter().map(leak_class).collect());
+
+        let version = self.version.map_or("", |version| version.leak());
+        let date = self.date.map_or("", |date| date.leak());
+        let database = PciDatabase::new(vendors, classes).with_version(version, date);
+        match name_index {
+            Some(name_index) => database.with_name_index(name_index),
+            None => database,
+        }
+    }
+}
+
+/// Leak a vector as a static slice.
+fn leak<T>(items: Vec<T>) -> &'static [T] {
+    items.leak()
+}
+
+/// Leak a name, along with an ASCII transliteration if it is not plain ASCII.
+fn leak_name(name: String) -> (&'static str, Option<&'static str>) {
+    let ascii_name = (!name.is_ascii()).then(|| to_ascii_lossy(&name).leak() as &'static str);
+    (name.leak(), ascii_name)
 }
 
-/// Internal parser state for vendors and devices.
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct VendorBuilder {
-    /// The vendor ID
-    pub id: VendorId,
-    /// The vendor name
-    pub name: String,
-    /// The devices for this vendor
-    pub devices: Vec<DeviceBuilder>,
+/// Convert a vendor builder and its devices into static entries.
+fn leak_vendor(vendor: VendorBuilder) -> Vendor {
+    let (name, ascii_name) = leak_name(vendor.name);
+    let devices = leak(vendor.devices.into_iter().map(leak_device).collect());
+    let vendor = Vendor::new(vendor.id, name, devices);
+    match ascii_name {
+        Some(ascii_name) => vendor.with_ascii_name(ascii_name),
+        None => vendor,
+    }
 }
 
-/// Internal parser state for devices.
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct DeviceBuilder {
-    /// The device ID
-    pub id: DeviceId,
-    /// The device name
-    pub name: String,
-    /// The subsystems for this device
-    pub subsystems: Vec<SubsystemBuilder>,
+/// Convert a device builder and its subsystems into static entries.
+fn leak_device(device: DeviceBuilder) -> Device {
+    let (name, ascii_name) = leak_name(device.name);
+    let subsystems = leak(device.subsystems.into_iter().map(leak_subsystem).collect());
+    let device = Device::new(device.id, name, subsystems);
+    match ascii_name {
+        Some(ascii_name) => device.with_ascii_name(ascii_name),
+        None => device,
+    }
 }
 
-/// Internal parser state for subsystems.
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct SubsystemBuilder {
-    /// The subvendor ID
-    pub subvendor_id: SubvendorId,
-    /// The subdevice ID
-    pub subdevice_id: SubdeviceId,
-    /// The subsystem name
-    pub name: String,
+/// Convert a subsystem builder into a static entry.
+fn leak_subsystem(subsystem: SubsystemBuilder) -> Subsystem {
+    let (name, ascii_name) = leak_name(subsystem.name);
+    let subsystem = Subsystem::new(subsystem.subvendor_id, subsystem.subdevice_id, name);
+    match ascii_name {
+        Some(ascii_name) => subsystem.with_ascii_name(ascii_name),
+        None => subsystem,
+    }
 }
 
-/// Internal parser state for device classes.
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct ClassBuilder {
-    /// The device class ID
-    pub id: DeviceClassId,
-    /// The device class name
-    pub name: String,
-    /// The subclasses for this device class
-    pub subclasses: Vec<SubClassBuilder>,
+/// Convert a class builder and its subclasses into static entries.
+fn leak_class(class: ClassBuilder) -> DeviceClass {
+    let subclasses = leak(class.subclasses.into_iter().map(leak_subclass).collect());
+    DeviceClass::new(class.id, class.name.leak(), subclasses)
 }
 
-/// Internal parser state for subclasses.
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct SubClassBuilder {
-    /// The subclass ID
-    pub id: SubClassId,
-    /// The subclass name
-    pub name: String,
-    /// The programming interfaces for this subclass
-    pub prog_interfaces: Vec<ProgInterfaceBuilder>,
+/// Convert a subclass builder and its programming interfaces into static entries.
+fn leak_subclass(subclass: SubClassBuilder) -> SubClass {
+    let prog_interfaces = leak(
+        subclass
+            .prog_interfaces
+            .into_iter()
+            .map(|p| ProgInterface::new(p.id, p.name.leak()))
+            .collect(),
+    );
+    SubClass::new(subclass.id, subclass.name.leak(), prog_interfaces)
 }
 
-/// Internal parser state for programming interfaces.
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct ProgInterfaceBuilder {
-    /// The programming interface ID
-    pub id: ProgInterfaceId,
-    /// The programming interface name
-    pub name: String,
+/// How far a parse has got, as reported to a progress hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseProgress {
+    /// Lines processed so far, including comments and empty lines
+    pub lines: usize,
+    /// Bytes of input processed so far
+    pub bytes: usize,
+    /// Vendors, devices, subsystems, classes, subclasses, and programming interfaces read so far
+    pub entries: usize,
 }
 
 /// Parser for the PCI IDs database format.
 pub struct PciIdsParser {
     vendors: Vec<VendorBuilder>,
     classes: Vec<ClassBuilder>,
+    indentation: Indentation,
+    separator: Separator,
+    duplicates: DuplicatePolicy,
+    config: ParserConfig,
+    retain_comments: bool,
+    comments: Comments,
+    version: Option<String>,
+    date: Option<String>,
+    warnings: Vec<ParseWarning>,
 }
 
 impl PciIdsParser {
@@ -95,9 +162,111 @@ impl PciIdsParser {
         Self {
             vendors: Vec::new(),
             classes: Vec::new(),
+            indentation: Indentation::Auto,
+            separator: Separator::TwoSpaces,
+            duplicates: DuplicatePolicy::Error,
+            config: ParserConfig::new(),
+            retain_comments: false,
+            comments: Comments::default(),
+            version: None,
+            date: None,
+            warnings: Vec::new(),
         }
     }
 
+    /// Set how indentation levels are written (default: [`Indentation::Auto`]).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::{Indentation, PciIdsParser};
+    ///
+    /// let mut parser = PciIdsParser::new().indentation(Indentation::Spaces(4));
+    /// parser.parse("1234  Test Vendor\n    5678  Test Device\n").unwrap();
+    /// assert_eq!(parser.vendors()[0].devices.len(), 1);
+    /// ```
+    pub fn indentation(mut self, indentation: Indentation) -> Self {
+        self.indentation = indentation;
+        self
+    }
+
+    /// Set how IDs are separated from names (default: [`Separator::TwoSpaces`]).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::{PciIdsParser, Separator};
+    ///
+    /// let mut parser = PciIdsParser::new().separator(Separator::Whitespace);
+    /// parser.parse("1234 Test Vendor\n\t5678\tTest Device\n").unwrap();
+    /// assert_eq!(parser.vendors()[0].name, "Test Vendor");
+    /// assert_eq!(parser.vendors()[0].devices[0].name, "Test Device");
+    /// ```
+    pub fn separator(mut self, separator: Separator) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Set how duplicate IDs are resolved (default: [`DuplicatePolicy::Error`]).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::{DuplicatePolicy, PciIdsParser};
+    ///
+    /// let content = "1234  Old Name\n\t0001  Device A\n1234  New Name\n\t0002  Device B\n";
+    /// let mut parser = PciIdsParser::new().duplicates(DuplicatePolicy::MergeChildren);
+    /// parser.parse(content).unwrap();
+    /// assert_eq!(parser.vendors().len(), 1);
+    /// assert_eq!(parser.vendors()[0].name, "New Name");
+    /// assert_eq!(parser.vendors()[0].devices.len(), 2);
+    /// ```
+    pub fn duplicates(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicates = policy;
+        self
+    }
+
+    /// Set the limits applied to the input (default: none).
+    ///
+    /// See [`ParserConfig`]; set limits when parsing files from untrusted sources.
+    pub fn config(mut self, config: ParserConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set whether comment and blank lines are kept (default: `false`).
+    ///
+    /// Kept lines are attached to the entry that follows them and stored in
+    /// [`ParsedDatabase::comments`], so that
+    /// [`write_pci_ids`](ParsedDatabase::write_pci_ids) reproduces the original
+    /// file except for intended edits, as needed to contribute changes
+    /// upstream. Line endings are normalized to LF, whitespace around names is
+    /// trimmed as always, and comments before entries dropped by
+    /// [`parse_filtered`](Self::parse_filtered) are dropped with them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::{EntryKey, PciIdsParser};
+    /// use ids_rs::VendorId;
+    ///
+    /// let content = "# Vendors\n\n1234  Old Name\n# Last vendor\nabcd  Other Vendor\n";
+    /// let mut parser = PciIdsParser::new().retain_comments(true);
+    /// parser.parse(content).unwrap();
+    ///
+    /// let mut parsed = parser.into_parsed();
+    /// assert_eq!(parsed.comments.before(EntryKey::Vendor(VendorId::new(0xabcd))), ["# Last vendor"]);
+    /// parsed.vendors[0].name = "New Name".into();
+    ///
+    /// let mut text = String::new();
+    /// parsed.write_pci_ids(&mut text).unwrap();
+    /// assert_eq!(text, content.replace("Old Name", "New Name"));
+    /// ```
+    pub fn retain_comments(mut self, retain: bool) -> Self {
+        self.retain_comments = retain;
+        self
+    }
+
     /// Parse the PCI IDs database content.
     ///
     /// The PCI IDs format is structured as follows:
@@ -109,210 +278,452 @@ impl PciIdsParser {
     /// - Programming interface lines are indented with two tabs, followed by 2 hex digits, two spaces, and interface name
     /// - Comments start with "#" and are ignored
     /// - Empty lines are ignored
+    ///
+    /// Entries are sorted by ID once parsed, since lookups rely on it; each
+    /// list that had to be reordered is reported in [`warnings`](Self::warnings).
+    ///
+    /// Tabs may be replaced by spaces; see [`indentation`](Self::indentation).
+    /// A leading UTF-8 byte order mark is ignored, and lines may end with LF,
+    /// CRLF, or a bare CR.
     pub fn parse(&mut self, content: &str) -> PciResult<()> {
-        self.vendors.clear();
-        self.classes.clear();
+        self.clear();
 
-        let mut current_vendor: Option<VendorBuilder> = None;
-        let mut current_device: Option<DeviceBuilder> = None;
-        let mut current_class: Option<ClassBuilder> = None;
-        let mut current_subclass: Option<SubClassBuilder> = None;
-        let mut parsing_mode = ParsingMode::Vendors;
+        let parsed = self.parse_content(content, |_| true, |_| ControlFlow::Continue(()))?;
+        self.store(parsed);
 
-        for (_line_num, line) in content.lines().enumerate() {
-            // Skip empty lines and comments
-            if line.trim().is_empty() || line.trim().starts_with('#') {
-                continue;
-            }
+        Ok(())
+    }
 
-            // Check for section transitions
-            if line.trim().starts_with("C ") && count_leading_tabs(line) == 0 {
-                // Switch to classes mode
-                parsing_mode = ParsingMode::Classes;
-
-                // Finalize any remaining vendor/device
-                self.finalize_vendor_device(&mut current_vendor, &mut current_device)?;
-            } else if count_leading_tabs(line) == 0 && !line.trim().starts_with("C ") && parsing_mode == ParsingMode::Classes {
-                // Check if this looks like a vendor line (4 hex digits followed by two spaces)
-                if line.trim().len() >= 6 && line.trim().chars().nth(4) == Some(' ') && line.trim().chars().nth(5) == Some(' ') {
-                    let hex_part = &line.trim()[..4];
-                    if hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
-                        // Switch back to vendors mode
-                        parsing_mode = ParsingMode::Vendors;
-
-                        // Finalize any remaining class/subclass
-                        self.finalize_class_subclass(&mut current_class, &mut current_subclass)?;
-                    }
-                }
-            }
+    /// Parse the PCI IDs database content, reporting progress after each line.
+    ///
+    /// Works like [`parse`](Self::parse). Returning [`ControlFlow::Break`] from
+    /// `progress` stops parsing with [`PciError::Cancelled`], leaving the parser
+    /// empty; `bytes` against the content length gives a progress fraction.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use core::ops::ControlFlow;
+    /// use ids_rs::parser::PciIdsParser;
+    /// use ids_rs::PciError;
+    ///
+    /// let content = "8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n";
+    /// let mut parser = PciIdsParser::new();
+    ///
+    /// // Stop after the first entry
+    /// let result = parser.parse_with_progress(content, |progress| {
+    ///     if progress.entries < 1 {
+    ///         ControlFlow::Continue(())
+    ///     } else {
+    ///         ControlFlow::Break(())
+    ///     }
+    /// });
+    /// assert_eq!(result, Err(PciError::Cancelled));
+    /// ```
+    pub fn parse_with_progress<P>(&mut self, content: &str, progress: P) -> PciResult<()>
+    where
+        P: FnMut(ParseProgress) -> ControlFlow<()>,
+    {
+        self.clear();
+
+        let parsed = self.parse_content(content, |_| true, progress)?;
+        self.store(parsed);
 
-            let indentation = count_leading_tabs(line);
-            let trimmed = line.trim();
-
-            let result = match parsing_mode {
-                ParsingMode::Vendors => self.parse_vendor_section(
-                    trimmed,
-                    indentation,
-                    &mut current_vendor,
-                    &mut current_device,
-                ),
-                ParsingMode::Classes => self.parse_class_section(
-                    trimmed,
-                    indentation,
-                    &mut current_class,
-                    &mut current_subclass,
-                ),
-            };
+        Ok(())
+    }
 
-            if let Err(e) = result {
-                // Add line number context to error (note: no_std doesn't have eprintln!)
-                return Err(e);
-            }
+    /// Parse several PCI IDs documents into one database.
+    ///
+    /// Each document is parsed like [`parse`](Self::parse), with duplicates
+    /// inside it resolved by the [`duplicates`](Self::duplicates) policy. The
+    /// documents are then merged in order, so later ones take precedence: an
+    /// entry defined again replaces the earlier name and adds its children to
+    /// the earlier ones, recursively. This lets a local override file rename
+    /// or add entries without repeating the rest of the database.
+    ///
+    /// Warnings from every document are kept. With
+    /// [`retain_comments`](Self::retain_comments), comments before an entry
+    /// defined again replace the earlier ones, and the trailing lines of all
+    /// documents are kept in order. The header version and date are taken from
+    /// the first document that has them. If any document fails to parse, the
+    /// error is returned and the parser is left empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::PciIdsParser;
+    ///
+    /// let pci_ids = "8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n";
+    /// let local = "8086  Intel Corporation\n\t1533  Onboard NIC\n\tfffe  Lab Prototype\n";
+    ///
+    /// let mut parser = PciIdsParser::new();
+    /// parser.parse_many(&[pci_ids, local]).unwrap();
+    /// let devices = &parser.vendors()[0].devices;
+    /// assert_eq!(devices[0].name, "Onboard NIC");
+    /// assert_eq!(devices[1].name, "Lab Prototype");
+    /// ```
+    pub fn parse_many(&mut self, documents: &[&str]) -> PciResult<()> {
+        self.clear();
+
+        let mut merged = ParsedDatabase::default();
+        let mut warnings = Vec::new();
+        for content in documents {
+            let parsed = self.parse_content(content, |_| true, |_| ControlFlow::Continue(()))?;
+            warnings.append(&mut self.warnings);
+            merged.merge(parsed);
         }
+        self.store(merged);
+        self.warnings = warnings;
 
-        // Finalize any remaining items
-        self.finalize_vendor_device(&mut current_vendor, &mut current_device)?;
-        self.finalize_class_subclass(&mut current_class, &mut current_subclass)?;
+        Ok(())
+    }
+
+    /// Parse PCI IDs content, keeping only some vendors and classes.
+    ///
+    /// Works like [`parse`](Self::parse), but `keep_vendor` and `keep_class` are
+    /// asked once per vendor and class, and entries they reject are dropped
+    /// together with their devices, subsystems, subclasses, and programming
+    /// interfaces. Dropped entries are never stored, so trimmed databases parse
+    /// faster and with less memory than parsing everything and discarding.
+    /// Every line is still checked, so errors in dropped entries are reported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::PciIdsParser;
+    /// use ids_rs::{DeviceClassId, VendorId};
+    ///
+    /// let content = "\
+    /// 1002  Advanced Micro Devices, Inc. [AMD/ATI]
+    /// 8086  Intel Corporation
+    /// \t1533  I210 Gigabit Network Connection
+    /// C 02  Network controller
+    /// C 03  Display controller
+    /// ";
+    ///
+    /// let mut parser = PciIdsParser::new();
+    /// parser
+    ///     .parse_filtered(content, |vendor| vendor == VendorId::new(0x8086), |class| class == DeviceClassId::new(0x02))
+    ///     .unwrap();
+    /// assert_eq!(parser.vendors().len(), 1);
+    /// assert_eq!(parser.vendors()[0].devices.len(), 1);
+    /// assert_eq!(parser.classes().len(), 1);
+    /// ```
+    pub fn parse_filtered<V, C>(&mut self, content: &str, mut keep_vendor: V, mut keep_class: C) -> PciResult<()>
+    where
+        V: FnMut(VendorId) -> bool,
+        C: FnMut(DeviceClassId) -> bool,
+    {
+        self.clear();
+
+        // Children follow the decision made for their vendor or class
+        let (mut vendor_kept, mut class_kept) = (false, false);
+        let keep = |event: &ParseEvent<'_>| match *event {
+            ParseEvent::Vendor { id, .. } => {
+                vendor_kept = keep_vendor(id);
+                vendor_kept
+            }
+            ParseEvent::Device { .. } | ParseEvent::Subsystem { .. } => vendor_kept,
+            ParseEvent::Class { id, .. } => {
+                class_kept = keep_class(id);
+                class_kept
+            }
+            ParseEvent::SubClass { .. } | ParseEvent::ProgInterface { .. } => class_kept,
+        };
+
+        let parsed = self.parse_content(content, keep, |_| ControlFlow::Continue(()))?;
+        self.store(parsed);
 
         Ok(())
     }
 
-    fn parse_vendor_section(
-        &mut self,
-        trimmed: &str,
-        indentation: usize,
-        current_vendor: &mut Option<VendorBuilder>,
-        current_device: &mut Option<DeviceBuilder>,
-    ) -> PciResult<()> {
-        match indentation {
-            0 => {
-                // Vendor definition (XXXX  Name)
-                self.finalize_vendor_device(current_vendor, current_device)?;
-
-                let (id, name) = parse_vendor_line(trimmed)?;
-                *current_vendor = Some(VendorBuilder {
-                    id,
-                    name,
-                    devices: Vec::new(),
-                });
+    /// Parse PCI IDs content without copying names.
+    ///
+    /// Works like [`parse`](Self::parse), but the returned builders borrow their
+    /// names from `content` instead of allocating a `String` per entry, and the
+    /// parser's own [`vendors`](Self::vendors) and [`classes`](Self::classes) are
+    /// left empty. Warnings are still recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::PciIdsParser;
+    ///
+    /// let content = "8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n";
+    /// let parsed = PciIdsParser::new().parse_borrowed(content).unwrap();
+    /// assert_eq!(parsed.vendors[0].name, "Intel Corporation");
+    /// assert_eq!(parsed.vendors[0].devices[0].name, "I210 Gigabit Network Connection");
+    /// ```
+    pub fn parse_borrowed<'a>(&mut self, content: &'a str) -> PciResult<ParsedDatabase<&'a str>> {
+        self.clear();
+
+        self.parse_content(content, |_| true, |_| ControlFlow::Continue(()))
+    }
+
+    /// Parse raw PCI IDs bytes, replacing invalid UTF-8 sequences.
+    ///
+    /// Files that went through a broken proxy or a legacy code page may not be
+    /// valid UTF-8. Invalid sequences in names become U+FFFD instead of failing
+    /// the whole parse; IDs are ASCII and unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::PciIdsParser;
+    ///
+    /// let mut parser = PciIdsParser::new();
+    /// parser.parse_lossy(b"1234  Caf\xe9 Corp\r\n").unwrap();
+    /// assert_eq!(parser.vendors()[0].name, "Caf\u{fffd} Corp");
+    /// ```
+    pub fn parse_lossy(&mut self, content: &[u8]) -> PciResult<()> {
+        self.parse(&String::from_utf8_lossy(content))
+    }
+
+    /// Parse PCI IDs content from a reader, one line at a time.
+    ///
+    /// The input is never held in memory as a whole, so tools can parse
+    /// `/usr/share/hwdata/pci.ids` without reading it into a `String` first.
+    /// Like [`parse_lossy`](Self::parse_lossy), invalid UTF-8 is replaced
+    /// rather than rejected.
+    ///
+    /// Gzip-compressed input such as `pci.ids.gz` is recognized by its magic
+    /// bytes and decompressed on the fly with the `flate2` feature; without
+    /// it, such input is rejected.
+    ///
+    /// Parse errors are returned as [`std::io::ErrorKind::InvalidData`] errors
+    /// wrapping the [`PciError`].
+    ///
+    /// This method requires the `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use std::{fs::File, io::BufReader};
+    /// use ids_rs::parser::PciIdsParser;
+    ///
+    /// let mut parser = PciIdsParser::new();
+    /// parser.parse_reader(BufReader::new(File::open("/usr/share/hwdata/pci.ids")?))?;
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn parse_reader<R: std::io::BufRead>(&mut self, mut reader: R) -> std::io::Result<()> {
+        if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            #[cfg(feature = "flate2")]
+            return self.read_lines(std::io::BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)));
+
+            #[cfg(not(feature = "flate2"))]
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "gzip-compressed input requires the `flate2` feature",
+            ));
+        }
+
+        self.read_lines(reader)
+    }
+
+    /// Parse uncompressed content from a reader.
+    #[cfg(feature = "std")]
+    fn read_lines<R: std::io::BufRead>(&mut self, mut reader: R) -> std::io::Result<()> {
+        use std::io::{BufRead, Read};
+
+        self.clear();
+        self.warnings.clear();
+
+        let invalid_data = |error: PciError| std::io::Error::new(std::io::ErrorKind::InvalidData, error);
+        let mut lines = LineParser::new(self.indentation, self.separator, self.config);
+        let mut tree = TreeBuilder::new(self.retain_comments);
+        let mut buffer = Vec::new();
+        // Never buffer more than one line of the maximum length and its CRLF
+        let max_chunk = u64::try_from(self.config.max_line_length.saturating_add(2)).unwrap_or(u64::MAX);
+
+        loop {
+            let budget = max_chunk.saturating_sub(buffer.len() as u64);
+            let read = (&mut reader).take(budget).read_until(b'\n', &mut buffer)?;
+            let at_end = read == 0 && budget > 0;
+            if buffer.is_empty() && at_end {
+                break;
             }
-            1 => {
-                // Device definition (\tXXXX  Name)
-                if let Some(device) = current_device.take() {
-                    if let Some(ref mut vendor) = current_vendor {
-                        vendor.devices.push(device);
-                    }
+
+            // A read cut short by the budget ends mid-line; keep the partial line
+            // after the last bare CR, or fail if the line itself is too long
+            let end = if at_end || buffer.ends_with(b"\n") {
+                buffer.len()
+            } else if (buffer.len() as u64) < max_chunk {
+                continue;
+            } else {
+                match buffer.iter().rposition(|&b| b == b'\r') {
+                    Some(cr) => cr + 1,
+                    None => return Err(invalid_data(PciError::LimitExceeded)),
                 }
+            };
 
-                let (id, name) = parse_device_line(trimmed)?;
-                *current_device = Some(DeviceBuilder {
-                    id,
-                    name,
-                    subsystems: Vec::new(),
-                });
-            }
-            2 => {
-                // Subsystem definition (\t\tXXXX XXXX  Name)
-                if let Some(ref mut device) = current_device {
-                    let (subvendor_id, subdevice_id, name) = parse_subsystem_line(trimmed)?;
-                    device.subsystems.push(SubsystemBuilder {
-                        subvendor_id,
-                        subdevice_id,
-                        name,
-                    });
+            {
+                // The chunk holds at most one LF, at its end; bare CRs do not end a
+                // read, so split it further after removing the final terminator
+                let chunk = &buffer[..end];
+                let chunk = match chunk.strip_suffix(b"\n") {
+                    Some(chunk) => chunk.strip_suffix(b"\r").unwrap_or(chunk),
+                    None => chunk.strip_suffix(b"\r").unwrap_or(chunk),
+                };
+                let text = String::from_utf8_lossy(chunk);
+                for line in text.split('\r') {
+                    match lines.parse_line(line).map_err(invalid_data)? {
+                        Some(event) => tree.push(event),
+                        None => tree.comment(line),
+                    }
                 }
             }
-            _ => {
-                return Err(PciError::InvalidIndentation);
+            buffer.drain(..end);
+
+            if at_end {
+                break;
             }
         }
+
+        let parsed = self.finish(tree).map_err(invalid_data)?;
+        self.store(parsed);
+
         Ok(())
     }
 
-    fn parse_class_section(
-        &mut self,
-        trimmed: &str,
-        indentation: usize,
-        current_class: &mut Option<ClassBuilder>,
-        current_subclass: &mut Option<SubClassBuilder>,
-    ) -> PciResult<()> {
-        match indentation {
-            0 => {
-                // Class definition (C XX  Name)
-                self.finalize_class_subclass(current_class, current_subclass)?;
-
-                if trimmed.starts_with("C ") {
-                    let (id, name) = parse_class_line(trimmed)?;
-                    *current_class = Some(ClassBuilder {
-                        id,
-                        name,
-                        subclasses: Vec::new(),
-                    });
-                }
-            }
-            1 => {
-                // Subclass definition (\tXX  Name)
-                if let Some(subclass) = current_subclass.take() {
-                    if let Some(ref mut class) = current_class {
-                        class.subclasses.push(subclass);
+    /// Parse content into builders whose names are converted from slices of it.
+    ///
+    /// Only entries for which `keep` returns `true` are stored.
+    fn parse_content<'a, S, K, P>(&mut self, content: &'a str, mut keep: K, mut progress: P) -> PciResult<ParsedDatabase<S>>
+    where
+        S: From<&'a str>,
+        K: FnMut(&ParseEvent<'a>) -> bool,
+        P: FnMut(ParseProgress) -> ControlFlow<()>,
+    {
+        self.warnings.clear();
+
+        let mut lines = LineParser::new(self.indentation, self.separator, self.config);
+        let mut tree = TreeBuilder::new(self.retain_comments);
+        let mut status = ParseProgress::default();
+
+        // Splitting on both CR and LF turns CRLF into an extra empty piece
+        for line in content.split(['\n', '\r']) {
+            // Neither the gap inside a CRLF nor the end of the input is a line
+            let start = status.bytes;
+            let bytes = content.as_bytes();
+            let crlf = start > 0 && bytes[start - 1] == b'\r' && bytes.get(start) == Some(&b'\n');
+            let is_line = !(line.is_empty() && (start == content.len() || crlf));
+
+            match lines.parse_line(line)? {
+                Some(event) => {
+                    if keep(&event) {
+                        tree.push(event);
+                    } else {
+                        tree.drop_comments();
                     }
+                    status.entries += 1;
                 }
-
-                let (id, name) = parse_subclass_line(trimmed)?;
-                *current_subclass = Some(SubClassBuilder {
-                    id,
-                    name,
-                    prog_interfaces: Vec::new(),
-                });
+                None if is_line => tree.comment(line),
+                None => {}
             }
-            2 => {
-                // Programming interface definition (\t\tXX  Name)
-                if let Some(ref mut subclass) = current_subclass {
-                    let (id, name) = parse_prog_interface_line(trimmed)?;
-                    subclass.prog_interfaces.push(ProgInterfaceBuilder { id, name });
-                }
+
+            if is_line {
+                status.lines += 1;
             }
-            _ => {
-                return Err(PciError::InvalidIndentation);
+            status.bytes = (start + line.len() + 1).min(content.len());
+            if progress(status).is_break() {
+                return Err(PciError::Cancelled);
             }
         }
-        Ok(())
+
+        self.finish(tree)
     }
 
-    fn finalize_vendor_device(
-        &mut self,
-        current_vendor: &mut Option<VendorBuilder>,
-        current_device: &mut Option<DeviceBuilder>,
-    ) -> PciResult<()> {
-        if let Some(device) = current_device.take() {
-            if let Some(ref mut vendor) = current_vendor {
-                vendor.devices.push(device);
-            }
-        }
+    /// Resolve duplicates and sort the assembled builders, recording warnings.
+    fn finish<S>(&mut self, tree: TreeBuilder<S>) -> PciResult<ParsedDatabase<S>> {
+        let mut parsed = tree.finish();
+        parsed.resolve_duplicates(self.duplicates)?;
+        self.warnings = parsed.sort();
 
-        if let Some(vendor) = current_vendor.take() {
-            self.vendors.push(vendor);
-        }
-
-        Ok(())
+        Ok(parsed)
     }
 
-    fn finalize_class_subclass(
-        &mut self,
-        current_class: &mut Option<ClassBuilder>,
-        current_subclass: &mut Option<SubClassBuilder>,
-    ) -> PciResult<()> {
-        if let Some(subclass) = current_subclass.take() {
-            if let Some(ref mut class) = current_class {
-                class.subclasses.push(subclass);
+    /// Check PCI IDs content without building a database.
+    ///
+    /// Reports every line that fails to parse (including entries with empty
+    /// names), names containing control characters, and vendors, devices, and
+    /// classes out of ID order or duplicated, using the same issues as
+    /// [`PciDatabase::validate`](crate::PciDatabase::validate). Only the IDs of
+    /// the previous entries are kept, so memory use does not grow with the
+    /// input; as with that check, a duplicate is reported as such when it
+    /// directly follows the same ID and as out of order otherwise.
+    ///
+    /// The parser's indentation setting applies; its parsed data and warnings
+    /// are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::PciIdsParser;
+    /// use ids_rs::{PciError, ValidationIssue};
+    ///
+    /// let report = PciIdsParser::new().validate("1234  Vendor\n\tzzzz  Device\n");
+    /// assert_eq!(report.issues, [ValidationIssue::InvalidLine { line: 2, error: PciError::InvalidHexValue }]);
+    /// ```
+    pub fn validate(&self, content: &str) -> ValidationReport {
+        let mut issues = Vec::new();
+        let mut lines = LineParser::new(self.indentation, self.separator, self.config);
+        let mut previous_vendor = None;
+        let mut previous_device = None;
+        let mut previous_class = None;
+
+        for (index, line) in split_lines(content).enumerate() {
+            let event = match lines.parse_line(line) {
+                Ok(Some(event)) => event,
+                Ok(None) => continue,
+                Err(error) => {
+                    issues.push(ValidationIssue::InvalidLine { line: index + 1, error });
+                    continue;
+                }
+            };
+
+            if event_name(&event).chars().any(char::is_control) {
+                issues.push(ValidationIssue::InvalidName { line: index + 1 });
             }
-        }
 
-        if let Some(class) = current_class.take() {
-            self.classes.push(class);
+            match event {
+                ParseEvent::Vendor { id, .. } => {
+                    check_next(
+                        &mut previous_vendor,
+                        id,
+                        &mut issues,
+                        ValidationIssue::VendorOutOfOrder,
+                        ValidationIssue::DuplicateVendor,
+                    );
+                    previous_device = None;
+                }
+                ParseEvent::Device { vendor, id, .. } => {
+                    check_next(
+                        &mut previous_device,
+                        id,
+                        &mut issues,
+                        |device| ValidationIssue::DeviceOutOfOrder(vendor, device),
+                        |device| ValidationIssue::DuplicateDevice(vendor, device),
+                    );
+                }
+                ParseEvent::Class { id, .. } => {
+                    check_next(
+                        &mut previous_class,
+                        id,
+                        &mut issues,
+                        ValidationIssue::ClassOutOfOrder,
+                        ValidationIssue::DuplicateClass,
+                    );
+                }
+                ParseEvent::Subsystem { .. } | ParseEvent::SubClass { .. } | ParseEvent::ProgInterface { .. } => {}
+            }
         }
 
-        Ok(())
+        ValidationReport { issues }
+    }
+
+    /// Get the warnings from the last parse.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
     }
 
     /// Get the parsed vendors (for use by build scripts and tests).
@@ -327,143 +738,162 @@ impl PciIdsParser {
         &self.classes
     }
 
-    /// Generate Rust code for the parsed database.
-    pub fn generate_code(&self) -> String {
-        let mut code = String::new();
-
-        // Generate vendor data
-        code.push_str("// Generated PCI vendor and device data\n");
-        code.push_str("use crate::vendors::Vendor;\n");
-        code.push_str("use crate::devices::{Device, Subsystem};\n");
-        code.push_str("use crate::classes::{DeviceClass, SubClass, ProgInterface};\n");
-        code.push_str("use crate::types::*;\n\n");
-
-        // Generate static arrays for all data structures
-        // This will be used by the build script to generate the actual database
-
-        code
+    /// Get the comment and blank lines from the last parse, if they were retained.
+    pub fn comments(&self) -> &Comments {
+        &self.comments
     }
-}
 
-impl Default for PciIdsParser {
-    fn default() -> Self {
-        Self::new()
+    /// Get the `Version:` field of the last parsed file header, such as `2025.07.11`.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
     }
-}
 
-/// Count the number of leading tabs in a line.
-fn count_leading_tabs(line: &str) -> usize {
-    line.chars().take_while(|&c| c == '\t').count()
-}
-
-/// Parse a vendor line: "XXXX  Name"
-fn parse_vendor_line(line: &str) -> PciResult<(VendorId, String)> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-
-    if parts.len() != 2 {
-        return Err(PciError::InvalidFormat);
+    /// Get the `Date:` field of the last parsed file header, such as `2025-07-11 03:15:02`.
+    pub fn date(&self) -> Option<&str> {
+        self.date.as_deref()
     }
 
-    let id = parse_hex_u16(parts[0])?;
-    let name = parts[1].trim().to_string();
-
-    Ok((VendorId::new(id), name))
-}
-
-/// Parse a device line: "XXXX  Name"
-fn parse_device_line(line: &str) -> PciResult<(DeviceId, String)> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-
-    if parts.len() != 2 {
-        return Err(PciError::InvalidFormat);
+    /// Consume the parser and return everything it has parsed.
+    pub fn into_parsed(self) -> ParsedDatabase {
+        ParsedDatabase {
+            vendors: self.vendors,
+            classes: self.classes,
+            comments: self.comments,
+            version: self.version,
+            date: self.date,
+        }
     }
 
-    let id = parse_hex_u16(parts[0])?;
-    let name = parts[1].trim().to_string();
-
-    Ok((DeviceId::new(id), name))
-}
-
-/// Parse a subsystem line: "XXXX XXXX  Name"
-fn parse_subsystem_line(line: &str) -> PciResult<(SubvendorId, SubdeviceId, String)> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-
-    if parts.len() != 2 {
-        return Err(PciError::InvalidFormat);
+    /// Keep a parse result as the parser's data.
+    fn store(&mut self, parsed: ParsedDatabase) {
+        self.vendors = parsed.vendors;
+        self.classes = parsed.classes;
+        self.comments = parsed.comments;
+        self.version = parsed.version;
+        self.date = parsed.date;
     }
 
-    let ids: Vec<&str> = parts[0].split_whitespace().collect();
-    if ids.len() != 2 {
-        return Err(PciError::InvalidFormat);
+    /// Drop the data of the previous parse.
+    fn clear(&mut self) {
+        self.vendors.clear();
+        self.classes.clear();
+        self.comments = Comments::default();
+        self.version = None;
+        self.date = None;
     }
 
-    let subvendor_id = parse_hex_u16(ids[0])?;
-    let subdevice_id = parse_hex_u16(ids[1])?;
-    let name = parts[1].trim().to_string();
-
-    Ok((SubvendorId::new(subvendor_id), SubdeviceId::new(subdevice_id), name))
-}
-
-/// Parse a class line: "C XX  Name"
-fn parse_class_line(line: &str) -> PciResult<(DeviceClassId, String)> {
-    if !line.starts_with("C ") {
-        return Err(PciError::InvalidFormat);
+    /// Consume the parser and convert everything it has parsed into a runtime database.
+    ///
+    /// See [`ParsedDatabase::into_database`]; the memory is leaked, so load once.
+    pub fn into_database(self) -> PciDatabase {
+        self.into_parsed().into_database()
     }
 
-    let rest = &line[2..]; // Skip "C "
-    let parts: Vec<&str> = rest.splitn(2, "  ").collect();
-
-    if parts.len() != 2 {
-        return Err(PciError::InvalidFormat);
+    /// Generate Rust code for the parsed database.
+    ///
+    /// This is [`generate_rust`] with the default [`GenOptions`]: the same string
+    /// tables, sorted arrays, and name index the build script embeds, referencing
+    /// types through `crate::` and defining a `pub static GLOBAL_DATABASE`. Use
+    /// `generate_rust` directly to change paths or names, e.g. when generating
+    /// from a downstream build script.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::PciIdsParser;
+    ///
+    /// let mut parser = PciIdsParser::new();
+    /// parser.parse("8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n").unwrap();
+    ///
+    /// let code = parser.generate_code();
+    /// assert!(code.contains("static DEVICES_32902: &[crate::devices::Device]"));
+    /// assert!(code.contains("pub static GLOBAL_DATABASE"));
+    /// ```
+    pub fn generate_code(&self) -> String {
+        let database = ParsedDatabase::new(self.vendors.clone(), self.classes.clone());
+        generate_rust(&database, &GenOptions::new())
     }
-
-    let id = parse_hex_u8(parts[0])?;
-    let name = parts[1].trim().to_string();
-
-    Ok((DeviceClassId::new(id), name))
 }
 
-/// Parse a subclass line: "XX  Name"
-fn parse_subclass_line(line: &str) -> PciResult<(SubClassId, String)> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
-
-    if parts.len() != 2 {
-        return Err(PciError::InvalidFormat);
+impl Default for PciIdsParser {
+    fn default() -> Self {
+        Self::new()
     }
-
-    let id = parse_hex_u8(parts[0])?;
-    let name = parts[1].trim().to_string();
-
-    Ok((SubClassId::new(id), name))
 }
 
-/// Parse a programming interface line: "XX  Name"
-fn parse_prog_interface_line(line: &str) -> PciResult<(ProgInterfaceId, String)> {
-    let parts: Vec<&str> = line.splitn(2, "  ").collect();
+/// Push-style parser that reports entries as lines are fed in.
+///
+/// Nothing is stored besides the IDs of the current parents, so a file can be
+/// parsed straight from a paging reader or a network stream. Events arrive in
+/// file order: unlike [`PciIdsParser`], duplicates are not resolved and
+/// unsorted input is not reordered.
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::parser::{ParseEvent, StreamingParser};
+///
+/// let mut devices = 0;
+/// let mut parser = StreamingParser::new(|event| {
+///     if let ParseEvent::Device { .. } = event {
+///         devices += 1;
+///     }
+/// });
+///
+/// for line in ["8086  Intel Corporation", "\t1533  I210 Gigabit Network Connection"] {
+///     parser.feed_line(line).unwrap();
+/// }
+/// assert_eq!(devices, 1);
+/// ```
+pub struct StreamingParser<F> {
+    lines: LineParser,
+    on_event: F,
+}
 
-    if parts.len() != 2 {
-        return Err(PciError::InvalidFormat);
+impl<F: FnMut(ParseEvent<'_>)> StreamingParser<F> {
+    /// Create a parser that passes each entry to `on_event`.
+    pub fn new(on_event: F) -> Self {
+        Self {
+            lines: LineParser::new(Indentation::Auto, Separator::TwoSpaces, ParserConfig::new()),
+            on_event,
+        }
     }
 
-    let id = parse_hex_u8(parts[0])?;
-    let name = parts[1].trim().to_string();
+    /// Set how indentation levels are written (default: [`Indentation::Auto`]).
+    pub fn indentation(mut self, indentation: Indentation) -> Self {
+        self.lines.indentation = indentation;
+        self
+    }
 
-    Ok((ProgInterfaceId::new(id), name))
-}
+    /// Set how IDs are separated from names (default: [`Separator::TwoSpaces`]).
+    pub fn separator(mut self, separator: Separator) -> Self {
+        self.lines.separator = separator;
+        self
+    }
 
-/// Parse a hexadecimal string to u16.
-fn parse_hex_u16(hex_str: &str) -> PciResult<u16> {
-    u16::from_str_radix(hex_str.trim(), 16).map_err(|_| PciError::InvalidHexValue)
-}
+    /// Set the limits applied to the input (default: none).
+    pub fn config(mut self, config: ParserConfig) -> Self {
+        self.lines.config = config;
+        self
+    }
 
-/// Parse a hexadecimal string to u8.
-fn parse_hex_u8(hex_str: &str) -> PciResult<u8> {
-    u8::from_str_radix(hex_str.trim(), 16).map_err(|_| PciError::InvalidHexValue)
+    /// Parse one line, with or without its line terminator.
+    ///
+    /// Empty lines and comments produce no event. A UTF-8 byte order mark is
+    /// ignored on the first line.
+    pub fn feed_line(&mut self, line: &str) -> PciResult<()> {
+        if let Some(event) = self.lines.parse_line(line)? {
+            (self.on_event)(event);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::format;
+    use alloc::string::ToString;
 
     #[test]
     fn test_basic_vendor_parsing() {
@@ -551,4 +981,563 @@ C 02  Network controller
         assert_eq!(parser.vendors.len(), 1);
         assert_eq!(parser.classes.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_space_indentation() {
+        let content = "1234  Test Vendor\n  5678  Test Device\n    abcd 1234  Test Subsystem\nC 02  Network controller\n  00  Ethernet controller\n";
+
+        let mut parser = PciIdsParser::new();
+        parser.parse(content).expect("Failed to parse");
+        assert_eq!(parser.vendors[0].devices[0].subsystems.len(), 1);
+        assert_eq!(parser.classes[0].subclasses.len(), 1);
+
+        let mut parser = PciIdsParser::new().indentation(Indentation::Spaces(2));
+        parser.parse(content).expect("Failed to parse");
+        assert_eq!(parser.vendors[0].devices[0].subsystems.len(), 1);
+
+        let mut parser = PciIdsParser::new().indentation(Indentation::Spaces(4));
+        assert_eq!(parser.parse(content), Err(PciError::InvalidIndentation));
+
+        let mut parser = PciIdsParser::new();
+        let result = parser.parse("1234  Test Vendor\n  5678  Test Device\n   abcd 1234  Odd\n");
+        assert_eq!(result, Err(PciError::InvalidIndentation));
+    }
+
+    #[test]
+    fn test_line_endings_and_bom() {
+        let crlf = "\u{feff}1234  Test Vendor\r\n\t5678  Test Device\r\nC 02  Network controller\r\n\t00  Ethernet controller\r\n";
+        let cr = "1234  Test Vendor\r\t5678  Test Device\rC 02  Network controller\r\t00  Ethernet controller\r";
+
+        for content in [crlf, cr] {
+            let mut parser = PciIdsParser::new();
+            parser.parse(content).expect("Failed to parse");
+            assert_eq!(parser.vendors[0].id.value(), 0x1234);
+            assert_eq!(parser.vendors[0].devices[0].name, "Test Device");
+            assert_eq!(parser.classes[0].subclasses[0].name, "Ethernet controller");
+        }
+    }
+
+    #[test]
+    fn test_parse_lossy() {
+        let mut parser = PciIdsParser::new();
+        parser
+            .parse_lossy(b"\xef\xbb\xbf1234  Test \xff Vendor\n\t5678  Test Device\n")
+            .expect("Failed to parse");
+        assert_eq!(parser.vendors[0].name, "Test \u{fffd} Vendor");
+        assert_eq!(parser.vendors[0].devices.len(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_policy() {
+        let content = r#"
+1234  Old Vendor
+	0001  Old Device
+		abcd 0001  Old Subsystem
+1234  New Vendor
+	0001  New Device
+	0002  Other Device
+C 02  Network controller
+	00  Ethernet controller
+C 02  Network controller
+	80  Network controller
+"#;
+
+        let mut parser = PciIdsParser::new();
+        assert_eq!(parser.parse(content), Err(PciError::DuplicateEntry));
+
+        let mut parser = PciIdsParser::new().duplicates(DuplicatePolicy::KeepFirst);
+        parser.parse(content).expect("Failed to parse");
+        assert_eq!(parser.vendors.len(), 1);
+        assert_eq!(parser.vendors[0].name, "Old Vendor");
+        assert_eq!(parser.vendors[0].devices.len(), 1);
+        assert_eq!(parser.classes[0].subclasses.len(), 1);
+
+        let mut parser = PciIdsParser::new().duplicates(DuplicatePolicy::KeepLast);
+        parser.parse(content).expect("Failed to parse");
+        assert_eq!(parser.vendors[0].name, "New Vendor");
+        assert_eq!(parser.vendors[0].devices.len(), 2);
+        assert!(parser.vendors[0].devices[0].subsystems.is_empty());
+        assert_eq!(parser.classes[0].subclasses[0].id.value(), 0x80);
+
+        let mut parser = PciIdsParser::new().duplicates(DuplicatePolicy::MergeChildren);
+        parser.parse(content).expect("Failed to parse");
+        let vendor = &parser.vendors[0];
+        assert_eq!(vendor.name, "New Vendor");
+        assert_eq!(vendor.devices.len(), 2);
+        assert_eq!(vendor.devices[0].name, "New Device");
+        assert_eq!(vendor.devices[0].subsystems.len(), 1);
+        assert_eq!(parser.classes.len(), 1);
+        assert_eq!(parser.classes[0].subclasses.len(), 2);
+    }
+
+    #[test]
+    fn test_unsorted_input() {
+        let content = r#"
+abcd  Second Vendor
+1234  First Vendor
+	0002  Second Device
+	0001  First Device
+C 03  Display controller
+C 02  Network controller
+	80  Network controller
+	00  Ethernet controller
+"#;
+
+        let mut parser = PciIdsParser::new();
+        parser.parse(content).expect("Failed to parse");
+        assert_eq!(parser.vendors[0].name, "First Vendor");
+        assert_eq!(parser.vendors[0].devices[0].name, "First Device");
+        assert_eq!(parser.classes[0].id.value(), 0x02);
+        assert_eq!(parser.classes[0].subclasses[0].id.value(), 0x00);
+        assert_eq!(
+            parser.warnings(),
+            [
+                ParseWarning::UnsortedVendors,
+                ParseWarning::UnsortedDevices(VendorId::new(0x1234)),
+                ParseWarning::UnsortedClasses,
+                ParseWarning::UnsortedSubclasses(DeviceClassId::new(0x02)),
+            ]
+        );
+        assert_eq!(
+            parser.warnings()[1].to_string(),
+            "Devices of vendor 1234 are not sorted by ID"
+        );
+
+        parser.parse("1234  First Vendor\nabcd  Second Vendor\n").expect("Failed to parse");
+        assert!(parser.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_parse_borrowed() {
+        let content = "abcd  Second Vendor\n1234  First Vendor\n\t5678  Test Device\n\t\tabcd 1234  Test Subsystem\nC 02  Network controller\n\t00  Ethernet controller\n";
+
+        let mut parser = PciIdsParser::new();
+        let parsed = parser.parse_borrowed(content).expect("Failed to parse");
+        assert!(parser.vendors().is_empty());
+        assert_eq!(parser.warnings(), [ParseWarning::UnsortedVendors]);
+
+        let vendor = &parsed.vendors[0];
+        assert_eq!(vendor.name, "First Vendor");
+        assert_eq!(vendor.devices[0].subsystems[0].name, "Test Subsystem");
+        assert_eq!(parsed.classes[0].subclasses[0].name, "Ethernet controller");
+
+        // Names point into the input rather than into new allocations
+        let input = content.as_bytes().as_ptr_range();
+        assert!(input.contains(&vendor.name.as_ptr()));
+        assert!(input.contains(&vendor.devices[0].name.as_ptr()));
+    }
+
+    #[test]
+    fn test_streaming_parser() {
+        let content = "\u{feff}1234  Test Vendor\r\n\t5678  Test Device\r\n\t\tabcd 0001  Test Subsystem\r\n# comment\r\nC 02  Network controller\r\n\t00  Ethernet controller\r\n\t\t01  Test interface\r\n";
+
+        let mut events = Vec::new();
+        let mut parser = StreamingParser::new(|event: ParseEvent<'_>| events.push(format!("{:?}", event)));
+        for line in content.split_inclusive('\n') {
+            parser.feed_line(line).expect("Failed to parse");
+        }
+        assert_eq!(parser.feed_line("\t\t\tfoo"), Err(PciError::InvalidIndentation));
+
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0], format!("{:?}", ParseEvent::Vendor { id: VendorId::new(0x1234), name: "Test Vendor" }));
+        assert_eq!(
+            events[2],
+            format!(
+                "{:?}",
+                ParseEvent::Subsystem {
+                    vendor: VendorId::new(0x1234),
+                    device: DeviceId::new(0x5678),
+                    subvendor_id: SubvendorId::new(0xabcd),
+                    subdevice_id: SubdeviceId::new(0x0001),
+                    name: "Test Subsystem",
+                }
+            )
+        );
+        assert_eq!(
+            events[5],
+            format!(
+                "{:?}",
+                ParseEvent::ProgInterface {
+                    class: DeviceClassId::new(0x02),
+                    subclass: SubClassId::new(0x00),
+                    id: ProgInterfaceId::new(0x01),
+                    name: "Test interface",
+                }
+            )
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_reader() {
+        let content: &[u8] = b"abcd  Second Vendor\r\n1234  First \xff Vendor\r\t5678  Test Device\n";
+
+        let mut parser = PciIdsParser::new();
+        parser.parse_reader(content).expect("Failed to parse");
+        assert_eq!(parser.vendors.len(), 2);
+        assert_eq!(parser.vendors[0].name, "First \u{fffd} Vendor");
+        assert_eq!(parser.vendors[0].devices[0].name, "Test Device");
+        assert_eq!(parser.warnings(), [ParseWarning::UnsortedVendors]);
+
+        let error = parser.parse_reader(&b"zzzz  Bad Vendor\n"[..]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(error.get_ref().unwrap().downcast_ref::<PciError>(), Some(&PciError::InvalidHexValue));
+
+        #[cfg(not(feature = "flate2"))]
+        {
+            let error = parser.parse_reader(&[0x1f, 0x8b, 0x08, 0x00][..]).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_reader_limits() {
+        let config = ParserConfig::new().max_line_length(12);
+
+        // Lines ending in bare CRs are read in chunks without losing any
+        let content: &[u8] = b"1234  Vendor\r\t0001  Dev A\r\t0002  Dev B\r5678  Other\r\n";
+        let mut parser = PciIdsParser::new().config(config);
+        parser.parse_reader(content).unwrap();
+        assert_eq!(parser.vendors().len(), 2);
+        assert_eq!(parser.vendors()[0].devices.len(), 2);
+
+        let error = parser.parse_reader(&b"1234  A Vendor Name\n"[..]).unwrap_err();
+        assert_eq!(error.get_ref().unwrap().downcast_ref::<PciError>(), Some(&PciError::LimitExceeded));
+        let error = parser.parse_reader(&b"1234  Vendor\n\t0001  A Device\r"[..]).unwrap_err();
+        assert_eq!(error.get_ref().unwrap().downcast_ref::<PciError>(), Some(&PciError::LimitExceeded));
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn test_parse_reader_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"1234  Test Vendor\n\t5678  Test Device\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut parser = PciIdsParser::new();
+        parser.parse_reader(compressed.as_slice()).expect("Failed to parse");
+        assert_eq!(parser.vendors[0].devices[0].name, "Test Device");
+    }
+
+    #[test]
+    fn test_parse_with_progress() {
+        let content = "# comment\n1234  Test Vendor\n\t5678  Test Device\n";
+
+        let mut reports = Vec::new();
+        let mut parser = PciIdsParser::new();
+        parser
+            .parse_with_progress(content, |progress| {
+                reports.push(progress);
+                ControlFlow::Continue(())
+            })
+            .expect("Failed to parse");
+        assert_eq!(parser.vendors.len(), 1);
+        assert_eq!(reports[0], ParseProgress { lines: 1, bytes: 10, entries: 0 });
+        assert_eq!(reports.last(), Some(&ParseProgress { lines: 3, bytes: content.len(), entries: 2 }));
+
+        reports.clear();
+        parser
+            .parse_with_progress("1234  Test Vendor\r\n\r\n", |progress| {
+                reports.push(progress);
+                ControlFlow::Continue(())
+            })
+            .expect("Failed to parse");
+        assert_eq!(reports.last(), Some(&ParseProgress { lines: 2, bytes: 21, entries: 1 }));
+
+        let result = parser.parse_with_progress(content, |progress| {
+            if progress.lines < 2 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        assert_eq!(result, Err(PciError::Cancelled));
+        assert!(parser.vendors.is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let content = "\
+1234  Test Vendor\r
+\t0002  Second Device\r
+\t0001  First\tDevice\r
+\t0001  Again\r
+1000  Earlier Vendor\r
+\tzzzz  Bad Device\r
+\t\t\t0000  Too Deep\r
+C 02  Network controller\r
+";
+
+        let report = PciIdsParser::new().validate(content);
+        let (v, d) = (VendorId::new, DeviceId::new);
+        assert_eq!(
+            report.issues,
+            [
+                ValidationIssue::InvalidName { line: 3 },
+                ValidationIssue::DeviceOutOfOrder(v(0x1234), d(0x0001)),
+                ValidationIssue::DuplicateDevice(v(0x1234), d(0x0001)),
+                ValidationIssue::VendorOutOfOrder(v(0x1000)),
+                ValidationIssue::InvalidLine { line: 6, error: PciError::InvalidHexValue },
+                ValidationIssue::InvalidLine { line: 7, error: PciError::InvalidIndentation },
+            ]
+        );
+        assert_eq!(report.issues[4].to_string(), "line 6: Invalid hexadecimal value");
+        assert!(PciIdsParser::new().validate("1234  Test Vendor\n\t0001  Device\n").is_valid());
+        assert_eq!(
+            PciIdsParser::new().validate("1234  \n").issues,
+            [ValidationIssue::InvalidLine { line: 1, error: PciError::InvalidFormat }]
+        );
+    }
+
+    #[test]
+    fn test_into_database() {
+        let mut parser = PciIdsParser::new();
+        parser
+            .parse(
+                "\
+8086  Intel Corporation
+\t1533  I210 Gigabit Network Connection
+\t\t8086 0001  Ethernet Server Adapter I210-T1
+1234  Gerät GmbH
+\t0001  Wi-Fi Gerät
+C 0c  Serial bus controller
+\t03  USB controller
+\t\t30  XHCI
+",
+            )
+            .unwrap();
+        let db = parser.into_database();
+
+        assert!(db.validate().is_valid());
+        assert_eq!(db.vendors().len(), 2);
+        assert_eq!(db.vendors()[0].id(), VendorId::new(0x1234));
+        let vendor = db.find_vendor(VendorId::new(0x1234)).unwrap();
+        assert_eq!(vendor.name(), "Gerät GmbH");
+        assert_eq!(vendor.name_ascii(), "Gerat GmbH");
+        assert_eq!(
+            db.find_subsystem(VendorId::new(0x8086), DeviceId::new(0x1533), SubvendorId::new(0x8086), SubdeviceId::new(0x0001))
+                .unwrap()
+                .name(),
+            "Ethernet Server Adapter I210-T1"
+        );
+        assert_eq!(
+            db.find_prog_interface(DeviceClassId::new(0x0c), SubClassId::new(0x03), ProgInterfaceId::new(0x30))
+                .unwrap()
+                .name(),
+            "XHCI"
+        );
+
+        let names: Vec<_> = db.names_with_prefix("i210").iter().map(|m| m.name(&db)).collect();
+        assert_eq!(names, ["I210 Gigabit Network Connection"]);
+        assert_eq!(db.query().device_name_contains("Wi-Fi").execute().len(), 1);
+    }
+
+    #[test]
+    fn test_header_version() {
+        let mut parser = PciIdsParser::new();
+        parser
+            .parse("#\tVersion: 2025.07.11\n#\tDate:    2025-07-11 03:15:02\n\n8086  Intel Corporation\n# Version: 1.0\n")
+            .unwrap();
+        assert_eq!(parser.version(), Some("2025.07.11"));
+        assert_eq!(parser.date(), Some("2025-07-11 03:15:02"));
+
+        let db = parser.into_database();
+        assert_eq!(db.version(), Some("2025.07.11"));
+        assert_eq!(db.date(), Some("2025-07-11 03:15:02"));
+        assert_eq!(PciIdsParser::new().into_database().version(), None);
+    }
+
+    #[test]
+    fn test_generate_code() {
+        let mut parser = PciIdsParser::new();
+        parser.parse("1234  Test Vendor\n\t0001  Test Device\nC 02  Network controller\n").unwrap();
+
+        let code = parser.generate_code();
+        assert_eq!(code, generate_rust(&parser.into_parsed(), &GenOptions::new()));
+        assert!(code.contains("crate::vendors::Vendor::new(crate::types::VendorId::new(0x1234), \"Test Vendor\", DEVICES_4660)"));
+        assert!(code.contains("static NAME_INDEX: &[crate::index::NameIndexEntry]"));
+    }
+
+    #[test]
+    fn test_parse_filtered() {
+        let content = "\
+1002  AMD
+\t67df  Ellesmere
+8086  Intel Corporation
+\t1533  I210 Gigabit Network Connection
+\t\t8086 0001  Ethernet Server Adapter I210-T1
+10de  NVIDIA Corporation
+\t2204  GA102
+C 02  Network controller
+\t00  Ethernet controller
+C 03  Display controller
+\t00  VGA compatible controller
+\t\t00  VGA controller
+";
+
+        let mut parser = PciIdsParser::new();
+        let mut asked = Vec::new();
+        parser
+            .parse_filtered(
+                content,
+                |vendor| {
+                    asked.push(vendor);
+                    vendor == VendorId::new(0x8086)
+                },
+                |class| class == DeviceClassId::new(0x03),
+            )
+            .unwrap();
+        assert_eq!(asked, [VendorId::new(0x1002), VendorId::new(0x8086), VendorId::new(0x10de)]);
+
+        assert_eq!(parser.vendors().len(), 1);
+        assert_eq!(parser.vendors()[0].devices[0].subsystems.len(), 1);
+        assert_eq!(parser.classes().len(), 1);
+        assert_eq!(parser.classes()[0].id, DeviceClassId::new(0x03));
+        assert_eq!(parser.classes()[0].subclasses[0].prog_interfaces.len(), 1);
+
+        // Dropped entries are still checked
+        assert_eq!(
+            parser.parse_filtered("1002  AMD\n\tzzzz  Bad Device\n", |_| false, |_| false),
+            Err(PciError::InvalidHexValue)
+        );
+    }
+
+    #[test]
+    fn test_parse_many() {
+        let pci_ids = "\
+8086  Intel Corporation
+\t1533  I210 Gigabit Network Connection
+\t\t8086 0001  Ethernet Server Adapter I210-T1
+\t1539  I211 Gigabit Network Connection
+C 02  Network controller
+\t00  Ethernet controller
+";
+        let local = "\
+1234  Lab Vendor
+8086  Intel
+\t1000  Prototype
+\t1533  Onboard NIC
+\t\t8086 0001  Server Adapter
+\t\t8086 0002  Desktop Adapter
+C 02  Network controller
+\t80  Network controller
+";
+
+        let mut parser = PciIdsParser::new();
+        parser.parse_many(&[pci_ids, local]).unwrap();
+        assert!(parser.warnings().is_empty());
+
+        let vendors = parser.vendors();
+        assert_eq!(vendors.len(), 2);
+        assert_eq!(vendors[0].id, VendorId::new(0x1234));
+        assert_eq!(vendors[1].name, "Intel");
+        let devices: Vec<_> = vendors[1].devices.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(devices, ["Prototype", "Onboard NIC", "I211 Gigabit Network Connection"]);
+        let subsystems: Vec<_> = vendors[1].devices[1].subsystems.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(subsystems, ["Server Adapter", "Desktop Adapter"]);
+        assert_eq!(parser.classes().len(), 1);
+        assert_eq!(parser.classes()[0].subclasses.len(), 2);
+
+        // Duplicates inside one document still follow the policy
+        assert_eq!(parser.parse_many(&[pci_ids, "1234  A\n1234  B\n"]), Err(PciError::DuplicateEntry));
+        assert!(parser.vendors().is_empty());
+        parser.parse_many(&[]).unwrap();
+        assert!(parser.vendors().is_empty());
+    }
+
+    #[test]
+    fn test_merge() {
+        let parse = |content| {
+            let mut parser = PciIdsParser::new();
+            parser.parse(content).unwrap();
+            parser.into_parsed()
+        };
+
+        let mut database = parse("# Version: 2025.07.11\n8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n");
+        database.merge(parse("# Version: 2099.01.01\n8086  Intel Corporation\n\t1533  Onboard NIC\n1234  Lab Vendor\n"));
+        assert_eq!(database.version.as_deref(), Some("2025.07.11"));
+        assert_eq!(database.vendors.iter().map(|v| v.id.value()).collect::<Vec<_>>(), [0x1234, 0x8086]);
+        assert_eq!(database.vendors[1].devices.len(), 1);
+        assert_eq!(database.vendors[1].devices[0].name, "Onboard NIC");
+        assert!(database.find_duplicate().is_none());
+    }
+
+    #[test]
+    fn test_parser_config_limits() {
+        let content = "1234  Test Vendor\n\t0001  Test Device\n\t\t1234 0001  Test Subsystem\n";
+        let parse = |config: ParserConfig| PciIdsParser::new().config(config).parse(content);
+
+        assert_eq!(parse(ParserConfig::new()), Ok(()));
+        assert_eq!(parse(ParserConfig::new().max_line_length(27).max_name_length(14).max_entries(3)), Ok(()));
+        assert_eq!(parse(ParserConfig::new().max_line_length(26)), Err(PciError::LimitExceeded));
+        assert_eq!(parse(ParserConfig::new().max_name_length(13)), Err(PciError::LimitExceeded));
+        assert_eq!(parse(ParserConfig::new().max_entries(2)), Err(PciError::LimitExceeded));
+
+        // Comments count against the line length but not the entry count
+        let mut parser = PciIdsParser::new().config(ParserConfig::new().max_entries(1));
+        assert_eq!(parser.parse("# comment\n1234  Vendor\n"), Ok(()));
+
+        let mut streaming = StreamingParser::new(|_| {}).config(ParserConfig::new().max_name_length(4));
+        assert_eq!(streaming.feed_line("1234  Long Name"), Err(PciError::LimitExceeded));
+    }
+
+    #[test]
+    fn test_arbitrary_input_does_not_panic() {
+        // Deterministic pseudo-random lines built from bytes that matter to the format
+        const ALPHABET: &[u8] = b"0123456789abcdefC #\t\t  \r\n\xff\xfe[]x\xc3\xa9";
+        let mut state: u32 = 0x1234_5678;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+
+        for _ in 0..2000 {
+            let len = next() % 64;
+            let bytes: Vec<u8> = (0..len).map(|_| ALPHABET[next() as usize % ALPHABET.len()]).collect();
+
+            for policy in [DuplicatePolicy::Error, DuplicatePolicy::MergeChildren] {
+                let mut parser = PciIdsParser::new().duplicates(policy).config(ParserConfig::new().max_entries(8));
+                let _ = parser.parse_lossy(&bytes);
+                let _ = parser.into_parsed();
+            }
+            let _ = PciIdsParser::new().validate(&String::from_utf8_lossy(&bytes));
+        }
+    }
+
+    #[test]
+    fn test_permissive_separator() {
+        let content = "\
+1234 Test Vendor
+\t5678\tTest Device
+\t\t1234 0001 Test Subsystem
+C 02 Network controller
+\t00\tEthernet controller
+\t\t01  Two spaces still work
+abcd Vendor After Classes
+";
+
+        assert_eq!(PciIdsParser::new().parse(content), Err(PciError::InvalidFormat));
+
+        let mut parser = PciIdsParser::new().separator(Separator::Whitespace);
+        parser.parse(content).expect("Failed to parse");
+        assert_eq!(parser.vendors().len(), 2);
+        let subsystem = &parser.vendors()[0].devices[0].subsystems[0];
+        assert_eq!((subsystem.subvendor_id, subsystem.subdevice_id), (SubvendorId::new(0x1234), SubdeviceId::new(0x0001)));
+        assert_eq!(subsystem.name, "Test Subsystem");
+        assert_eq!(parser.vendors()[1].name, "Vendor After Classes");
+        assert_eq!(parser.classes()[0].name, "Network controller");
+        assert_eq!(parser.classes()[0].subclasses[0].prog_interfaces[0].name, "Two spaces still work");
+
+        let mut names = Vec::new();
+        let mut streaming = StreamingParser::new(|event| names.push(event_name(&event).to_string()))
+            .separator(Separator::Whitespace);
+        streaming.feed_line("1234\tTest Vendor").unwrap();
+        assert_eq!(streaming.feed_line("1234"), Err(PciError::InvalidFormat));
+        assert_eq!(names, ["Test Vendor"]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Build-time perfect-hash indexes for constant-time lookups.
+//!
+//! With the `phf` feature (or
+//! [`GenOptions::emit_phf`](crate::codegen::GenOptions::emit_phf) for
+//! generated databases), the code generator emits a perfect-hash index over
+//! vendor IDs and another over vendor and device ID pairs. Both are plain
+//! static tables, so there is nothing to build at run time, and
+//! [`find_vendor`](PciDatabase::find_vendor) and
+//! [`find_device`](PciDatabase::find_device) resolve an ID with two hashes and
+//! two table reads. This suits hot paths such as lookups from interrupt
+//! context, where the binary search over vendors and the scan over a vendor's
+//! devices would otherwise cost dozens of comparisons.
+//!
+//! Databases without the indexes answer the same lookups by searching, so
+//! code does not depend on the feature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ids_rs::{DeviceId, PciDatabase, VendorId};
+//!
+//! let db = PciDatabase::get();
+//! if db.capabilities().phf {
+//!     println!("Vendor and device lookups take constant time");
+//! }
+//! let _ = db.find_device(VendorId::new(0x8086), DeviceId::new(0x1533));
+//! ```
+
+use crate::codegen::{phf_hash, PHF_EMPTY_SLOT};
+use crate::database::PciDatabase;
+use crate::types::{DeviceId, VendorId};
+
+/// A perfect-hash index generated at build time.
+///
+/// Every key the index was built from maps to its own slot, which holds the
+/// value stored for it. Other keys map to an arbitrary slot, so callers must
+/// check that the entry the value refers to has the key they looked up.
+#[derive(Debug, Clone, Copy)]
+pub struct PhfIndex {
+    displacements: &'static [u16],
+    slots: &'static [u32],
+}
+
+impl PhfIndex {
+    /// No index; lookups fall back to searching the tables.
+    pub const EMPTY: Self = Self::new(&[], &[]);
+
+    /// Create an index from generated tables.
+    ///
+    /// This is used by generated code; the tables must come from the code
+    /// generator and match the database they are attached to.
+    #[doc(hidden)]
+    pub const fn new(displacements: &'static [u16], slots: &'static [u32]) -> Self {
+        Self { displacements, slots }
+    }
+
+    /// Check if the index is empty.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Get the value stored in the slot a key maps to, if that slot is used.
+    #[inline]
+    pub(crate) const fn get(&self, key: u32) -> Option<u32> {
+        if self.displacements.is_empty() || self.slots.is_empty() {
+            return None;
+        }
+        let bucket = phf_hash(key, 0) as usize % self.displacements.len();
+        let seed = self.displacements[bucket] as u32 + 1;
+        let value = self.slots[phf_hash(key, seed) as usize % self.slots.len()];
+        if value == PHF_EMPTY_SLOT {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+impl Default for PhfIndex {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl PciDatabase {
+    /// Find the position of a vendor with the perfect-hash index, if there is one.
+    ///
+    /// Returns `Some(None)` if the index rules the vendor out.
+    pub(crate) const fn phf_vendor_index(&self, vendor_id: VendorId) -> Option<Option<usize>> {
+        let index = self.vendor_phf();
+        if index.is_empty() {
+            return None;
+        }
+        let found = match index.get(vendor_id.value() as u32) {
+            Some(value) if (value as usize) < self.vendors().len() => value as usize,
+            _ => return Some(None),
+        };
+        if self.vendors()[found].id.value() == vendor_id.value() {
+            Some(Some(found))
+        } else {
+            Some(None)
+        }
+    }
+
+    /// Find the positions of a vendor and one of its devices with the
+    /// perfect-hash index, if there is one.
+    ///
+    /// Returns `Some(None)` if the index rules the device out.
+    pub(crate) fn phf_device_index(&self, vendor_id: VendorId, device_id: DeviceId) -> Option<Option<(usize, usize)>> {
+        let index = self.device_phf();
+        if index.is_empty() {
+            return None;
+        }
+        let key = u32::from(vendor_id.value()) << 16 | u32::from(device_id.value());
+        let found = index.get(key).and_then(|value| {
+            let (vendor_index, device_index) = ((value >> 16) as usize, (value & 0xffff) as usize);
+            let vendor = self.vendors().get(vendor_index)?;
+            let device = vendor.devices().get(device_index)?;
+            (vendor.id() == vendor_id && device.id() == device_id).then_some((vendor_index, device_index))
+        });
+        Some(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::build_phf_indexes;
+    use crate::parser::PciIdsParser;
+    use alloc::vec::Vec;
+
+    fn leak<T>(items: Vec<T>) -> &'static [T] {
+        items.leak()
+    }
+
+    #[test]
+    fn test_phf_finds_every_entry() {
+        let mut content = alloc::string::String::new();
+        for vendor in (0..600u32).map(|i| i * 97 % 0x10000) {
+            content.push_str(&alloc::format!("{:04x}  Vendor {}\n", vendor, vendor));
+            for device in (0..vendor % 7).map(|i| i * 0x1111) {
+                content.push_str(&alloc::format!("\t{:04x}  Device {}\n", device, device));
+            }
+        }
+        let mut parser = PciIdsParser::new();
+        parser.parse(&content).unwrap();
+        let parsed = parser.into_parsed();
+
+        let (vendor_phf, device_phf) = build_phf_indexes(&parsed.vendors).unwrap();
+        let db = parsed.into_database().with_phf(
+            PhfIndex::new(leak(vendor_phf.displacements), leak(vendor_phf.slots)),
+            PhfIndex::new(leak(device_phf.displacements), leak(device_phf.slots)),
+        );
+        assert!(db.capabilities().phf);
+
+        for (vendor_index, vendor) in db.vendors().iter().enumerate() {
+            assert_eq!(db.phf_vendor_index(vendor.id()), Some(Some(vendor_index)));
+            assert_eq!(db.find_vendor(vendor.id()).map(|v| v.id()), Some(vendor.id()));
+            for (device_index, device) in vendor.devices().iter().enumerate() {
+                assert_eq!(db.phf_device_index(vendor.id(), device.id()), Some(Some((vendor_index, device_index))));
+                assert_eq!(db.find_device(vendor.id(), device.id()).map(|d| d.name()), Some(device.name()));
+            }
+        }
+
+        // Keys that were not indexed land on some slot, but never match
+        assert!(db.find_vendor(VendorId::new(0x0001)).is_none());
+        assert_eq!(db.phf_vendor_index(VendorId::new(0x0001)), Some(None));
+        assert!(db.find_device(VendorId::new(0x0061), DeviceId::new(0x0001)).is_none());
+        assert!(db.find_device(VendorId::new(0x0001), DeviceId::new(0x0000)).is_none());
+    }
+
+    #[test]
+    fn test_empty_phf() {
+        assert!(PhfIndex::EMPTY.is_empty());
+        assert_eq!(PhfIndex::EMPTY.get(0x8086), None);
+        assert_eq!(build_phf_indexes(&[]).unwrap(), (Default::default(), Default::default()));
+
+        let db = PciDatabase::new(&[], &[]);
+        assert_eq!(db.phf_vendor_index(VendorId::new(0x8086)), None);
+        assert_eq!(db.phf_device_index(VendorId::new(0x8086), DeviceId::new(0x1533)), None);
+    }
+}
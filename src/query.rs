@@ -1,11 +1,21 @@
 //! Advanced query interface for the PCI database.
 
+use crate::chain::SourcedMatch;
+use crate::compressed::NameCache;
 use crate::database::PciDatabase;
-use crate::vendors::Vendor;
-use crate::devices::Device;
-use crate::classes::{DeviceClass, SubClass};
+use crate::index::NameMatch;
+use crate::vendors::{alias_list, contains_ignore_case, eq_ignore_case, split_aliases, Vendor};
+use crate::devices::{Device, Subsystem};
+use crate::error::{PciError, PciResult};
+use crate::classes::{DeviceClass, ProgInterface, SubClass};
 use crate::types::*;
-use alloc::{vec::Vec, string::String, string::ToString};
+use alloc::{borrow::Cow, vec::Vec, string::String, string::ToString};
+use core::cmp::Ordering;
+use core::ops::{ControlFlow, Range, RangeInclusive};
+#[cfg(feature = "memchr")]
+use crate::vendors::ascii_lowercase;
+#[cfg(feature = "memchr")]
+use memchr::memmem::Finder;
 
 /// Builder for constructing complex PCI device queries.
 ///
@@ -24,17 +34,56 @@ use alloc::{vec::Vec, string::String, string::ToString};
 ///     .class_name_contains("Network")
 ///     .execute();
 /// ```
-#[derive(Debug)]
+///
+/// Executing a query borrows the builder, so a configured query can be run
+/// repeatedly (for example in a polling loop) or cloned and refined further:
+///
+/// ```rust
+/// use ids_rs::PciDatabase;
+///
+/// let db = PciDatabase::get();
+/// let intel = db.query().vendor_name_contains("Intel");
+/// let ethernet = intel.clone().device_name_contains("Ethernet");
+/// assert!(ethernet.count() <= intel.count());
+/// assert_eq!(intel.count(), intel.execute().len());
+/// ```
+#[derive(Debug, Clone)]
 pub struct QueryBuilder<'db> {
     database: &'db PciDatabase,
     vendor_id_filter: Option<VendorId>,
-    vendor_name_filter: Option<String>,
+    vendor_ids_filter: Option<Vec<VendorId>>,
+    vendor_id_range: Option<RangeInclusive<VendorId>>,
+    vendor_name_filter: Option<NameFilter>,
     device_id_filter: Option<DeviceId>,
-    device_name_filter: Option<String>,
+    device_ids_filter: Option<Vec<DeviceId>>,
+    device_id_range: Option<RangeInclusive<DeviceId>>,
+    device_name_filter: Option<NameFilter>,
+    excluded_vendor_ids: Vec<VendorId>,
+    excluded_vendor_names: Vec<NameFilter>,
+    excluded_device_names: Vec<NameFilter>,
+    any_of_groups: Vec<AnyOf>,
+    token_filters: Vec<NameFilter>,
+    #[cfg(feature = "regex")]
+    vendor_name_regex: Option<regex_lite::Regex>,
+    #[cfg(feature = "regex")]
+    device_name_regex: Option<regex_lite::Regex>,
     class_id_filter: Option<DeviceClassId>,
-    class_name_filter: Option<String>,
+    class_name_filter: Option<NameFilter>,
     subclass_id_filter: Option<SubClassId>,
-    subclass_name_filter: Option<String>,
+    subclass_name_filter: Option<NameFilter>,
+    prog_if_id_filter: Option<ProgInterfaceId>,
+    prog_if_name_filter: Option<NameFilter>,
+    subvendor_id_filter: Option<SubvendorId>,
+    subdevice_id_filter: Option<SubdeviceId>,
+    subsystem_name_filter: Option<NameFilter>,
+    infer_classes: bool,
+    include_subsystem_names: bool,
+    record_spans: bool,
+    full_descriptions: bool,
+    match_mode: MatchMode,
+    offset: usize,
+    limit: Option<usize>,
+    order: Option<(OrderBy, SortDirection)>,
 }
 
 impl<'db> QueryBuilder<'db> {
@@ -43,13 +92,39 @@ impl<'db> QueryBuilder<'db> {
         Self {
             database,
             vendor_id_filter: None,
+            vendor_ids_filter: None,
+            vendor_id_range: None,
             vendor_name_filter: None,
             device_id_filter: None,
+            device_ids_filter: None,
+            device_id_range: None,
             device_name_filter: None,
+            excluded_vendor_ids: Vec::new(),
+            excluded_vendor_names: Vec::new(),
+            excluded_device_names: Vec::new(),
+            any_of_groups: Vec::new(),
+            token_filters: Vec::new(),
+            #[cfg(feature = "regex")]
+            vendor_name_regex: None,
+            #[cfg(feature = "regex")]
+            device_name_regex: None,
             class_id_filter: None,
             class_name_filter: None,
             subclass_id_filter: None,
             subclass_name_filter: None,
+            prog_if_id_filter: None,
+            prog_if_name_filter: None,
+            subvendor_id_filter: None,
+            subdevice_id_filter: None,
+            subsystem_name_filter: None,
+            infer_classes: false,
+            include_subsystem_names: false,
+            record_spans: false,
+            full_descriptions: false,
+            match_mode: MatchMode::default(),
+            offset: 0,
+            limit: None,
+            order: None,
         }
     }
 
@@ -59,9 +134,67 @@ impl<'db> QueryBuilder<'db> {
         self
     }
 
+    /// Filter by a set of vendor IDs, matching any of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, VendorId, DeviceId};
+    ///
+    /// // Every device in a driver's supported-ID list, in one query
+    /// let db = PciDatabase::get();
+    /// let supported = db.query()
+    ///     .vendor_id_in(&[VendorId::new(0x8086)])
+    ///     .device_id_in(&[DeviceId::new(0x1533), DeviceId::new(0x1539), DeviceId::new(0x157b)])
+    ///     .execute();
+    /// ```
+    pub fn vendor_id_in(mut self, vendor_ids: &[VendorId]) -> Self {
+        self.vendor_ids_filter = Some(vendor_ids.to_vec());
+        self
+    }
+
+    /// Filter by an lspci-style `vendor:device` ID string.
+    ///
+    /// Each side is up to four hex digits, `*` or empty for any ID, or leading
+    /// hex digits followed by `*` for a block of IDs: `"8086:15*"` selects Intel
+    /// devices 1500 to 15ff. The device part may be left out (`"8086"`).
+    /// Returns [`PciError::InvalidHexValue`] or [`PciError::InvalidFormat`] if
+    /// the string cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> ids_rs::PciResult<()> {
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// for device_match in db.query().id_str("8086:15*")?.execute() {
+    ///     println!("{}:{} {}", device_match.vendor_id(), device_match.device_id(), device_match.device_name());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn id_str(mut self, ids: &str) -> PciResult<Self> {
+        let (vendor, device) = match ids.split_once(':') {
+            Some((vendor, device)) => (vendor, device),
+            None => (ids, ""),
+        };
+        if device.contains(':') {
+            return Err(PciError::InvalidFormat);
+        }
+
+        if let Some((low, high)) = parse_id_pattern(vendor)? {
+            narrow(&mut self.vendor_id_range, VendorId::new(low), VendorId::new(high));
+        }
+        if let Some((low, high)) = parse_id_pattern(device)? {
+            narrow(&mut self.device_id_range, DeviceId::new(low), DeviceId::new(high));
+        }
+        Ok(self)
+    }
+
     /// Filter by vendor name (case-insensitive substring match).
     pub fn vendor_name_contains(mut self, name: &str) -> Self {
-        self.vendor_name_filter = Some(name.to_lowercase());
+        self.vendor_name_filter = Some(NameFilter::new(name));
         self
     }
 
@@ -71,12 +204,162 @@ impl<'db> QueryBuilder<'db> {
         self
     }
 
+    /// Filter by a set of device IDs, matching any of them.
+    pub fn device_id_in(mut self, device_ids: &[DeviceId]) -> Self {
+        self.device_ids_filter = Some(device_ids.to_vec());
+        self
+    }
+
+    /// Filter by an inclusive range of device IDs.
+    ///
+    /// Many families are allocated contiguous IDs. Ranges from several calls,
+    /// or from [`id_str`](Self::id_str), are intersected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, VendorId, DeviceId};
+    ///
+    /// let db = PciDatabase::get();
+    /// let family = db.query()
+    ///     .vendor_id(VendorId::new(0x8086))
+    ///     .device_id_between(DeviceId::new(0x1520), DeviceId::new(0x153f))
+    ///     .execute();
+    /// ```
+    pub fn device_id_between(mut self, low: DeviceId, high: DeviceId) -> Self {
+        narrow(&mut self.device_id_range, low, high);
+        self
+    }
+
     /// Filter by device name (case-insensitive substring match).
     pub fn device_name_contains(mut self, name: &str) -> Self {
-        self.device_name_filter = Some(name.to_lowercase());
+        self.device_name_filter = Some(NameFilter::new(name));
+        self
+    }
+
+    /// Exclude a vendor ID.
+    ///
+    /// Exclusions accumulate: each call removes another vendor or name from the results.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, VendorId};
+    ///
+    /// // Ethernet devices not from Realtek
+    /// let db = PciDatabase::get();
+    /// let devices = db.query()
+    ///     .device_name_contains("Ethernet")
+    ///     .exclude_vendor_id(VendorId::new(0x10ec))
+    ///     .execute();
+    /// assert!(devices.iter().all(|m| m.vendor_id() != VendorId::new(0x10ec)));
+    /// ```
+    pub fn exclude_vendor_id(mut self, vendor_id: VendorId) -> Self {
+        self.excluded_vendor_ids.push(vendor_id);
+        self
+    }
+
+    /// Exclude vendors whose name contains `name` (case-insensitive).
+    pub fn exclude_vendor_name_contains(mut self, name: &str) -> Self {
+        self.excluded_vendor_names.push(NameFilter::new(name));
         self
     }
 
+    /// Exclude devices whose name contains `name` (case-insensitive).
+    pub fn exclude_device_name_contains(mut self, name: &str) -> Self {
+        self.excluded_device_names.push(NameFilter::new(name));
+        self
+    }
+
+    /// Require at least one of a group of alternative filters to match.
+    ///
+    /// All other filters are combined with AND. Inside the group built by
+    /// `build`, each filter is an alternative, so a device passes the group if
+    /// any one of them matches. Several groups can be added and must all pass.
+    /// A group with no alternatives matches nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, VendorId};
+    ///
+    /// // Intel or AMD devices with "Graphics" in the name
+    /// let db = PciDatabase::get();
+    /// let gpus = db.query()
+    ///     .any_of(|q| q.vendor_id(VendorId::new(0x8086)).vendor_id(VendorId::new(0x1002)))
+    ///     .device_name_contains("Graphics")
+    ///     .execute();
+    /// ```
+    pub fn any_of(mut self, build: impl FnOnce(AnyOf) -> AnyOf) -> Self {
+        self.any_of_groups.push(build(AnyOf::default()));
+        self
+    }
+
+    /// Require every whitespace-separated word of `text` to appear in the vendor or device name.
+    ///
+    /// Words can match in any order and in either name, so `"intel 10gbe sfp"`
+    /// finds Intel's `"82599ES 10-Gigabit SFI/SFP+ Network Connection"`
+    /// without guessing how pci.ids words it. Each word is a substring filter
+    /// that honors [`case_sensitive`](Self::case_sensitive) and
+    /// [`match_whole_words`](Self::match_whole_words).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let nics = db.query().search_tokens("intel 10-gigabit sfp").execute();
+    /// ```
+    pub fn search_tokens(mut self, text: &str) -> Self {
+        self.token_filters.extend(text.split_whitespace().map(NameFilter::new));
+        self
+    }
+
+    /// Filter by vendor name with a regular expression.
+    ///
+    /// The pattern uses [`regex_lite`] syntax and is case-sensitive unless it
+    /// starts with `(?i)`. Returns [`PciError::InvalidPattern`] if it does not compile.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> ids_rs::PciResult<()> {
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let vendors = db.query().vendor_name_matches(r"^(?i)intel\b")?.execute_vendors();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn vendor_name_matches(mut self, pattern: &str) -> PciResult<Self> {
+        self.vendor_name_regex = Some(regex_lite::Regex::new(pattern).map_err(|_| PciError::InvalidPattern)?);
+        Ok(self)
+    }
+
+    /// Filter by device name with a regular expression.
+    ///
+    /// See [`vendor_name_matches`](Self::vendor_name_matches).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> ids_rs::PciResult<()> {
+    /// use ids_rs::{PciDatabase, VendorId};
+    ///
+    /// // Intel 82xxx-generation parts
+    /// let db = PciDatabase::get();
+    /// let parts = db.query().vendor_id(VendorId::new(0x8086)).device_name_matches(r"^82\d{3}")?.execute();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn device_name_matches(mut self, pattern: &str) -> PciResult<Self> {
+        self.device_name_regex = Some(regex_lite::Regex::new(pattern).map_err(|_| PciError::InvalidPattern)?);
+        Ok(self)
+    }
+
     /// Filter by device class ID.
     pub fn class_id(mut self, class_id: DeviceClassId) -> Self {
         self.class_id_filter = Some(class_id);
@@ -85,7 +368,7 @@ impl<'db> QueryBuilder<'db> {
 
     /// Filter by device class name (case-insensitive substring match).
     pub fn class_name_contains(mut self, name: &str) -> Self {
-        self.class_name_filter = Some(name.to_lowercase());
+        self.class_name_filter = Some(NameFilter::new(name));
         self
     }
 
@@ -97,87 +380,792 @@ impl<'db> QueryBuilder<'db> {
 
     /// Filter by subclass name (case-insensitive substring match).
     pub fn subclass_name_contains(mut self, name: &str) -> Self {
-        self.subclass_name_filter = Some(name.to_lowercase());
+        self.subclass_name_filter = Some(NameFilter::new(name));
         self
     }
 
-    /// Execute the query and return matching device results.
-    pub fn execute(self) -> Vec<DeviceMatch<'db>> {
-        let mut results = Vec::new();
+    /// Filter by programming interface ID.
+    ///
+    /// A subclass passes only if it has a matching programming interface, and
+    /// [`execute_classes`](Self::execute_classes) lists the matches in
+    /// [`ClassMatch::matching_prog_ifs`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, ProgInterfaceId, SubClassId};
+    ///
+    /// let db = PciDatabase::get();
+    /// let xhci = db.query().subclass_id(SubClassId::new(0x03)).prog_if_id(ProgInterfaceId::new(0x30)).execute_classes();
+    /// for class_match in xhci {
+    ///     for (subclass, prog_if) in &class_match.matching_prog_ifs {
+    ///         println!("{} / {} / {}", class_match.class_name(), subclass.name(), prog_if.name());
+    ///     }
+    /// }
+    /// ```
+    pub fn prog_if_id(mut self, prog_if_id: ProgInterfaceId) -> Self {
+        self.prog_if_id_filter = Some(prog_if_id);
+        self
+    }
 
-        for vendor in self.database.vendors() {
-            // Check vendor filters
-            if let Some(ref vendor_id) = self.vendor_id_filter {
-                if vendor.id() != *vendor_id {
-                    continue;
-                }
-            }
+    /// Filter by programming interface name (case-insensitive substring match).
+    pub fn prog_if_name_contains(mut self, name: &str) -> Self {
+        self.prog_if_name_filter = Some(NameFilter::new(name));
+        self
+    }
 
-            if let Some(ref vendor_name) = self.vendor_name_filter {
-                if !vendor.name().to_lowercase().contains(vendor_name) {
-                    continue;
-                }
-            }
+    /// Filter by subsystem vendor ID.
+    ///
+    /// Subsystem filters keep devices with at least one subsystem that passes
+    /// all of them, and the passing subsystems are listed in
+    /// [`DeviceMatch::matching_subsystems`].
+    pub fn subvendor_id(mut self, subvendor_id: SubvendorId) -> Self {
+        self.subvendor_id_filter = Some(subvendor_id);
+        self
+    }
 
-            for device in vendor.devices() {
-                // Check device filters
-                if let Some(ref device_id) = self.device_id_filter {
-                    if device.id() != *device_id {
-                        continue;
-                    }
-                }
+    /// Filter by subsystem device ID.
+    pub fn subdevice_id(mut self, subdevice_id: SubdeviceId) -> Self {
+        self.subdevice_id_filter = Some(subdevice_id);
+        self
+    }
 
-                if let Some(ref device_name) = self.device_name_filter {
-                    if !device.name().to_lowercase().contains(device_name) {
-                        continue;
-                    }
-                }
+    /// Filter by subsystem name (case-insensitive substring match).
+    pub fn subsystem_name_contains(mut self, name: &str) -> Self {
+        self.subsystem_name_filter = Some(NameFilter::new(name));
+        self
+    }
 
-                // If we have class filters, we need to check if any class matches
-                let class_match = self.find_matching_class();
+    /// Match class filters against each device's inferred class.
+    ///
+    /// By default, class filters only check that some class in the database matches,
+    /// because pci.ids does not tie devices to classes. With inference enabled, each
+    /// device is classified using the best-effort [`class_map`](crate::class_map) and only devices whose
+    /// inferred class passes the class and subclass filters are returned.
+    pub fn infer_device_classes(mut self) -> Self {
+        self.infer_classes = true;
+        self
+    }
 
-                if self.has_class_filters() && class_match.is_none() {
-                    continue;
-                }
+    /// Also match [`device_name_contains`](Self::device_name_contains) against subsystem names.
+    ///
+    /// Some marketing names, such as a particular laptop dock, only appear at
+    /// the subsystem level. With this set, a device passes the name filter if
+    /// its own name or any of its subsystem names matches, and the subsystems
+    /// whose names match are listed in [`DeviceMatch::matching_subsystems`].
+    pub fn include_subsystem_names(mut self) -> Self {
+        self.include_subsystem_names = true;
+        self
+    }
 
-                results.push(DeviceMatch {
-                    vendor,
-                    device,
-                    class_info: class_match,
-                });
+    /// Record where the name filters matched in each result.
+    ///
+    /// The byte ranges matched by the vendor, device, and class name filters
+    /// (including regex filters and [`search_tokens`](Self::search_tokens)
+    /// words) are stored in [`DeviceMatch::vendor_name_spans`],
+    /// [`DeviceMatch::device_name_spans`], and [`ClassMatch::class_name_spans`],
+    /// so frontends can highlight matches without repeating the search.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// for device_match in db.query().device_name_contains("ethernet").record_match_spans().execute() {
+    ///     let name = db.device_name(device_match.vendor_id(), device_match.device_id());
+    ///     for span in &device_match.device_name_spans {
+    ///         println!("{} -> [{}]", name, &name[span.clone()]);
+    ///     }
+    /// }
+    /// ```
+    pub fn record_match_spans(mut self) -> Self {
+        self.record_spans = true;
+        self
+    }
+
+    /// Describe results as completely as the filters allow.
+    ///
+    /// By default [`DeviceMatch::description`] only appends the class name.
+    /// With this set, results also carry the subclass, programming interface,
+    /// and subsystem selected by the subclass, programming interface, and
+    /// subsystem filters, and the description includes them in the same
+    /// format as [`PciDatabase::describe_device`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, SubvendorId, VendorId};
+    ///
+    /// let db = PciDatabase::get();
+    /// let results = db
+    ///     .query()
+    ///     .vendor_id(VendorId::new(0x8086))
+    ///     .subvendor_id(SubvendorId::new(0x1028))
+    ///     .full_descriptions()
+    ///     .execute();
+    /// for device_match in results {
+    ///     println!("{}", device_match.description());
+    /// }
+    /// ```
+    pub fn full_descriptions(mut self) -> Self {
+        self.full_descriptions = true;
+        self
+    }
+
+    /// Make name substring filters case-sensitive.
+    ///
+    /// Name filters ignore case by default, so `"ARM"` also matches `"Swarm"`.
+    /// This applies to every `*_name_contains` filter, including exclusions and
+    /// [`any_of`](Self::any_of) groups, wherever they appear in the chain.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.match_mode.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Make name substring filters match whole words only.
+    ///
+    /// A match must not be directly preceded or followed by a letter or digit,
+    /// so `"AC"` matches `"AC'97 Audio"` and `"802.11ac AC"` but not `"MacBook"`
+    /// or `"Blackmagic"`. Like [`case_sensitive`](Self::case_sensitive), this
+    /// applies to every name filter in the query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let nics = db.query().device_name_contains("NIC").match_whole_words().execute();
+    /// ```
+    pub fn match_whole_words(mut self) -> Self {
+        self.match_mode.whole_words = true;
+        self
+    }
+
+    /// Skip the first `offset` matching devices.
+    ///
+    /// Together with [`limit`](Self::limit) this pages through large result
+    /// sets. Both are applied while the database is traversed, so skipped and
+    /// excess matches are never built, and they apply to every device terminal
+    /// ([`execute`](Self::execute), [`count`](Self::count), and so on).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, VendorId};
+    ///
+    /// let db = PciDatabase::get();
+    /// // The third page of 20 Intel devices
+    /// let page = db.query().vendor_id(VendorId::new(0x8086)).offset(40).limit(20).execute();
+    /// assert!(page.len() <= 20);
+    /// ```
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return at most `limit` matching devices.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sort matching devices by `key`.
+    ///
+    /// Ties keep database order, so results are deterministic. Sorting needs
+    /// every match before the first one is returned, so [`offset`](Self::offset)
+    /// and [`limit`](Self::limit) are applied after sorting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{OrderBy, PciDatabase, SortDirection};
+    ///
+    /// let db = PciDatabase::get();
+    /// let nics = db.query()
+    ///     .device_name_contains("Ethernet")
+    ///     .order_by(OrderBy::DeviceName, SortDirection::Ascending)
+    ///     .limit(10)
+    ///     .execute();
+    /// ```
+    pub fn order_by(mut self, key: OrderBy, direction: SortDirection) -> Self {
+        self.order = Some((key, direction));
+        self
+    }
+
+    /// Describe how the query would run, without running it.
+    ///
+    /// The returned [`QueryPlan`] lists the filters that are set, how
+    /// candidate vendors are found, how many vendors and devices the scan
+    /// visits, and a rough cost estimate, along with hints for restructuring
+    /// slow queries. Counting the visited devices applies the vendor filters,
+    /// so this costs about as much as a vendor query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{PciDatabase, VendorId};
+    ///
+    /// let db = PciDatabase::get();
+    /// let by_name = db.query().vendor_name_contains("Intel").device_name_contains("Ethernet").explain();
+    /// let by_id = db.query().vendor_id(VendorId::new(0x8086)).device_name_contains("Ethernet").explain();
+    /// assert!(by_id.estimated_cost <= by_name.estimated_cost);
+    /// println!("{}", by_name);
+    /// ```
+    pub fn explain(&self) -> QueryPlan {
+        let mut filters = Vec::new();
+        let mut add = |set: bool, name: &'static str| {
+            if set {
+                filters.push(name);
+            }
+        };
+        add(self.vendor_id_filter.is_some(), "vendor_id");
+        add(self.vendor_ids_filter.is_some(), "vendor_id_in");
+        add(self.vendor_id_range.is_some(), "vendor ID range");
+        add(self.vendor_name_filter.is_some(), "vendor_name_contains");
+        add(self.device_id_filter.is_some(), "device_id");
+        add(self.device_ids_filter.is_some(), "device_id_in");
+        add(self.device_id_range.is_some(), "device ID range");
+        add(self.device_name_filter.is_some(), "device_name_contains");
+        add(!self.excluded_vendor_ids.is_empty(), "exclude_vendor_id");
+        add(!self.excluded_vendor_names.is_empty(), "exclude_vendor_name_contains");
+        add(!self.excluded_device_names.is_empty(), "exclude_device_name_contains");
+        add(!self.any_of_groups.is_empty(), "any_of");
+        add(!self.token_filters.is_empty(), "search_tokens");
+        #[cfg(feature = "regex")]
+        add(self.vendor_name_regex.is_some(), "vendor_name_matches");
+        #[cfg(feature = "regex")]
+        add(self.device_name_regex.is_some(), "device_name_matches");
+        add(self.class_id_filter.is_some(), "class_id");
+        add(self.class_name_filter.is_some(), "class_name_contains");
+        add(self.subclass_id_filter.is_some(), "subclass_id");
+        add(self.subclass_name_filter.is_some(), "subclass_name_contains");
+        add(self.prog_if_id_filter.is_some(), "prog_if_id");
+        add(self.prog_if_name_filter.is_some(), "prog_if_name_contains");
+        add(self.subvendor_id_filter.is_some(), "subvendor_id");
+        add(self.subdevice_id_filter.is_some(), "subdevice_id");
+        add(self.subsystem_name_filter.is_some(), "subsystem_name_contains");
+
+        let vendor_access = if self.vendor_id_filter.is_some() {
+            VendorAccess::BinarySearch
+        } else if self.vendor_id_range.is_some() {
+            VendorAccess::RangeSearch
+        } else {
+            VendorAccess::FullScan
+        };
+        let candidates = self.candidate_vendors();
+        let devices_scanned: usize = candidates
+            .iter()
+            .filter(|vendor| self.vendor_matches(vendor))
+            .map(|vendor| vendor.device_count())
+            .sum();
+
+        // One unit per entry visited plus one per name comparison; subsystem
+        // filters and inference look at more than one name per device
+        let mut device_cost = 1 + usize::from(self.device_name_filter.is_some()) + self.excluded_device_names.len()
+            + 2 * self.token_filters.len()
+            + self.any_of_groups.len();
+        if self.has_subsystem_filters() || (self.include_subsystem_names && self.device_name_filter.is_some()) {
+            device_cost += 4;
+        }
+        if self.infer_classes {
+            device_cost += 8;
+        }
+        let class_cost = if self.has_class_filters() && !self.infer_classes { self.database.classes().len() } else { 0 };
+        let estimated_cost = candidates.len() + devices_scanned * device_cost + class_cost;
+
+        let mut hints = Vec::new();
+        if vendor_access == VendorAccess::FullScan {
+            if self.vendor_ids_filter.is_some() {
+                hints.push("vendor_id_in checks every vendor; a single vendor_id or an id_str range uses binary search");
+            } else {
+                hints.push("every vendor is scanned; add vendor_id or an id_str range if the vendor is known");
             }
         }
+        if self.include_subsystem_names && self.device_name_filter.is_some() {
+            hints.push("include_subsystem_names also compares every subsystem name of each device");
+        }
+        if self.infer_classes {
+            hints.push("infer_device_classes runs the class heuristics for every device");
+        }
+        if self.order.is_some() && self.limit.is_some() {
+            hints.push("order_by collects every match before applying limit");
+        }
+        #[cfg(feature = "regex")]
+        if self.vendor_name_regex.is_some() || self.device_name_regex.is_some() {
+            hints.push("regular expressions are slower than substring filters");
+        }
 
-        results
+        QueryPlan {
+            filters,
+            vendor_access,
+            vendors_scanned: candidates.len(),
+            devices_scanned,
+            estimated_cost,
+            hints,
+        }
     }
 
-    /// Execute the query and return matching vendor results.
-    pub fn execute_vendors(self) -> Vec<&'db Vendor> {
-        let mut results = Vec::new();
+    /// Compile the query for matching devices found at runtime.
+    ///
+    /// See [`CompiledQuery`].
+    pub fn compile(self) -> CompiledQuery<'db> {
+        let class_codes = self.has_class_filters().then(|| self.class_code_patterns());
+        CompiledQuery { query: self, class_codes }
+    }
 
-        for vendor in self.database.vendors() {
-            // Check vendor filters
-            if let Some(ref vendor_id) = self.vendor_id_filter {
-                if vendor.id() != *vendor_id {
-                    continue;
-                }
+    /// Resolve the class, subclass, and programming interface filters to the
+    /// class codes they accept, with `None` parts accepting any value.
+    fn class_code_patterns(&self) -> Vec<ClassCodePattern> {
+        let mut patterns = Vec::new();
+        for class in self.database.classes().iter().filter(|class| self.class_matches(class)) {
+            if !self.has_subclass_filters() {
+                patterns.push((class.id(), None, None));
+                continue;
             }
-
-            if let Some(ref vendor_name) = self.vendor_name_filter {
-                if !vendor.name().to_lowercase().contains(vendor_name) {
+            for subclass in class.subclasses().iter().filter(|subclass| self.subclass_matches(class.id(), subclass)) {
+                if !self.has_prog_if_filters() {
+                    patterns.push((class.id(), Some(subclass.id()), None));
                     continue;
                 }
+                patterns.extend(
+                    subclass
+                        .prog_interfaces()
+                        .iter()
+                        .filter(|prog_if| self.prog_if_matches(class.id(), subclass.id(), prog_if))
+                        .map(|prog_if| (class.id(), Some(subclass.id()), Some(prog_if.id()))),
+                );
             }
-
-            results.push(vendor);
         }
+        patterns
+    }
 
+    /// Execute the query and return matching device results.
+    pub fn execute(&self) -> Vec<DeviceMatch<'db>> {
+        let mut results = Vec::new();
+        let _ = self.execute_with(|device_match| {
+            results.push(device_match);
+            ControlFlow::<()>::Continue(())
+        });
         results
     }
 
-    /// Execute the query and return matching class results.
-    pub fn execute_classes(self) -> Vec<ClassMatch<'db>> {
-        let mut results = Vec::new();
+    /// Execute the query, passing each matching device to `f` as it is found.
+    ///
+    /// No intermediate results are collected unless [`order_by`](Self::order_by)
+    /// is set. Returning [`ControlFlow::Break`] from `f` stops the query early,
+    /// and the break value is returned. See [`for_each`](Self::for_each) for a
+    /// variant that never allocates.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use core::ops::ControlFlow;
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// // Find the first Intel device with "Ethernet" in its name
+    /// let first = db.query()
+    ///     .vendor_name_contains("Intel")
+    ///     .device_name_contains("Ethernet")
+    ///     .execute_with(|device_match| ControlFlow::Break(device_match.device_id()));
+    ///
+    /// if let ControlFlow::Break(device_id) = first {
+    ///     println!("First match: {}", device_id);
+    /// }
+    /// ```
+    pub fn execute_with<B>(&self, mut f: impl FnMut(DeviceMatch<'db>) -> ControlFlow<B>) -> ControlFlow<B> {
+        let names = NameCache::new(self.database);
+        let Some((key, direction)) = self.order else {
+            return self.visit_matches(self.offset, self.limit, |vendor, device, class_info| {
+                f(self.device_match(&names, vendor, device, class_info))
+            });
+        };
+
+        // Sort everything first, then take the requested page
+        let mut matches = Vec::new();
+        let _ = self.visit_matches(0, None, |vendor, device, class_info| {
+            matches.push((vendor, device, class_info));
+            ControlFlow::<()>::Continue(())
+        });
+        matches.sort_by(|a, b| direction.apply(key.compare((self.database, a.0, a.1), (self.database, b.0, b.1))));
+
+        for (vendor, device, class_info) in matches.into_iter().skip(self.offset).take(self.limit.unwrap_or(usize::MAX)) {
+            f(self.device_match(&names, vendor, device, class_info))?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Run the query against several databases and merge the results.
+    ///
+    /// Devices that an earlier database also lists are left out, matching the
+    /// precedence of [`ChainedDatabase`](crate::ChainedDatabase). Ordering,
+    /// offset, and limit apply to the merged results.
+    pub(crate) fn execute_sourced(&self, databases: &[&'db PciDatabase]) -> Vec<SourcedMatch<'db>> {
+        let unpaged = Self {
+            offset: 0,
+            limit: None,
+            ..self.clone()
+        };
+
+        let mut results = Vec::new();
+        for (source, &database) in databases.iter().enumerate() {
+            let earlier = &databases[..source];
+            let query = Self {
+                database,
+                ..unpaged.clone()
+            };
+            let _ = query.execute_with(|device_match| {
+                let shadowed = earlier
+                    .iter()
+                    .any(|database| database.find_device(device_match.vendor_id(), device_match.device_id()).is_some());
+                if !shadowed {
+                    results.push(SourcedMatch { source, device_match });
+                }
+                ControlFlow::<()>::Continue(())
+            });
+        }
+
+        if let Some((key, direction)) = self.order {
+            results.sort_by(|a, b| {
+                let a = (databases[a.source], a.device_match.vendor, a.device_match.device);
+                let b = (databases[b.source], b.device_match.vendor, b.device_match.device);
+                direction.apply(key.compare(a, b))
+            });
+        }
+        results.into_iter().skip(self.offset).take(self.limit.unwrap_or(usize::MAX)).collect()
+    }
+
+    /// Stream matching devices to `f` in database order without allocating.
+    ///
+    /// This is [`execute_with`](Self::execute_with) for environments that
+    /// cannot allocate at all: [`order_by`](Self::order_by) is ignored, since
+    /// sorting needs every match up front, and
+    /// [`DeviceMatch::matching_subsystems`] is always left empty. Offset and
+    /// limit still apply, and returning [`ControlFlow::Break`] stops early.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use core::ops::ControlFlow;
+    /// use ids_rs::{PciDatabase, VendorId};
+    ///
+    /// let db = PciDatabase::get();
+    /// let mut ethernet = 0;
+    /// let _ = db.query().vendor_id(VendorId::new(0x8086)).for_each(|device_match| {
+    ///     if device_match.device_name().contains("Ethernet") {
+    ///         ethernet += 1;
+    ///     }
+    ///     ControlFlow::<()>::Continue(())
+    /// });
+    /// ```
+    pub fn for_each<B>(&self, mut f: impl FnMut(DeviceMatch<'db>) -> ControlFlow<B>) -> ControlFlow<B> {
+        self.visit_matches(self.offset, self.limit, |vendor, device, class_info| {
+            f(DeviceMatch {
+                class_info,
+                ..DeviceMatch::new(self.database, vendor, device)
+            })
+        })
+    }
+
+    /// Execute the query and return the first matching device, in database order.
+    ///
+    /// The search stops at the first hit, so this is much cheaper than
+    /// [`execute`](Self::execute) for existence checks.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// if let Some(device_match) = db.query().device_name_contains("I210").execute_first() {
+    ///     println!("{}", device_match.description());
+    /// }
+    /// ```
+    pub fn execute_first(&self) -> Option<DeviceMatch<'db>> {
+        match self.execute_with(ControlFlow::Break) {
+            ControlFlow::Break(device_match) => Some(device_match),
+            ControlFlow::Continue(()) => None,
+        }
+    }
+
+    /// Count the matching devices.
+    ///
+    /// This runs the same filters as [`execute`](Self::execute) but builds no
+    /// [`DeviceMatch`] values and does not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let count = db.query().vendor_name_contains("Intel").device_name_contains("Ethernet").count();
+    /// println!("{} Intel Ethernet devices", count);
+    /// ```
+    pub fn count(&self) -> usize {
+        let mut count = 0;
+        let _ = self.visit_matches(self.offset, self.limit, |_, _, _| {
+            count += 1;
+            ControlFlow::<()>::Continue(())
+        });
+        count
+    }
+
+    /// Count the matching devices per vendor and per class.
+    ///
+    /// Matches are counted as they are found, without building
+    /// [`DeviceMatch`] values. pci.ids does not record device classes, so
+    /// each device is counted under its best-effort class from [`class_map`](crate::class_map)
+    /// (or the class it matched, with
+    /// [`infer_device_classes`](Self::infer_device_classes)); devices that
+    /// cannot be classified are counted as unclassified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let summary = db.query().device_name_contains("Ethernet").summarize();
+    /// println!("{} Ethernet devices across {} vendors", summary.device_count, summary.vendor_count());
+    /// ```
+    pub fn summarize(&self) -> QuerySummary<'db> {
+        let mut device_count = 0;
+        let mut vendors: Vec<(&'db Vendor, usize)> = Vec::new();
+        let mut class_counts = [0usize; 256];
+        let mut unclassified = 0;
+
+        let names = NameCache::new(self.database);
+        let _ = self.visit_matches(self.offset, self.limit, |vendor, device, class_info| {
+            device_count += 1;
+
+            // Matches arrive grouped by vendor
+            match vendors.last_mut() {
+                Some((last, count)) if last.id() == vendor.id() => *count += 1,
+                _ => vendors.push((vendor, 1)),
+            }
+
+            let class_id = match class_info.filter(|_| self.infer_classes) {
+                Some(class) => Some(class.id()),
+                None => names.infer_class(vendor, device).map(|inferred| inferred.class_id),
+            };
+            match class_id.filter(|&class_id| self.database.find_class(class_id).is_some()) {
+                Some(class_id) => class_counts[class_id.value() as usize] += 1,
+                None => unclassified += 1,
+            }
+            ControlFlow::<()>::Continue(())
+        });
+
+        let classes = self
+            .database
+            .classes()
+            .iter()
+            .map(|class| (class, class_counts[class.id().value() as usize]))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+
+        QuerySummary {
+            database: self.database,
+            device_count,
+            vendors,
+            classes,
+            unclassified,
+        }
+    }
+
+    /// Check if no device matches, stopping at the first match.
+    ///
+    /// Like [`count`](Self::count), this does not allocate.
+    pub fn is_empty(&self) -> bool {
+        self.visit_matches(self.offset, self.limit, |_, _, _| ControlFlow::Break(())).is_continue()
+    }
+
+    fn device_match(
+        &self,
+        names: &NameCache<'_>,
+        vendor: &'db Vendor,
+        device: &'db Device,
+        class_info: Option<&'db DeviceClass>,
+    ) -> DeviceMatch<'db> {
+        let subsystem_name_term = self.device_name_filter.as_ref().filter(|_| self.include_subsystem_names);
+        let matching_subsystems = if self.has_subsystem_filters() || subsystem_name_term.is_some() {
+            device
+                .subsystems()
+                .iter()
+                .filter(|subsystem| self.subsystem_matches(names, vendor, device, subsystem))
+                .filter(|subsystem| {
+                    subsystem_name_term
+                        .is_none_or(|term| term.matches(&names.subsystem_name(vendor, device, subsystem), self.match_mode))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let (vendor_name_spans, device_name_spans) = if self.record_spans {
+            let vendor_name = names.vendor_name(vendor);
+            let device_name = names.device_name(vendor, device);
+            #[cfg_attr(not(feature = "regex"), allow(unused_mut))]
+            let mut spans = (
+                self.name_spans(&vendor_name, self.vendor_name_filter.iter().chain(&self.token_filters)),
+                self.name_spans(&device_name, self.device_name_filter.iter().chain(&self.token_filters)),
+            );
+            #[cfg(feature = "regex")]
+            {
+                add_regex_spans(&mut spans.0, &vendor_name, self.vendor_name_regex.as_ref());
+                add_regex_spans(&mut spans.1, &device_name, self.device_name_regex.as_ref());
+            }
+            spans
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        let (subclass_info, prog_if_info, subsystem_info) = if self.full_descriptions {
+            let subclass_info = class_info
+                .filter(|_| self.has_subclass_filters())
+                .and_then(|class| self.find_matching_subclass(names, class, vendor, device));
+            let prog_if_info = subclass_info
+                .filter(|_| self.has_prog_if_filters())
+                .and_then(|subclass| {
+                    let class_id = class_info?.id();
+                    subclass.prog_interfaces().iter().find(|prog_if| self.prog_if_matches(class_id, subclass.id(), prog_if))
+                });
+            let subsystem_info = matching_subsystems.first().copied().filter(|_| self.has_subsystem_filters());
+            (subclass_info, prog_if_info, subsystem_info)
+        } else {
+            (None, None, None)
+        };
+
+        DeviceMatch {
+            database: self.database,
+            vendor,
+            device,
+            class_info,
+            subclass_info,
+            prog_if_info,
+            subsystem_info,
+            matching_subsystems,
+            vendor_name_spans,
+            device_name_spans,
+        }
+    }
+
+    /// Find the subclass of `class` that the subclass filters selected for a device.
+    fn find_matching_subclass(
+        &self,
+        names: &NameCache<'_>,
+        class: &'db DeviceClass,
+        vendor: &Vendor,
+        device: &Device,
+    ) -> Option<&'db SubClass> {
+        if self.infer_classes {
+            let subclass = class.find_subclass(names.infer_class(vendor, device)?.subclass_id?)?;
+            return self.subclass_matches(class.id(), subclass).then_some(subclass);
+        }
+        class.subclasses().iter().find(|subclass| self.subclass_matches(class.id(), subclass))
+    }
+
+    fn name_spans<'f>(&self, name: &str, filters: impl Iterator<Item = &'f NameFilter>) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = filters.flat_map(|filter| filter.spans(name, self.match_mode)).collect();
+        spans.sort_by_key(|span| (span.start, span.end));
+        spans
+    }
+
+    /// Run the vendor, device, subsystem, and class filters, passing each
+    /// matching device and its class to `f`, after skipping `offset` matches
+    /// and stopping after `limit`.
+    fn visit_matches<B>(
+        &self,
+        offset: usize,
+        limit: Option<usize>,
+        mut f: impl FnMut(&'db Vendor, &'db Device, Option<&'db DeviceClass>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let mut to_skip = offset;
+        let mut remaining = limit.unwrap_or(usize::MAX);
+        if remaining == 0 {
+            return ControlFlow::Continue(());
+        }
+
+        // Without inference the class does not depend on the device, so find it once
+        let fixed_class = (!self.infer_classes).then(|| self.find_matching_class());
+        if self.has_class_filters() && fixed_class == Some(None) {
+            return ControlFlow::Continue(());
+        }
+
+        let names = NameCache::new(self.database);
+        for vendor in self.candidate_vendors() {
+            if !self.vendor_matches(vendor) {
+                continue;
+            }
+
+            for device in vendor.devices() {
+                if !self.device_passes(&names, vendor, device) {
+                    continue;
+                }
+
+                // If we have class filters, we need to check if any class matches
+                let class_match = match fixed_class {
+                    Some(class_match) => class_match,
+                    None => self.find_inferred_class(&names, vendor, device),
+                };
+
+                if self.has_class_filters() && class_match.is_none() {
+                    continue;
+                }
+
+                if to_skip > 0 {
+                    to_skip -= 1;
+                    continue;
+                }
+
+                f(vendor, device, class_match)?;
+                remaining -= 1;
+                if remaining == 0 {
+                    return ControlFlow::Continue(());
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Execute the query and return matching vendor results.
+    pub fn execute_vendors(&self) -> Vec<&'db Vendor> {
+        let mut results = Vec::new();
+
+        let names = NameCache::new(self.database);
+        for vendor in self.candidate_vendors() {
+            if !self.vendor_matches(vendor) {
+                continue;
+            }
+
+            // Groups with device alternatives pass if any of the vendor's devices does
+            if !self.any_of_groups.iter().all(|group| group.matches_vendor(&names, vendor, self.match_mode)) {
+                continue;
+            }
+
+            // Likewise for words that are not in the vendor name
+            if !self.token_filters.is_empty() {
+                let vendor_name = names.vendor_name(vendor);
+                if !self.token_filters.iter().all(|token| token.matches(&vendor_name, self.match_mode))
+                    && !vendor.devices().iter().any(|device| self.tokens_match(&names, vendor, device))
+                {
+                    continue;
+                }
+            }
+
+            results.push(vendor);
+        }
+
+        results
+    }
+
+    /// Execute the query and return matching class results.
+    pub fn execute_classes(&self) -> Vec<ClassMatch<'db>> {
+        let mut results = Vec::new();
 
         for class in self.database.classes() {
             // Check class filters
@@ -187,8 +1175,9 @@ impl<'db> QueryBuilder<'db> {
                 }
             }
 
-            if let Some(ref class_name) = self.class_name_filter {
-                if !class.name().to_lowercase().contains(class_name) {
+            let class_name = self.database.resolve_class_name(class);
+            if let Some(ref class_name_filter) = self.class_name_filter {
+                if !class_name_filter.matches(&class_name, self.match_mode) {
                     continue;
                 }
             }
@@ -197,108 +1186,823 @@ impl<'db> QueryBuilder<'db> {
             let matching_subclasses: Vec<&SubClass> = class
                 .subclasses()
                 .iter()
-                .filter(|subclass| {
-                    if let Some(ref subclass_id) = self.subclass_id_filter {
-                        if subclass.id() != *subclass_id {
-                            return false;
-                        }
-                    }
-
-                    if let Some(ref subclass_name) = self.subclass_name_filter {
-                        if !subclass.name().to_lowercase().contains(subclass_name) {
-                            return false;
-                        }
-                    }
-
-                    true
-                })
+                .filter(|subclass| self.subclass_matches(class.id(), subclass))
                 .collect();
 
             if self.has_subclass_filters() && matching_subclasses.is_empty() {
                 continue;
             }
 
+            let matching_prog_ifs = if self.has_subclass_filters() {
+                matching_subclasses
+                    .iter()
+                    .flat_map(|&subclass| subclass.prog_interfaces().iter().map(move |prog_if| (subclass, prog_if)))
+                    .filter(|(subclass, prog_if)| self.prog_if_matches(class.id(), subclass.id(), prog_if))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let class_name_spans = match self.class_name_filter {
+                Some(ref class_name_filter) if self.record_spans => class_name_filter.spans(&class_name, self.match_mode),
+                _ => Vec::new(),
+            };
+
             results.push(ClassMatch {
+                database: self.database,
                 class,
                 matching_subclasses,
+                matching_prog_ifs,
+                class_name_spans,
             });
         }
 
         results
     }
 
-    fn has_class_filters(&self) -> bool {
-        self.class_id_filter.is_some() || self.class_name_filter.is_some() || self.has_subclass_filters()
-    }
+    fn has_class_filters(&self) -> bool {
+        self.class_id_filter.is_some() || self.class_name_filter.is_some() || self.has_subclass_filters()
+    }
+
+    fn has_subclass_filters(&self) -> bool {
+        self.subclass_id_filter.is_some() || self.subclass_name_filter.is_some() || self.has_prog_if_filters()
+    }
+
+    fn has_prog_if_filters(&self) -> bool {
+        self.prog_if_id_filter.is_some() || self.prog_if_name_filter.is_some()
+    }
+
+    /// Get the vendors that can pass the vendor ID filters, found by binary
+    /// search in the ID-sorted vendor table where possible.
+    fn candidate_vendors(&self) -> &'db [Vendor] {
+        let vendors = self.database.vendors();
+
+        if let Some(vendor_id) = self.vendor_id_filter {
+            return match self.database.index_of_vendor(vendor_id) {
+                Some(index) => &vendors[index..=index],
+                None => &[],
+            };
+        }
+
+        if let Some(ref range) = self.vendor_id_range {
+            let start = vendors.partition_point(|vendor| vendor.id() < *range.start());
+            let end = vendors.partition_point(|vendor| vendor.id() <= *range.end());
+            return &vendors[start..end.max(start)];
+        }
+
+        vendors
+    }
+
+    fn vendor_matches(&self, vendor: &Vendor) -> bool {
+        if let Some(ref vendor_id) = self.vendor_id_filter {
+            if vendor.id() != *vendor_id {
+                return false;
+            }
+        }
+
+        if let Some(ref vendor_ids) = self.vendor_ids_filter {
+            if !vendor_ids.contains(&vendor.id()) {
+                return false;
+            }
+        }
+
+        if let Some(ref vendor_id_range) = self.vendor_id_range {
+            if !vendor_id_range.contains(&vendor.id()) {
+                return false;
+            }
+        }
+
+        let vendor_name = || self.database.resolve_vendor_name(vendor);
+        if let Some(ref vendor_name_filter) = self.vendor_name_filter {
+            if !vendor_name_filter.matches(&vendor_name(), self.match_mode) {
+                return false;
+            }
+        }
+
+        #[cfg(feature = "regex")]
+        if let Some(ref regex) = self.vendor_name_regex {
+            if !regex.is_match(&vendor_name()) {
+                return false;
+            }
+        }
+
+        if self.excluded_vendor_ids.contains(&vendor.id())
+            || self.excluded_vendor_names.iter().any(|name| name.matches(&vendor_name(), self.match_mode))
+        {
+            return false;
+        }
+
+        true
+    }
+
+    fn device_matches(&self, names: &NameCache<'_>, vendor: &Vendor, device: &Device) -> bool {
+        if let Some(ref device_id) = self.device_id_filter {
+            if device.id() != *device_id {
+                return false;
+            }
+        }
+
+        if let Some(ref device_ids) = self.device_ids_filter {
+            if !device_ids.contains(&device.id()) {
+                return false;
+            }
+        }
+
+        if let Some(ref device_id_range) = self.device_id_range {
+            if !device_id_range.contains(&device.id()) {
+                return false;
+            }
+        }
+
+        let device_name = || names.device_name(vendor, device);
+        if let Some(ref device_name_filter) = self.device_name_filter {
+            let subsystem_matches = || {
+                self.include_subsystem_names
+                    && device.subsystems().iter().any(|subsystem| {
+                        device_name_filter.matches(&names.subsystem_name(vendor, device, subsystem), self.match_mode)
+                    })
+            };
+            if !device_name_filter.matches(&device_name(), self.match_mode) && !subsystem_matches() {
+                return false;
+            }
+        }
+
+        #[cfg(feature = "regex")]
+        if let Some(ref regex) = self.device_name_regex {
+            if !regex.is_match(&device_name()) {
+                return false;
+            }
+        }
+
+        if self.excluded_device_names.iter().any(|name| name.matches(&device_name(), self.match_mode)) {
+            return false;
+        }
+
+        true
+    }
+
+    /// Run the device, group, word, and subsystem filters for a device of a matching vendor.
+    fn device_passes(&self, names: &NameCache<'_>, vendor: &Vendor, device: &Device) -> bool {
+        if !self.device_matches(names, vendor, device)
+            || !self.any_of_matches(names, vendor, device)
+            || !self.tokens_match(names, vendor, device)
+        {
+            return false;
+        }
+
+        // Check subsystem filters
+        !self.has_subsystem_filters()
+            || device.subsystems().iter().any(|subsystem| self.subsystem_matches(names, vendor, device, subsystem))
+    }
+
+    fn tokens_match(&self, names: &NameCache<'_>, vendor: &Vendor, device: &Device) -> bool {
+        if self.token_filters.is_empty() {
+            return true;
+        }
+        let device_name = names.device_name(vendor, device);
+        let vendor_name = names.vendor_name(vendor);
+        self.token_filters.iter().all(|token| {
+            token.matches(&device_name, self.match_mode) || token.matches(&vendor_name, self.match_mode)
+        })
+    }
+
+    fn any_of_matches(&self, names: &NameCache<'_>, vendor: &Vendor, device: &Device) -> bool {
+        self.any_of_groups.iter().all(|group| group.matches(names, vendor, device, self.match_mode))
+    }
+
+    fn has_subsystem_filters(&self) -> bool {
+        self.subvendor_id_filter.is_some() || self.subdevice_id_filter.is_some() || self.subsystem_name_filter.is_some()
+    }
+
+    fn subsystem_matches(&self, names: &NameCache<'_>, vendor: &Vendor, device: &Device, subsystem: &Subsystem) -> bool {
+        if let Some(ref subvendor_id) = self.subvendor_id_filter {
+            if subsystem.subvendor_id() != *subvendor_id {
+                return false;
+            }
+        }
+
+        if let Some(ref subdevice_id) = self.subdevice_id_filter {
+            if subsystem.subdevice_id() != *subdevice_id {
+                return false;
+            }
+        }
+
+        if let Some(ref subsystem_name) = self.subsystem_name_filter {
+            if !subsystem_name.matches(&names.subsystem_name(vendor, device, subsystem), self.match_mode) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn class_matches(&self, class: &DeviceClass) -> bool {
+        if let Some(ref class_id) = self.class_id_filter {
+            if class.id() != *class_id {
+                return false;
+            }
+        }
+
+        if let Some(ref class_name) = self.class_name_filter {
+            if !class_name.matches(&self.database.resolve_class_name(class), self.match_mode) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn subclass_matches(&self, class_id: DeviceClassId, subclass: &SubClass) -> bool {
+        if let Some(ref subclass_id) = self.subclass_id_filter {
+            if subclass.id() != *subclass_id {
+                return false;
+            }
+        }
+
+        if let Some(ref subclass_name) = self.subclass_name_filter {
+            if !subclass_name.matches(&self.database.resolve_subclass_name(class_id, subclass), self.match_mode) {
+                return false;
+            }
+        }
+
+        if self.has_prog_if_filters()
+            && !subclass.prog_interfaces().iter().any(|prog_if| self.prog_if_matches(class_id, subclass.id(), prog_if))
+        {
+            return false;
+        }
+
+        true
+    }
+
+    fn prog_if_matches(&self, class_id: DeviceClassId, subclass_id: SubClassId, prog_if: &ProgInterface) -> bool {
+        if let Some(ref prog_if_id) = self.prog_if_id_filter {
+            if prog_if.id() != *prog_if_id {
+                return false;
+            }
+        }
+
+        if let Some(ref prog_if_name) = self.prog_if_name_filter {
+            let name = self.database.resolve_prog_interface_name(class_id, subclass_id, prog_if);
+            if !prog_if_name.matches(&name, self.match_mode) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn find_matching_class(&self) -> Option<&'db DeviceClass> {
+        self.database.classes().iter().find(|class| {
+            self.class_matches(class)
+                && (!self.has_subclass_filters()
+                    || class.subclasses().iter().any(|subclass| self.subclass_matches(class.id(), subclass)))
+        })
+    }
+
+    fn find_inferred_class(&self, names: &NameCache<'_>, vendor: &Vendor, device: &Device) -> Option<&'db DeviceClass> {
+        let inferred = names.infer_class(vendor, device)?;
+        let class = self.database.find_class(inferred.class_id)?;
+
+        if !self.class_matches(class) {
+            return None;
+        }
+
+        if self.has_subclass_filters() {
+            let subclass = class.find_subclass(inferred.subclass_id?)?;
+            if !self.subclass_matches(class.id(), subclass) {
+                return None;
+            }
+        }
+
+        Some(class)
+    }
+}
+
+/// A group of alternative filters for [`QueryBuilder::any_of`].
+///
+/// Each method adds one alternative; the group matches a device if any
+/// alternative does.
+#[derive(Debug, Clone, Default)]
+pub struct AnyOf {
+    alternatives: Vec<Alternative>,
+}
+
+#[derive(Debug, Clone)]
+enum Alternative {
+    VendorId(VendorId),
+    VendorName(NameFilter),
+    DeviceId(VendorId, DeviceId),
+    DeviceName(NameFilter),
+}
+
+impl AnyOf {
+    /// Match devices from a vendor.
+    pub fn vendor_id(mut self, vendor_id: VendorId) -> Self {
+        self.alternatives.push(Alternative::VendorId(vendor_id));
+        self
+    }
+
+    /// Match devices whose vendor name contains `name` (case-insensitive).
+    pub fn vendor_name_contains(mut self, name: &str) -> Self {
+        self.alternatives.push(Alternative::VendorName(NameFilter::new(name)));
+        self
+    }
+
+    /// Match one device of one vendor.
+    pub fn device(mut self, vendor_id: VendorId, device_id: DeviceId) -> Self {
+        self.alternatives.push(Alternative::DeviceId(vendor_id, device_id));
+        self
+    }
+
+    /// Match devices whose name contains `name` (case-insensitive).
+    pub fn device_name_contains(mut self, name: &str) -> Self {
+        self.alternatives.push(Alternative::DeviceName(NameFilter::new(name)));
+        self
+    }
+
+    fn matches(&self, names: &NameCache<'_>, vendor: &Vendor, device: &Device, mode: MatchMode) -> bool {
+        self.alternatives.iter().any(|alternative| match alternative {
+            Alternative::VendorId(vendor_id) => vendor.id() == *vendor_id,
+            Alternative::VendorName(name) => name.matches(&names.vendor_name(vendor), mode),
+            Alternative::DeviceId(vendor_id, device_id) => vendor.id() == *vendor_id && device.id() == *device_id,
+            Alternative::DeviceName(name) => name.matches(&names.device_name(vendor, device), mode),
+        })
+    }
+
+    fn matches_vendor(&self, names: &NameCache<'_>, vendor: &Vendor, mode: MatchMode) -> bool {
+        self.alternatives.iter().any(|alternative| match alternative {
+            Alternative::VendorId(vendor_id) => vendor.id() == *vendor_id,
+            Alternative::VendorName(name) => name.matches(&names.vendor_name(vendor), mode),
+            Alternative::DeviceId(vendor_id, device_id) => vendor.id() == *vendor_id && vendor.has_device(*device_id),
+            Alternative::DeviceName(name) => {
+                vendor.devices().iter().any(|device| name.matches(&names.device_name(vendor, device), mode))
+            }
+        })
+    }
+}
+
+/// How a name matched a vendor in [`PciDatabase::vendor_by_name`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VendorNameMatch {
+    /// The full or canonical name
+    Name,
+    /// One of the bracketed aliases
+    Alias,
+}
+
+/// Intersect an optional range filter with `low..=high`.
+fn narrow<T: Ord + Copy>(range: &mut Option<RangeInclusive<T>>, low: T, high: T) {
+    *range = Some(match range.take() {
+        Some(existing) => (*existing.start()).max(low)..=(*existing.end()).min(high),
+        None => low..=high,
+    });
+}
+
+/// Parse one side of an ID string into the inclusive range of IDs it selects.
+///
+/// Returns `None` for a side that selects every ID.
+fn parse_id_pattern(pattern: &str) -> PciResult<Option<(u16, u16)>> {
+    let (digits, wildcard) = match pattern.strip_suffix('*') {
+        Some(digits) => (digits, true),
+        None => (pattern, false),
+    };
+    if digits.is_empty() {
+        return Ok(None);
+    }
+    // `from_str_radix` would also accept a leading sign
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(PciError::InvalidHexValue);
+    }
+    if digits.len() > 4 {
+        return Err(PciError::InvalidFormat);
+    }
+    let value = u16::from_str_radix(digits, 16).map_err(|_| PciError::InvalidHexValue)?;
+
+    if wildcard {
+        // The digits are the leading digits of a four-digit ID
+        let free_bits = 4 * (4 - digits.len() as u32);
+        let low = value.checked_shl(free_bits).unwrap_or(0);
+        let high = low | u16::MAX.checked_shr(16 - free_bits).unwrap_or(0);
+        Ok(Some((low, high)))
+    } else {
+        Ok(Some((value, value)))
+    }
+}
+
+/// Add the non-empty byte ranges matched by `regex` to `spans`, keeping them in order.
+#[cfg(feature = "regex")]
+fn add_regex_spans(spans: &mut Vec<Range<usize>>, name: &str, regex: Option<&regex_lite::Regex>) {
+    if let Some(regex) = regex {
+        spans.extend(regex.find_iter(name).map(|found| found.range()).filter(|range| !range.is_empty()));
+        spans.sort_by_key(|span| (span.start, span.end));
+    }
+}
+
+/// A name substring filter, kept as given and lowercased.
+#[derive(Debug, Clone)]
+struct NameFilter {
+    text: String,
+    folded: String,
+    /// Substring searchers for `text` and `folded`, built once per query
+    #[cfg(feature = "memchr")]
+    finders: (Finder<'static>, Finder<'static>),
+}
+
+impl NameFilter {
+    fn new(text: &str) -> Self {
+        let folded = text.to_lowercase();
+        Self {
+            #[cfg(feature = "memchr")]
+            finders: (Finder::new(text).into_owned(), Finder::new(&folded).into_owned()),
+            text: text.to_string(),
+            folded,
+        }
+    }
+
+    fn matches(&self, name: &str, mode: MatchMode) -> bool {
+        if mode.whole_words {
+            return self.matches_whole_word(name, mode.case_sensitive);
+        }
+        if mode.case_sensitive {
+            return self.contains(name);
+        }
+
+        #[cfg(feature = "memchr")]
+        {
+            let mut buffer = [0u8; 256];
+            if let Some(lowered) = ascii_lowercase(name, &mut buffer) {
+                return self.finders.1.find(lowered).is_some();
+            }
+        }
+        contains_ignore_case(name, &self.folded)
+    }
+
+    #[cfg(feature = "memchr")]
+    fn contains(&self, name: &str) -> bool {
+        self.finders.0.find(name.as_bytes()).is_some()
+    }
+
+    #[cfg(not(feature = "memchr"))]
+    fn contains(&self, name: &str) -> bool {
+        name.contains(self.text.as_str())
+    }
+
+    fn matches_whole_word(&self, name: &str, case_sensitive: bool) -> bool {
+        let mode = MatchMode { case_sensitive, whole_words: true };
+        name.char_indices()
+            .map(|(start, _)| start)
+            .chain([name.len()])
+            .any(|start| self.match_at(name, start, mode).is_some())
+    }
+
+    /// Get the byte ranges of all non-overlapping, non-empty matches in `name`.
+    fn spans(&self, name: &str, mode: MatchMode) -> Vec<Range<usize>> {
+        let mut spans = Vec::new();
+        if self.text.is_empty() {
+            return spans;
+        }
+
+        let mut next = 0;
+        for (start, _) in name.char_indices() {
+            if start < next {
+                continue;
+            }
+            if let Some(len) = self.match_at(name, start, mode) {
+                spans.push(start..start + len);
+                next = start + len;
+            }
+        }
+        spans
+    }
+
+    /// Get the byte length of the match starting at `start` in `name`, if any.
+    fn match_at(&self, name: &str, start: usize, mode: MatchMode) -> Option<usize> {
+        let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+
+        if mode.whole_words && is_word(name[..start].chars().next_back()) && is_word(self.text.chars().next()) {
+            return None;
+        }
+        let len = self.match_len(&name[start..], mode.case_sensitive)?;
+        if mode.whole_words && is_word(name[start + len..].chars().next()) && is_word(self.text.chars().next_back()) {
+            return None;
+        }
+        Some(len)
+    }
+
+    /// Get the byte length of the match if `rest` starts with this filter.
+    fn match_len(&self, rest: &str, case_sensitive: bool) -> Option<usize> {
+        if case_sensitive {
+            return rest.starts_with(self.text.as_str()).then_some(self.text.len());
+        }
+
+        let mut needle = self.folded.chars().peekable();
+        for (offset, c) in rest.char_indices() {
+            if needle.peek().is_none() {
+                return Some(offset);
+            }
+            for lower in c.to_lowercase() {
+                if needle.next() != Some(lower) {
+                    return None;
+                }
+            }
+        }
+        needle.peek().is_none().then_some(rest.len())
+    }
+}
+
+/// How name filters compare text.
+#[derive(Debug, Clone, Copy, Default)]
+struct MatchMode {
+    case_sensitive: bool,
+    whole_words: bool,
+}
+
+/// The key that [`QueryBuilder::order_by`] sorts devices by.
+///
+/// Names are compared ignoring case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderBy {
+    /// Vendor ID, then device ID (the database order)
+    VendorId,
+    /// Device ID, then vendor ID
+    DeviceId,
+    /// Vendor name
+    VendorName,
+    /// Device name
+    DeviceName,
+}
+
+impl OrderBy {
+    fn compare(
+        self,
+        (database_a, vendor_a, device_a): (&PciDatabase, &Vendor, &Device),
+        (database_b, vendor_b, device_b): (&PciDatabase, &Vendor, &Device),
+    ) -> Ordering {
+        match self {
+            OrderBy::VendorId => (vendor_a.id(), device_a.id()).cmp(&(vendor_b.id(), device_b.id())),
+            OrderBy::DeviceId => (device_a.id(), vendor_a.id()).cmp(&(device_b.id(), vendor_b.id())),
+            OrderBy::VendorName => cmp_ignore_case(&database_a.resolve_vendor_name(vendor_a), &database_b.resolve_vendor_name(vendor_b)),
+            OrderBy::DeviceName => cmp_ignore_case(
+                &database_a.resolve_device_name(vendor_a.id(), device_a),
+                &database_b.resolve_device_name(vendor_b.id(), device_b),
+            ),
+        }
+    }
+}
+
+/// The direction of a [`QueryBuilder::order_by`] sort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    /// Smallest first
+    Ascending,
+    /// Largest first
+    Descending,
+}
+
+impl SortDirection {
+    fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    }
+}
+
+fn cmp_ignore_case(a: &str, b: &str) -> Ordering {
+    a.chars().flat_map(char::to_lowercase).cmp(b.chars().flat_map(char::to_lowercase))
+}
+
+/// Counts of matching devices per vendor and per class, from [`QueryBuilder::summarize`].
+#[derive(Debug, Clone)]
+pub struct QuerySummary<'db> {
+    /// The database that was queried, for resolving names
+    pub database: &'db PciDatabase,
+    /// The number of matching devices
+    pub device_count: usize,
+    /// The number of matching devices of each vendor, in vendor ID order
+    pub vendors: Vec<(&'db Vendor, usize)>,
+    /// The number of matching devices of each class, in class ID order
+    pub classes: Vec<(&'db DeviceClass, usize)>,
+    /// The number of matching devices without a known class
+    pub unclassified: usize,
+}
+
+impl QuerySummary<'_> {
+    /// Get the number of vendors with matching devices.
+    pub fn vendor_count(&self) -> usize {
+        self.vendors.len()
+    }
+
+    /// Get the number of classes with matching devices.
+    pub fn class_count(&self) -> usize {
+        self.classes.len()
+    }
+}
+
+impl core::fmt::Display for QuerySummary<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} devices across {} vendors", self.device_count, self.vendor_count())?;
+        for (class, count) in &self.classes {
+            write!(f, "\n  {}: {}", self.database.resolve_class_name(class), count)?;
+        }
+        if self.unclassified > 0 {
+            write!(f, "\n  Unclassified: {}", self.unclassified)?;
+        }
+        Ok(())
+    }
+}
+
+/// How a query finds the vendors it scans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VendorAccess {
+    /// A single vendor found by binary search
+    BinarySearch,
+    /// A contiguous range of vendor IDs found by binary search
+    RangeSearch,
+    /// Every vendor in the database
+    FullScan,
+}
 
-    fn has_subclass_filters(&self) -> bool {
-        self.subclass_id_filter.is_some() || self.subclass_name_filter.is_some()
-    }
+/// A description of how a query would run, from [`QueryBuilder::explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlan {
+    /// The builder methods whose filters are set
+    pub filters: Vec<&'static str>,
+    /// How candidate vendors are found
+    pub vendor_access: VendorAccess,
+    /// The number of vendors visited
+    pub vendors_scanned: usize,
+    /// The number of devices visited (those of vendors passing the vendor filters)
+    pub devices_scanned: usize,
+    /// A rough cost in entries visited and names compared
+    pub estimated_cost: usize,
+    /// Suggestions for making the query cheaper
+    pub hints: Vec<&'static str>,
+}
 
-    fn find_matching_class(&self) -> Option<&'db DeviceClass> {
-        for class in self.database.classes() {
-            if let Some(ref class_id) = self.class_id_filter {
-                if class.id() != *class_id {
-                    continue;
-                }
-            }
+impl core::fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Query plan:")?;
+        if self.filters.is_empty() {
+            write!(f, "\n  Filters: none")?;
+        } else {
+            write!(f, "\n  Filters: {}", self.filters.join(", "))?;
+        }
+        let access = match self.vendor_access {
+            VendorAccess::BinarySearch => "binary search",
+            VendorAccess::RangeSearch => "range search",
+            VendorAccess::FullScan => "full scan",
+        };
+        write!(f, "\n  Vendor access: {}", access)?;
+        write!(f, "\n  Vendors scanned: {}", self.vendors_scanned)?;
+        write!(f, "\n  Devices scanned: {}", self.devices_scanned)?;
+        write!(f, "\n  Estimated cost: {}", self.estimated_cost)?;
+        for hint in &self.hints {
+            write!(f, "\n  Hint: {}", hint)?;
+        }
+        Ok(())
+    }
+}
 
-            if let Some(ref class_name) = self.class_name_filter {
-                if !class.name().to_lowercase().contains(class_name) {
-                    continue;
-                }
-            }
+/// A class code pattern: class, and optionally subclass and programming interface.
+type ClassCodePattern = (DeviceClassId, Option<SubClassId>, Option<ProgInterfaceId>);
 
-            // Check if any subclass matches
-            if self.has_subclass_filters() {
-                let has_matching_subclass = class.subclasses().iter().any(|subclass| {
-                    if let Some(ref subclass_id) = self.subclass_id_filter {
-                        if subclass.id() != *subclass_id {
-                            return false;
-                        }
-                    }
+/// A query prepared for matching devices found during enumeration.
+///
+/// Name filters are lowercased and regular expressions compiled when the
+/// builder methods are called; compiling additionally resolves the class,
+/// subclass, and programming interface filters to the set of class codes they
+/// accept. [`match_device`](Self::match_device) then checks a live
+/// `(vendor, device, class code)` tuple with a vendor binary search and no
+/// scan of the class table, and class filters apply to the class code the
+/// device reports rather than to inferred classes.
+///
+/// Offset, limit, and ordering only apply to the database-wide terminals,
+/// which remain available through [`query`](Self::query).
+///
+/// # Examples
+///
+/// ```rust
+/// use ids_rs::{ClassCode, DeviceId, PciDatabase, VendorId};
+///
+/// let db = PciDatabase::get();
+/// let nics = db.query().vendor_name_contains("Intel").class_name_contains("Network").compile();
+///
+/// // For every function found while enumerating the bus:
+/// let class_code = ClassCode::from((0x02, 0x00, 0x00));
+/// if let Some(device_match) = nics.match_device(VendorId::new(0x8086), DeviceId::new(0x1533), class_code) {
+///     println!("found {}", device_match.description());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompiledQuery<'db> {
+    query: QueryBuilder<'db>,
+    /// The class codes accepted by the class filters, if there are any
+    class_codes: Option<Vec<ClassCodePattern>>,
+}
 
-                    if let Some(ref subclass_name) = self.subclass_name_filter {
-                        if !subclass.name().to_lowercase().contains(subclass_name) {
-                            return false;
-                        }
-                    }
+impl<'db> CompiledQuery<'db> {
+    /// Get the query this was compiled from, to run its database-wide terminals.
+    pub fn query(&self) -> &QueryBuilder<'db> {
+        &self.query
+    }
 
-                    true
-                });
+    /// Check if a device found at runtime passes the query.
+    ///
+    /// Devices missing from the database never match.
+    pub fn matches(&self, vendor_id: VendorId, device_id: DeviceId, class_code: ClassCode) -> bool {
+        self.find(vendor_id, device_id, class_code).is_some()
+    }
 
-                if !has_matching_subclass {
-                    continue;
-                }
-            }
+    /// Match a device found at runtime, returning its details if it passes the query.
+    ///
+    /// The match's class is the one `class_code` names. Devices missing from
+    /// the database never match.
+    pub fn match_device(&self, vendor_id: VendorId, device_id: DeviceId, class_code: ClassCode) -> Option<DeviceMatch<'db>> {
+        let (vendor, device) = self.find(vendor_id, device_id, class_code)?;
+        let class_info = self.query.database.find_class(class_code.class_id());
+        Some(self.query.device_match(&NameCache::new(self.query.database), vendor, device, class_info))
+    }
 
-            return Some(class);
+    fn find(&self, vendor_id: VendorId, device_id: DeviceId, class_code: ClassCode) -> Option<(&'db Vendor, &'db Device)> {
+        if !self.class_code_matches(class_code) {
+            return None;
         }
 
-        None
+        // Uses the perfect-hash index when the database has one
+        let device = self.query.database.find_device(vendor_id, device_id)?;
+        let vendor = self.query.database.find_vendor(vendor_id)?;
+        let names = NameCache::new(self.query.database);
+        (self.query.vendor_matches(vendor) && self.query.device_passes(&names, vendor, device)).then_some((vendor, device))
+    }
+
+    fn class_code_matches(&self, class_code: ClassCode) -> bool {
+        let Some(ref patterns) = self.class_codes else {
+            return true;
+        };
+        patterns.iter().any(|&(class_id, subclass_id, prog_if_id)| {
+            class_id == class_code.class_id()
+                && subclass_id.is_none_or(|id| id == class_code.subclass_id())
+                && prog_if_id.is_none_or(|id| id == class_code.prog_interface_id())
+        })
     }
 }
 
 /// A device match result from a query.
 #[derive(Debug)]
 pub struct DeviceMatch<'db> {
+    /// The database the match came from, for resolving names
+    pub database: &'db PciDatabase,
     /// The matching vendor
     pub vendor: &'db Vendor,
     /// The matching device
     pub device: &'db Device,
     /// Optional class information if class filters were used
     pub class_info: Option<&'db DeviceClass>,
+    /// The subclass selected by the subclass filters (only with
+    /// [`QueryBuilder::full_descriptions`])
+    pub subclass_info: Option<&'db SubClass>,
+    /// The programming interface selected by the programming interface
+    /// filters (only with [`QueryBuilder::full_descriptions`])
+    pub prog_if_info: Option<&'db ProgInterface>,
+    /// The first subsystem selected by the subsystem filters (only with
+    /// [`QueryBuilder::full_descriptions`])
+    pub subsystem_info: Option<&'db Subsystem>,
+    /// Subsystems that matched the query (empty if no subsystem filters were used)
+    pub matching_subsystems: Vec<&'db Subsystem>,
+    /// Byte ranges of the vendor name matched by name filters (empty unless
+    /// [`QueryBuilder::record_match_spans`] was used)
+    pub vendor_name_spans: Vec<Range<usize>>,
+    /// Byte ranges of the device name matched by name filters (empty unless
+    /// [`QueryBuilder::record_match_spans`] was used)
+    pub device_name_spans: Vec<Range<usize>>,
 }
 
 impl<'db> DeviceMatch<'db> {
+    /// Create a match for a device with no class, subsystem, or span details.
+    pub(crate) fn new(database: &'db PciDatabase, vendor: &'db Vendor, device: &'db Device) -> Self {
+        Self {
+            database,
+            vendor,
+            device,
+            class_info: None,
+            subclass_info: None,
+            prog_if_info: None,
+            subsystem_info: None,
+            matching_subsystems: Vec::new(),
+            vendor_name_spans: Vec::new(),
+            device_name_spans: Vec::new(),
+        }
+    }
+
     /// Get the vendor ID.
     pub fn vendor_id(&self) -> VendorId {
         self.vendor.id()
     }
 
-    /// Get the vendor name.
-    pub fn vendor_name(&self) -> &'static str {
-        self.vendor.name()
+    /// Get the vendor name, decompressed under the `compressed-names` feature.
+    pub fn vendor_name(&self) -> Cow<'static, str> {
+        self.database.resolve_vendor_name(self.vendor)
     }
 
     /// Get the device ID.
@@ -306,33 +2010,56 @@ impl<'db> DeviceMatch<'db> {
         self.device.id()
     }
 
-    /// Get the device name.
-    pub fn device_name(&self) -> &'static str {
-        self.device.name()
+    /// Get the device name, decompressed under the `compressed-names` feature.
+    pub fn device_name(&self) -> Cow<'static, str> {
+        self.database.resolve_device_name(self.vendor.id(), self.device)
     }
 
     /// Get a formatted description of this device match.
+    ///
+    /// Includes the subclass, programming interface, and subsystem when the
+    /// query selected them with [`QueryBuilder::full_descriptions`].
     pub fn description(&self) -> String {
-        if let Some(class) = self.class_info {
+        let mut description = if let Some(class) = self.class_info {
             alloc::format!(
                 "{} {} ({})",
                 self.vendor_name(),
                 self.device_name(),
-                class.name()
+                self.database.resolve_class_description(
+                    class,
+                    self.subclass_info.map(SubClass::id),
+                    self.prog_if_info.map(ProgInterface::id)
+                )
             )
         } else {
             alloc::format!("{} {}", self.vendor_name(), self.device_name())
+        };
+
+        if let Some(subsystem) = self.subsystem_info {
+            let subsystem_name = self.database.resolve_subsystem_name(self.vendor.id(), self.device.id(), subsystem);
+            description = alloc::format!("{} [{}]", description, subsystem_name);
         }
+
+        description
     }
 }
 
 /// A class match result from a query.
 #[derive(Debug)]
 pub struct ClassMatch<'db> {
+    /// The database the match came from, for resolving names
+    pub database: &'db PciDatabase,
     /// The matching class
     pub class: &'db DeviceClass,
     /// Subclasses that matched the query (empty if no subclass filters were used)
     pub matching_subclasses: Vec<&'db SubClass>,
+    /// Programming interfaces of the matching subclasses that matched the
+    /// query, with their subclass (empty if no subclass or programming
+    /// interface filters were used)
+    pub matching_prog_ifs: Vec<(&'db SubClass, &'db ProgInterface)>,
+    /// Byte ranges of the class name matched by the name filter (empty unless
+    /// [`QueryBuilder::record_match_spans`] was used)
+    pub class_name_spans: Vec<Range<usize>>,
 }
 
 impl<'db> ClassMatch<'db> {
@@ -341,20 +2068,20 @@ impl<'db> ClassMatch<'db> {
         self.class.id()
     }
 
-    /// Get the class name.
-    pub fn class_name(&self) -> &'static str {
-        self.class.name()
+    /// Get the class name, decompressed under the `compressed-names` feature.
+    pub fn class_name(&self) -> Cow<'static, str> {
+        self.database.resolve_class_name(self.class)
     }
 
     /// Get a formatted description of this class match.
     pub fn description(&self) -> String {
         if self.matching_subclasses.is_empty() {
-            self.class_name().to_string()
+            self.class_name().into_owned()
         } else {
-            let subclass_names: Vec<&str> = self
+            let subclass_names: Vec<Cow<'static, str>> = self
                 .matching_subclasses
                 .iter()
-                .map(|sc| sc.name())
+                .map(|sc| self.database.resolve_subclass_name(self.class.id(), sc))
                 .collect();
             alloc::format!("{} ({})", self.class_name(), subclass_names.join(", "))
         }
@@ -379,10 +2106,118 @@ impl PciDatabase {
 
     /// Find all devices of a specific class.
     ///
-    /// Note: This returns device IDs that could belong to the class, but
-    /// actual class assignment depends on the device's configuration.
+    /// pci.ids does not record device classes, so this is best-effort: devices are
+    /// classified with the curated ranges and name heuristics in [`class_map`](crate::class_map).
+    /// Devices with uninformative names are missed, and the actual class of a
+    /// device is whatever its configuration space reports.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    /// use ids_rs::classes::well_known::NETWORK;
+    ///
+    /// let db = PciDatabase::get();
+    /// for nic in db.devices_by_class(NETWORK) {
+    ///     println!("{}", nic.description());
+    /// }
+    /// ```
     pub fn devices_by_class(&self, class_id: DeviceClassId) -> Vec<DeviceMatch<'_>> {
-        QueryBuilder::new(self).class_id(class_id).execute()
+        QueryBuilder::new(self)
+            .class_id(class_id)
+            .infer_device_classes()
+            .execute()
+    }
+
+    /// Look up devices by an lspci-style `vendor:device` ID string.
+    ///
+    /// See [`QueryBuilder::id_str`] for the accepted formats, including wildcards.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{pci_database, PciDatabase};
+    ///
+    /// static DB: PciDatabase = pci_database! {
+    ///     vendors {
+    ///         0x8086 "Intel Corporation" {
+    ///             0x10d3 "82574L Gigabit Network Connection",
+    ///             0x1533 "I210 Gigabit Network Connection",
+    ///         },
+    ///     }
+    ///     classes {}
+    /// };
+    ///
+    /// assert_eq!(DB.lookup_str("8086:1533").unwrap().len(), 1);
+    /// assert_eq!(DB.lookup_str("8086:*").unwrap().len(), 2);
+    /// ```
+    pub fn lookup_str(&self, ids: &str) -> PciResult<Vec<DeviceMatch<'_>>> {
+        Ok(self.query().id_str(ids)?.execute())
+    }
+
+    /// Find a vendor by its exact name, ignoring case.
+    ///
+    /// Unlike [`search_vendors`](Self::search_vendors), this does not match
+    /// substrings. The full name, the [canonical name](Vendor::canonical_name),
+    /// and the [aliases](Vendor::aliases) are all accepted, with full and
+    /// canonical names taking priority over aliases. Several vendors can share a
+    /// name (pci.ids lists more than one "3Com Corporation"); the one with the
+    /// lowest ID is returned, and [`vendors_by_name`](Self::vendors_by_name)
+    /// returns all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// if let Some(vendor) = db.vendor_by_name("intel corporation") {
+    ///     println!("{}: {}", vendor.id(), vendor.name());
+    /// }
+    /// ```
+    pub fn vendor_by_name(&self, name: &str) -> Option<&Vendor> {
+        let mut alias_match = None;
+        for vendor in self.vendors() {
+            match self.vendor_name_match(vendor, name) {
+                Some(VendorNameMatch::Name) => return Some(vendor),
+                Some(VendorNameMatch::Alias) => alias_match = alias_match.or(Some(vendor)),
+                None => {}
+            }
+        }
+        alias_match
+    }
+
+    /// Find all vendors with exactly the given name or alias, ignoring case.
+    pub fn vendors_by_name(&self, name: &str) -> Vec<&Vendor> {
+        self.vendors()
+            .iter()
+            .filter(|vendor| self.vendor_name_match(vendor, name).is_some())
+            .collect()
+    }
+
+    /// Find a device of a vendor by its exact name, ignoring case.
+    pub fn device_by_name(&self, vendor_id: VendorId, name: &str) -> Option<&Device> {
+        let vendor = self.find_vendor(vendor_id)?;
+        vendor
+            .devices()
+            .iter()
+            .find(|device| eq_ignore_case(&self.resolve_device_name(vendor_id, device), name))
+    }
+
+    /// Check how `name` matches a vendor's full or canonical name, or one of its aliases.
+    ///
+    /// This works on the decompressed name, as the canonical name and aliases
+    /// stored with the vendor are empty when names are compressed.
+    fn vendor_name_match(&self, vendor: &Vendor, name: &str) -> Option<VendorNameMatch> {
+        let full_name = self.resolve_vendor_name(vendor);
+        let (canonical_name, alias_text) = split_aliases(&full_name);
+        if eq_ignore_case(&full_name, name) || eq_ignore_case(canonical_name, name) {
+            Some(VendorNameMatch::Name)
+        } else if alias_list(alias_text).any(|alias| eq_ignore_case(alias, name)) {
+            Some(VendorNameMatch::Alias)
+        } else {
+            None
+        }
     }
 
     /// Search for vendors by name (case-insensitive).
@@ -417,6 +2252,97 @@ impl PciDatabase {
             .execute()
     }
 
+    /// Search for devices whose vendor and device names contain all words of `text`, in any order.
+    ///
+    /// See [`QueryBuilder::search_tokens`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let nics = db.search_tokens("intel 10-gigabit sfp");
+    /// ```
+    pub fn search_tokens(&self, text: &str) -> Vec<DeviceMatch<'_>> {
+        QueryBuilder::new(self)
+            .search_tokens(text)
+            .execute()
+    }
+
+    /// Search for devices by subsystem name (case-insensitive).
+    ///
+    /// The subsystems whose names matched are listed in each result's
+    /// [`matching_subsystems`](DeviceMatch::matching_subsystems).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// for device_match in db.search_subsystems("ThinkPad") {
+    ///     for subsystem in &device_match.matching_subsystems {
+    ///         println!("{} ({})", subsystem.name(), device_match.device_name());
+    ///     }
+    /// }
+    /// ```
+    pub fn search_subsystems(&self, name: &str) -> Vec<DeviceMatch<'_>> {
+        QueryBuilder::new(self)
+            .subsystem_name_contains(name)
+            .execute()
+    }
+
+    /// Find vendors whose name, or a bracketed part of it, starts with a prefix.
+    ///
+    /// This is the vendor half of [`names_with_prefix`](Self::names_with_prefix):
+    /// matching is ASCII case-insensitive and uses the build-time name index, so
+    /// it suits autocomplete where substring search is too noisy and too slow.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// for vendor in db.vendors_with_prefix("Intel") {
+    ///     println!("{:04x} {}", vendor.id().value(), vendor.name());
+    /// }
+    /// ```
+    pub fn vendors_with_prefix(&self, prefix: &str) -> Vec<&Vendor> {
+        self.names_with_prefix(prefix)
+            .into_iter()
+            .filter_map(|name_match| match name_match {
+                NameMatch::Vendor(vendor) => Some(vendor),
+                NameMatch::Device(..) => None,
+            })
+            .collect()
+    }
+
+    /// Find devices whose name, or a bracketed part of it, starts with a prefix.
+    ///
+    /// See [`vendors_with_prefix`](Self::vendors_with_prefix).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// for device_match in db.devices_with_prefix("GeForce RTX") {
+    ///     println!("{}", device_match.description());
+    /// }
+    /// ```
+    pub fn devices_with_prefix(&self, prefix: &str) -> Vec<DeviceMatch<'_>> {
+        self.names_with_prefix(prefix)
+            .into_iter()
+            .filter_map(|name_match| match name_match {
+                NameMatch::Vendor(_) => None,
+                NameMatch::Device(vendor, device) => Some(DeviceMatch::new(self, vendor, device)),
+            })
+            .collect()
+    }
+
     /// Search for device classes by name (case-insensitive).
     ///
     /// # Examples
@@ -457,7 +2383,6 @@ impl PciDatabase {
 mod tests {
     use super::*;
     use crate::vendors::Vendor;
-    use crate::devices::Device;
     use crate::classes::DeviceClass;
 
     #[test]
@@ -481,4 +2406,825 @@ mod tests {
         assert!(db.search_devices("test").is_empty());
         assert!(db.search_classes("test").is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_exact_name_lookups() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Acme Corporation" {
+                    0x0001 "Widget Pro",
+                    0x0002 "Widget",
+                },
+                0x2000 "Acme",
+                0x3000 "ACME",
+            }
+            classes {}
+        };
+
+        assert_eq!(db.vendor_by_name("acme").map(|v| v.id()), Some(VendorId::new(0x2000)));
+        assert_eq!(db.vendor_by_name("ACME CORPORATION").map(|v| v.id()), Some(VendorId::new(0x1000)));
+        assert!(db.vendor_by_name("Acme Corp").is_none());
+        assert_eq!(db.vendors_by_name("Acme").len(), 2);
+        assert_eq!(db.search_vendors("acme").len(), 3);
+        let aliased = crate::pci_database! {
+            vendors {
+                0x1002 "Advanced Micro Devices, Inc. [AMD/ATI]",
+                0x1022 "Advanced Micro Devices, Inc. [AMD]",
+                0x2000 "AMD",
+            }
+            classes {}
+        };
+        // A full name beats an alias, even at a higher ID
+        assert_eq!(aliased.vendor_by_name("amd").map(|v| v.id()), Some(VendorId::new(0x2000)));
+        assert_eq!(aliased.vendor_by_name("ATI").map(|v| v.id()), Some(VendorId::new(0x1002)));
+        assert_eq!(aliased.vendors_by_name("AMD").len(), 3);
+
+        let widget = db.device_by_name(VendorId::new(0x1000), "WIDGET");
+        assert_eq!(widget.map(|d| d.id()), Some(DeviceId::new(0x0002)));
+        assert!(db.device_by_name(VendorId::new(0x2000), "Widget").is_none());
+        assert!(db.device_by_name(VendorId::new(0xffff), "Widget").is_none());
+    }
+
+    #[test]
+    fn test_execute_with_stops_early() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Test Vendor" {
+                    0x0001 "Ethernet Controller",
+                    0x0002 "Audio Device",
+                    0x0003 "Ethernet Adapter",
+                },
+            }
+            classes {}
+        };
+
+        let mut seen = Vec::new();
+        let flow = db.query().device_name_contains("ethernet").execute_with(|device_match| {
+            seen.push(device_match.device_id());
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(seen, [DeviceId::new(0x0001), DeviceId::new(0x0003)]);
+
+        let mut calls = 0;
+        let flow = db.query().execute_with(|device_match| {
+            calls += 1;
+            ControlFlow::Break(device_match.device_id())
+        });
+        assert_eq!(flow, ControlFlow::Break(DeviceId::new(0x0001)));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_subsystem_filters() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Test Vendor" {
+                    0x0001 "Ethernet Controller" {
+                        0x1028 0x0001 "PowerEdge NIC",
+                        0x1028 0x0002 "Precision NIC",
+                        0x103c 0x0001 "ProLiant NIC",
+                    },
+                    0x0002 "Audio Device",
+                },
+            }
+            classes {}
+        };
+        let subsystem_names = |results: &[DeviceMatch<'_>]| -> Vec<&str> {
+            results.iter().flat_map(|m| m.matching_subsystems.iter().map(|s| s.name())).collect()
+        };
+
+        let dell = db.query().subvendor_id(SubvendorId::new(0x1028)).execute();
+        assert_eq!(dell.len(), 1);
+        assert_eq!(subsystem_names(&dell), ["PowerEdge NIC", "Precision NIC"]);
+
+        // All subsystem filters must hold for the same subsystem
+        let results = db.query().subvendor_id(SubvendorId::new(0x103c)).subdevice_id(SubdeviceId::new(0x0001)).execute();
+        assert_eq!(subsystem_names(&results), ["ProLiant NIC"]);
+        assert!(db.query().subvendor_id(SubvendorId::new(0x103c)).subsystem_name_contains("poweredge").execute().is_empty());
+        assert_eq!(subsystem_names(&db.query().subsystem_name_contains("precision").execute()), ["Precision NIC"]);
+
+        // Without subsystem filters every device matches and none are listed
+        let all = db.query().execute();
+        assert_eq!(all.len(), 2);
+        assert!(all.iter().all(|m| m.matching_subsystems.is_empty()));
+    }
+
+    #[test]
+    fn test_count_is_empty_and_first() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Test Vendor" {
+                    0x0001 "Ethernet Controller" {
+                        0x1028 0x0001 "PowerEdge NIC",
+                    },
+                    0x0002 "Audio Device",
+                    0x0003 "Ethernet Adapter",
+                },
+            }
+            classes {}
+        };
+
+        assert_eq!(db.query().count(), 3);
+        assert_eq!(db.query().device_name_contains("ETHERNET").count(), 2);
+        assert_eq!(db.query().subvendor_id(SubvendorId::new(0x1028)).count(), 1);
+        assert!(!db.query().device_name_contains("audio").is_empty());
+        assert!(db.query().vendor_name_contains("other").is_empty());
+        assert!(PciDatabase::new(&[], &[]).query().is_empty());
+
+        let first = db.query().device_name_contains("ethernet").execute_first();
+        assert_eq!(first.map(|m| m.device_id()), Some(DeviceId::new(0x0001)));
+        let first = db.query().device_name_contains("adapter").execute_first();
+        assert_eq!(first.map(|m| m.device_name()).as_deref(), Some("Ethernet Adapter"));
+        assert!(db.query().device_name_contains("modem").execute_first().is_none());
+    }
+
+    #[test]
+    fn test_limit_and_offset() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Vendor A" {
+                    0x0001 "Device 1",
+                    0x0002 "Device 2",
+                    0x0003 "Device 3",
+                },
+                0x2000 "Vendor B" {
+                    0x0001 "Device 1",
+                    0x0002 "Device 2",
+                    0x0003 "Device 3",
+                },
+            }
+            classes {}
+        };
+        let page = |offset, limit| -> Vec<(u16, u16)> {
+            db.query()
+                .offset(offset)
+                .limit(limit)
+                .execute()
+                .iter()
+                .map(|m| (m.vendor_id().value(), m.device_id().value()))
+                .collect()
+        };
+
+        assert_eq!(page(0, 2), [(0x1000, 0x0001), (0x1000, 0x0002)]);
+        // Pages cross vendor boundaries
+        assert_eq!(page(2, 2), [(0x1000, 0x0003), (0x2000, 0x0001)]);
+        assert_eq!(page(5, 2), [(0x2000, 0x0003)]);
+        assert!(page(6, 2).is_empty());
+        assert!(page(0, 0).is_empty());
+
+        assert_eq!(db.query().offset(4).count(), 2);
+        assert_eq!(db.query().limit(4).count(), 4);
+        assert_eq!(db.query().device_name_contains("3").offset(1).execute_first().map(|m| m.vendor_id()), Some(VendorId::new(0x2000)));
+    }
+
+    #[test]
+    fn test_order_by() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Zeta Corp" {
+                    0x0001 "beta",
+                    0x0003 "Alpha",
+                },
+                0x2000 "Acme" {
+                    0x0002 "Gamma",
+                    0x0003 "alpha",
+                },
+            }
+            classes {}
+        };
+        let names = |key, direction| -> Vec<String> {
+            db.query().order_by(key, direction).execute().iter().map(|m| m.device_name().into_owned()).collect()
+        };
+
+        assert_eq!(names(OrderBy::VendorId, SortDirection::Ascending), ["beta", "Alpha", "Gamma", "alpha"]);
+        assert_eq!(names(OrderBy::VendorId, SortDirection::Descending), ["alpha", "Gamma", "Alpha", "beta"]);
+        assert_eq!(names(OrderBy::DeviceId, SortDirection::Ascending), ["beta", "Gamma", "Alpha", "alpha"]);
+        assert_eq!(names(OrderBy::VendorName, SortDirection::Ascending), ["Gamma", "alpha", "beta", "Alpha"]);
+        // Equal names keep database order
+        assert_eq!(names(OrderBy::DeviceName, SortDirection::Ascending), ["Alpha", "alpha", "beta", "Gamma"]);
+        assert_eq!(names(OrderBy::DeviceName, SortDirection::Descending), ["Gamma", "beta", "Alpha", "alpha"]);
+
+        // Pagination applies to the sorted results
+        let page = db.query().order_by(OrderBy::DeviceName, SortDirection::Ascending).offset(1).limit(2).execute();
+        let page: Vec<String> = page.iter().map(|m| m.device_name().into_owned()).collect();
+        assert_eq!(page, ["alpha", "beta"]);
+        let first = db.query().order_by(OrderBy::DeviceName, SortDirection::Descending).execute_first();
+        assert_eq!(first.map(|m| m.device_name()).as_deref(), Some("Gamma"));
+    }
+
+    #[test]
+    fn test_id_set_filters() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Vendor A" {
+                    0x0001 "Device 1",
+                    0x0002 "Device 2",
+                    0x0003 "Device 3",
+                },
+                0x2000 "Vendor B" {
+                    0x0001 "Device 1",
+                    0x0002 "Device 2",
+                    0x0003 "Device 3",
+                },
+                0x3000 "Vendor C" {
+                    0x0001 "Device 1",
+                    0x0002 "Device 2",
+                    0x0003 "Device 3",
+                },
+            }
+            classes {}
+        };
+        let (a, c) = (VendorId::new(0x1000), VendorId::new(0x3000));
+
+        assert_eq!(db.query().vendor_id_in(&[a, c]).count(), 6);
+        assert_eq!(db.query().vendor_id_in(&[c, a]).device_id_in(&[DeviceId::new(0x0002)]).count(), 2);
+        assert_eq!(db.query().vendor_id_in(&[a, c]).execute_vendors().len(), 2);
+        // Combined with a single-ID filter, both must hold
+        assert_eq!(db.query().vendor_id(a).vendor_id_in(&[c]).count(), 0);
+        assert_eq!(db.query().device_id_in(&[]).count(), 0);
+    }
+
+    #[test]
+    fn test_exclusions() {
+        let db = crate::pci_database! {
+            vendors {
+                0x10ec "Realtek Semiconductor Co., Ltd." {
+                    0x0001 "Fast Ethernet",
+                },
+                0x8086 "Intel Corporation" {
+                    0x0001 "Gigabit Ethernet",
+                    0x0002 "Gigabit Ethernet (engineering sample)",
+                },
+                0x9000 "Realtek Clone Inc." {
+                    0x0001 "Fast Ethernet",
+                },
+            }
+            classes {}
+        };
+
+        assert_eq!(db.query().exclude_vendor_id(VendorId::new(0x10ec)).count(), 3);
+        assert_eq!(db.query().exclude_vendor_name_contains("REALTEK").count(), 2);
+        assert_eq!(db.query().exclude_device_name_contains("sample").count(), 3);
+        // Exclusions accumulate
+        let results = db
+            .query()
+            .exclude_vendor_id(VendorId::new(0x10ec))
+            .exclude_vendor_id(VendorId::new(0x9000))
+            .exclude_device_name_contains("sample")
+            .execute();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].device_name(), "Gigabit Ethernet");
+        assert_eq!(db.query().exclude_vendor_name_contains("intel").execute_vendors().len(), 2);
+    }
+
+    #[test]
+    fn test_any_of() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1002 "AMD" {
+                    0x0001 "HD Graphics",
+                    0x0002 "Ethernet",
+                },
+                0x10de "NVIDIA" {
+                    0x0001 "HD Graphics",
+                    0x0002 "Ethernet",
+                },
+                0x8086 "Intel" {
+                    0x0001 "HD Graphics",
+                    0x0002 "Ethernet",
+                },
+            }
+            classes {}
+        };
+        let (amd, intel) = (VendorId::new(0x1002), VendorId::new(0x8086));
+
+        let results = db.query().any_of(|q| q.vendor_id(intel).vendor_id(amd)).device_name_contains("graphics").execute();
+        let vendors: Vec<VendorId> = results.iter().map(|m| m.vendor_id()).collect();
+        assert_eq!(vendors, [amd, intel]);
+
+        // Alternatives can mix levels, and groups AND together
+        let mixed = db.query().any_of(|q| q.vendor_name_contains("nvidia").device(amd, DeviceId::new(0x0002)));
+        assert_eq!(mixed.count(), 3);
+        let both = db
+            .query()
+            .any_of(|q| q.vendor_id(intel).vendor_id(amd))
+            .any_of(|q| q.device_name_contains("ethernet"));
+        assert_eq!(both.count(), 2);
+
+        assert_eq!(db.query().any_of(|q| q).count(), 0);
+        assert_eq!(db.query().any_of(|q| q.device(intel, DeviceId::new(0x0001))).execute_vendors().len(), 1);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_regex_filters() {
+        let db = crate::pci_database! {
+            vendors {
+                0x8086 "Intel Corporation" {
+                    0x1229 "82557/8/9/0/1 Ethernet Pro 100",
+                    0x1533 "I210 Gigabit Network Connection",
+                    0x2449 "82801BA/BAM/CA/CAM Ethernet Controller",
+                },
+                0x9000 "Not Intel",
+            }
+            classes {}
+        };
+
+        assert_eq!(db.query().device_name_matches(r"^82\d{3}").unwrap().count(), 2);
+        assert_eq!(db.query().device_name_matches(r"^82\d{3}/").unwrap().count(), 1);
+        assert_eq!(db.query().vendor_name_matches("^Intel").unwrap().execute_vendors().len(), 1);
+        assert_eq!(db.query().vendor_name_matches("^intel").unwrap().execute_vendors().len(), 0);
+        assert_eq!(db.query().vendor_name_matches("(").unwrap_err(), PciError::InvalidPattern);
+
+        let results = db
+            .query()
+            .device_name_contains("ethernet")
+            .device_name_matches(r"^82\d{3}")
+            .unwrap()
+            .record_match_spans()
+            .execute();
+        assert_eq!(results[0].device_name_spans, [0..5, 14..22]);
+    }
+
+    #[test]
+    fn test_case_sensitive() {
+        let db = crate::pci_database! {
+            vendors {
+                0x13b5 "ARM Ltd" {
+                    0x0001 "ARM Cortex Bridge",
+                    0x0002 "Swarm Controller",
+                },
+            }
+            classes {}
+        };
+
+        assert_eq!(db.query().device_name_contains("ARM").count(), 2);
+        assert_eq!(db.query().device_name_contains("ARM").case_sensitive(true).count(), 1);
+        // The toggle applies to filters added before and after it
+        assert_eq!(db.query().case_sensitive(true).device_name_contains("arm").count(), 1);
+        assert_eq!(db.query().case_sensitive(true).exclude_device_name_contains("ARM").count(), 1);
+        assert_eq!(db.query().case_sensitive(true).any_of(|q| q.device_name_contains("Arm")).count(), 0);
+        assert_eq!(db.query().case_sensitive(true).vendor_name_contains("arm").execute_vendors().len(), 0);
+    }
+
+    #[test]
+    fn test_whole_words() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Vendor" {
+                    0x0001 "AC'97 Audio Controller",
+                    0x0002 "MacBook Pro Bridge",
+                    0x0003 "Blackmagic Capture",
+                    0x0004 "Wi-Fi 802.11ac",
+                    0x0005 "Dual Band AC",
+                },
+            }
+            classes {}
+        };
+        let ids = |query: QueryBuilder<'_>| -> Vec<u16> { query.execute().iter().map(|m| m.device_id().value()).collect() };
+
+        assert_eq!(ids(db.query().device_name_contains("ac")), [1, 2, 3, 4, 5]);
+        assert_eq!(ids(db.query().device_name_contains("ac").match_whole_words()), [1, 5]);
+        assert_eq!(ids(db.query().device_name_contains("AC").match_whole_words().case_sensitive(true)), [1, 5]);
+        assert_eq!(ids(db.query().device_name_contains("ac").match_whole_words().case_sensitive(true)), Vec::<u16>::new());
+        // Multi-word terms and terms with punctuation at the edges
+        assert_eq!(ids(db.query().device_name_contains("pro bridge").match_whole_words()), [2]);
+        assert_eq!(ids(db.query().device_name_contains("802.11").match_whole_words()), Vec::<u16>::new());
+        assert_eq!(ids(db.query().device_name_contains("-fi").match_whole_words()), [4]);
+        assert_eq!(ids(db.query().exclude_device_name_contains("ac").match_whole_words()), [2, 3, 4]);
+    }
+
+    #[test]
+    fn test_subsystem_name_search() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Vendor" {
+                    0x0001 "USB Ethernet" {
+                        0x17aa 0x0001 "ThinkPad X1 dock",
+                        0x17aa 0x0002 "ThinkPad T14",
+                        0x17aa 0x0003 "IdeaPad",
+                    },
+                    0x0002 "ThinkPad Audio",
+                },
+            }
+            classes {}
+        };
+
+        let results = db.search_subsystems("thinkpad");
+        assert_eq!(results.len(), 1);
+        let names: Vec<&str> = results[0].matching_subsystems.iter().map(|s| s.name()).collect();
+        assert_eq!(names, ["ThinkPad X1 dock", "ThinkPad T14"]);
+
+        assert_eq!(db.query().device_name_contains("x1 dock").count(), 0);
+        let results = db.query().device_name_contains("thinkpad").include_subsystem_names().execute();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].matching_subsystems.len(), 2);
+        assert!(results[1].matching_subsystems.is_empty());
+        // Combined with subsystem filters, a subsystem must pass both
+        let results = db
+            .query()
+            .device_name_contains("thinkpad")
+            .include_subsystem_names()
+            .subdevice_id(SubdeviceId::new(0x0002))
+            .execute();
+        assert_eq!(results[0].matching_subsystems.len(), 1);
+    }
+
+    #[test]
+    fn test_id_strings() {
+        let db = crate::pci_database! {
+            vendors {
+                0x8086 "Intel" {
+                    0x0015 "Low",
+                    0x1500 "First",
+                    0x1533 "I210",
+                    0x15ff "Last",
+                    0x1600 "Next",
+                },
+                0x8087 "Intel 2" {
+                    0x1533 "Other",
+                },
+            }
+            classes {}
+        };
+        let names = |ids| -> Vec<String> { db.lookup_str(ids).unwrap().iter().map(|m| m.device_name().into_owned()).collect() };
+
+        assert_eq!(names("8086:1533"), ["I210"]);
+        assert_eq!(names("8086:15*"), ["First", "I210", "Last"]);
+        assert_eq!(names("8086:15"), ["Low"]);
+        assert_eq!(names(":1533"), ["I210", "Other"]);
+        assert_eq!(names("*:1533"), ["I210", "Other"]);
+        assert_eq!(names("808*:1533"), ["I210", "Other"]);
+        assert_eq!(names("8087"), ["Other"]);
+        assert_eq!(names("8087:"), ["Other"]);
+        assert_eq!(names("8086:*").len(), 5);
+        assert_eq!(names("8086:1533*"), ["I210"]);
+
+        assert_eq!(db.lookup_str("80z6:1533").unwrap_err(), PciError::InvalidHexValue);
+        assert_eq!(db.lookup_str("8086:15334").unwrap_err(), PciError::InvalidFormat);
+        assert_eq!(db.lookup_str("8086:1533:0200").unwrap_err(), PciError::InvalidFormat);
+        assert_eq!(db.lookup_str("+808").unwrap_err(), PciError::InvalidHexValue);
+    }
+
+    #[test]
+    fn test_device_id_between() {
+        let db = crate::pci_database! {
+            vendors {
+                0x8086 "Intel" {
+                    0x1520 "A",
+                    0x1533 "B",
+                    0x153f "C",
+                    0x1540 "D",
+                },
+            }
+            classes {}
+        };
+        let between = |low, high| db.query().device_id_between(DeviceId::new(low), DeviceId::new(high));
+
+        assert_eq!(between(0x1520, 0x153f).count(), 3);
+        assert_eq!(between(0x1533, 0x1533).count(), 1);
+        assert_eq!(between(0x153f, 0x1520).count(), 0);
+        // Ranges intersect
+        assert_eq!(between(0x1500, 0x1535).device_id_between(DeviceId::new(0x1530), DeviceId::new(0x15ff)).count(), 1);
+        assert_eq!(between(0x1530, 0x1600).id_str(":15*").unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_for_each() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Test Vendor" {
+                    0x0001 "Zeta Ethernet" {
+                        0x1028 0x0001 "PowerEdge NIC",
+                    },
+                    0x0002 "Alpha Audio",
+                    0x0003 "Beta Ethernet" {
+                        0x1028 0x0001 "PowerEdge NIC",
+                    },
+                },
+            }
+            classes {}
+        };
+
+        // Database order, even with ordering requested, and no subsystem lists
+        let mut seen = Vec::new();
+        let flow = db
+            .query()
+            .subvendor_id(SubvendorId::new(0x1028))
+            .order_by(OrderBy::DeviceName, SortDirection::Ascending)
+            .for_each(|device_match| {
+                assert!(device_match.matching_subsystems.is_empty());
+                seen.push(device_match.device_name());
+                ControlFlow::<()>::Continue(())
+            });
+        assert!(flow.is_continue());
+        assert_eq!(seen, ["Zeta Ethernet", "Beta Ethernet"]);
+
+        let flow = db.query().offset(1).for_each(|device_match| ControlFlow::Break(device_match.device_id()));
+        assert_eq!(flow, ControlFlow::Break(DeviceId::new(0x0002)));
+    }
+
+    #[test]
+    fn test_reusable_query() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Test Vendor" {
+                    0x0001 "Zeta Ethernet",
+                    0x0002 "Alpha Audio",
+                    0x0003 "Beta Ethernet",
+                },
+            }
+            classes {}
+        };
+
+        let query = db
+            .query()
+            .device_name_contains("Ethernet")
+            .order_by(OrderBy::DeviceName, SortDirection::Ascending)
+            .limit(1);
+        for _ in 0..3 {
+            let results = query.execute();
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].device_name(), "Beta Ethernet");
+        }
+        assert_eq!(query.count(), 1);
+        assert!(!query.is_empty());
+
+        // Clones are independent of the original
+        let page = query.clone().offset(1);
+        assert_eq!(page.execute_first().map(|m| m.device_name()).as_deref(), Some("Zeta Ethernet"));
+        assert_eq!(query.execute_first().map(|m| m.device_name()).as_deref(), Some("Beta Ethernet"));
+    }
+
+    #[test]
+    fn test_match_spans() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Net Vendor" {
+                    0x0001 "Ethernet Controller (ethernet PHY)",
+                    0x0002 "Größe Ethernet",
+                },
+            }
+            classes {
+                0x02 "Network controller",
+            }
+        };
+
+        // Spans are only recorded on request
+        let results = db.query().device_name_contains("ethernet").execute();
+        assert!(results[0].device_name_spans.is_empty());
+
+        let results = db
+            .query()
+            .vendor_name_contains("net")
+            .device_name_contains("ETHERNET")
+            .record_match_spans()
+            .execute();
+        assert_eq!(results[0].vendor_name_spans, [Range { start: 0, end: 3 }]);
+        assert_eq!(results[0].device_name_spans, [0..8, 21..29]);
+        assert_eq!(&results[1].device_name()[results[1].device_name_spans[0].clone()], "Ethernet");
+
+        let results = db.query().device_name_contains("ethernet").case_sensitive(true).record_match_spans().execute();
+        assert_eq!(results[0].device_name_spans, [Range { start: 21, end: 29 }]);
+
+        let results = db.query().device_name_contains("net").match_whole_words().record_match_spans().execute();
+        assert!(results.is_empty());
+
+        let classes = db.query().class_name_contains("work").record_match_spans().execute_classes();
+        assert_eq!(classes[0].class_name_spans, [Range { start: 3, end: 7 }]);
+    }
+
+    #[test]
+    fn test_prog_if_filters() {
+        let db = crate::pci_database! {
+            vendors {}
+            classes {
+                0x01 "Mass storage controller" {
+                    0x06 "SATA controller" {
+                        0x01 "AHCI 1.0",
+                    },
+                },
+                0x0c "Serial bus controller" {
+                    0x03 "USB controller" {
+                        0x20 "EHCI",
+                        0x30 "XHCI",
+                    },
+                },
+            }
+        };
+
+        let classes = db.query().prog_if_name_contains("hci").execute_classes();
+        assert_eq!(classes.len(), 2);
+        let prog_ifs: Vec<_> = classes[1].matching_prog_ifs.iter().map(|(subclass, prog_if)| (subclass.id(), prog_if.name())).collect();
+        assert_eq!(prog_ifs, [(SubClassId::new(0x03), "EHCI"), (SubClassId::new(0x03), "XHCI")]);
+
+        let classes = db.query().prog_if_id(ProgInterfaceId::new(0x30)).execute_classes();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].matching_subclasses[0].name(), "USB controller");
+        assert_eq!(classes[0].matching_prog_ifs.len(), 1);
+
+        // Subclass filters alone list every programming interface of the matching subclasses
+        let classes = db.query().subclass_name_contains("USB").execute_classes();
+        assert_eq!(classes[0].matching_prog_ifs.len(), 2);
+        assert!(db.query().class_id(DeviceClassId::new(0x0c)).execute_classes()[0].matching_prog_ifs.is_empty());
+
+        assert!(db.query().prog_if_id(ProgInterfaceId::new(0x40)).execute_classes().is_empty());
+    }
+
+    #[test]
+    fn test_full_descriptions() {
+        let db = crate::pci_database! {
+            vendors {
+                0x8086 "Intel Corporation" {
+                    0x1e31 "USB 3.0 Host Controller" {
+                        0x17aa 0x2233 "ThinkPad X1",
+                    },
+                },
+            }
+            classes {
+                0x0c "Serial bus controller" {
+                    0x03 "USB controller" {
+                        0x30 "XHCI",
+                    },
+                },
+            }
+        };
+
+        let query = db
+            .query()
+            .subclass_id(SubClassId::new(0x03))
+            .prog_if_name_contains("xhci")
+            .subvendor_id(SubvendorId::new(0x17aa));
+        assert_eq!(
+            query.execute()[0].description(),
+            "Intel Corporation USB 3.0 Host Controller (Serial bus controller)"
+        );
+
+        let results = query.full_descriptions().execute();
+        assert_eq!(results[0].subclass_info.map(SubClass::name), Some("USB controller"));
+        assert_eq!(
+            results[0].description(),
+            "Intel Corporation USB 3.0 Host Controller (Serial bus controller - USB controller - XHCI) [ThinkPad X1]"
+        );
+
+        // Only the supplied filters are described
+        let results = db.query().subclass_id(SubClassId::new(0x03)).full_descriptions().execute();
+        assert_eq!(
+            results[0].description(),
+            "Intel Corporation USB 3.0 Host Controller (Serial bus controller - USB controller)"
+        );
+    }
+
+    #[test]
+    fn test_search_tokens() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Other Corp" {
+                    0x0001 "Intel-compatible SFP+ 10-Gigabit Adapter",
+                },
+                0x8086 "Intel Corporation" {
+                    0x10fb "82599ES 10-Gigabit SFI/SFP+ Network Connection",
+                    0x1533 "I210 Gigabit Network Connection",
+                },
+            }
+            classes {}
+        };
+
+        // Words match in any order, in the vendor or the device name
+        let names: Vec<_> = db.search_tokens("sfp intel 10-GIGABIT").iter().map(|m| m.device_id()).collect();
+        assert_eq!(names, [DeviceId::new(0x0001), DeviceId::new(0x10fb)]);
+        assert_eq!(db.search_tokens("intel corporation network").len(), 2);
+        assert!(db.search_tokens("intel 40-gigabit").is_empty());
+        assert_eq!(db.search_tokens("  ").len(), 3);
+
+        let vendors = db.query().search_tokens("corporation i210").execute_vendors();
+        assert_eq!(vendors.len(), 1);
+        assert_eq!(vendors[0].id(), VendorId::new(0x8086));
+
+        let results = db.query().search_tokens("network intel").record_match_spans().execute();
+        assert_eq!(results[0].vendor_name_spans, [Range { start: 0, end: 5 }]);
+        assert_eq!(results[0].device_name_spans, [Range { start: 28, end: 35 }]);
+    }
+
+    #[test]
+    fn test_compiled_query() {
+        let db = crate::pci_database! {
+            vendors {
+                0x8086 "Intel Corporation" {
+                    0x1533 "I210 Gigabit Network Connection",
+                    0x1e31 "USB 3.0 Host Controller",
+                },
+            }
+            classes {
+                0x02 "Network controller" {
+                    0x00 "Ethernet controller",
+                },
+                0x0c "Serial bus controller" {
+                    0x03 "USB controller" {
+                        0x30 "XHCI",
+                    },
+                },
+            }
+        };
+        let intel = VendorId::new(0x8086);
+        let ethernet = ClassCode::from((0x02, 0x00, 0x00));
+        let xhci = ClassCode::from((0x0c, 0x03, 0x30));
+
+        // Class filters check the reported class code
+        let nics = db.query().vendor_name_contains("intel").class_name_contains("network").compile();
+        let found = nics.match_device(intel, DeviceId::new(0x1533), ethernet).unwrap();
+        assert_eq!(found.class_info.map(DeviceClass::name), Some("Network controller"));
+        assert!(nics.matches(intel, DeviceId::new(0x1e31), ethernet));
+        assert!(!nics.matches(intel, DeviceId::new(0x1e31), xhci));
+        assert!(!nics.matches(intel, DeviceId::new(0xffff), ethernet));
+        assert!(!nics.matches(VendorId::new(0x10de), DeviceId::new(0x1533), ethernet));
+
+        let usb3 = db.query().prog_if_name_contains("xhci").device_name_contains("usb").compile();
+        assert!(usb3.matches(intel, DeviceId::new(0x1e31), xhci));
+        assert!(!usb3.matches(intel, DeviceId::new(0x1e31), ClassCode::from((0x0c, 0x03, 0x20))));
+
+        // Without class filters any class code is accepted
+        let any = db.query().device_id(DeviceId::new(0x1533)).compile();
+        assert!(any.matches(intel, DeviceId::new(0x1533), xhci));
+        assert_eq!(any.query().count(), 1);
+    }
+
+    #[test]
+    fn test_explain() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Other Corp" {
+                    0x0001 "Widget",
+                },
+                0x8086 "Intel Corporation" {
+                    0x1533 "I210 Gigabit Network Connection",
+                    0x1563 "Ethernet Controller X550",
+                },
+                0x9000 "Intel Clone" {
+                    0x0001 "Widget",
+                },
+            }
+            classes {}
+        };
+
+        let plan = db.query().explain();
+        assert!(plan.filters.is_empty());
+        assert_eq!(plan.vendor_access, VendorAccess::FullScan);
+        assert_eq!((plan.vendors_scanned, plan.devices_scanned), (3, 4));
+
+        let by_name = db.query().vendor_name_contains("intel").device_name_contains("x550").explain();
+        assert_eq!(by_name.filters, ["vendor_name_contains", "device_name_contains"]);
+        assert_eq!((by_name.vendors_scanned, by_name.devices_scanned), (3, 3));
+        assert!(!by_name.hints.is_empty());
+
+        let by_id = db.query().vendor_id(VendorId::new(0x8086)).device_name_contains("x550").explain();
+        assert_eq!(by_id.vendor_access, VendorAccess::BinarySearch);
+        assert_eq!((by_id.vendors_scanned, by_id.devices_scanned), (1, 2));
+        assert!(by_id.estimated_cost < by_name.estimated_cost);
+        assert!(by_id.hints.is_empty());
+
+        let by_range = db.query().id_str("8*").unwrap().explain();
+        assert_eq!(by_range.vendor_access, VendorAccess::RangeSearch);
+        assert_eq!(by_range.vendors_scanned, 1);
+        assert_eq!(db.query().id_str("8*").unwrap().execute_vendors().len(), 1);
+        assert!(db.query().vendor_id(VendorId::new(0x4321)).is_empty());
+
+        let text = by_id.to_string();
+        assert!(text.contains("Vendor access: binary search\n  Vendors scanned: 1"), "{}", text);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let db = crate::pci_database! {
+            vendors {
+                0x10ec "Realtek Semiconductor Co., Ltd." {
+                    0x8168 "RTL8111 Gigabit Ethernet Controller",
+                },
+                0x8086 "Intel Corporation" {
+                    0x1533 "I210 Gigabit Ethernet Connection",
+                    0x1563 "Ethernet Controller X550",
+                    0x9999 "Mystery Widget",
+                },
+            }
+            classes {
+                0x02 "Network controller",
+            }
+        };
+
+        let summary = db.query().summarize();
+        assert_eq!(summary.device_count, 4);
+        let vendors: Vec<_> = summary.vendors.iter().map(|&(vendor, count)| (vendor.id(), count)).collect();
+        assert_eq!(vendors, [(VendorId::new(0x10ec), 1), (VendorId::new(0x8086), 3)]);
+        assert_eq!(summary.class_count(), 1);
+        assert_eq!(summary.classes[0].1, 3);
+        assert_eq!(summary.unclassified, 1);
+        assert_eq!(
+            summary.to_string(),
+            "4 devices across 2 vendors\n  Network controller: 3\n  Unclassified: 1"
+        );
+
+        let summary = db.query().device_name_contains("ethernet").limit(2).summarize();
+        assert_eq!((summary.device_count, summary.vendor_count(), summary.unclassified), (2, 2, 0));
+    }
+}
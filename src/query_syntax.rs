@@ -0,0 +1,300 @@
+//! A compact text syntax for queries.
+//!
+//! [`QueryBuilder::parse`] turns a string such as
+//! `vendor:intel class:network device:x550` into builder calls, so command
+//! line tools and configuration files can express queries without their own
+//! parsing code. A query is a whitespace-separated list of terms:
+//!
+//! | Term                     | Builder call                                                              |
+//! |--------------------------|---------------------------------------------------------------------------|
+//! | `vendor:intel`           | [`vendor_name_contains`](QueryBuilder::vendor_name_contains)              |
+//! | `vendor:0x8086`          | [`vendor_id`](QueryBuilder::vendor_id)                                    |
+//! | `vendor:~^Intel`         | `vendor_name_matches` (`regex` feature)                                   |
+//! | `device:…`               | the `device_*` equivalents of the `vendor:` forms                         |
+//! | `-vendor:…`, `-device:…` | the `exclude_*` filters (names, or IDs for `-vendor:0x…`)                 |
+//! | `class:`, `subclass:`, `progif:` | class filters by name, or by ID with `0x`                         |
+//! | `subsystem:thinkpad`     | [`subsystem_name_contains`](QueryBuilder::subsystem_name_contains)        |
+//! | `subvendor:17aa`, `subdevice:2233` | subsystem ID filters (hexadecimal, `0x` optional)               |
+//! | `id:8086:15*`            | [`id_str`](QueryBuilder::id_str)                                          |
+//! | `limit:10`, `offset:20`  | [`limit`](QueryBuilder::limit) and [`offset`](QueryBuilder::offset)       |
+//! | `ethernet` (no key)      | [`search_tokens`](QueryBuilder::search_tokens)                            |
+//!
+//! Values containing spaces are quoted: `vendor:"red hat"`. Name values are
+//! case-insensitive substring matches, like the builder methods they map to.
+
+use crate::error::{PciError, PciResult};
+use crate::query::QueryBuilder;
+use crate::types::*;
+use alloc::vec::Vec;
+
+impl<'db> QueryBuilder<'db> {
+    /// Add the filters described by a query string.
+    ///
+    /// See the [module documentation](crate::query_syntax) for the syntax.
+    /// Returns [`PciError::InvalidFormat`] for unknown keys, empty values,
+    /// unbalanced quotes, and negated terms that have no exclusion filter,
+    /// [`PciError::InvalidHexValue`] for malformed IDs, and
+    /// [`PciError::InvalidPattern`] for regular expressions that do not
+    /// compile or are used without the `regex` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> ids_rs::PciResult<()> {
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let db = PciDatabase::get();
+    /// let results = db.query().parse(r#"vendor:intel device:x550 -device:"virtual function""#)?.execute();
+    /// for device_match in results {
+    ///     println!("{}", device_match.description());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(mut self, query: &str) -> PciResult<Self> {
+        for term in split_terms(query)? {
+            self = self.apply_term(term)?;
+        }
+        Ok(self)
+    }
+
+    fn apply_term(self, term: Term<'_>) -> PciResult<Self> {
+        let Some(key) = term.key else {
+            if term.negated {
+                return Err(PciError::InvalidFormat);
+            }
+            return Ok(self.search_tokens(term.value));
+        };
+
+        let value = Value::parse(term.value)?;
+        match (key, term.negated, value) {
+            ("vendor", false, Value::Id(id)) => Ok(self.vendor_id(VendorId::new(parse_u16(id)?))),
+            ("vendor", false, Value::Name(name)) => Ok(self.vendor_name_contains(name)),
+            ("vendor", false, Value::Pattern(pattern)) => self.apply_vendor_pattern(pattern),
+            ("vendor", true, Value::Id(id)) => Ok(self.exclude_vendor_id(VendorId::new(parse_u16(id)?))),
+            ("vendor", true, Value::Name(name)) => Ok(self.exclude_vendor_name_contains(name)),
+            ("device", false, Value::Id(id)) => Ok(self.device_id(DeviceId::new(parse_u16(id)?))),
+            ("device", false, Value::Name(name)) => Ok(self.device_name_contains(name)),
+            ("device", false, Value::Pattern(pattern)) => self.apply_device_pattern(pattern),
+            ("device", true, Value::Name(name)) => Ok(self.exclude_device_name_contains(name)),
+            ("class", false, Value::Id(id)) => Ok(self.class_id(DeviceClassId::new(parse_u8(id)?))),
+            ("class", false, Value::Name(name)) => Ok(self.class_name_contains(name)),
+            ("subclass", false, Value::Id(id)) => Ok(self.subclass_id(SubClassId::new(parse_u8(id)?))),
+            ("subclass", false, Value::Name(name)) => Ok(self.subclass_name_contains(name)),
+            ("progif", false, Value::Id(id)) => Ok(self.prog_if_id(ProgInterfaceId::new(parse_u8(id)?))),
+            ("progif", false, Value::Name(name)) => Ok(self.prog_if_name_contains(name)),
+            ("subsystem", false, Value::Name(name)) => Ok(self.subsystem_name_contains(name)),
+            ("subvendor", false, Value::Id(id) | Value::Name(id)) => Ok(self.subvendor_id(SubvendorId::new(parse_u16(id)?))),
+            ("subdevice", false, Value::Id(id) | Value::Name(id)) => Ok(self.subdevice_id(SubdeviceId::new(parse_u16(id)?))),
+            ("id", false, Value::Name(ids)) => self.id_str(ids),
+            ("limit", false, Value::Name(count)) => Ok(self.limit(parse_count(count)?)),
+            ("offset", false, Value::Name(count)) => Ok(self.offset(parse_count(count)?)),
+            _ => Err(PciError::InvalidFormat),
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    fn apply_vendor_pattern(self, pattern: &str) -> PciResult<Self> {
+        self.vendor_name_matches(pattern)
+    }
+
+    #[cfg(feature = "regex")]
+    fn apply_device_pattern(self, pattern: &str) -> PciResult<Self> {
+        self.device_name_matches(pattern)
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn apply_vendor_pattern(self, _pattern: &str) -> PciResult<Self> {
+        Err(PciError::InvalidPattern)
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn apply_device_pattern(self, _pattern: &str) -> PciResult<Self> {
+        Err(PciError::InvalidPattern)
+    }
+}
+
+/// A single `[-][key:]value` term of a query string, with quotes removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Term<'a> {
+    negated: bool,
+    key: Option<&'a str>,
+    value: &'a str,
+}
+
+/// The kind of value a keyed term holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value<'a> {
+    /// A hexadecimal ID, written with a `0x` prefix
+    Id(&'a str),
+    /// A regular expression, written with a `~` prefix
+    Pattern(&'a str),
+    /// Anything else
+    Name(&'a str),
+}
+
+impl<'a> Value<'a> {
+    fn parse(value: &'a str) -> PciResult<Self> {
+        let parsed = if let Some(id) = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            Value::Id(id)
+        } else if let Some(pattern) = value.strip_prefix('~') {
+            Value::Pattern(pattern)
+        } else {
+            Value::Name(value)
+        };
+
+        match parsed {
+            Value::Id(text) | Value::Pattern(text) | Value::Name(text) if text.is_empty() => Err(PciError::InvalidFormat),
+            parsed => Ok(parsed),
+        }
+    }
+}
+
+/// Split a query string into terms, honoring double quotes.
+fn split_terms(query: &str) -> PciResult<Vec<Term<'_>>> {
+    let mut terms = Vec::new();
+    let mut rest = query.trim_start();
+
+    while !rest.is_empty() {
+        // A term ends at the first whitespace outside quotes
+        let mut in_quotes = false;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                c.is_whitespace() && !in_quotes
+            })
+            .map_or(rest.len(), |(end, _)| end);
+        if in_quotes {
+            return Err(PciError::InvalidFormat);
+        }
+
+        terms.push(parse_term(&rest[..end])?);
+        rest = rest[end..].trim_start();
+    }
+
+    Ok(terms)
+}
+
+fn parse_term(text: &str) -> PciResult<Term<'_>> {
+    let (negated, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+
+    let (key, value) = match text.split_once(':') {
+        Some((key, value)) if !key.is_empty() && key.bytes().all(|b| b.is_ascii_alphabetic()) => (Some(key), value),
+        _ => (None, text),
+    };
+
+    let value = match value.strip_prefix('"') {
+        Some(quoted) => quoted.strip_suffix('"').ok_or(PciError::InvalidFormat)?,
+        None if value.contains('"') => return Err(PciError::InvalidFormat),
+        None => value,
+    };
+    if value.is_empty() || value.contains('"') {
+        return Err(PciError::InvalidFormat);
+    }
+
+    Ok(Term { negated, key, value })
+}
+
+fn parse_u16(hex: &str) -> PciResult<u16> {
+    // `from_str_radix` would also accept a leading sign
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(PciError::InvalidHexValue);
+    }
+    u16::from_str_radix(hex, 16).map_err(|_| PciError::InvalidHexValue)
+}
+
+fn parse_u8(hex: &str) -> PciResult<u8> {
+    u8::try_from(parse_u16(hex)?).map_err(|_| PciError::InvalidHexValue)
+}
+
+fn parse_count(count: &str) -> PciResult<usize> {
+    if !count.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(PciError::InvalidFormat);
+    }
+    count.parse().map_err(|_| PciError::InvalidFormat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::PciDatabase;
+
+    #[test]
+    fn test_split_terms() {
+        let terms = split_terms(r#"  vendor:"red hat"  -device:virtio ethernet id:1af4:10* "#).unwrap();
+        assert_eq!(
+            terms,
+            [
+                Term { negated: false, key: Some("vendor"), value: "red hat" },
+                Term { negated: true, key: Some("device"), value: "virtio" },
+                Term { negated: false, key: None, value: "ethernet" },
+                Term { negated: false, key: Some("id"), value: "1af4:10*" },
+            ]
+        );
+        assert!(split_terms("").unwrap().is_empty());
+
+        assert_eq!(split_terms(r#"vendor:"red hat"#), Err(PciError::InvalidFormat));
+        assert_eq!(split_terms(r#"vendor:red"hat""#), Err(PciError::InvalidFormat));
+        assert_eq!(split_terms("vendor:"), Err(PciError::InvalidFormat));
+        assert_eq!(split_terms(r#"vendor:"""#), Err(PciError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_parse() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1af4 "Red Hat, Inc." {
+                    0x1000 "Virtio network device",
+                },
+                0x8086 "Intel Corporation" {
+                    0x1533 "I210 Gigabit Network Connection" {
+                        0x17aa 0x2233 "ThinkPad NIC",
+                    },
+                    0x1563 "Ethernet Controller X550",
+                },
+            }
+            classes {}
+        };
+        let ids = |query: &str| -> Vec<DeviceId> {
+            db.query().parse(query).unwrap().execute().iter().map(|m| m.device_id()).collect()
+        };
+
+        assert_eq!(ids("vendor:intel device:x550"), [DeviceId::new(0x1563)]);
+        assert_eq!(ids(r#"vendor:"red hat""#), [DeviceId::new(0x1000)]);
+        assert_eq!(ids("vendor:0x8086 -device:x550"), [DeviceId::new(0x1533)]);
+        assert_eq!(ids("-vendor:0x8086"), [DeviceId::new(0x1000)]);
+        assert_eq!(ids("network intel"), [DeviceId::new(0x1533)]);
+        assert_eq!(ids("subvendor:17aa subdevice:0x2233"), [DeviceId::new(0x1533)]);
+        assert_eq!(ids("subsystem:thinkpad"), [DeviceId::new(0x1533)]);
+        assert_eq!(ids("id:8086:15* offset:1 limit:1"), [DeviceId::new(0x1563)]);
+        assert_eq!(ids(""), [DeviceId::new(0x1000), DeviceId::new(0x1533), DeviceId::new(0x1563)]);
+
+        assert_eq!(db.query().parse("vendor:0xzz").unwrap_err(), PciError::InvalidHexValue);
+        assert_eq!(db.query().parse("class:0x100").unwrap_err(), PciError::InvalidHexValue);
+        assert_eq!(db.query().parse("colour:red").unwrap_err(), PciError::InvalidFormat);
+        assert_eq!(db.query().parse("-class:network").unwrap_err(), PciError::InvalidFormat);
+        assert_eq!(db.query().parse("-ethernet").unwrap_err(), PciError::InvalidFormat);
+        assert_eq!(db.query().parse("limit:-1").unwrap_err(), PciError::InvalidFormat);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_parse_patterns() {
+        let db = PciDatabase::get();
+        assert!(db.query().parse("device:~^I2[0-9]{2}").is_ok());
+        assert_eq!(db.query().parse("device:~(").unwrap_err(), PciError::InvalidPattern);
+    }
+
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn test_parse_patterns_unsupported() {
+        let db = PciDatabase::get();
+        assert_eq!(db.query().parse("device:~x550").unwrap_err(), PciError::InvalidPattern);
+    }
+}
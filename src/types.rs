@@ -291,4 +291,144 @@ impl From<ProgInterfaceId> for u8 {
     fn from(id: ProgInterfaceId) -> Self {
         id.value()
     }
-}
\ No newline at end of file
+}
+
+/// A PCI class code: the class, subclass, and programming interface bytes
+/// of the class code register in configuration space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClassCode(u32);
+
+impl ClassCode {
+    /// Create a new class code from its three parts.
+    #[inline]
+    pub const fn new(class_id: DeviceClassId, subclass_id: SubClassId, prog_interface_id: ProgInterfaceId) -> Self {
+        Self(((class_id.value() as u32) << 16) | ((subclass_id.value() as u32) << 8) | prog_interface_id.value() as u32)
+    }
+
+    /// Create a class code from a 24-bit value laid out as `0xCCSSPP`.
+    ///
+    /// Bits above the low 24 are ignored. When reading the 32-bit register at
+    /// configuration space offset `0x08`, shift out the revision ID first.
+    #[inline]
+    pub const fn from_value(value: u32) -> Self {
+        Self(value & 0x00ff_ffff)
+    }
+
+    /// Get the raw 24-bit class code value.
+    #[inline]
+    pub const fn value(self) -> u32 {
+        self.0
+    }
+
+    /// Get the device class ID.
+    #[inline]
+    pub const fn class_id(self) -> DeviceClassId {
+        DeviceClassId::new((self.0 >> 16) as u8)
+    }
+
+    /// Get the subclass ID.
+    #[inline]
+    pub const fn subclass_id(self) -> SubClassId {
+        SubClassId::new((self.0 >> 8) as u8)
+    }
+
+    /// Get the programming interface ID.
+    #[inline]
+    pub const fn prog_interface_id(self) -> ProgInterfaceId {
+        ProgInterfaceId::new(self.0 as u8)
+    }
+}
+
+impl fmt::Display for ClassCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:06x}", self.0)
+    }
+}
+
+impl From<(u8, u8, u8)> for ClassCode {
+    /// Create a class code from `(class, subclass, prog_if)` bytes, as returned
+    /// (after the revision) by class register readers such as `pci_types`.
+    fn from((class_id, subclass_id, prog_interface_id): (u8, u8, u8)) -> Self {
+        Self::new(
+            DeviceClassId::new(class_id),
+            SubClassId::new(subclass_id),
+            ProgInterfaceId::new(prog_interface_id),
+        )
+    }
+}
+
+impl From<ClassCode> for u32 {
+    fn from(code: ClassCode) -> Self {
+        code.value()
+    }
+}
+
+/// The address of a PCI function: its segment, bus, device, and function numbers.
+///
+/// Addresses order by segment, then bus, device, and function, which is the
+/// order of a depth-first bus scan, and display as `ssss:bb:dd.f` like `lspci -D`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PciAddress {
+    segment: u16,
+    bus: u8,
+    device: u8,
+    function: u8,
+}
+
+impl PciAddress {
+    /// Create a new address.
+    ///
+    /// Only the low 5 bits of `device` and the low 3 bits of `function` are
+    /// kept, as in the configuration space address.
+    #[inline]
+    pub const fn new(segment: u16, bus: u8, device: u8, function: u8) -> Self {
+        Self { segment, bus, device: device & 0x1f, function: function & 0x07 }
+    }
+
+    /// Get the PCI segment group number.
+    #[inline]
+    pub const fn segment(self) -> u16 {
+        self.segment
+    }
+
+    /// Get the bus number.
+    #[inline]
+    pub const fn bus(self) -> u8 {
+        self.bus
+    }
+
+    /// Get the device number.
+    #[inline]
+    pub const fn device(self) -> u8 {
+        self.device
+    }
+
+    /// Get the function number.
+    #[inline]
+    pub const fn function(self) -> u8 {
+        self.function
+    }
+}
+
+impl fmt::Display for PciAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04x}:{:02x}:{:02x}.{:x}", self.segment, self.bus, self.device, self.function)
+    }
+}
+
+/// How a name with non-ASCII characters was stored by the code generator.
+///
+/// pci.ids names are UTF-8, which some firmware consoles cannot render. The
+/// generator keeps them as they are by default, or can replace them with an
+/// ASCII form; entries record which one they hold, so callers can tell an
+/// escaped or transliterated name from one that was ASCII to begin with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum NameForm {
+    /// The name as given in pci.ids (always the case for ASCII names)
+    #[default]
+    Original,
+    /// Non-ASCII characters replaced by Rust-style escapes, such as `\u{fc}` for `ü`
+    Escaped,
+    /// Non-ASCII characters transliterated to ASCII, such as `u` for `ü`
+    Transliterated,
+}
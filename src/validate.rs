@@ -0,0 +1,266 @@
+//! Integrity checks for PCI databases.
+//!
+//! Lookups such as [`PciDatabase::find_vendor`] rely on the vendor and class
+//! arrays being sorted by ID with no duplicates. The generated database always
+//! satisfies this, but databases built by hand with [`PciDatabase::new`] may
+//! not. [`PciDatabase::validate`] checks these invariants and reports every
+//! violation it finds.
+//!
+//! [`PciIdsParser::validate`](crate::parser::PciIdsParser::validate) runs the
+//! same checks on pci.ids text, along with format checks, without building a
+//! database first.
+
+use core::fmt;
+
+use crate::database::PciDatabase;
+use crate::error::PciError;
+use crate::types::*;
+use alloc::vec::Vec;
+
+/// A single integrity problem found in a database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// A vendor ID is smaller than the ID of the vendor before it
+    VendorOutOfOrder(VendorId),
+    /// A vendor ID appears more than once
+    DuplicateVendor(VendorId),
+    /// A vendor has an empty name
+    EmptyVendorName(VendorId),
+    /// A device ID is smaller than the ID of the device before it
+    DeviceOutOfOrder(VendorId, DeviceId),
+    /// A device ID appears more than once within a vendor
+    DuplicateDevice(VendorId, DeviceId),
+    /// A device has an empty name
+    EmptyDeviceName(VendorId, DeviceId),
+    /// A class ID is smaller than the ID of the class before it
+    ClassOutOfOrder(DeviceClassId),
+    /// A class ID appears more than once
+    DuplicateClass(DeviceClassId),
+    /// A device class has an empty name
+    EmptyClassName(DeviceClassId),
+    /// A line of pci.ids text could not be parsed (lines are numbered from 1)
+    InvalidLine {
+        /// The line number
+        line: usize,
+        /// What is wrong with the line
+        error: PciError,
+    },
+    /// A name in pci.ids text contains control characters (lines are numbered from 1)
+    InvalidName {
+        /// The line number
+        line: usize,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::VendorOutOfOrder(vendor) => write!(f, "vendor {} is out of order", vendor),
+            ValidationIssue::DuplicateVendor(vendor) => write!(f, "vendor {} appears more than once", vendor),
+            ValidationIssue::EmptyVendorName(vendor) => write!(f, "vendor {} has an empty name", vendor),
+            ValidationIssue::DeviceOutOfOrder(vendor, device) => {
+                write!(f, "device {}:{} is out of order", vendor, device)
+            }
+            ValidationIssue::DuplicateDevice(vendor, device) => {
+                write!(f, "device {}:{} appears more than once", vendor, device)
+            }
+            ValidationIssue::EmptyDeviceName(vendor, device) => {
+                write!(f, "device {}:{} has an empty name", vendor, device)
+            }
+            ValidationIssue::ClassOutOfOrder(class) => write!(f, "class {} is out of order", class),
+            ValidationIssue::DuplicateClass(class) => write!(f, "class {} appears more than once", class),
+            ValidationIssue::EmptyClassName(class) => write!(f, "class {} has an empty name", class),
+            ValidationIssue::InvalidLine { line, error } => write!(f, "line {}: {}", line, error),
+            ValidationIssue::InvalidName { line } => write!(f, "line {}: name contains control characters", line),
+        }
+    }
+}
+
+/// The result of validating a database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Every issue found, in the order the entries appear in the database
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Check if no issues were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "PCI Database Validation: OK");
+        }
+        write!(f, "PCI Database Validation: {} issues", self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "\n  {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check consecutive IDs, reporting an issue for each one that is not strictly
+/// greater than the one before it.
+fn check_order<T: Ord + Copy>(
+    ids: impl Iterator<Item = T>,
+    issues: &mut Vec<ValidationIssue>,
+    out_of_order: impl Fn(T) -> ValidationIssue,
+    duplicate: impl Fn(T) -> ValidationIssue,
+) {
+    let mut previous: Option<T> = None;
+    for id in ids {
+        check_next(&mut previous, id, issues, &out_of_order, &duplicate);
+    }
+}
+
+/// Check one ID against the one before it, then remember it as the previous ID.
+pub(crate) fn check_next<T: Ord + Copy>(
+    previous: &mut Option<T>,
+    id: T,
+    issues: &mut Vec<ValidationIssue>,
+    out_of_order: impl Fn(T) -> ValidationIssue,
+    duplicate: impl Fn(T) -> ValidationIssue,
+) {
+    match *previous {
+        Some(prev) if id == prev => issues.push(duplicate(id)),
+        Some(prev) if id < prev => issues.push(out_of_order(id)),
+        _ => {}
+    }
+    *previous = Some(id);
+}
+
+impl PciDatabase {
+    /// Check that the database satisfies the invariants lookups depend on.
+    ///
+    /// Vendors, devices within each vendor, and classes must be sorted by ID
+    /// without duplicates, and every vendor, device, and class must have a
+    /// non-empty name. Names are not checked when they are stored
+    /// [compressed](crate::compressed), as the tables then hold empty names.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let report = PciDatabase::get().validate();
+    /// assert!(report.is_valid(), "{}", report);
+    /// ```
+    pub fn validate(&self) -> ValidationReport {
+        let mut issues = Vec::new();
+        let check_names = self.compressed_names().is_none();
+
+        check_order(
+            self.vendors().iter().map(|v| v.id()),
+            &mut issues,
+            ValidationIssue::VendorOutOfOrder,
+            ValidationIssue::DuplicateVendor,
+        );
+        for vendor in self.vendors() {
+            if check_names && vendor.name().is_empty() {
+                issues.push(ValidationIssue::EmptyVendorName(vendor.id()));
+            }
+            check_order(
+                vendor.devices().iter().map(|d| d.id()),
+                &mut issues,
+                |device| ValidationIssue::DeviceOutOfOrder(vendor.id(), device),
+                |device| ValidationIssue::DuplicateDevice(vendor.id(), device),
+            );
+            for device in vendor.devices() {
+                if check_names && device.name().is_empty() {
+                    issues.push(ValidationIssue::EmptyDeviceName(vendor.id(), device.id()));
+                }
+            }
+        }
+
+        check_order(
+            self.classes().iter().map(|c| c.id()),
+            &mut issues,
+            ValidationIssue::ClassOutOfOrder,
+            ValidationIssue::DuplicateClass,
+        );
+        for class in self.classes() {
+            if check_names && class.name().is_empty() {
+                issues.push(ValidationIssue::EmptyClassName(class.id()));
+            }
+        }
+
+        ValidationReport { issues }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::DeviceClass;
+    use crate::devices::Device;
+    use crate::vendors::Vendor;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_valid_database() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1000 "Vendor A" {
+                    0x0001 "First",
+                    0x0002 "Second",
+                },
+                0x2000 "Vendor B",
+            }
+            classes {
+                0x02 "Network controller",
+            }
+        };
+
+        let report = db.validate();
+        assert!(report.is_valid());
+        assert!(db.is_sorted());
+        assert_eq!(report.to_string(), "PCI Database Validation: OK");
+        assert!(PciDatabase::new(&[], &[]).validate().is_valid());
+    }
+
+    #[test]
+    fn test_reports_every_issue() {
+        // Hand-written, since `pci_database!` rejects unsorted entries
+        static DEVICES: &[Device] = &[
+            Device::new(DeviceId::new(0x0002), "Second", &[]),
+            Device::new(DeviceId::new(0x0001), "", &[]),
+            Device::new(DeviceId::new(0x0001), "Again", &[]),
+        ];
+        static VENDORS: &[Vendor] = &[
+            Vendor::new(VendorId::new(0x2000), "Vendor B", DEVICES),
+            Vendor::new(VendorId::new(0x1000), "", &[]),
+            Vendor::new(VendorId::new(0x1000), "Vendor A", &[]),
+        ];
+        static CLASSES: &[DeviceClass] = &[
+            DeviceClass::new(DeviceClassId::new(0x02), "", &[]),
+            DeviceClass::new(DeviceClassId::new(0x02), "Network controller", &[]),
+            DeviceClass::new(DeviceClassId::new(0x01), "Mass storage controller", &[]),
+        ];
+
+        let report = PciDatabase::new(VENDORS, CLASSES).validate();
+        let v = VendorId::new;
+        assert_eq!(
+            report.issues,
+            [
+                ValidationIssue::VendorOutOfOrder(v(0x1000)),
+                ValidationIssue::DuplicateVendor(v(0x1000)),
+                ValidationIssue::DeviceOutOfOrder(v(0x2000), DeviceId::new(0x0001)),
+                ValidationIssue::DuplicateDevice(v(0x2000), DeviceId::new(0x0001)),
+                ValidationIssue::EmptyDeviceName(v(0x2000), DeviceId::new(0x0001)),
+                ValidationIssue::EmptyVendorName(v(0x1000)),
+                ValidationIssue::DuplicateClass(DeviceClassId::new(0x02)),
+                ValidationIssue::ClassOutOfOrder(DeviceClassId::new(0x01)),
+                ValidationIssue::EmptyClassName(DeviceClassId::new(0x02)),
+            ]
+        );
+        assert!(!report.is_valid());
+        assert!(!PciDatabase::new(VENDORS, CLASSES).is_sorted());
+        assert!(!PciDatabase::new(&VENDORS[..1], &[]).is_sorted());
+        assert!(!PciDatabase::new(&[], CLASSES).is_sorted());
+        assert!(report.to_string().starts_with("PCI Database Validation: 9 issues\n  vendor 1000 is out of order"));
+    }
+}
@@ -1,7 +1,8 @@
 //! PCI vendor definitions and utilities.
 
-use crate::types::{VendorId, DeviceId};
+use crate::types::{VendorId, DeviceId, NameForm};
 use crate::devices::Device;
+use core::cmp::Ordering;
 
 /// Represents a PCI vendor.
 #[derive(Debug, Clone)]
@@ -12,13 +13,55 @@ pub struct Vendor {
     pub name: &'static str,
     /// The devices manufactured by this vendor
     pub devices: &'static [Device],
+    /// The name without its bracketed aliases
+    canonical_name: &'static str,
+    /// The text inside the trailing brackets, if any
+    alias_text: &'static str,
+    /// The canonical name without legal-entity suffixes
+    short_name: &'static str,
+    /// The name transliterated to ASCII
+    ascii_name: &'static str,
+    /// Whether the name was escaped or transliterated at generation time
+    name_form: NameForm,
 }
 
 impl Vendor {
     /// Create a new vendor.
+    ///
+    /// The canonical name, short name, and aliases are derived from `name`
+    /// here, so for static databases this happens at compile time.
     #[inline]
     pub const fn new(id: VendorId, name: &'static str, devices: &'static [Device]) -> Self {
-        Self { id, name, devices }
+        let (canonical_name, alias_text) = split_aliases(name);
+        Self {
+            id,
+            name,
+            devices,
+            canonical_name,
+            alias_text,
+            short_name: strip_legal_suffixes(canonical_name),
+            ascii_name: name,
+            name_form: NameForm::Original,
+        }
+    }
+
+    /// Set the ASCII version of the name returned by [`name_ascii`](Self::name_ascii).
+    ///
+    /// The generated database calls this for every name that is not plain ASCII.
+    #[inline]
+    pub const fn with_ascii_name(mut self, ascii_name: &'static str) -> Self {
+        self.ascii_name = ascii_name;
+        self
+    }
+
+    /// Record how the name was stored, returned by [`name_form`](Self::name_form).
+    ///
+    /// The generated database calls this for every non-ASCII name it escaped
+    /// or transliterated.
+    #[inline]
+    pub const fn with_name_form(mut self, name_form: NameForm) -> Self {
+        self.name_form = name_form;
+        self
     }
 
     /// Get the vendor ID.
@@ -33,6 +76,66 @@ impl Vendor {
         self.name
     }
 
+    /// Get the vendor name as ASCII, for consoles and serial logs that cannot render UTF-8.
+    ///
+    /// A few pci.ids names contain characters such as `ü`, `®`, or non-breaking
+    /// spaces. The build script transliterates these (`"für"` becomes `"fur"`,
+    /// `"Wi-Fi®"` becomes `"Wi-Fi(R)"`) and replaces anything it cannot map with
+    /// `?`. ASCII names are returned unchanged, and entries created with
+    /// [`new`](Self::new) use the name as given unless
+    /// [`with_ascii_name`](Self::with_ascii_name) is called.
+    #[inline]
+    pub const fn name_ascii(&self) -> &'static str {
+        self.ascii_name
+    }
+
+    /// Check whether the name is as given in pci.ids, or was escaped or transliterated to ASCII.
+    ///
+    /// The `escape-names` and `transliterate-names` features (or
+    /// [`GenOptions::non_ascii_names`](crate::codegen::GenOptions::non_ascii_names))
+    /// store non-ASCII names in ASCII form, so that [`name`](Self::name) is safe
+    /// to print on consoles that cannot render UTF-8. Those entries report
+    /// [`NameForm::Escaped`] or [`NameForm::Transliterated`]; all others report
+    /// [`NameForm::Original`].
+    #[inline]
+    pub const fn name_form(&self) -> NameForm {
+        self.name_form
+    }
+
+    /// Get the vendor name without its bracketed aliases.
+    ///
+    /// For `"Advanced Micro Devices, Inc. [AMD/ATI]"` this is
+    /// `"Advanced Micro Devices, Inc."`. Names without aliases are returned unchanged.
+    #[inline]
+    pub const fn canonical_name(&self) -> &'static str {
+        self.canonical_name
+    }
+
+    /// Get a concise display name for narrow columns.
+    ///
+    /// This is the [canonical name](Self::canonical_name) without legal-entity
+    /// suffixes such as ", Inc.", "Corporation", or "Co., Ltd.", so
+    /// `"Intel Corporation"` becomes `"Intel"`.
+    #[inline]
+    pub const fn short_name(&self) -> &'static str {
+        self.short_name
+    }
+
+    /// Iterate over the alternative names given in brackets.
+    ///
+    /// For `"Advanced Micro Devices, Inc. [AMD/ATI]"` this yields `"AMD"` and `"ATI"`.
+    pub fn aliases(&self) -> impl Iterator<Item = &'static str> {
+        alias_list(self.alias_text)
+    }
+
+    /// Check if `name` is this vendor's full name, canonical name, or one of its
+    /// aliases, ignoring case.
+    pub fn is_named(&self, name: &str) -> bool {
+        eq_ignore_case(self.name, name)
+            || eq_ignore_case(self.canonical_name, name)
+            || self.aliases().any(|alias| eq_ignore_case(alias, name))
+    }
+
     /// Get all devices from this vendor.
     #[inline]
     pub const fn devices(&self) -> &'static [Device] {
@@ -44,6 +147,60 @@ impl Vendor {
         self.devices.iter().find(|device| device.id() == device_id)
     }
 
+    /// Find the known devices with the IDs closest to `device_id`.
+    ///
+    /// New steppings and variants are often given IDs next to existing parts,
+    /// so when [`find_device`](Self::find_device) fails the nearest known device
+    /// is a useful hint. The result holds the exact match if there is one,
+    /// otherwise the closest device, or the devices just below and above if they
+    /// are equally close. It is empty only if the vendor has no devices.
+    /// Devices must be sorted by ID, as they are in generated databases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::{pci_database, PciDatabase, VendorId, DeviceId};
+    ///
+    /// static DB: PciDatabase = pci_database! {
+    ///     vendors {
+    ///         0x8086 "Intel Corporation" {
+    ///             0x1533 "I210 Gigabit Network Connection",
+    ///             0x1539 "I211 Gigabit Network Connection",
+    ///         },
+    ///     }
+    ///     classes {}
+    /// };
+    ///
+    /// let intel = DB.find_vendor(VendorId::new(0x8086)).unwrap();
+    /// let nearest = intel.nearest_device(DeviceId::new(0x1534));
+    /// assert_eq!(nearest[0].name(), "I210 Gigabit Network Connection");
+    /// ```
+    pub fn nearest_device(&self, device_id: DeviceId) -> &'static [Device] {
+        let devices = self.devices;
+        let index = match devices.binary_search_by_key(&device_id, Device::id) {
+            Ok(index) => return &devices[index..=index],
+            Err(index) => index,
+        };
+
+        let distance = |device: &Device| device.id().value().abs_diff(device_id.value());
+        match (index.checked_sub(1), devices.get(index)) {
+            (Some(below), Some(above)) => match distance(&devices[below]).cmp(&distance(above)) {
+                Ordering::Less => &devices[below..index],
+                Ordering::Equal => &devices[below..=index],
+                Ordering::Greater => &devices[index..=index],
+            },
+            (Some(below), None) => &devices[below..index],
+            (None, _) => &devices[..devices.len().min(1)],
+        }
+    }
+
+    /// Find a device by its exact name, ignoring case.
+    ///
+    /// If several devices share the name, the one with the lowest ID is returned.
+    pub fn device_by_name(&self, name: &str) -> Option<&Device> {
+        self.devices.iter().find(|device| eq_ignore_case(device.name(), name))
+    }
+
     /// Get the number of devices from this vendor.
     #[inline]
     pub const fn device_count(&self) -> usize {
@@ -70,48 +227,243 @@ impl PartialEq for Vendor {
 impl Eq for Vendor {}
 
 impl PartialOrd for Vendor {
-    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
 impl Ord for Vendor {
-    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+    fn cmp(&self, other: &Self) -> Ordering {
         self.id.cmp(&other.id)
     }
 }
 
+/// Iterate over the aliases in alias text like `"FOO/Bar"`.
+pub(crate) fn alias_list(alias_text: &str) -> impl Iterator<Item = &str> {
+    alias_text.split('/').map(str::trim).filter(|alias| !alias.is_empty())
+}
+
+/// Split a name like `"Foo Inc. [FOO/Bar]"` into `("Foo Inc.", "FOO/Bar")`.
+///
+/// Only a bracketed part at the end of the name counts. Names without one are
+/// returned whole with no alias text.
+pub(crate) const fn split_aliases(name: &str) -> (&str, &str) {
+    let bytes = name.as_bytes();
+    if bytes.is_empty() || bytes[bytes.len() - 1] != b']' {
+        return (name, "");
+    }
+
+    let mut open = 0;
+    while open < bytes.len() && bytes[open] != b'[' {
+        open += 1;
+    }
+    if open == bytes.len() {
+        return (name, "");
+    }
+
+    let mut end = open;
+    while end > 0 && bytes[end - 1] == b' ' {
+        end -= 1;
+    }
+
+    // Both cut points are next to ASCII brackets or spaces, so they are char boundaries
+    let (canonical, rest) = bytes.split_at(open);
+    let (canonical, _) = canonical.split_at(end);
+    let (_, aliases) = rest.split_at(1);
+    let (aliases, _) = aliases.split_at(aliases.len() - 1);
+    match (core::str::from_utf8(canonical), core::str::from_utf8(aliases)) {
+        (Ok(canonical), Ok(aliases)) if canonical.is_empty() => (aliases, aliases),
+        (Ok(canonical), Ok(aliases)) => (canonical, aliases),
+        _ => (name, ""),
+    }
+}
+
+/// Legal-entity suffixes removed by [`Vendor::short_name`].
+///
+/// Multi-word suffixes come first so they are removed whole.
+const LEGAL_SUFFIXES: &[&str] = &[
+    "Private Limited", "Pvt. Ltd.", "Pvt Ltd", "Inc", "Inc.", "Incorporated", "Corporation", "Corp", "Corp.", "Co", "Co.", "Company", "Ltd", "Ltd.",
+    "Co.,Ltd", "Co.,Ltd.", "Limited", "LLC", "GmbH", "GmBH", "AG", "AB", "A/S", "B.V.", "BV", "S.A.", "Oy",
+];
+
+/// Strip trailing legal-entity suffixes (and the separators before them).
+///
+/// Suffixes must be separate words, and a name is never reduced to nothing.
+const fn strip_legal_suffixes(name: &'static str) -> &'static str {
+    let bytes = name.as_bytes();
+    let mut end = bytes.len();
+
+    loop {
+        let mut stripped = false;
+        let mut i = 0;
+        while i < LEGAL_SUFFIXES.len() {
+            let suffix = LEGAL_SUFFIXES[i].as_bytes();
+            if ends_with_word(bytes, end, suffix) {
+                // Drop the suffix and the spaces or commas before it
+                let mut new_end = end - suffix.len();
+                while new_end > 0 && (bytes[new_end - 1] == b' ' || bytes[new_end - 1] == b',') {
+                    new_end -= 1;
+                }
+                if new_end > 0 {
+                    end = new_end;
+                    stripped = true;
+                }
+            }
+            i += 1;
+        }
+        if !stripped {
+            break;
+        }
+    }
+
+    match core::str::from_utf8(bytes.split_at(end).0) {
+        Ok(short) => short,
+        Err(_) => name,
+    }
+}
+
+/// Check if `bytes[..end]` ends with `suffix` preceded by a space or comma.
+const fn ends_with_word(bytes: &[u8], end: usize, suffix: &[u8]) -> bool {
+    if end <= suffix.len() {
+        return false;
+    }
+    let start = end - suffix.len();
+    if bytes[start - 1] != b' ' && bytes[start - 1] != b',' {
+        return false;
+    }
+    let mut i = 0;
+    while i < suffix.len() {
+        if bytes[start + i] != suffix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Compare two names for equality, ignoring case, without allocating.
+pub(crate) fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars().flat_map(char::to_lowercase).eq(b.chars().flat_map(char::to_lowercase))
+}
+
+/// Lowercase an ASCII `name` into `buffer`, or get `None` if it is not ASCII or does not fit.
+pub(crate) fn ascii_lowercase<'a>(name: &str, buffer: &'a mut [u8; 256]) -> Option<&'a [u8]> {
+    if !name.is_ascii() || name.len() > buffer.len() {
+        return None;
+    }
+    let lowered = &mut buffer[..name.len()];
+    lowered.copy_from_slice(name.as_bytes());
+    lowered.make_ascii_lowercase();
+    Some(lowered)
+}
+
+/// Check if `name` contains the already lowercased `needle`, ignoring case, without allocating.
+pub(crate) fn contains_ignore_case(name: &str, needle: &str) -> bool {
+    // Fast path: lowercase ASCII names into a stack buffer and use the optimized substring search
+    let mut buffer = [0u8; 256];
+    if let Some(lowered) = ascii_lowercase(name, &mut buffer) {
+        #[cfg(feature = "memchr")]
+        return memchr::memmem::find(lowered, needle.as_bytes()).is_some();
+        #[cfg(not(feature = "memchr"))]
+        if let Ok(lowered) = core::str::from_utf8(lowered) {
+            return lowered.contains(needle);
+        }
+    }
+    name.char_indices().map(|(start, _)| &name[start..]).chain([""]).any(|rest| {
+        let mut rest = rest.chars().flat_map(char::to_lowercase);
+        needle.chars().all(|c| rest.next() == Some(c))
+    })
+}
+
 /// Well-known vendor IDs for convenience.
+///
+/// The build script generates these from pci.ids: the curated constants listed
+/// under `[package.metadata.well-known-vendors]` in the crate manifest, which
+/// are always present, followed by the vendors with the most devices. Each
+/// constant is documented with the vendor's name from pci.ids.
 pub mod well_known {
-    use super::VendorId;
+    include!(concat!(env!("OUT_DIR"), "/well_known_vendors.rs"));
+}
 
-    /// Intel Corporation
-    pub const INTEL: VendorId = VendorId::new(0x8086);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// Advanced Micro Devices (AMD)
-    pub const AMD: VendorId = VendorId::new(0x1022);
+    #[test]
+    fn test_aliases() {
+        let amd = Vendor::new(VendorId::new(0x1002), "Advanced Micro Devices, Inc. [AMD/ATI]", &[]);
+        assert_eq!(amd.canonical_name(), "Advanced Micro Devices, Inc.");
+        assert!(amd.aliases().eq(["AMD", "ATI"]));
+        assert!(amd.is_named("ati"));
+        assert!(amd.is_named("advanced micro devices, inc."));
+        assert!(amd.is_named("Advanced Micro Devices, Inc. [AMD/ATI]"));
+        assert!(!amd.is_named("AMD/ATI"));
 
-    /// NVIDIA Corporation
-    pub const NVIDIA: VendorId = VendorId::new(0x10de);
+        let lg = Vendor::new(VendorId::new(0x107c), "LG Electronics [Lucky Goldstar Co. Ltd]", &[]);
+        assert!(lg.aliases().eq(["Lucky Goldstar Co. Ltd"]));
 
-    /// Broadcom
-    pub const BROADCOM: VendorId = VendorId::new(0x14e4);
+        let intel = Vendor::new(VendorId::new(0x8086), "Intel Corporation", &[]);
+        assert_eq!(intel.canonical_name(), "Intel Corporation");
+        assert_eq!(intel.aliases().count(), 0);
+    }
 
-    /// Realtek Semiconductor
-    pub const REALTEK: VendorId = VendorId::new(0x10ec);
+    #[test]
+    fn test_short_name() {
+        let short = |name| Vendor::new(VendorId::new(0x0001), name, &[]).short_name();
 
-    /// Qualcomm
-    pub const QUALCOMM: VendorId = VendorId::new(0x17cb);
+        assert_eq!(short("Intel Corporation"), "Intel");
+        assert_eq!(short("Advanced Micro Devices, Inc. [AMD/ATI]"), "Advanced Micro Devices");
+        assert_eq!(short("Realtek Semiconductor Co., Ltd."), "Realtek Semiconductor");
+        assert_eq!(short("Shenzhen Foo Co.,Ltd"), "Shenzhen Foo");
+        assert_eq!(short("Siemens AG"), "Siemens");
+        assert_eq!(short("VVDN Technologies Private Limited"), "VVDN Technologies");
+        assert_eq!(short("NVIDIA Corporation"), "NVIDIA");
+        // Suffixes must be whole words, and the name never becomes empty
+        assert_eq!(short("Zilog"), "Zilog");
+        assert_eq!(short("Corporation"), "Corporation");
+        assert_eq!(short("Ltd Inc"), "Ltd");
+        assert_eq!(short(""), "");
+    }
 
-    /// Marvell Technology Group
-    pub const MARVELL: VendorId = VendorId::new(0x11ab);
+    #[test]
+    fn test_nearest_device() {
+        static DEVICES: &[Device] = &[
+            Device::new(DeviceId::new(0x1530), "A", &[]),
+            Device::new(DeviceId::new(0x1533), "B", &[]),
+            Device::new(DeviceId::new(0x1539), "C", &[]),
+        ];
+        let vendor = Vendor::new(VendorId::new(0x8086), "Intel Corporation", DEVICES);
+        let nearest = |id| -> alloc::vec::Vec<&str> {
+            vendor.nearest_device(DeviceId::new(id)).iter().map(Device::name).collect()
+        };
 
-    /// VIA Technologies
-    pub const VIA: VendorId = VendorId::new(0x1106);
+        assert_eq!(nearest(0x1533), ["B"]);
+        assert_eq!(nearest(0x1534), ["B"]);
+        assert_eq!(nearest(0x1536), ["B", "C"]);
+        assert_eq!(nearest(0x1538), ["C"]);
+        assert_eq!(nearest(0x0000), ["A"]);
+        assert_eq!(nearest(0xffff), ["C"]);
+        assert!(Vendor::new(VendorId::new(0x0001), "Empty", &[]).nearest_device(DeviceId::new(0x1533)).is_empty());
+    }
 
-    /// Atheros Communications
-    pub const ATHEROS: VendorId = VendorId::new(0x168c);
+    #[test]
+    fn test_contains_ignore_case() {
+        assert!(contains_ignore_case("Intel Corporation", "corp"));
+        assert!(contains_ignore_case("Intel Corporation", "intel corporation"));
+        assert!(contains_ignore_case("Intel Corporation", ""));
+        assert!(contains_ignore_case("", ""));
+        assert!(contains_ignore_case("Hilscher Gesellschaft FÜR", "für"));
+        assert!(!contains_ignore_case("Intel", "intel corporation"));
+        assert!(!contains_ignore_case("", "x"));
+    }
 
-    /// 3Com Corporation
-    pub const THREECOM: VendorId = VendorId::new(0x10b7);
-}
\ No newline at end of file
+    #[test]
+    fn test_split_aliases_edge_cases() {
+        assert_eq!(split_aliases(""), ("", ""));
+        assert_eq!(split_aliases("Name [Alias] Trailer"), ("Name [Alias] Trailer", ""));
+        assert_eq!(split_aliases("Name]"), ("Name]", ""));
+        assert_eq!(split_aliases("[Only Alias]"), ("Only Alias", "Only Alias"));
+        assert_eq!(split_aliases("Name []"), ("Name", ""));
+        assert_eq!(split_aliases("Bäckerei [BÄ]"), ("Bäckerei", "BÄ"));
+    }
+}
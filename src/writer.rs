@@ -0,0 +1,316 @@
+//! Serialization back to the pci.ids format.
+//!
+//! Tooling that edits the database, for example to add internal IDs, needs to
+//! re-emit a file that other tools accept. The `write_pci_ids` methods on
+//! [`ParsedDatabase`], [`PciDatabase`], and
+//! [`OverlayDatabase`](crate::overlay::OverlayDatabase) write canonical
+//! pci.ids text: tab indentation, lowercase hex IDs, two spaces before each
+//! name, and the class section after the vendors.
+//!
+//! A [`ParsedDatabase`] that kept its comments, see
+//! [`PciIdsParser::retain_comments`](crate::parser::PciIdsParser::retain_comments),
+//! writes them back in place of the generated class section header, so an
+//! unedited file is reproduced byte for byte (apart from line endings, which
+//! are always LF).
+//!
+//! Names and comments containing line breaks cannot be represented, so
+//! writing them fails with [`fmt::Error`].
+
+use crate::database::PciDatabase;
+use crate::parser::{EntryKey, ParsedDatabase};
+use crate::types::*;
+use core::fmt::{self, Write};
+
+/// Writes entries in pci.ids format, one line each.
+pub(crate) struct PciIdsWriter<'w, W> {
+    out: &'w mut W,
+    wrote_vendors: bool,
+    wrote_classes: bool,
+}
+
+impl<'w, W: Write> PciIdsWriter<'w, W> {
+    pub(crate) fn new(out: &'w mut W) -> Self {
+        Self {
+            out,
+            wrote_vendors: false,
+            wrote_classes: false,
+        }
+    }
+
+    pub(crate) fn vendor(&mut self, id: VendorId, name: &str) -> fmt::Result {
+        self.wrote_vendors = true;
+        writeln!(self.out, "{:04x}  {}", id.value(), single_line(name)?)
+    }
+
+    pub(crate) fn device(&mut self, id: DeviceId, name: &str) -> fmt::Result {
+        writeln!(self.out, "\t{:04x}  {}", id.value(), single_line(name)?)
+    }
+
+    pub(crate) fn subsystem(&mut self, subvendor_id: SubvendorId, subdevice_id: SubdeviceId, name: &str) -> fmt::Result {
+        writeln!(
+            self.out,
+            "\t\t{:04x} {:04x}  {}",
+            subvendor_id.value(),
+            subdevice_id.value(),
+            single_line(name)?
+        )
+    }
+
+    /// Write verbatim comment lines; this replaces the generated class section header.
+    pub(crate) fn comments<S: AsRef<str>>(&mut self, lines: &[S]) -> fmt::Result {
+        self.wrote_classes = true;
+        for line in lines {
+            writeln!(self.out, "{}", single_line(line.as_ref())?)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn class(&mut self, id: DeviceClassId, name: &str) -> fmt::Result {
+        if !self.wrote_classes {
+            self.wrote_classes = true;
+            if self.wrote_vendors {
+                self.out.write_str("\n")?;
+            }
+            self.out.write_str("# List of known device classes, subclasses and programming interfaces\n\n")?;
+        }
+        writeln!(self.out, "C {:02x}  {}", id.value(), single_line(name)?)
+    }
+
+    pub(crate) fn subclass(&mut self, id: SubClassId, name: &str) -> fmt::Result {
+        writeln!(self.out, "\t{:02x}  {}", id.value(), single_line(name)?)
+    }
+
+    pub(crate) fn prog_interface(&mut self, id: ProgInterfaceId, name: &str) -> fmt::Result {
+        writeln!(self.out, "\t\t{:02x}  {}", id.value(), single_line(name)?)
+    }
+
+    /// Write the device classes of a database with all their subclasses and programming interfaces.
+    pub(crate) fn classes(&mut self, database: &PciDatabase) -> fmt::Result {
+        for class in database.classes() {
+            self.class(class.id(), &database.resolve_class_name(class))?;
+            for subclass in class.subclasses() {
+                self.subclass(subclass.id(), &database.resolve_subclass_name(class.id(), subclass))?;
+                for prog_if in subclass.prog_interfaces() {
+                    let name = database.resolve_prog_interface_name(class.id(), subclass.id(), prog_if);
+                    self.prog_interface(prog_if.id(), &name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reject names that would break the line-based format.
+fn single_line(name: &str) -> Result<&str, fmt::Error> {
+    if name.contains(['\n', '\r']) {
+        Err(fmt::Error)
+    } else {
+        Ok(name)
+    }
+}
+
+impl<S: AsRef<str>> ParsedDatabase<S> {
+    /// Write the parsed database in pci.ids format.
+    ///
+    /// Entries are written in their current order; parsing always leaves them
+    /// sorted by ID. Retained [`comments`](ParsedDatabase::comments) are written
+    /// before the entries they belong to, and the trailing ones at the end.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::parser::PciIdsParser;
+    ///
+    /// let mut parser = PciIdsParser::new();
+    /// parser.parse("8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n").unwrap();
+    ///
+    /// let mut text = String::new();
+    /// parser.into_parsed().write_pci_ids(&mut text).unwrap();
+    /// assert_eq!(text, "8086  Intel Corporation\n\t1533  I210 Gigabit Network Connection\n");
+    /// ```
+    pub fn write_pci_ids<W: Write>(&self, out: &mut W) -> fmt::Result {
+        let mut writer = PciIdsWriter::new(out);
+        let retained = !self.comments.is_empty();
+        let comments = |writer: &mut PciIdsWriter<'_, W>, key| {
+            if retained {
+                writer.comments(self.comments.before(key))
+            } else {
+                Ok(())
+            }
+        };
+
+        for vendor in &self.vendors {
+            comments(&mut writer, EntryKey::Vendor(vendor.id))?;
+            writer.vendor(vendor.id, vendor.name.as_ref())?;
+            for device in &vendor.devices {
+                comments(&mut writer, EntryKey::Device(vendor.id, device.id))?;
+                writer.device(device.id, device.name.as_ref())?;
+                for subsystem in &device.subsystems {
+                    let key = EntryKey::Subsystem(vendor.id, device.id, subsystem.subvendor_id, subsystem.subdevice_id);
+                    comments(&mut writer, key)?;
+                    writer.subsystem(subsystem.subvendor_id, subsystem.subdevice_id, subsystem.name.as_ref())?;
+                }
+            }
+        }
+
+        for class in &self.classes {
+            comments(&mut writer, EntryKey::Class(class.id))?;
+            writer.class(class.id, class.name.as_ref())?;
+            for subclass in &class.subclasses {
+                comments(&mut writer, EntryKey::SubClass(class.id, subclass.id))?;
+                writer.subclass(subclass.id, subclass.name.as_ref())?;
+                for prog_if in &subclass.prog_interfaces {
+                    comments(&mut writer, EntryKey::ProgInterface(class.id, subclass.id, prog_if.id))?;
+                    writer.prog_interface(prog_if.id, prog_if.name.as_ref())?;
+                }
+            }
+        }
+
+        if retained {
+            writer.comments(&self.comments.trailing)?;
+        }
+        Ok(())
+    }
+}
+
+impl PciDatabase {
+    /// Write the database in pci.ids format.
+    ///
+    /// [Compressed](crate::compressed) names are decompressed as they are written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ids_rs::PciDatabase;
+    ///
+    /// let mut text = String::new();
+    /// PciDatabase::get().write_pci_ids(&mut text).unwrap();
+    /// ```
+    pub fn write_pci_ids<W: Write>(&self, out: &mut W) -> fmt::Result {
+        let mut writer = PciIdsWriter::new(out);
+        let Some(names) = self.compressed_names() else {
+            for vendor in self.vendors() {
+                writer.vendor(vendor.id(), vendor.name())?;
+                for device in vendor.devices() {
+                    writer.device(device.id(), device.name())?;
+                    for subsystem in device.subsystems() {
+                        writer.subsystem(subsystem.subvendor_id(), subsystem.subdevice_id(), subsystem.name())?;
+                    }
+                }
+            }
+            return writer.classes(self);
+        };
+
+        // The tables hold empty names, so decompress each one
+        let buffer = &mut alloc::vec![0; names.max_name_len()];
+        for vendor in self.vendors() {
+            writer.vendor(vendor.id(), self.vendor_name_into(vendor.id(), buffer).map_err(|_| fmt::Error)?)?;
+            for device in vendor.devices() {
+                let name = self.device_name_into(vendor.id(), device.id(), buffer).map_err(|_| fmt::Error)?;
+                writer.device(device.id(), name)?;
+                for subsystem in device.subsystems() {
+                    let (subvendor_id, subdevice_id) = (subsystem.subvendor_id(), subsystem.subdevice_id());
+                    let name = self
+                        .subsystem_name_into(vendor.id(), device.id(), subvendor_id, subdevice_id, buffer)
+                        .map_err(|_| fmt::Error)?;
+                    writer.subsystem(subvendor_id, subdevice_id, name)?;
+                }
+            }
+        }
+        writer.classes(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PciIdsParser;
+    use alloc::string::String;
+
+    const SAMPLE: &str = "\
+1234  Test Vendor
+\t0001  Test Device
+\t\tabcd 0002  Test Subsystem
+5678  Other Vendor
+
+# List of known device classes, subclasses and programming interfaces
+
+C 0c  Serial bus controller
+\t03  USB controller
+\t\t30  XHCI
+";
+
+    #[test]
+    fn test_round_trip() {
+        let mut parser = PciIdsParser::new();
+        parser.parse(SAMPLE).expect("Failed to parse");
+
+        let mut text = String::new();
+        parser.into_parsed().write_pci_ids(&mut text).unwrap();
+        assert_eq!(text, SAMPLE);
+    }
+
+    #[test]
+    fn test_write_static_database() {
+        let db = crate::pci_database! {
+            vendors {
+                0x1234 "Test Vendor" {
+                    0x0001 "Test Device" {
+                        0xabcd 0x0002 "Test Subsystem",
+                    },
+                },
+                0x5678 "Other Vendor",
+            }
+            classes {
+                0x0c "Serial bus controller" {
+                    0x03 "USB controller" {
+                        0x30 "XHCI",
+                    },
+                },
+            }
+        };
+
+        let mut text = String::new();
+        db.write_pci_ids(&mut text).unwrap();
+        assert_eq!(text, SAMPLE);
+    }
+
+    #[test]
+    fn test_rejects_multiline_names() {
+        let mut parser = PciIdsParser::new();
+        parser.parse("1234  Test Vendor\n").expect("Failed to parse");
+        let mut parsed = parser.into_parsed();
+        parsed.vendors[0].name.push_str("\nffff  Injected");
+
+        assert_eq!(parsed.write_pci_ids(&mut String::new()), Err(fmt::Error));
+    }
+
+    #[test]
+    fn test_retained_comments() {
+        let content = "\
+# Header
+
+1234  Test Vendor
+\t# Devices
+\t0001  Test Device
+\t\tabcd 0002  Test Subsystem
+
+# Classes
+
+C 0c  Serial bus controller
+\t03  USB controller
+# End
+";
+        let mut parser = PciIdsParser::new().retain_comments(true);
+        parser.parse(&content.replace('\n', "\r\n")).expect("Failed to parse");
+        let mut parsed = parser.into_parsed();
+        assert_eq!(parsed.comments.trailing, ["# End"]);
+
+        let mut text = String::new();
+        parsed.write_pci_ids(&mut text).unwrap();
+        assert_eq!(text, content);
+
+        parsed.comments.trailing[0].push_str("\r\n");
+        assert_eq!(parsed.write_pci_ids(&mut String::new()), Err(fmt::Error));
+    }
+}
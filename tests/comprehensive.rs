@@ -8,7 +8,7 @@
 //! - Performance characteristics
 
 use ids_rs::*;
-use ids_rs::parser::PciIdsParser;
+use ids_rs::parser::{ParseWarning, PciIdsParser};
 use std::collections::HashSet;
 
 // =============================================================================
@@ -171,6 +171,25 @@ fn test_const_creation() {
     assert_eq!(PROG_IF_ID.value(), 0x30);
 }
 
+#[test]
+fn test_class_code() {
+    const XHCI: ClassCode = ClassCode::new(DeviceClassId::new(0x0c), SubClassId::new(0x03), ProgInterfaceId::new(0x30));
+
+    assert_eq!(XHCI.value(), 0x0c0330);
+    assert_eq!(XHCI, ClassCode::from_value(0x0c0330));
+    assert_eq!(XHCI.class_id(), DeviceClassId::new(0x0c));
+    assert_eq!(XHCI.subclass_id(), SubClassId::new(0x03));
+    assert_eq!(XHCI.prog_interface_id(), ProgInterfaceId::new(0x30));
+    assert_eq!(format!("{}", XHCI), "0c0330");
+    assert_eq!(u32::from(XHCI), 0x0c0330);
+
+    // Bits above the class code are ignored
+    assert_eq!(ClassCode::from_value(0xff0c0330), XHCI);
+
+    // Byte tuples, as produced by config space readers, convert directly
+    assert_eq!(ClassCode::from((0x0c, 0x03, 0x30)), XHCI);
+}
+
 #[test]
 fn test_well_known_vendor_ids() {
     use ids_rs::vendors::well_known::*;
@@ -226,8 +245,8 @@ fn test_global_database_access() {
 
     // The database might be empty if no pci.ids file was found during build
     // but it should still be accessible
-    assert!(db.vendors().len() >= 0);  // Should not panic
-    assert!(db.classes().len() >= 0);  // Should not panic
+    let _ = db.vendors().len();  // Should not panic
+    let _ = db.classes().len();  // Should not panic
 
     // Test basic operations don't panic
     let _stats = db.stats();
@@ -235,6 +254,140 @@ fn test_global_database_access() {
     let _device_name = db.device_name(VendorId::new(0x8086), DeviceId::new(0x1234));
 }
 
+#[test]
+fn test_database_version() {
+    let db = PciDatabase::get();
+    assert_eq!(db.version().unwrap_or(""), DATABASE_VERSION);
+    assert_eq!(db.date().unwrap_or(""), DATABASE_DATE);
+}
+
+#[test]
+fn test_database_size() {
+    let stats = PciDatabase::get().stats();
+    let counts = (DATABASE_SIZE.vendors, DATABASE_SIZE.devices, DATABASE_SIZE.subsystems);
+    assert_eq!(counts, (stats.vendor_count, stats.device_count, stats.subsystem_count));
+    let counts = (DATABASE_SIZE.classes, DATABASE_SIZE.subclasses, DATABASE_SIZE.prog_interfaces);
+    assert_eq!(counts, (stats.class_count, stats.subclass_count, stats.prog_interface_count));
+    assert_eq!(DATABASE_SIZE.total_entries(), stats.total_entries());
+    assert!(DATABASE_SIZE.estimated_bytes(std::mem::size_of::<usize>()) >= DATABASE_SIZE.name_bytes);
+}
+
+#[test]
+fn test_every_entry_is_found() {
+    // Exercises the perfect-hash indexes when built with the `phf` feature
+    let db = PciDatabase::get();
+    for vendor in db.vendors() {
+        assert_eq!(db.find_vendor(vendor.id()).map(|v| v.id()), Some(vendor.id()));
+        for device in vendor.devices() {
+            let found = db.find_device(vendor.id(), device.id()).unwrap();
+            assert!(std::ptr::eq(found, device));
+        }
+    }
+    assert!(db.find_device(VendorId::new(0xffff), DeviceId::new(0xffff)).is_none());
+}
+
+#[cfg(feature = "usb")]
+#[test]
+fn test_usb_database() {
+    let usb = ids_rs::buses::usb::get();
+    assert!(usb.validate().is_valid());
+    for vendor in usb.vendors() {
+        for device in vendor.devices() {
+            assert_eq!(usb.find_device(vendor.id(), device.id()).map(|d| d.name()), Some(device.name()));
+        }
+    }
+}
+
+#[cfg(feature = "blob")]
+#[test]
+fn test_blob_database() {
+    let blob = ids_rs::BlobDatabase::get();
+    let db = PciDatabase::get();
+    assert_eq!(blob.stats().class_count, db.stats().class_count);
+    // The global database stays complete alongside the blob
+    assert_eq!(blob.vendor_count(), db.vendors().len());
+    for vendor in blob.vendors() {
+        assert_eq!(blob.find_vendor(vendor.id()).map(|v| v.name()), Some(vendor.name()));
+        for device in vendor.devices() {
+            assert_eq!(blob.find_device(vendor.id(), device.id()).map(|d| d.name()), Some(device.name()));
+        }
+    }
+}
+
+#[test]
+fn test_generated_database_validates() {
+    let report = PciDatabase::get().validate();
+    assert!(report.is_valid(), "{}", report);
+}
+
+#[test]
+fn test_find_by_class_code() {
+    let db = PciDatabase::get();
+    let code = ClassCode::from_value(0x0c0330);
+
+    let (class, subclass, prog_if) = db.find_by_class_code(code);
+    assert_eq!(class.map(|c| c.id()), db.find_class(code.class_id()).map(|c| c.id()));
+    assert_eq!(
+        subclass.map(|s| s.id()),
+        db.find_subclass(code.class_id(), code.subclass_id()).map(|s| s.id())
+    );
+    assert_eq!(
+        prog_if.map(|p| p.id()),
+        db.find_prog_interface(code.class_id(), code.subclass_id(), code.prog_interface_id()).map(|p| p.id())
+    );
+
+    if cfg!(feature = "no-prog-if") {
+        assert!(prog_if.is_none());
+    } else if !db.classes().is_empty() {
+        let mut buffer = [0u8; 256];
+        let name = prog_if.and_then(|p| {
+            db.prog_interface_name_into(code.class_id(), code.subclass_id(), p.id(), &mut buffer).ok()
+        });
+        assert_eq!(name, Some("XHCI"));
+    }
+
+    // An unknown programming interface still resolves the class and subclass
+    let (class, subclass, prog_if) = db.find_by_class_code(ClassCode::from_value(0x0c03f0));
+    assert_eq!(class.is_some(), subclass.is_some());
+    assert!(prog_if.is_none());
+}
+
+#[test]
+fn test_ascii_names() {
+    let db = PciDatabase::get();
+
+    for (vendor, device) in db.iter_devices() {
+        assert!(vendor.name_ascii().is_ascii(), "{:?}", vendor.name());
+        assert!(device.name_ascii().is_ascii(), "{:?}", device.name());
+        assert!(device.iter_subsystems().all(|subsystem| subsystem.name_ascii().is_ascii()));
+    }
+
+    // Vendor data is compiled out under `classes-only`
+    let (Some(intel), Some(hilscher)) = (db.find_vendor(VendorId::new(0x8086)), db.find_vendor(VendorId::new(0x15cf))) else {
+        return;
+    };
+    assert_eq!(intel.name_ascii(), intel.name());
+
+    // Compressed tables hold empty names, so only the decompressed name is left to check
+    if db.compressed_names().is_some() {
+        assert!(db.vendor_name(hilscher.id()).starts_with("Hilscher Gesellschaft f"));
+        return;
+    }
+    assert_eq!(hilscher.name_ascii(), "Hilscher Gesellschaft fur Systemautomation mbH");
+
+    // Transliteration takes precedence when both features are enabled
+    let form = if cfg!(feature = "transliterate-names") {
+        NameForm::Transliterated
+    } else if cfg!(feature = "escape-names") {
+        NameForm::Escaped
+    } else {
+        NameForm::Original
+    };
+    assert_eq!(hilscher.name_form(), form);
+    assert_eq!(hilscher.name().is_ascii(), form != NameForm::Original);
+    assert_eq!(intel.name_form(), NameForm::Original);
+}
+
 #[test]
 fn test_database_with_empty_lookups() {
     let db = PciDatabase::get();
@@ -251,13 +404,13 @@ fn test_database_with_empty_lookups() {
 
     // Test name methods with potentially unknown IDs
     let vendor_name = db.vendor_name(VendorId::new(0xFFFF));
-    assert!(vendor_name.len() > 0); // Should always return something
+    assert!(!vendor_name.is_empty()); // Should always return something
 
     let device_name = db.device_name(VendorId::new(0xFFFF), DeviceId::new(0xFFFF));
-    assert!(device_name.len() > 0); // Should always return something
+    assert!(!device_name.is_empty()); // Should always return something
 
     let class_name = db.class_name(DeviceClassId::new(0xFF));
-    assert!(class_name.len() > 0); // Should always return something
+    assert!(!class_name.is_empty()); // Should always return something
 }
 
 #[test]
@@ -267,18 +420,24 @@ fn test_database_statistics() {
     let stats = db.stats();
 
     // All counts should be non-negative
-    assert!(stats.vendor_count >= 0);
-    assert!(stats.device_count >= 0);
-    assert!(stats.subsystem_count >= 0);
-    assert!(stats.class_count >= 0);
-    assert!(stats.subclass_count >= 0);
-    assert!(stats.prog_interface_count >= 0);
-    assert!(stats.total_entries() >= 0);
+    let _ = stats.vendor_count;
+    let _ = stats.device_count;
+    let _ = stats.subsystem_count;
+    let _ = stats.class_count;
+    let _ = stats.subclass_count;
+    let _ = stats.prog_interface_count;
+    let _ = stats.total_entries();
 
     // Test display formatting
     let stats_string = format!("{}", stats);
     assert!(stats_string.contains("Vendors:"));
     assert!(stats_string.contains("Total Entries:"));
+
+    // Iterator lengths agree with the statistics
+    assert_eq!(db.iter_vendors().len(), stats.vendor_count);
+    assert_eq!(db.iter_classes().len(), stats.class_count);
+    assert_eq!(db.iter_devices().len(), stats.device_count);
+    assert_eq!(db.iter_devices().rev().count(), stats.device_count);
 }
 
 #[test]
@@ -290,17 +449,17 @@ fn test_database_convenience_methods() {
 
     // Test search methods (should always return a Vec, might be empty)
     let intel_vendors = db.search_vendors("intel");
-    assert!(intel_vendors.len() >= 0);
+    let _ = intel_vendors.len();
 
     let test_devices = db.search_devices("test");
-    assert!(test_devices.len() >= 0);
+    let _ = test_devices.len();
 
     let network_classes = db.search_classes("network");
-    assert!(network_classes.len() >= 0);
+    let _ = network_classes.len();
 
     // Test query builder
     let query_results = db.query().execute();
-    assert!(query_results.len() >= 0);
+    let _ = query_results.len();
 }
 
 #[test]
@@ -319,7 +478,198 @@ fn test_comprehensive_device_description() {
     );
 
     // Should always return a string
-    assert!(description.len() > 0);
+    assert!(!description.is_empty());
+}
+
+#[test]
+fn test_names_with_prefix() {
+    let db = PciDatabase::get();
+
+    // Every match must start with the prefix at the name start or after a bracket
+    for name_match in db.names_with_prefix("GeForce") {
+        let name = name_match.name(db).to_lowercase();
+        assert!(name.starts_with("geforce") || name.contains("[geforce"));
+        assert_eq!(name_match.device().is_none(), matches!(name_match, NameMatch::Vendor(_)));
+    }
+
+    // The indexed lookup must agree with the linear scan used by unindexed databases
+    let unindexed = PciDatabase::new(db.vendors(), db.classes())
+        .with_compressed_names(db.compressed_names().copied().unwrap_or_default());
+    for prefix in ["GeForce", "intel", "RTL", "I2", "zzzz-not-a-name", ""] {
+        let indexed: Vec<String> = db.names_with_prefix(prefix).iter().map(|m| m.name(db).into_owned()).collect();
+        let scanned: Vec<String> = unindexed.names_with_prefix(prefix).iter().map(|m| m.name(db).into_owned()).collect();
+        assert_eq!(indexed, scanned, "prefix {:?}", prefix);
+    }
+
+    if db.find_vendor(VendorId::new(0x8086)).is_some() {
+        let results = db.names_with_prefix("Intel Corporation");
+        assert!(results
+            .iter()
+            .any(|m| m.device().is_none() && m.vendor().id() == VendorId::new(0x8086)));
+    }
+}
+
+#[test]
+fn test_vendors_and_devices_with_prefix() {
+    let db = PciDatabase::get();
+
+    let vendors = db.vendors_with_prefix("intel");
+    assert!(vendors.iter().all(|vendor| {
+        let name = db.vendor_name(vendor.id()).to_lowercase();
+        name.starts_with("intel") || name.contains("[intel")
+    }));
+    let devices = db.devices_with_prefix("GeForce");
+    assert_eq!(devices.is_empty(), db.find_vendor(VendorId::new(0x10de)).is_none());
+
+    // Together they cover exactly the mixed prefix search
+    for prefix in ["intel", "GeForce", "RTL"] {
+        let total = db.vendors_with_prefix(prefix).len() + db.devices_with_prefix(prefix).len();
+        assert_eq!(total, db.names_with_prefix(prefix).len(), "prefix {:?}", prefix);
+    }
+}
+
+#[test]
+fn test_multiple_database_instances() {
+    // Two snapshots of the same vendor, as an older and a newer pci.ids would produce
+    static OLD_DEVICES: &[Device] = &[Device::new(DeviceId::new(0x0001), "Widget", &[])];
+    static NEW_DEVICES: &[Device] = &[
+        Device::new(DeviceId::new(0x0001), "Widget [Renamed]", &[]),
+        Device::new(DeviceId::new(0x0002), "Gadget", &[]),
+    ];
+    static OLD_VENDORS: &[Vendor] = &[Vendor::new(VendorId::new(0x1234), "Example Corp", OLD_DEVICES)];
+    static NEW_VENDORS: &[Vendor] = &[
+        Vendor::new(VendorId::new(0x1234), "Example Corporation", NEW_DEVICES),
+        Vendor::new(VendorId::new(0x5678), "Newcomer Inc", &[]),
+    ];
+    static OLD_CLASSES: &[DeviceClass] = &[DeviceClass::new(DeviceClassId::new(0x02), "Network controller", &[])];
+
+    let old = PciDatabase::new(OLD_VENDORS, OLD_CLASSES);
+    let new = PciDatabase::new(NEW_VENDORS, &[]);
+    let vendor_id = VendorId::new(0x1234);
+
+    assert_eq!(old.vendor_name(vendor_id), "Example Corp");
+    assert_eq!(new.vendor_name(vendor_id), "Example Corporation");
+    assert!(old.find_device(vendor_id, DeviceId::new(0x0002)).is_none());
+    assert!(new.find_device(vendor_id, DeviceId::new(0x0002)).is_some());
+    assert_eq!(old.class_name(DeviceClassId::new(0x02)), "Network controller");
+    assert_eq!(new.class_name(DeviceClassId::new(0x02)), "Unknown Class (02)");
+
+    // Results borrowed from both instances can be held at the same time
+    let old_matches = old.search_devices("widget");
+    let new_matches = new.query().vendor_id(vendor_id).execute();
+    assert_eq!(old_matches.len(), 1);
+    assert_eq!(new_matches.len(), 2);
+    assert_eq!(old_matches[0].device_name(), "Widget");
+    assert_eq!(new_matches[0].device_name(), "Widget [Renamed]");
+
+    assert_eq!(old.stats().vendor_count, 1);
+    assert_eq!(new.stats().vendor_count, 2);
+    assert_eq!(new.names_with_prefix("renamed").len(), 1);
+    assert!(old.names_with_prefix("renamed").is_empty());
+    assert!(old.validate().is_valid() && new.validate().is_valid());
+
+    // Neither instance affects the global database
+    let global = PciDatabase::get();
+    assert_ne!(global.vendors().len(), new.vendors().len());
+    let copy = new;
+    assert_eq!(copy.vendors().len(), new.vendors().len());
+}
+
+#[test]
+fn test_custom_static_database_macro() {
+    static CUSTOM: PciDatabase = pci_database! {
+        vendors {
+            0x1234 "Example Corp" {
+                0x0001 "Widget",
+                0x0002 "Gadget" {
+                    0x1234 0x0100 "Gadget Pro",
+                    0x5678 0x0001 "OEM Gadget",
+                },
+            },
+            0x5678 "Partner Inc",
+        }
+        classes {
+            0x02 "Network controller" {
+                0x00 "Ethernet controller",
+            },
+            0x0c "Serial bus controller" {
+                0x03 "USB controller" {
+                    0x20 "EHCI",
+                    0x30 "XHCI",
+                },
+                0x05 "SMBus",
+            },
+        }
+    };
+    static EMPTY: PciDatabase = pci_database! {
+        vendors {}
+        classes {}
+    };
+
+    assert!(CUSTOM.is_sorted() && CUSTOM.validate().is_valid());
+    assert_eq!(CUSTOM.stats().vendor_count, 2);
+    assert_eq!(CUSTOM.stats().device_count, 2);
+    assert_eq!(CUSTOM.stats().subsystem_count, 2);
+    assert_eq!(
+        CUSTOM.subsystem_name(VendorId::new(0x1234), DeviceId::new(0x0002), SubvendorId::new(0x5678), SubdeviceId::new(0x0001)),
+        "OEM Gadget"
+    );
+    assert_eq!(CUSTOM.describe_class(ClassCode::from_value(0x0c0330)), "Serial bus controller - USB controller - XHCI");
+    assert_eq!(CUSTOM.query().device_name_contains("gadget").execute().len(), 1);
+    assert_eq!(CUSTOM.search_vendors("partner").len(), 1);
+    assert_eq!(EMPTY.stats().total_entries(), 0);
+}
+
+#[test]
+fn test_embedded_tables_are_target_independent() {
+    // Run on 32-bit and big-endian targets (e.g. via `cross test`), this checks the
+    // generated tables mean the same thing there as on the host that generated them.
+    let db = PciDatabase::get();
+
+    assert!(db.vendors().windows(2).all(|w| w[0].id() < w[1].id()));
+    assert!(db.classes().windows(2).all(|w| w[0].id() < w[1].id()));
+
+    for vendor in db.vendors().iter().step_by(50) {
+        let found = db.names_with_prefix(vendor.name());
+        assert!(found.iter().any(|m| m.device().is_none() && m.vendor().id() == vendor.id()));
+    }
+}
+
+#[test]
+fn test_devices_by_class_inference() {
+    use ids_rs::classes::well_known::{DISPLAY, NETWORK};
+
+    let db = PciDatabase::get();
+    let network_devices = db.devices_by_class(NETWORK);
+
+    for device_match in &network_devices {
+        assert_eq!(device_match.class_info.map(|c| c.id()), Some(NETWORK));
+    }
+
+    // Class filters match nothing under `vendors-only`, where the class tree is compiled out
+    if db.find_class(NETWORK).is_some() && db.find_device(VendorId::new(0x8086), DeviceId::new(0x1533)).is_some() {
+        // Intel I210 is in a curated Ethernet range
+        assert!(network_devices
+            .iter()
+            .any(|m| m.vendor_id() == VendorId::new(0x8086) && m.device_id() == DeviceId::new(0x1533)));
+
+        let inferred = db.infer_device_class(VendorId::new(0x8086), DeviceId::new(0x1533)).unwrap();
+        assert_eq!(inferred.class_id, NETWORK);
+
+        // A NIC is not a display controller
+        assert!(!db
+            .devices_by_class(DISPLAY)
+            .iter()
+            .any(|m| m.vendor_id() == VendorId::new(0x8086) && m.device_id() == DeviceId::new(0x1533)));
+    }
+
+    // Subclass filters apply to the inferred subclass
+    let ethernet = db.query()
+        .class_id(NETWORK)
+        .subclass_id(SubClassId::new(0x00))
+        .infer_device_classes()
+        .execute();
+    assert!(ethernet.len() <= network_devices.len());
 }
 
 // =============================================================================
@@ -372,18 +722,21 @@ fn test_parser_vendor_parsing() {
     let result = parser.parse(content);
     assert!(result.is_ok());
 
+    // Vendors are sorted by ID
     let vendors = parser.vendors();
     assert_eq!(vendors.len(), 3);
 
-    assert_eq!(vendors[0].id.value(), 0x8086);
-    assert_eq!(vendors[0].name, "Intel Corporation");
+    assert_eq!(vendors[0].id.value(), 0x1022);
+    assert_eq!(vendors[0].name, "Advanced Micro Devices, Inc. [AMD/ATI]");
     assert_eq!(vendors[0].devices.len(), 0);
 
-    assert_eq!(vendors[1].id.value(), 0x1022);
-    assert_eq!(vendors[1].name, "Advanced Micro Devices, Inc. [AMD/ATI]");
+    assert_eq!(vendors[1].id.value(), 0x10de);
+    assert_eq!(vendors[1].name, "NVIDIA Corporation");
+
+    assert_eq!(vendors[2].id.value(), 0x8086);
+    assert_eq!(vendors[2].name, "Intel Corporation");
 
-    assert_eq!(vendors[2].id.value(), 0x10de);
-    assert_eq!(vendors[2].name, "NVIDIA Corporation");
+    assert_eq!(parser.warnings(), [ParseWarning::UnsortedVendors]);
 }
 
 #[test]
@@ -403,17 +756,17 @@ fn test_parser_device_parsing() {
     let vendors = parser.vendors();
     assert_eq!(vendors.len(), 2);
 
-    // Intel vendor
-    assert_eq!(vendors[0].devices.len(), 2);
-    assert_eq!(vendors[0].devices[0].id.value(), 0x1234);
-    assert_eq!(vendors[0].devices[0].name, "Test Device 1");
-    assert_eq!(vendors[0].devices[1].id.value(), 0x5678);
-    assert_eq!(vendors[0].devices[1].name, "Test Device 2");
+    // AMD vendor (sorted before Intel)
+    assert_eq!(vendors[0].devices.len(), 1);
+    assert_eq!(vendors[0].devices[0].id.value(), 0xabcd);
+    assert_eq!(vendors[0].devices[0].name, "AMD Device");
 
-    // AMD vendor
-    assert_eq!(vendors[1].devices.len(), 1);
-    assert_eq!(vendors[1].devices[0].id.value(), 0xabcd);
-    assert_eq!(vendors[1].devices[0].name, "AMD Device");
+    // Intel vendor
+    assert_eq!(vendors[1].devices.len(), 2);
+    assert_eq!(vendors[1].devices[0].id.value(), 0x1234);
+    assert_eq!(vendors[1].devices[0].name, "Test Device 1");
+    assert_eq!(vendors[1].devices[1].id.value(), 0x5678);
+    assert_eq!(vendors[1].devices[1].name, "Test Device 2");
 }
 
 #[test]
@@ -436,17 +789,18 @@ fn test_parser_subsystem_parsing() {
     let device = &vendors[0].devices[0];
     assert_eq!(device.subsystems.len(), 3);
 
-    assert_eq!(device.subsystems[0].subvendor_id.value(), 0x8086);
-    assert_eq!(device.subsystems[0].subdevice_id.value(), 0x1111);
-    assert_eq!(device.subsystems[0].name, "Intel Subsystem 1");
+    // Subsystems are sorted by subvendor, then subdevice
+    assert_eq!(device.subsystems[0].subvendor_id.value(), 0x1022);
+    assert_eq!(device.subsystems[0].subdevice_id.value(), 0x3333);
+    assert_eq!(device.subsystems[0].name, "AMD Subsystem");
 
     assert_eq!(device.subsystems[1].subvendor_id.value(), 0x8086);
-    assert_eq!(device.subsystems[1].subdevice_id.value(), 0x2222);
-    assert_eq!(device.subsystems[1].name, "Intel Subsystem 2");
+    assert_eq!(device.subsystems[1].subdevice_id.value(), 0x1111);
+    assert_eq!(device.subsystems[1].name, "Intel Subsystem 1");
 
-    assert_eq!(device.subsystems[2].subvendor_id.value(), 0x1022);
-    assert_eq!(device.subsystems[2].subdevice_id.value(), 0x3333);
-    assert_eq!(device.subsystems[2].name, "AMD Subsystem");
+    assert_eq!(device.subsystems[2].subvendor_id.value(), 0x8086);
+    assert_eq!(device.subsystems[2].subdevice_id.value(), 0x2222);
+    assert_eq!(device.subsystems[2].name, "Intel Subsystem 2");
 }
 
 #[test]
@@ -564,8 +918,8 @@ C 02  Network controller
     // Check vendors
     let vendors = parser.vendors();
     assert_eq!(vendors.len(), 2);
-    assert_eq!(vendors[0].name, "Intel Corporation");
-    assert_eq!(vendors[1].name, "AMD");
+    assert_eq!(vendors[0].name, "AMD");
+    assert_eq!(vendors[1].name, "Intel Corporation");
 
     // Check classes
     let classes = parser.classes();
@@ -575,6 +929,35 @@ C 02  Network controller
     assert_eq!(classes[0].subclasses[0].prog_interfaces[0].name, "Basic Ethernet");
 }
 
+#[test]
+fn test_write_pci_ids_round_trip() {
+    let db = PciDatabase::get();
+    let mut text = String::new();
+    db.write_pci_ids(&mut text).unwrap();
+
+    let mut parser = PciIdsParser::new();
+    parser.parse(&text).expect("Written database should parse");
+    assert!(parser.warnings().is_empty());
+    assert_eq!(parser.vendors().len(), db.vendors().len());
+    assert_eq!(parser.classes().len(), db.classes().len());
+}
+
+#[test]
+fn test_retained_comments_round_trip() {
+    // Only the bundled file is checked; it may be absent from packaged sources
+    let Ok(content) = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/pci.ids")) else {
+        return;
+    };
+
+    let mut parser = PciIdsParser::new().retain_comments(true);
+    parser.parse(&content).expect("Bundled pci.ids should parse");
+    let mut text = String::new();
+    parser.into_parsed().write_pci_ids(&mut text).unwrap();
+    // Names are trimmed, and a few upstream ones end in an ideographic space
+    assert_eq!(text.lines().count(), content.lines().count());
+    assert!(text.lines().eq(content.lines().map(str::trim_end)), "Retained comments should reproduce pci.ids");
+}
+
 #[test]
 fn test_parser_error_cases() {
     let mut parser = PciIdsParser::new();
@@ -616,6 +999,11 @@ fn test_error_display() {
     assert_eq!(format!("{}", PciError::ClassNotFound), "Device class not found");
     assert_eq!(format!("{}", PciError::SubclassNotFound), "Subclass not found");
     assert_eq!(format!("{}", PciError::ProgInterfaceNotFound), "Programming interface not found");
+    assert_eq!(format!("{}", PciError::InvalidPattern), "Invalid search pattern");
+    assert_eq!(format!("{}", PciError::DuplicateEntry), "Duplicate entry in PCI IDs file");
+    assert_eq!(format!("{}", PciError::Cancelled), "Parsing was cancelled");
+    assert_eq!(format!("{}", PciError::LimitExceeded), "Parser limit exceeded");
+    assert_eq!(format!("{}", PciError::BufferTooSmall), "Buffer too small");
 }
 
 #[test]
@@ -672,8 +1060,11 @@ fn test_query_performance_characteristics() {
     }
     let query_time = start.elapsed();
 
-    // Query operations should complete in reasonable time
-    assert!(query_time.as_millis() < 1000);
+    // Query operations should complete in reasonable time, allowing for
+    // decompressing or looking up every vendor name and Intel's device names
+    // on each query
+    let limit = if cfg!(any(feature = "compressed-names", feature = "string-table")) { 3000 } else { 1000 };
+    assert!(query_time.as_millis() < limit);
 }
 
 // =============================================================================
@@ -740,15 +1131,17 @@ fn test_end_to_end_workflow() {
     // 3. Look up some well-known vendor IDs
     let intel_id = VendorId::new(0x8086);
     let vendor_name = db.vendor_name(intel_id);
-    assert!(vendor_name.len() > 0); // Should always return something
+    assert!(!vendor_name.is_empty()); // Should always return something
 
     // 4. Search for network-related devices
     let network_devices = db.search_devices("network");
     let ethernet_devices = db.search_devices("ethernet");
 
     // The searches should not panic and should return reasonable results
-    assert!(network_devices.len() >= 0);
-    assert!(ethernet_devices.len() <= network_devices.len() || network_devices.is_empty());
+    let _ = network_devices.len();
+    assert!(ethernet_devices
+        .iter()
+        .all(|m| db.device_name(m.vendor_id(), m.device_id()).to_lowercase().contains("ethernet")));
 
     // 5. Look up device classes
     let network_class_id = DeviceClassId::new(0x02);
@@ -756,12 +1149,13 @@ fn test_end_to_end_workflow() {
 
     if let Some(class) = network_class {
         // If we found a network class, test its properties
-        assert!(class.name().len() > 0);
+        let mut buffer = [0u8; 256];
+        assert!(!db.class_name_into(class.id(), &mut buffer).unwrap().is_empty());
 
         // Look for Ethernet subclass
         let ethernet_subclass = class.find_subclass(SubClassId::new(0x00));
         if let Some(subclass) = ethernet_subclass {
-            assert!(subclass.name().len() > 0);
+            assert!(!db.subclass_name_into(class.id(), subclass.id(), &mut buffer).unwrap().is_empty());
         }
     }
 
@@ -771,7 +1165,7 @@ fn test_end_to_end_workflow() {
         .execute();
 
     // Should complete without panicking
-    assert!(complex_results.len() >= 0);
+    let _ = complex_results.len();
 
     // 7. Test device description generation
     let description = db.describe_device(
@@ -785,7 +1179,7 @@ fn test_end_to_end_workflow() {
     );
 
     // Should return a non-empty string
-    assert!(description.len() > 0);
+    assert!(!description.is_empty());
 }
 
 #[test]